@@ -0,0 +1,33 @@
+// Prints the time recorded per task over the last days:
+//
+//     cargo run --example report -- <data folder> [days, 7 by default]
+
+use chrono::{Duration, Local};
+use std::process::ExitCode;
+use work_timer::{Report, Workspace};
+
+fn main() -> ExitCode {
+    let mut args = std::env::args().skip(1);
+    let Some(dir) = args.next() else {
+        eprintln!("Usage: report <data folder> [days]");
+        return ExitCode::FAILURE;
+    };
+    let days: i64 = args.next().and_then(|days| days.parse().ok()).unwrap_or(7).max(1);
+    let workspace = match Workspace::open(&dir) {
+        Ok(workspace) => workspace,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let to = Local::now().date_naive();
+    let report = Report::range(&workspace, to - Duration::days(days - 1), to);
+    println!("{} to {}", report.from, report.to);
+    for row in &report.rows {
+        let folder = row.folder.as_deref().unwrap_or("Uncategorized");
+        println!("{:>3}:{:02}  {} ({})", row.seconds / 3600, row.seconds % 3600 / 60, row.description, folder);
+    }
+    println!("{:>3}:{:02}  Total", report.total_seconds() / 3600, report.total_seconds() % 3600 / 60);
+    ExitCode::SUCCESS
+}
@@ -0,0 +1,37 @@
+// Starts the timer of a task, or stops it when it is already running:
+//
+//     cargo run --example start_stop -- <data folder> "<task description>"
+//
+// The task is created in Uncategorized when no task has that description.
+
+use std::process::ExitCode;
+use work_timer::Workspace;
+
+fn main() -> ExitCode {
+    let mut args = std::env::args().skip(1);
+    let (Some(dir), Some(description)) = (args.next(), args.next()) else {
+        eprintln!("Usage: start_stop <data folder> <task description>");
+        return ExitCode::FAILURE;
+    };
+    let result = Workspace::open(&dir).and_then(|mut workspace| {
+        let mut task = match workspace.find_task(&description) {
+            Some(task) => task,
+            None => workspace.add_task(&description, None),
+        };
+        if task.task().is_running() {
+            let entry = task.stop()?.expect("the task was running");
+            println!("Stopped \"{}\" after {} min", description, entry.duration().num_minutes());
+        } else {
+            task.start()?;
+            println!("Started \"{}\"", description);
+        }
+        Ok(())
+    });
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,179 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+const RESCUETIME_URL: &str = "https://www.rescuetime.com/anapi/data";
+const DEFAULT_ACTIVITYWATCH_URL: &str = "http://localhost:5600";
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+pub enum ActivitySource {
+    #[default]
+    None,
+    RescueTime,
+    ActivityWatch,
+}
+
+impl ActivitySource {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ActivitySource::None => "Off",
+            ActivitySource::RescueTime => "RescueTime",
+            ActivitySource::ActivityWatch => "ActivityWatch",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ActivityConfig {
+    #[serde(default)]
+    pub source: ActivitySource,
+    #[serde(default)]
+    pub rescuetime_key: String,
+    #[serde(default)]
+    pub activitywatch_url: String, // Empty uses the local default server
+}
+
+impl ActivityConfig {
+    pub fn is_configured(&self) -> bool {
+        match self.source {
+            ActivitySource::None => false,
+            ActivitySource::RescueTime => !self.rescuetime_key.trim().is_empty(),
+            ActivitySource::ActivityWatch => true,
+        }
+    }
+}
+
+// What the computer reports for one day, largest category first
+#[derive(Debug, Clone)]
+pub struct DailyActivity {
+    pub date: NaiveDate,
+    pub categories: Vec<(String, i64)>, // Category (RescueTime) or application (ActivityWatch) -> seconds
+}
+
+impl DailyActivity {
+    pub fn total(&self) -> i64 {
+        self.categories.iter().map(|(_, seconds)| seconds).sum()
+    }
+}
+
+#[derive(Deserialize)]
+struct RescueTimeResponse {
+    rows: Vec<serde_json::Value>,
+}
+
+#[derive(Deserialize)]
+struct WatcherEvent {
+    duration: f64,
+    data: serde_json::Map<String, serde_json::Value>,
+}
+
+fn describe_error(error: ureq::Error) -> String {
+    match error {
+        ureq::Error::Status(code, response) => {
+            format!("HTTP {}: {}", code, response.into_string().unwrap_or_default())
+        }
+        ureq::Error::Transport(transport) => transport.to_string(),
+    }
+}
+
+fn add_seconds(days: &mut [DailyActivity], date: NaiveDate, category: String, seconds: i64) {
+    if let Some(day) = days.iter_mut().find(|day| day.date == date) {
+        match day.categories.iter_mut().find(|(name, _)| *name == category) {
+            Some((_, total)) => *total += seconds,
+            None => day.categories.push((category, seconds)),
+        }
+    }
+}
+
+// Rows look like ["2024-05-12T00:00:00", seconds, people, "Category"]
+fn fetch_rescuetime(config: &ActivityConfig, days: &mut [DailyActivity]) -> Result<(), String> {
+    let (Some(first), Some(last)) = (days.first(), days.last()) else {
+        return Ok(());
+    };
+    let response: RescueTimeResponse = ureq::get(RESCUETIME_URL)
+        .query("key", config.rescuetime_key.trim())
+        .query("format", "json")
+        .query("perspective", "interval")
+        .query("resolution_time", "day")
+        .query("restrict_kind", "category")
+        .query("restrict_begin", &first.date.to_string())
+        .query("restrict_end", &last.date.to_string())
+        .call()
+        .map_err(describe_error)?
+        .into_json()
+        .map_err(|e| e.to_string())?;
+
+    for row in response.rows {
+        let date = row[0]
+            .as_str()
+            .and_then(|date| NaiveDate::parse_from_str(date.get(..10)?, "%Y-%m-%d").ok());
+        let seconds = row[1].as_i64();
+        let category = row[3].as_str();
+        if let (Some(date), Some(seconds), Some(category)) = (date, seconds, category) {
+            add_seconds(days, date, category.to_string(), seconds);
+        }
+    }
+    Ok(())
+}
+
+// Active (not AFK) window time per application, one query period per day
+fn fetch_activitywatch(config: &ActivityConfig, days: &mut [DailyActivity]) -> Result<(), String> {
+    let base_url = match config.activitywatch_url.trim().trim_end_matches('/') {
+        "" => DEFAULT_ACTIVITYWATCH_URL,
+        url => url,
+    };
+    let periods: Vec<String> = days
+        .iter()
+        .map(|day| {
+            let start = crate::local_midnight(day.date);
+            let end = crate::local_midnight(day.date + chrono::Duration::days(1));
+            format!("{}/{}", start.to_rfc3339(), end.to_rfc3339())
+        })
+        .collect();
+    let query = [
+        "events = flood(query_bucket(find_bucket(\"aw-watcher-window_\")));",
+        "not_afk = flood(query_bucket(find_bucket(\"aw-watcher-afk_\")));",
+        "not_afk = filter_keyvals(not_afk, \"status\", [\"not-afk\"]);",
+        "events = filter_period_intersect(events, not_afk);",
+        "events = merge_events_by_keys(events, [\"app\"]);",
+        "RETURN = sort_by_duration(events);",
+    ];
+
+    let results: Vec<Vec<WatcherEvent>> = ureq::post(&format!("{}/api/0/query/", base_url))
+        .send_json(serde_json::json!({ "timeperiods": periods, "query": query }))
+        .map_err(describe_error)?
+        .into_json()
+        .map_err(|e| e.to_string())?;
+
+    for (day, events) in days.iter_mut().zip(results) {
+        day.categories = events
+            .into_iter()
+            .map(|event| {
+                let app = event.data.get("app").and_then(|app| app.as_str()).unwrap_or("Unknown");
+                (app.to_string(), event.duration.round() as i64)
+            })
+            .collect();
+    }
+    Ok(())
+}
+
+pub fn fetch_daily_activity(config: &ActivityConfig, from: NaiveDate, to: NaiveDate) -> Result<Vec<DailyActivity>, String> {
+    let mut days: Vec<DailyActivity> = from
+        .iter_days()
+        .take_while(|date| *date <= to)
+        .map(|date| DailyActivity {
+            date,
+            categories: Vec::new(),
+        })
+        .collect();
+
+    match config.source {
+        ActivitySource::None => return Err("no activity source selected".to_string()),
+        ActivitySource::RescueTime => fetch_rescuetime(config, &mut days)?,
+        ActivitySource::ActivityWatch => fetch_activitywatch(config, &mut days)?,
+    }
+
+    for day in &mut days {
+        day.categories.sort_by_key(|(_, seconds)| std::cmp::Reverse(*seconds));
+    }
+    Ok(days)
+}
@@ -0,0 +1,327 @@
+use crate::cli;
+use crate::reports::{self, DateRange, ReportDefinition};
+use chrono::{Duration, Local, NaiveDate, Utc};
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc;
+use std::time::Duration as Timeout;
+
+// HTTP API for dashboards, scripts and other tools that embed the tracker. Only listens on
+// localhost, only answers requests addressed to 127.0.0.1 or localhost, and reads the data
+// files on every request, so it always answers with what was last saved. Starting and stopping timers goes through the app, which carries it out on
+// its next frame, and has to be allowed in the settings.
+//
+// Endpoints and fields are only ever added within an API_VERSION, so clients can rely on
+// what they use staying the same:
+//   GET  /                      the version and the endpoints
+//   GET  /tasks                 tasks that aren't archived
+//   GET  /tasks/<id>            one task
+//   POST /tasks/<id>/start      start its timer, pausing the one running
+//   POST /tasks/<id>/stop       pause its timer
+//   GET  /report                totals for a date range, see report()
+//   GET  /calendar.ics          tracked sessions as a calendar feed
+//
+// e.g. curl -X POST http://127.0.0.1:7384/tasks/<id>/start
+pub const DEFAULT_PORT: u16 = 7384;
+pub const API_VERSION: u32 = 1;
+const DEFAULT_FEED_DAYS: i64 = 90;
+const MAX_FEED_DAYS: i64 = 3660; // Ten years, more would only slow down the calendar app
+// A client that stops sending or reading gives up its connection after this long
+const CONNECTION_TIMEOUT: Timeout = Timeout::from_secs(10);
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ApiConfig {
+    pub enabled: bool,
+    pub port: u16,
+    #[serde(default)]
+    pub allow_control: bool, // Let clients start and stop timers
+}
+
+impl Default for ApiConfig {
+    fn default() -> Self {
+        ApiConfig { enabled: false, port: DEFAULT_PORT, allow_control: false }
+    }
+}
+
+pub enum Command {
+    Start(String), // Task id
+    Stop(String),
+}
+
+// A command for the app, answered on `reply` once it has been carried out
+pub struct Request {
+    pub command: Command,
+    pub reply: mpsc::Sender<Result<(), String>>,
+}
+
+// Commands go to `requests`, `ctx` wakes the app up to take them
+pub fn start(port: u16, requests: mpsc::Sender<Request>, ctx: egui::Context) -> Result<(), String> {
+    let listener = TcpListener::bind(("127.0.0.1", port)).map_err(|e| format!("port {}: {}", port, e))?;
+    std::thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            // Each connection on its own thread, so a slow client doesn't hold up the others
+            let (requests, ctx) = (requests.clone(), ctx.clone());
+            std::thread::spawn(move || {
+                if let Err(e) = handle(stream, port, &requests, &ctx) {
+                    tracing::warn!("API request failed: {}", e);
+                }
+            });
+        }
+    });
+    tracing::info!("API listening on http://127.0.0.1:{}", port);
+    Ok(())
+}
+
+fn handle(stream: TcpStream, port: u16, requests: &mpsc::Sender<Request>, ctx: &egui::Context) -> std::io::Result<()> {
+    stream.set_read_timeout(Some(CONNECTION_TIMEOUT))?;
+    stream.set_write_timeout(Some(CONNECTION_TIMEOUT))?;
+    let mut reader = BufReader::new(&stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    // Browsers send an Origin with requests a web page makes, other headers are ignored
+    let mut from_web_page = false;
+    let mut host = None;
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 2 {
+        let lowercase = header.to_ascii_lowercase();
+        from_web_page |= lowercase.starts_with("origin:");
+        if let Some(value) = lowercase.strip_prefix("host:") {
+            host = Some(value.trim().to_string());
+        }
+        header.clear();
+    }
+    // A web page whose domain was pointed at 127.0.0.1 sends its own domain as the host
+    let local_host = host.is_some_and(|host| host == format!("127.0.0.1:{}", port) || host == format!("localhost:{}", port));
+
+    let mut parts = request_line.split_whitespace();
+    let (method, target) = (parts.next().unwrap_or(""), parts.next().unwrap_or(""));
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+    let error = |message: String| serde_json::json!({ "error": message }).to_string();
+    let not_found = || ("404 Not Found", "application/json", error("Not found".to_string()));
+    let (status, content_type, body) = match (method, segments.as_slice()) {
+        // Turning the API off in the settings keeps the port, so it can be turned on again
+        _ if !cli::load_settings().api.enabled => {
+            ("503 Service Unavailable", "application/json", error("The API is turned off in the settings".to_string()))
+        }
+        _ if !local_host => ("421 Misdirected Request", "application/json", error(format!("Use http://127.0.0.1:{}", port))),
+        ("GET", [""]) => ("200 OK", "application/json", index().to_string()),
+        ("GET", ["tasks"]) => ("200 OK", "application/json", tasks().to_string()),
+        ("GET", ["tasks", id]) => match task(id) {
+            Some(task) => ("200 OK", "application/json", task.to_string()),
+            None => not_found(),
+        },
+        ("POST", ["tasks", id, action @ ("start" | "stop")]) => {
+            // A page open in the browser could otherwise start timers behind the user's back
+            if from_web_page {
+                ("403 Forbidden", "application/json", error("Requests from web pages aren't allowed".to_string()))
+            } else if !cli::load_settings().api.allow_control {
+                ("403 Forbidden", "application/json", error("Starting and stopping timers isn't allowed in the settings".to_string()))
+            } else {
+                let command = if *action == "start" { Command::Start(id.to_string()) } else { Command::Stop(id.to_string()) };
+                match send(command, requests, ctx) {
+                    Ok(()) => match task(id) {
+                        Some(task) => ("200 OK", "application/json", task.to_string()),
+                        None => not_found(),
+                    },
+                    Err(e) => ("409 Conflict", "application/json", error(e)),
+                }
+            }
+        }
+        ("GET", ["report"]) => match report(query) {
+            Ok(report) => ("200 OK", "application/json", report.to_string()),
+            Err(e) => ("400 Bad Request", "application/json", error(e)),
+        },
+        ("GET", ["calendar.ics"]) => match calendar_feed(query) {
+            Ok(feed) => ("200 OK", "text/calendar; charset=utf-8", feed),
+            Err(e) => ("400 Bad Request", "application/json", error(e)),
+        },
+        ("GET" | "POST", _) => not_found(),
+        _ => ("405 Method Not Allowed", "application/json", error("Only GET and POST are supported".to_string())),
+    };
+
+    write!(
+        &stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    )
+}
+
+// Hands the command to the app and waits until it has been carried out
+fn send(command: Command, requests: &mpsc::Sender<Request>, ctx: &egui::Context) -> Result<(), String> {
+    let (reply, answer) = mpsc::channel();
+    requests.send(Request { command, reply }).map_err(|_| "The app is closing".to_string())?;
+    ctx.request_repaint();
+    answer.recv_timeout(CONNECTION_TIMEOUT).map_err(|_| "The app didn't answer".to_string())?
+}
+
+fn index() -> serde_json::Value {
+    serde_json::json!({
+        "name": "work_timer",
+        "api_version": API_VERSION,
+        "endpoints": [
+            "GET /tasks",
+            "GET /tasks/<id>",
+            "POST /tasks/<id>/start",
+            "POST /tasks/<id>/stop",
+            "GET /report",
+            "GET /calendar.ics",
+        ],
+    })
+}
+
+fn task_json(task: &crate::Task) -> serde_json::Value {
+    serde_json::json!({
+        "id": task.id,
+        "description": task.description,
+        "folder": task.folder,
+        "tags": task.tags,
+        "billable": task.billable,
+        "running": task.start_time.is_some(),
+        "started_at": task.start_time,
+        "total_seconds": task.get_current_duration(),
+    })
+}
+
+// GET /tasks, the ones that aren't archived, by description
+fn tasks() -> serde_json::Value {
+    let tasks = cli::load_tasks();
+    let mut tasks: Vec<&crate::Task> = tasks.values().filter(|task| !task.archived).collect();
+    tasks.sort_by(|a, b| a.description.cmp(&b.description));
+    tasks.into_iter().map(task_json).collect()
+}
+
+// GET /tasks/<id>
+fn task(id: &str) -> Option<serde_json::Value> {
+    cli::load_tasks().get(id).map(task_json)
+}
+
+// Decoded names and values of the query string
+fn parameters(query: &str) -> Vec<(String, String)> {
+    url::form_urlencoded::parse(query.as_bytes()).into_owned().collect()
+}
+
+// GET /report?from=YYYY-MM-DD&to=YYYY-MM-DD&group_by=folder|day|tag&timezone=Zone/City
+// Without dates it covers the last 30 days. group_by takes a comma separated list.
+fn report(query: &str) -> Result<serde_json::Value, String> {
+    let settings = cli::load_settings();
+    let calendar = settings.calendar();
+
+    let mut definition = ReportDefinition::new("API".to_string());
+    definition.range = DateRange::Last30Days;
+    let mut from = None;
+    let mut to = None;
+    for (key, value) in parameters(query) {
+        match key.as_str() {
+            "from" | "to" => {
+                let date = NaiveDate::parse_from_str(&value, "%Y-%m-%d")
+                    .map_err(|_| format!("Invalid date: {} (use YYYY-MM-DD)", value))?;
+                if key == "from" {
+                    from = Some(date);
+                } else {
+                    to = Some(date);
+                }
+            }
+            "group_by" => definition.dimensions = cli::parse_list(&value, cli::parse_dimension)?,
+            "timezone" => definition.timezone = Some(value),
+            _ => return Err(format!("Unknown parameter: {}", key)),
+        }
+    }
+    definition.zone()?;
+    let today = definition.today(calendar);
+    if from.is_some() || to.is_some() {
+        let to = to.unwrap_or(today);
+        let from = from.unwrap_or(to - Duration::days(29));
+        if from > to {
+            return Err("from is after to".to_string());
+        }
+        definition.range = DateRange::Custom { from, to };
+    }
+
+    let tasks = cli::load_tasks();
+    let adjustments = cli::load_adjustments();
+    let expenses = cli::load_expenses();
+    let tasks = tasks.values().filter(|task| !task.archived);
+    Ok(reports::build_json(&definition, tasks, &adjustments, &expenses, today, calendar, &settings.billing))
+}
+
+// GET /calendar.ics?days=N
+// Tracked sessions of the last N days (90 by default, at most 3660) as events, for calendar
+// apps to subscribe to. Archived tasks are left out, as they are from /report.
+fn calendar_feed(query: &str) -> Result<String, String> {
+    let mut days = DEFAULT_FEED_DAYS;
+    for (key, value) in parameters(query) {
+        match key.as_str() {
+            "days" => {
+                days = value
+                    .parse()
+                    .ok()
+                    .filter(|days| (1..=MAX_FEED_DAYS).contains(days))
+                    .ok_or_else(|| format!("Invalid days: {} (use 1 to {})", value, MAX_FEED_DAYS))?
+            }
+            _ => return Err(format!("Unknown parameter: {}", key)),
+        }
+    }
+    let since = Duration::try_days(days)
+        .and_then(|days| Local::now().checked_sub_signed(days))
+        .ok_or_else(|| format!("Invalid days: {}", days))?;
+    let stamp = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+    let time = |time: chrono::DateTime<Local>| time.with_timezone(&Utc).format("%Y%m%dT%H%M%SZ").to_string();
+
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//work_timer//Tracked time//EN".to_string(),
+        "X-WR-CALNAME:Tracked time".to_string(),
+        "REFRESH-INTERVAL;VALUE=DURATION:PT15M".to_string(),
+    ];
+    for task in cli::load_tasks().values().filter(|task| !task.archived) {
+        for entry in task.entries.iter().filter(|entry| entry.end > since) {
+            lines.push("BEGIN:VEVENT".to_string());
+            // Sessions don't have ids, but a task never has two starting at the same moment
+            lines.push(format!("UID:{}-{}@work_timer", task.id, entry.start.timestamp()));
+            lines.push(format!("DTSTAMP:{}", stamp));
+            lines.push(format!("DTSTART:{}", time(entry.start)));
+            lines.push(format!("DTEND:{}", time(entry.end)));
+            lines.push(format!("SUMMARY:{}", escape(&task.description)));
+            if let Some(folder) = &task.folder {
+                lines.push(format!("CATEGORIES:{}", escape(folder)));
+            }
+            if let Some(annotation) = &entry.annotation {
+                lines.push(format!("DESCRIPTION:{}", escape(annotation)));
+            }
+            lines.push("TRANSP:TRANSPARENT".to_string());
+            lines.push("END:VEVENT".to_string());
+        }
+    }
+    lines.push("END:VCALENDAR".to_string());
+    Ok(lines.iter().map(|line| fold(line)).collect::<Vec<_>>().join("\r\n") + "\r\n")
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+        .replace('\r', "")
+}
+
+// Lines longer than 75 bytes continue on the next line after a space
+fn fold(line: &str) -> String {
+    let mut folded = String::new();
+    let mut length = 0;
+    for c in line.chars() {
+        if length + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            length = 1;
+        }
+        folded.push(c);
+        length += c.len_utf8();
+    }
+    folded
+}
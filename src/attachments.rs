@@ -0,0 +1,76 @@
+use crate::backup::ATTACHMENTS_DIR;
+use eframe::egui;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// Attachments are meant for screenshots and small documents, not for storing large files
+pub const MAX_ATTACHMENT_BYTES: u64 = 10 * 1024 * 1024;
+const THUMBNAIL_SIZE: u32 = 96;
+
+// Copies the file into the task's folder under attachments/. Returns the path of the copy,
+// which is what the task refers to.
+pub fn add(task_id: &str, source: &Path) -> Result<String, String> {
+    let size = fs::metadata(source).map_err(|e| format!("{}: {}", source.display(), e))?.len();
+    if size > MAX_ATTACHMENT_BYTES {
+        return Err(format!("{} is larger than {} MB", source.display(), MAX_ATTACHMENT_BYTES / 1024 / 1024));
+    }
+    let name = source
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .ok_or_else(|| format!("{} is not a file", source.display()))?;
+    // The extension is kept apart, the file name sanitizer would replace its dot
+    let (stem, extension) = match name.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() => (crate::sanitize_filename(stem), format!(".{}", crate::sanitize_filename(extension))),
+        _ => (crate::sanitize_filename(&name), String::new()),
+    };
+
+    let dir = Path::new(ATTACHMENTS_DIR).join(task_id);
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let target = unique_path(&dir, &stem, &extension);
+    fs::copy(source, &target).map_err(|e| format!("{}: {}", source.display(), e))?;
+    // Stored with forward slashes so data files stay the same across platforms
+    Ok(format!("{}/{}/{}", ATTACHMENTS_DIR, task_id, target.file_name().unwrap_or_default().to_string_lossy()))
+}
+
+fn unique_path(dir: &Path, stem: &str, extension: &str) -> PathBuf {
+    let mut path = dir.join(format!("{}{}", stem, extension));
+    let mut counter = 1;
+    while path.exists() {
+        path = dir.join(format!("{}_{}{}", stem, counter, extension));
+        counter += 1;
+    }
+    path
+}
+
+// Deletes the file, and the task's folder once it is empty
+pub fn remove(path: &str) {
+    if let Err(e) = fs::remove_file(path) {
+        tracing::warn!("Failed to delete attachment {}: {}", path, e);
+    }
+    if let Some(dir) = Path::new(path).parent() {
+        // Fails while other attachments are left, which is fine
+        let _ = fs::remove_dir(dir);
+    }
+}
+
+pub fn file_name(path: &str) -> &str {
+    path.rsplit('/').next().unwrap_or(path)
+}
+
+pub fn is_image(path: &str) -> bool {
+    path.to_lowercase().ends_with(".png")
+}
+
+// A small preview of a PNG attachment, None for other files or when it can't be read
+pub fn thumbnail(path: &str) -> Option<egui::ColorImage> {
+    if !is_image(path) {
+        return None;
+    }
+    let image = image::open(path)
+        .map_err(|e| tracing::warn!("Failed to read attachment {}: {}", path, e))
+        .ok()?
+        .thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE)
+        .to_rgba8();
+    let size = [image.width() as usize, image.height() as usize];
+    Some(egui::ColorImage::from_rgba_unmultiplied(size, image.as_raw()))
+}
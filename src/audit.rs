@@ -0,0 +1,66 @@
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::{fs, io::Write};
+
+// Append-only record of what happened to tasks, one JSON object per line
+pub const AUDIT_LOG: &str = "audit_log.jsonl";
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AuditEvent {
+    pub timestamp: DateTime<Local>,
+    pub action: String, // "created", "started", "stopped", "completed", "duration edited", ...
+    pub task_id: String,
+    pub task: String,
+    pub folder: Option<String>,
+    pub duration_delta: i64, // Seconds added to (or removed from) the task's tracked time
+}
+
+pub fn record(event: &AuditEvent) -> Result<(), String> {
+    let line = serde_json::to_string(event).map_err(|e| e.to_string())?;
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(AUDIT_LOG)
+        .map_err(|e| e.to_string())?;
+    writeln!(file, "{}", line).map_err(|e| e.to_string())
+}
+
+// Records an action on a task. Failures are printed but never interrupt the action itself.
+pub fn log(action: &str, task: &crate::Task, duration_delta: i64) {
+    let event = AuditEvent {
+        timestamp: Local::now(),
+        action: action.to_string(),
+        task_id: task.id.clone(),
+        task: task.description.clone(),
+        folder: task.folder.clone(),
+        duration_delta,
+    };
+    if let Err(e) = record(&event) {
+        tracing::error!("Failed to write {}: {}", AUDIT_LOG, e);
+    }
+}
+
+// Lines that fail to parse (e.g. a write cut short) are skipped
+pub fn load() -> Vec<AuditEvent> {
+    fs::read_to_string(AUDIT_LOG)
+        .map(|data| data.lines().filter_map(|line| serde_json::from_str(line).ok()).collect())
+        .unwrap_or_default()
+}
+
+pub fn export_csv() -> Result<String, Box<dyn std::error::Error>> {
+    let filename = "audit_log_export.csv";
+    let mut writer = csv::Writer::from_path(filename)?;
+    writer.write_record(["Timestamp", "Action", "Task", "Folder", "Duration Delta (seconds)", "Task ID"])?;
+    for event in load() {
+        writer.write_record([
+            event.timestamp.to_rfc3339(),
+            event.action,
+            event.task,
+            event.folder.unwrap_or_default(),
+            event.duration_delta.to_string(),
+            event.task_id,
+        ])?;
+    }
+    writer.flush()?;
+    Ok(filename.to_string())
+}
@@ -0,0 +1,248 @@
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Component, Path};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+// Folder for files attached to tasks. Included in backups whenever it exists.
+pub const ATTACHMENTS_DIR: &str = "attachments";
+
+// Passwords and tokens in settings.json, as JSON pointers. Backups leave them out, so an
+// archive copied elsewhere doesn't hand out access to Harvest, Redmine and the rest.
+const SETTINGS_FILE: &str = "settings.json";
+const SECRETS: [&str; 8] = [
+    "/harvest/access_token",
+    "/redmine/api_key",
+    "/notion/token",
+    "/smtp/password",
+    "/task_import/asana_token",
+    "/task_import/trello_api_key",
+    "/task_import/trello_token",
+    "/activity/rescuetime_key",
+];
+
+// One file in a backup archive
+pub struct ZipEntry {
+    pub name: String,
+    pub data: Vec<u8>,
+}
+
+// Collects the given data files plus everything under the attachments folder. Secrets are
+// blanked in settings.json; if it can't be read as JSON it is left out altogether.
+pub fn collect(files: &[&str]) -> Result<Vec<ZipEntry>, String> {
+    let mut entries = Vec::new();
+    for file in files {
+        if Path::new(file).is_file() {
+            let mut data = fs::read(file).map_err(|e| format!("{}: {}", file, e))?;
+            if *file == SETTINGS_FILE {
+                match without_secrets(&data) {
+                    Some(stripped) => data = stripped,
+                    None => continue,
+                }
+            }
+            entries.push(ZipEntry { name: file.to_string(), data });
+        }
+    }
+    if Path::new(ATTACHMENTS_DIR).is_dir() {
+        collect_dir(Path::new(ATTACHMENTS_DIR), &mut entries)?;
+    }
+    Ok(entries)
+}
+
+fn without_secrets(settings: &[u8]) -> Option<Vec<u8>> {
+    let mut settings: serde_json::Value = serde_json::from_slice(settings).ok()?;
+    for pointer in SECRETS {
+        // Blanked rather than removed, so the settings still load
+        if let Some(value) = settings.pointer_mut(pointer) {
+            *value = serde_json::Value::String(String::new());
+        }
+    }
+    serde_json::to_vec_pretty(&settings).ok()
+}
+
+// The settings.json from an archive with the secrets of the current one filled in wherever
+// the archive has none, so restoring a backup doesn't sign out of every integration
+pub fn with_current_secrets(restored: &[u8], current: &[u8]) -> Vec<u8> {
+    let (Ok(mut settings), Ok(current)) =
+        (serde_json::from_slice::<serde_json::Value>(restored), serde_json::from_slice::<serde_json::Value>(current))
+    else {
+        return restored.to_vec();
+    };
+    for pointer in SECRETS {
+        let Some(secret) = current.pointer(pointer).filter(|value| value.as_str().is_some_and(|text| !text.is_empty())) else {
+            continue;
+        };
+        if let Some(value) = settings.pointer_mut(pointer).filter(|value| value.as_str().is_some_and(str::is_empty)) {
+            *value = secret.clone();
+        }
+    }
+    serde_json::to_vec_pretty(&settings).unwrap_or_else(|_| restored.to_vec())
+}
+
+pub fn collect_dir(dir: &Path, entries: &mut Vec<ZipEntry>) -> Result<(), String> {
+    for item in fs::read_dir(dir).map_err(|e| e.to_string())? {
+        let path = item.map_err(|e| e.to_string())?.path();
+        if path.is_dir() {
+            collect_dir(&path, entries)?;
+        } else {
+            let data = fs::read(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+            // ZIP paths always use forward slashes
+            let name = path.components().map(|part| part.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/");
+            entries.push(ZipEntry { name, data });
+        }
+    }
+    Ok(())
+}
+
+// Whether an archive entry is a file in the attachments folder. Anything that could end up
+// outside it, like .. or backslashes that Windows would take as separators, is not.
+pub fn is_attachment(name: &str) -> bool {
+    if name.contains('\\') {
+        return false;
+    }
+    let mut components = Path::new(name).components();
+    components.next() == Some(Component::Normal(ATTACHMENTS_DIR.as_ref()))
+        && components.clone().next().is_some()
+        && components.all(|component| matches!(component, Component::Normal(_)))
+}
+
+// Archives with more than 65 535 files or over 4 GiB are written as ZIP64
+pub fn write(path: &str, entries: &[ZipEntry]) -> Result<(), String> {
+    let file = fs::File::create(path).map_err(|e| e.to_string())?;
+    let mut archive = ZipWriter::new(file);
+    for entry in entries {
+        let options = SimpleFileOptions::default()
+            .compression_method(CompressionMethod::Deflated)
+            .large_file(entry.data.len() >= u32::MAX as usize);
+        archive.start_file(entry.name.as_str(), options).map_err(|e| format!("{}: {}", entry.name, e))?;
+        archive.write_all(&entry.data).map_err(|e| format!("{}: {}", entry.name, e))?;
+    }
+    archive.finish().map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+pub fn read(path: &str) -> Result<Vec<ZipEntry>, String> {
+    let file = fs::File::open(path).map_err(|e| e.to_string())?;
+    let mut archive = ZipArchive::new(file).map_err(|e| format!("not a valid zip archive: {}", e))?;
+    let mut entries = Vec::with_capacity(archive.len());
+    for index in 0..archive.len() {
+        let mut file = archive.by_index(index).map_err(|e| e.to_string())?;
+        if file.is_dir() {
+            continue;
+        }
+        let name = file.name().to_string();
+        let mut data = Vec::new();
+        // Reading to the end also checks the CRC
+        file.read_to_end(&mut data).map_err(|e| format!("{} is corrupted: {}", name, e))?;
+        entries.push(ZipEntry { name, data });
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A fresh path in the system's temporary folder, removed when dropped
+    struct TempFile(std::path::PathBuf);
+
+    impl TempFile {
+        fn new() -> Self {
+            TempFile(std::env::temp_dir().join(format!("work_timer_test_{}.zip", uuid::Uuid::new_v4())))
+        }
+
+        fn path(&self) -> &str {
+            self.0.to_str().unwrap()
+        }
+    }
+
+    impl Drop for TempFile {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.0);
+        }
+    }
+
+    #[test]
+    fn write_then_read_gives_back_the_entries() {
+        let file = TempFile::new();
+        let entries = vec![
+            ZipEntry { name: "tasks.json".to_string(), data: br#"{"a":{"id":"a"}}"#.to_vec() },
+            ZipEntry { name: "empty.json".to_string(), data: Vec::new() },
+            ZipEntry { name: "attachments/a/notes ü.txt".to_string(), data: "ünïcode\n".repeat(1000).into_bytes() },
+            ZipEntry { name: "attachments/a/image.bin".to_string(), data: (0..=255u8).cycle().take(100_000).collect() },
+        ];
+        write(file.path(), &entries).unwrap();
+        let read_back = read(file.path()).unwrap();
+        assert_eq!(read_back.len(), entries.len());
+        for (read, written) in read_back.iter().zip(&entries) {
+            assert_eq!(read.name, written.name);
+            assert_eq!(read.data, written.data, "{}", written.name);
+        }
+    }
+
+    #[test]
+    fn empty_archive_round_trips() {
+        let file = TempFile::new();
+        write(file.path(), &[]).unwrap();
+        assert!(read(file.path()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn corrupted_archive_is_refused() {
+        let file = TempFile::new();
+        let data = b"the same line over and over\n".repeat(100);
+        write(file.path(), &[ZipEntry { name: "tasks.json".to_string(), data }]).unwrap();
+        let mut bytes = fs::read(file.path()).unwrap();
+        // Inside the compressed data of the only entry, after its 30 byte header and name
+        bytes[30 + "tasks.json".len() + 2] ^= 0xff;
+        fs::write(file.path(), &bytes).unwrap();
+        assert!(read(file.path()).is_err());
+
+        fs::write(file.path(), b"not a zip").unwrap();
+        assert!(read(file.path()).err().is_some_and(|e| e.starts_with("not a valid zip archive")));
+    }
+
+    #[test]
+    fn backups_leave_out_secrets_and_restores_keep_the_current_ones() {
+        let mut settings = crate::Settings::default();
+        settings.harvest.access_token = "harvest-token".to_string();
+        settings.smtp.password = "hunter2".to_string();
+        settings.activity.rescuetime_key = "rescuetime-key".to_string();
+        settings.harvest.account_id = "12345".to_string();
+        let current = serde_json::to_vec(&settings).unwrap();
+        let value: serde_json::Value = serde_json::from_slice(&current).unwrap();
+        for pointer in SECRETS {
+            assert!(value.pointer(pointer).is_some_and(serde_json::Value::is_string), "{} isn't in the settings", pointer);
+        }
+
+        let backed_up = without_secrets(&current).unwrap();
+        let text = String::from_utf8(backed_up.clone()).unwrap();
+        assert!(!text.contains("harvest-token") && !text.contains("hunter2") && !text.contains("rescuetime-key"));
+        assert!(text.contains("12345"));
+        let loaded: crate::Settings = serde_json::from_slice(&backed_up).unwrap();
+        assert!(loaded.harvest.access_token.is_empty());
+        assert!(without_secrets(b"not json").is_none());
+
+        let mut elsewhere = crate::Settings::default();
+        elsewhere.smtp.password = "other-password".to_string();
+        let restored = with_current_secrets(&backed_up, &serde_json::to_vec(&elsewhere).unwrap());
+        let restored: crate::Settings = serde_json::from_slice(&restored).unwrap();
+        assert_eq!(restored.smtp.password, "other-password");
+        assert!(restored.harvest.access_token.is_empty());
+        assert_eq!(restored.harvest.account_id, "12345");
+    }
+
+    #[test]
+    fn only_paths_inside_the_attachments_folder_are_attachments() {
+        assert!(is_attachment("attachments/task/file.txt"));
+        assert!(is_attachment("attachments/file.txt"));
+        assert!(!is_attachment("attachments"));
+        assert!(!is_attachment("attachments/"));
+        assert!(!is_attachment("tasks.json"));
+        assert!(!is_attachment("attachments/../tasks.json"));
+        assert!(!is_attachment("attachments/task/../../settings.json"));
+        assert!(!is_attachment("attachments\\..\\tasks.json"));
+        assert!(!is_attachment("/attachments/file.txt"));
+        assert!(!is_attachment("./attachments/file.txt"));
+    }
+}
@@ -0,0 +1,151 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+// Tax on billable amounts and how amounts are written in reports, exports and statistics.
+// Amounts are hours times the task's hourly rate. In inclusive mode the rates already
+// contain the tax, otherwise it is added on top.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct BillingConfig {
+    #[serde(default)]
+    pub tax_percent: f64, // 0 for no tax line
+    #[serde(default)]
+    pub tax_inclusive: bool,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub folder_tax_percents: HashMap<String, f64>, // Per client folder, e.g. 0 for a client abroad
+    #[serde(default)]
+    pub number_format: NumberFormat,
+}
+
+// Subtotal, tax and total of an amount
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Taxed {
+    pub subtotal: f64,
+    pub tax: f64,
+    pub total: f64,
+}
+
+impl std::ops::AddAssign for Taxed {
+    fn add_assign(&mut self, other: Taxed) {
+        self.subtotal += other.subtotal;
+        self.tax += other.tax;
+        self.total += other.total;
+    }
+}
+
+impl BillingConfig {
+    pub fn tax_percent(&self, folder: Option<&str>) -> f64 {
+        folder
+            .and_then(|folder| self.folder_tax_percents.get(folder))
+            .copied()
+            .unwrap_or(self.tax_percent)
+    }
+
+    pub fn taxed(&self, amount: f64, folder: Option<&str>) -> Taxed {
+        let rate = self.tax_percent(folder) / 100.0;
+        if self.tax_inclusive {
+            let subtotal = amount / (1.0 + rate);
+            Taxed { subtotal, tax: amount - subtotal, total: amount }
+        } else {
+            Taxed { subtotal: amount, tax: amount * rate, total: amount * (1.0 + rate) }
+        }
+    }
+
+    pub fn format(&self, amount: f64) -> String {
+        self.number_format.format(amount)
+    }
+}
+
+// Separators for amounts, as customary where the invoices go
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+pub enum NumberFormat {
+    #[default]
+    Plain, // 1234.56, for spreadsheets in any locale
+    English, // 1,234.56
+    European, // 1.234,56
+    Swiss, // 1'234.56
+    French, // 1 234,56
+}
+
+impl NumberFormat {
+    pub const ALL: [NumberFormat; 5] =
+        [NumberFormat::Plain, NumberFormat::English, NumberFormat::European, NumberFormat::Swiss, NumberFormat::French];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            NumberFormat::Plain => "1234.56",
+            NumberFormat::English => "1,234.56",
+            NumberFormat::European => "1.234,56",
+            NumberFormat::Swiss => "1'234.56",
+            NumberFormat::French => "1 234,56",
+        }
+    }
+
+    fn separators(&self) -> (Option<char>, char) {
+        match self {
+            NumberFormat::Plain => (None, '.'),
+            NumberFormat::English => (Some(','), '.'),
+            NumberFormat::European => (Some('.'), ','),
+            NumberFormat::Swiss => (Some('\''), '.'),
+            NumberFormat::French => (Some('\u{202f}'), ','),
+        }
+    }
+
+    // With two decimals
+    pub fn format(&self, amount: f64) -> String {
+        let (thousands, decimal) = self.separators();
+        let plain = format!("{:.2}", amount.abs());
+        let (whole, fraction) = plain.split_once('.').unwrap_or((&plain, "00"));
+        let mut grouped = String::new();
+        for (i, digit) in whole.chars().enumerate() {
+            if let Some(separator) = thousands.filter(|_| i > 0 && (whole.len() - i) % 3 == 0) {
+                grouped.push(separator);
+            }
+            grouped.push(digit);
+        }
+        // No minus sign on an amount that rounds to zero
+        let sign = if amount < 0.0 && plain.chars().any(|c| c.is_ascii_digit() && c != '0') { "-" } else { "" };
+        format!("{}{}{}{}", sign, grouped, decimal, fraction)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exclusive_tax_is_added() {
+        let billing = BillingConfig { tax_percent: 20.0, ..Default::default() };
+        let taxed = billing.taxed(100.0, None);
+        assert_eq!(taxed.subtotal, 100.0);
+        assert!((taxed.tax - 20.0).abs() < 1e-9);
+        assert!((taxed.total - 120.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn inclusive_tax_is_taken_out() {
+        let billing = BillingConfig { tax_percent: 25.0, tax_inclusive: true, ..Default::default() };
+        let taxed = billing.taxed(125.0, Some("Client"));
+        assert!((taxed.subtotal - 100.0).abs() < 1e-9);
+        assert!((taxed.tax - 25.0).abs() < 1e-9);
+        assert_eq!(taxed.total, 125.0);
+    }
+
+    #[test]
+    fn folder_rate_overrides_the_default() {
+        let mut billing = BillingConfig { tax_percent: 19.0, ..Default::default() };
+        billing.folder_tax_percents.insert("Abroad".to_string(), 0.0);
+        assert_eq!(billing.taxed(50.0, Some("Abroad")).tax, 0.0);
+        assert!((billing.taxed(100.0, Some("Local")).tax - 19.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn formats_with_separators() {
+        assert_eq!(NumberFormat::Plain.format(1234567.891), "1234567.89");
+        assert_eq!(NumberFormat::English.format(1234567.891), "1,234,567.89");
+        assert_eq!(NumberFormat::European.format(1234.5), "1.234,50");
+        assert_eq!(NumberFormat::Swiss.format(-1234.5), "-1'234.50");
+        assert_eq!(NumberFormat::French.format(999.999), "1\u{202f}000,00");
+        assert_eq!(NumberFormat::English.format(123.0), "123.00");
+        assert_eq!(NumberFormat::English.format(-0.001), "0.00");
+    }
+}
@@ -0,0 +1,580 @@
+use crate::reports::{self, DateRange, Dimension, Measure, ReportDefinition, ReportFormat};
+use crate::paths;
+use crate::schema;
+use crate::{Adjustment, DurationFormat, EntrySource, Expense, Settings, Task, TimeEntry, WorkTimer};
+use chrono::{DateTime, Duration, Local, NaiveDate};
+use std::{collections::HashMap, fs, path::Path};
+
+const DEFAULT_STATUS_FORMAT: &str = "{task} {elapsed}";
+
+// Shape of the made up data from --generate-test-data
+const TEST_FOLDERS: usize = 20;
+const TEST_SESSIONS_PER_TASK: usize = 60;
+const TEST_HISTORY_DAYS: i64 = 730;
+
+const USAGE: &str = "Usage:
+  work_timer status [--format <format>] [--idle <text>] [--json]
+  work_timer report [--from <date>] [--to <date>] [--by <dimension>,...]
+                    [--measure <measure>,...] [--format csv|markdown]
+                    [--folder <name>] [--tag <tag>] [--billable] [--output <file>]
+                    [--timezone <zone>]
+  work_timer report --saved <name> [--timezone <zone>] [--output <file>]
+  work_timer export --all | --entries [--append]
+  work_timer validate [<file>...] [--as <data file>]
+  work_timer validate --print-schema <data file>
+
+status prints the running task on a single line, for status bars like polybar,
+waybar or tmux. Placeholders: {task} {folder} {elapsed} {total}
+--format org prints today's sessions of the running task as Emacs org-mode CLOCK
+lines instead, newest first, with the running one ending now.
+
+report prints a report to standard output, or to --output. Dates are YYYY-MM-DD.
+Dimensions: day, week, month, folder, tag, task (default folder).
+Measures: hours, billable-hours, amount, tax, total, expenses (default hours).
+--saved runs a report saved in the Reports window instead.
+--timezone counts days in another time zone, e.g. a client's America/New_York.
+
+export writes the same files as the app: --all the task summary CSV,
+--entries every session as JSON Lines (--append adds only new ones).
+
+validate checks data files before the app loads them, by default every one in
+the data folder. Files are recognized by name, --as tells which data file a
+file with another name stands in for, e.g. --as tasks.json. Besides the schema
+it checks that the app can read the file and saves it unchanged.
+--print-schema prints the JSON Schema of tasks.json, folders.json,
+adjustments.json, expenses.json, time_off.json, plan.json or allocations.json.
+
+The data is kept in the app data folder of the system, WORK_TIMER_DATA_DIR
+sets another one.";
+
+// Handles command line invocations. Returns the exit code when the arguments
+// were a CLI command, or None when the GUI should start.
+pub fn run(args: &[String]) -> Option<i32> {
+    let command = args.first().map(String::as_str);
+    // validate only reads the files it is given
+    if matches!(command, Some("status" | "report" | "export" | "--generate-test-data")) {
+        note_old_data();
+    }
+    match command {
+        Some("status") => Some(status(&args[1..])),
+        Some("report") => Some(report(&args[1..])),
+        Some("export") => Some(export(&args[1..])),
+        Some("validate") => Some(validate(&args[1..])),
+        Some("--generate-test-data") => Some(generate_test_data(&args[1..])),
+        Some("help" | "--help" | "-h") => {
+            println!("{}", USAGE);
+            Some(0)
+        }
+        _ => None,
+    }
+}
+
+// The data of an older version is only moved when the app starts
+fn note_old_data() {
+    if let Some(dir) = paths::old_data_dir() {
+        eprintln!(
+            "Note: the data in {} is from an older version. Start the app once to move it to {}.",
+            dir.display(),
+            std::env::current_dir().unwrap_or_default().display()
+        );
+    }
+}
+
+pub fn load_tasks() -> HashMap<String, Task> {
+    crate::migrate_tasks(
+        fs::read_to_string("tasks.json")
+            .ok()
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .unwrap_or_default(),
+    )
+}
+
+pub fn load_adjustments() -> Vec<Adjustment> {
+    fs::read_to_string("adjustments.json")
+        .ok()
+        .and_then(|data| serde_json::from_str(&data).ok())
+        .unwrap_or_default()
+}
+
+pub fn load_expenses() -> Vec<Expense> {
+    fs::read_to_string("expenses.json")
+        .ok()
+        .and_then(|data| serde_json::from_str(&data).ok())
+        .unwrap_or_default()
+}
+
+pub fn load_settings() -> Settings {
+    fs::read_to_string("settings.json")
+        .ok()
+        .and_then(|data| serde_json::from_str(&data).ok())
+        .unwrap_or_default()
+}
+
+// Values may be given once as a comma separated list or with the option repeated
+pub fn parse_list<T>(value: &str, parse: impl Fn(&str) -> Option<T>) -> Result<Vec<T>, String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(|item| parse(item).ok_or_else(|| format!("Unknown value: {}", item)))
+        .collect()
+}
+
+pub fn parse_dimension(text: &str) -> Option<Dimension> {
+    match text.to_lowercase().as_str() {
+        "day" => Some(Dimension::Day),
+        "week" => Some(Dimension::Week),
+        "month" => Some(Dimension::Month),
+        "folder" | "client" => Some(Dimension::Folder),
+        "tag" => Some(Dimension::Tag),
+        "task" => Some(Dimension::Task),
+        _ => None,
+    }
+}
+
+fn parse_measure(text: &str) -> Option<Measure> {
+    match text.to_lowercase().as_str() {
+        "hours" => Some(Measure::Hours),
+        "billable-hours" | "billable" => Some(Measure::BillableHours),
+        "amount" | "billable-amount" => Some(Measure::BillableAmount),
+        "tax" => Some(Measure::Tax),
+        "total" | "total-amount" => Some(Measure::TotalAmount),
+        "expenses" => Some(Measure::Expenses),
+        _ => None,
+    }
+}
+
+fn report(args: &[String]) -> i32 {
+    let mut definition = ReportDefinition::new("Report".to_string());
+    definition.range = DateRange::AllTime;
+    let mut dimensions = Vec::new();
+    let mut measures = Vec::new();
+    let mut from = None;
+    let mut to = None;
+    let mut saved = None;
+    let mut output = None;
+    let mut timezone = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == "--billable" {
+            definition.billable_only = true;
+            continue;
+        }
+        let Some(value) = args.next() else {
+            eprintln!("{} needs a value\n\n{}", arg, USAGE);
+            return 2;
+        };
+        let parsed = match arg.as_str() {
+            "--from" | "--to" => match NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+                Ok(date) => {
+                    if arg == "--from" {
+                        from = Some(date);
+                    } else {
+                        to = Some(date);
+                    }
+                    Ok(())
+                }
+                Err(_) => Err(format!("Invalid date: {} (use YYYY-MM-DD)", value)),
+            },
+            "--by" => parse_list(value, parse_dimension).map(|list| dimensions.extend(list)),
+            "--measure" => parse_list(value, parse_measure).map(|list| measures.extend(list)),
+            "--format" => match value.to_lowercase().as_str() {
+                "csv" => {
+                    definition.format = ReportFormat::Csv;
+                    Ok(())
+                }
+                "markdown" | "md" => {
+                    definition.format = ReportFormat::Markdown;
+                    Ok(())
+                }
+                _ => Err(format!("Unknown format: {}", value)),
+            },
+            "--folder" | "--tag" | "--saved" | "--output" | "--timezone" => {
+                let value = Some(value.clone());
+                match arg.as_str() {
+                    "--folder" => definition.folder = value,
+                    "--tag" => definition.tag = value,
+                    "--saved" => saved = value,
+                    "--timezone" => timezone = value,
+                    _ => output = value,
+                }
+                Ok(())
+            }
+            _ => Err(format!("Unknown argument: {}", arg)),
+        };
+        if let Err(e) = parsed {
+            eprintln!("{}\n\n{}", e, USAGE);
+            return 2;
+        }
+    }
+
+    let settings = load_settings();
+    let calendar = settings.calendar();
+    if let Some(name) = &saved {
+        let Some(found) = settings.reports.into_iter().find(|report| report.name.eq_ignore_ascii_case(name)) else {
+            eprintln!("No saved report named \"{}\"", name);
+            return 1;
+        };
+        definition = found;
+    }
+    if timezone.is_some() {
+        definition.timezone = timezone;
+    }
+    if let Err(e) = definition.zone() {
+        eprintln!("{}", e);
+        return 2;
+    }
+    let today = definition.today(calendar);
+    if saved.is_none() {
+        if !dimensions.is_empty() {
+            definition.dimensions = dimensions;
+        }
+        if !measures.is_empty() {
+            definition.measures = measures;
+        }
+        if from.is_some() || to.is_some() {
+            let from = from.unwrap_or(NaiveDate::MIN);
+            let to = to.unwrap_or(today);
+            if from > to {
+                eprintln!("--from is after --to");
+                return 2;
+            }
+            definition.range = DateRange::Custom { from, to };
+        }
+    }
+
+    let tasks = load_tasks();
+    let adjustments = load_adjustments();
+    let expenses = load_expenses();
+    let tasks = tasks.values().filter(|task| !task.archived);
+    let table = reports::build(&definition, tasks, &adjustments, &expenses, today, calendar, &settings.billing);
+    let text = match reports::render(&definition, &table) {
+        Ok(text) => text,
+        Err(e) => {
+            eprintln!("Failed to build report: {}", e);
+            return 1;
+        }
+    };
+    match output {
+        Some(path) => {
+            if let Err(e) = fs::write(paths::from_launch_dir(&path), text) {
+                eprintln!("Failed to write {}: {}", path, e);
+                return 1;
+            }
+            eprintln!("Report saved to {}", path);
+        }
+        None => print!("{}", text),
+    }
+    0
+}
+
+// Full path of a file the app wrote, which is in the data folder rather than where the user is
+fn in_data_dir(filename: &str) -> String {
+    std::env::current_dir().map(|dir| dir.join(filename).display().to_string()).unwrap_or_else(|_| filename.to_string())
+}
+
+// Runs the app's own exports against the data files in the current directory
+fn export(args: &[String]) -> i32 {
+    let mut all = false;
+    let mut entries = false;
+    let mut append = false;
+    for arg in args {
+        match arg.as_str() {
+            "--all" => all = true,
+            "--entries" => entries = true,
+            "--append" => append = true,
+            _ => {
+                eprintln!("Unknown argument: {}\n\n{}", arg, USAGE);
+                return 2;
+            }
+        }
+    }
+    if !all && !entries {
+        eprintln!("Nothing to export\n\n{}", USAGE);
+        return 2;
+    }
+
+    // Loaded as the app does, so the export follows the settings
+    let mut timer = WorkTimer::new();
+    timer.finish_loading();
+    if all {
+        match timer.export_to_csv() {
+            Ok(filename) => println!("Exported tasks to {}", in_data_dir(&filename)),
+            Err(e) => {
+                eprintln!("Error exporting CSV: {}", e);
+                return 1;
+            }
+        }
+    }
+    if entries {
+        match timer.export_entries_ndjson(append) {
+            Ok((filename, count)) => println!("Exported {} entries to {}", count, in_data_dir(&filename)),
+            Err(e) => {
+                eprintln!("Error exporting entries: {}", e);
+                return 1;
+            }
+        }
+    }
+    0
+}
+
+fn validate(args: &[String]) -> i32 {
+    let mut files = Vec::new();
+    let mut stands_in_for = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--as" | "--print-schema" => {
+                let Some(value) = args.next() else {
+                    eprintln!("{} needs a value\n\n{}", arg, USAGE);
+                    return 2;
+                };
+                if arg == "--as" {
+                    stands_in_for = Some(value.clone());
+                    continue;
+                }
+                let Some(schema) = schema::schema(value) else {
+                    eprintln!("There is no schema for {}, only for {}", value, schema::SCHEMA_FILES.join(", "));
+                    return 1;
+                };
+                println!("{}", serde_json::to_string_pretty(&schema).unwrap_or_default());
+                return 0;
+            }
+            _ if arg.starts_with("--") => {
+                eprintln!("Unknown argument: {}\n\n{}", arg, USAGE);
+                return 2;
+            }
+            _ => files.push(arg.clone()),
+        }
+    }
+    // Files named on the command line are where the user is, the default ones in the data folder
+    let named = !files.is_empty();
+    if files.is_empty() {
+        if stands_in_for.is_some() {
+            eprintln!("--as needs a file to check\n\n{}", USAGE);
+            return 2;
+        }
+        files = schema::CHECKED_FILES.iter().filter(|file| Path::new(file).exists()).map(|file| file.to_string()).collect();
+        if files.is_empty() {
+            println!("No data files in the data folder");
+            return 0;
+        }
+    }
+
+    let mut failed = false;
+    for file in &files {
+        let kind = stands_in_for.as_deref().unwrap_or(file);
+        if !schema::is_checked(kind) {
+            eprintln!("{}: not a data file the app reads, use --as to say which one it stands in for", file);
+            failed = true;
+            continue;
+        }
+        let path = if named { paths::from_launch_dir(file) } else { Path::new(file).to_path_buf() };
+        let problems = match fs::read_to_string(path) {
+            Ok(text) => schema::check_file(kind, &text),
+            Err(e) => vec![e.to_string()],
+        };
+        if problems.is_empty() {
+            println!("{}: ok", file);
+        }
+        for problem in &problems {
+            println!("{}: {}", file, problem);
+        }
+        failed |= !problems.is_empty();
+    }
+    if failed {
+        1
+    } else {
+        0
+    }
+}
+
+// Hidden from the usage text. Fills an empty data folder with made up tasks and sessions
+// so list rendering and statistics can be timed against a large history.
+fn generate_test_data(args: &[String]) -> i32 {
+    let Some(count) = args.first().and_then(|arg| arg.parse::<usize>().ok()) else {
+        eprintln!("Usage: work_timer --generate-test-data <number of tasks>");
+        return 2;
+    };
+    if Path::new("tasks.json").exists() {
+        eprintln!(
+            "tasks.json already exists. Set {} to an empty folder so no real data is replaced.",
+            paths::DATA_DIR_VAR
+        );
+        return 1;
+    }
+
+    // Fixed seed, so runs are comparable
+    let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+    let mut random = move |below: i64| {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        (state % below as u64) as i64
+    };
+
+    let now = Local::now();
+    let folders: Vec<String> = (1..=TEST_FOLDERS).map(|n| format!("Client {}", n)).collect();
+    let mut tasks = HashMap::new();
+    let mut sessions = 0;
+    for n in 0..count {
+        let mut task = Task::new(format!("Test task {}", n + 1));
+        task.folder = Some(folders[n % folders.len()].clone());
+        task.billable = random(2) == 0;
+        if random(4) == 0 {
+            task.tags.push(format!("tag{}", random(10)));
+        }
+
+        // Sessions of up to four hours, a few weeks apart on average, oldest first
+        let mut start = now - Duration::days(TEST_HISTORY_DAYS) + Duration::minutes(random(60 * 24 * 30));
+        for _ in 0..TEST_SESSIONS_PER_TASK {
+            let end = start + Duration::minutes(5 + random(240));
+            if end > now {
+                break;
+            }
+            task.entries.push(TimeEntry { source: EntrySource::Cli, ..TimeEntry::new(start, end) });
+            start = end + Duration::minutes(random(60 * 24 * 28));
+        }
+        task.created_at = task.entries.first().map(|entry| entry.start).or(task.created_at);
+        task.last_active = task.entries.last().map(|entry| entry.end);
+        sessions += task.entries.len();
+        tasks.insert(task.id.clone(), task);
+    }
+
+    let files = [
+        ("tasks.json", serde_json::to_string(&tasks)),
+        ("folders.json", serde_json::to_string(&folders)),
+    ];
+    for (file, data) in files {
+        if let Err(e) = data.map_err(|e| e.to_string()).and_then(|data| fs::write(file, data).map_err(|e| e.to_string())) {
+            eprintln!("Failed to write {}: {}", file, e);
+            return 1;
+        }
+    }
+    println!("Generated {} tasks with {} sessions in {} folders", count, sessions, folders.len());
+    0
+}
+
+fn status(args: &[String]) -> i32 {
+    let mut format = DEFAULT_STATUS_FORMAT.to_string();
+    let mut idle = String::new();
+    let mut json = false;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--json" => json = true,
+            "--format" | "--idle" => {
+                let Some(value) = args.next() else {
+                    eprintln!("{} needs a value\n\n{}", arg, USAGE);
+                    return 2;
+                };
+                if arg == "--format" {
+                    format = value.clone();
+                } else {
+                    idle = value.clone();
+                }
+            }
+            _ => {
+                eprintln!("Unknown argument: {}\n\n{}", arg, USAGE);
+                return 2;
+            }
+        }
+    }
+
+    // The GUI saves tasks.json whenever a timer starts or stops, so the running
+    // task is the one with a start time; elapsed time is computed at call time.
+    let tasks = load_tasks();
+    let running = tasks
+        .values()
+        .filter_map(|task| task.start_time.map(|start| (start, task)))
+        .max_by_key(|(start, _)| *start);
+
+    if json {
+        let status = match running {
+            Some((start, task)) => {
+                let elapsed = (Local::now() - start).num_seconds();
+                serde_json::json!({
+                    "running": true,
+                    "task": task.description,
+                    "folder": task.folder,
+                    "started_at": start.to_rfc3339(),
+                    "elapsed_seconds": elapsed,
+                    "elapsed": DurationFormat::Clock.format(elapsed),
+                    "total_seconds": task.get_current_duration(),
+                })
+            }
+            None => serde_json::json!({ "running": false }),
+        };
+        println!("{}", status);
+        return 0;
+    }
+
+    match running {
+        Some((_, task)) if format == "org" => {
+            for line in org_clock_lines(task, Local::now()) {
+                println!("{}", line);
+            }
+        }
+        Some((start, task)) => {
+            let elapsed = (Local::now() - start).num_seconds();
+            println!(
+                "{}",
+                format
+                    .replace("{task}", &task.description)
+                    .replace("{folder}", task.folder.as_deref().unwrap_or("Uncategorized"))
+                    .replace("{elapsed}", &DurationFormat::Clock.format(elapsed))
+                    .replace("{total}", &task.format_duration(DurationFormat::Clock))
+            );
+        }
+        None => println!("{}", idle),
+    }
+    0
+}
+
+// CLOCK: [2024-05-06 Mon 09:00]--[2024-05-06 Mon 10:30] =>  1:30, like org-clock-out writes
+fn org_clock_lines(task: &Task, now: DateTime<Local>) -> Vec<String> {
+    let timestamp = |time: DateTime<Local>| time.format("[%Y-%m-%d %a %H:%M]").to_string();
+    let running = task.start_time.map(|start| (start, now));
+    let mut sessions: Vec<_> = task
+        .entries
+        .iter()
+        .map(|entry| (entry.start, entry.end))
+        .chain(running)
+        .filter(|(start, _)| start.date_naive() == now.date_naive())
+        .collect();
+    sessions.sort_by_key(|(start, _)| std::cmp::Reverse(*start));
+    sessions
+        .into_iter()
+        .map(|(start, end)| {
+            let minutes = (end - start).num_minutes();
+            format!("CLOCK: {}--{} => {:>2}:{:02}", timestamp(start), timestamp(end), minutes / 60, minutes % 60)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::central_european;
+
+    #[test]
+    fn org_clock_lines_list_todays_sessions_newest_first() {
+        let mut task = Task::new("Review".to_string());
+        let session = |start: &str, end: &str| TimeEntry::new(central_european(start), central_european(end));
+        task.entries.push(session("2024-05-05 22:00", "2024-05-05 23:00"));
+        task.entries.push(session("2024-05-06 09:00", "2024-05-06 10:30"));
+        task.entries.push(session("2024-05-06 11:15", "2024-05-06 23:20"));
+        task.start_time = Some(central_european("2024-05-06 23:30"));
+
+        assert_eq!(
+            org_clock_lines(&task, central_european("2024-05-06 23:45")),
+            [
+                "CLOCK: [2024-05-06 Mon 23:30]--[2024-05-06 Mon 23:45] =>  0:15",
+                "CLOCK: [2024-05-06 Mon 11:15]--[2024-05-06 Mon 23:20] => 12:05",
+                "CLOCK: [2024-05-06 Mon 09:00]--[2024-05-06 Mon 10:30] =>  1:30",
+            ]
+        );
+    }
+}
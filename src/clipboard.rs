@@ -0,0 +1,170 @@
+use eframe::egui;
+use std::sync::mpsc;
+use std::time::Duration;
+
+// Recognizes ticket ids and issue links copied to the clipboard, so a task can be created
+// for them with one click. Folders can add their own patterns, where # stands for a
+// number and * for any text without spaces, e.g. SUPPORT-# or https://tracker.example.com/*/#.
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+const MAX_TEXT_LENGTH: usize = 300;
+const ISSUE_URL_PATTERNS: [&str; 4] = ["http*/issues/#", "http*/pull/#", "http*/merge_requests/#", "http*/browse/*"];
+
+#[derive(Debug, Clone)]
+pub struct Ticket {
+    pub key: String,            // Used as the task name, e.g. PROJ-1234 or repo#12
+    pub folder: Option<String>, // Folder whose pattern matched
+}
+
+// Sends the clipboard text whenever it changes. Whatever is on the clipboard when watching
+// starts is ignored. The thread ends once the receiver is dropped and the clipboard changes.
+pub fn watch(ctx: egui::Context) -> mpsc::Receiver<String> {
+    let (sender, receiver) = mpsc::channel();
+    std::thread::spawn(move || {
+        let mut clipboard = match arboard::Clipboard::new() {
+            Ok(clipboard) => clipboard,
+            Err(e) => {
+                tracing::warn!("Failed to open the clipboard: {}", e);
+                return;
+            }
+        };
+        let mut last = clipboard.get_text().ok();
+        loop {
+            std::thread::sleep(POLL_INTERVAL);
+            // Fails while the clipboard holds something other than text
+            let text = clipboard.get_text().ok();
+            if text != last {
+                if let Some(text) = &text {
+                    if sender.send(text.clone()).is_err() {
+                        return;
+                    }
+                    ctx.request_repaint();
+                }
+                last = text;
+            }
+        }
+    });
+    receiver
+}
+
+// Only a single id or link counts, not a ticket mentioned somewhere in copied prose
+pub fn recognize(text: &str, folder_patterns: &[(String, String)]) -> Option<Ticket> {
+    let text = text.trim();
+    if text.is_empty() || text.len() > MAX_TEXT_LENGTH || text.contains(char::is_whitespace) {
+        return None;
+    }
+    for (folder, pattern) in folder_patterns {
+        if let Some(found) = find(pattern, text) {
+            return Some(Ticket { key: key(found), folder: Some(folder.clone()) });
+        }
+    }
+    ISSUE_URL_PATTERNS
+        .iter()
+        .find_map(|pattern| find(pattern, text))
+        .or_else(|| is_issue_key(text).then(|| text.to_string()))
+        .map(|found| Ticket { key: key(found), folder: None })
+}
+
+// Links are named after the issue they point to
+fn key(found: String) -> String {
+    if !found.contains("://") {
+        return found;
+    }
+    let segments: Vec<&str> = found.trim_end_matches('/').split('/').collect();
+    match segments.as_slice() {
+        [.., "browse", key] => key.to_string(),
+        [.., "-", "merge_requests", number] => format!("!{}", number),
+        [.., repo, "issues" | "pull" | "merge_requests", number] => format!("{}#{}", repo, number),
+        [.., parent, last] => format!("{}/{}", parent, last),
+        _ => found.clone(),
+    }
+}
+
+// Jira style keys: PROJ-1234
+pub fn is_issue_key(text: &str) -> bool {
+    let Some((project, number)) = text.split_once('-') else {
+        return false;
+    };
+    project.len() >= 2
+        && project.starts_with(|c: char| c.is_ascii_uppercase())
+        && project.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit())
+        && !number.is_empty()
+        && number.chars().all(|c| c.is_ascii_digit())
+}
+
+// First part of the text matching the pattern
+fn find(pattern: &str, text: &str) -> Option<String> {
+    let pattern: Vec<char> = pattern.trim().chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    if pattern.is_empty() {
+        return None;
+    }
+    (0..text.len()).find_map(|start| {
+        let length = match_length(&pattern, &text[start..])?;
+        Some(text[start..start + length].iter().collect())
+    })
+}
+
+// Number of characters at the start of the text that match the whole pattern
+fn match_length(pattern: &[char], text: &[char]) -> Option<usize> {
+    let Some((&first, rest)) = pattern.split_first() else {
+        return Some(0);
+    };
+    let run = |accept: fn(&char) -> bool| -> Option<usize> {
+        let available = text.iter().take_while(|&c| accept(c)).count();
+        (1..=available)
+            .rev()
+            .find_map(|taken| match_length(rest, &text[taken..]).map(|length| taken + length))
+    };
+    match first {
+        '#' => run(|c| c.is_ascii_digit()),
+        '*' => run(|c| !c.is_whitespace()),
+        _ => {
+            let c = text.first()?;
+            if c.eq_ignore_ascii_case(&first) {
+                match_length(rest, &text[1..]).map(|length| length + 1)
+            } else {
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recognized(text: &str) -> Option<(String, Option<String>)> {
+        let patterns = [
+            ("Support".to_string(), "SUPPORT-#".to_string()),
+            ("Ops".to_string(), "https://tracker.example.com/*/#".to_string()),
+        ];
+        recognize(text, &patterns).map(|ticket| (ticket.key, ticket.folder))
+    }
+
+    #[test]
+    fn recognizes_ids_and_issue_links() {
+        let cases = [
+            ("PROJ-1234", "PROJ-1234", None),
+            ("  ABC2-7\n", "ABC2-7", None),
+            ("https://github.com/acme/widgets/issues/42", "widgets#42", None),
+            ("https://github.com/acme/widgets/pull/7/files", "widgets#7", None),
+            ("https://gitlab.com/acme/api/-/merge_requests/15", "!15", None),
+            ("https://acme.atlassian.net/browse/PROJ-12", "PROJ-12", None),
+            // Folder patterns come first and file the task in their folder
+            ("SUPPORT-88", "SUPPORT-88", Some("Support")),
+            ("https://tracker.example.com/ops/311", "ops/311", Some("Ops")),
+        ];
+        for (text, key, folder) in cases {
+            assert_eq!(recognized(text), Some((key.to_string(), folder.map(str::to_string))), "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn ignores_other_text() {
+        let long = format!("PROJ-{}", "1".repeat(MAX_TEXT_LENGTH));
+        let cases = ["", "   ", "Please look at PROJ-12", "P-12", "proj-12", "PROJ-", "PROJ-12a", "https://example.com/docs/page", &long];
+        for text in cases {
+            assert_eq!(recognized(text), None, "{:?}", text);
+        }
+    }
+}
@@ -0,0 +1,68 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+// Exports are written relative to the working directory; the system tools need full paths
+fn absolute(path: &str) -> Result<PathBuf, String> {
+    Path::new(path).canonicalize().map_err(|e| e.to_string())
+}
+
+fn spawn(command: &mut Command) -> Result<(), String> {
+    let mut child = command.spawn().map_err(|e| e.to_string())?;
+    // Reap the process in the background so it doesn't linger as a zombie
+    std::thread::spawn(move || {
+        let _ = child.wait();
+    });
+    Ok(())
+}
+
+// Opens the file with the system's default application for its type
+pub fn open(path: &str) -> Result<(), String> {
+    let path = absolute(path)?;
+    if cfg!(target_os = "macos") {
+        spawn(Command::new("open").arg(&path))
+    } else if cfg!(windows) {
+        // The empty argument is the window title `start` expects before the path
+        spawn(Command::new("cmd").args(["/C", "start", ""]).arg(&path))
+    } else {
+        spawn(Command::new("xdg-open").arg(&path))
+    }
+}
+
+// Opens the file manager with the file selected, or at least its folder
+pub fn reveal(path: &str) -> Result<(), String> {
+    let path = absolute(path)?;
+    if cfg!(target_os = "macos") {
+        spawn(Command::new("open").arg("-R").arg(&path))
+    } else if cfg!(windows) {
+        spawn(Command::new("explorer").arg(format!("/select,{}", path.display())))
+    } else {
+        // File managers implementing the freedesktop interface can select the file
+        let uri: String = format!("file://{}", path.display())
+            .bytes()
+            .map(|byte| match byte {
+                b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'/' | b':' | b'-' | b'_' | b'.' | b'~' => (byte as char).to_string(),
+                _ => format!("%{:02X}", byte),
+            })
+            .collect();
+        let selected = Command::new("gdbus")
+            .args([
+                "call",
+                "--session",
+                "--dest",
+                "org.freedesktop.FileManager1",
+                "--object-path",
+                "/org/freedesktop/FileManager1",
+                "--method",
+                "org.freedesktop.FileManager1.ShowItems",
+                &format!("['{}']", uri),
+                "",
+            ])
+            .output()
+            .is_ok_and(|output| output.status.success());
+        if selected {
+            return Ok(());
+        }
+        let folder = path.parent().unwrap_or(&path);
+        spawn(Command::new("xdg-open").arg(folder))
+    }
+}
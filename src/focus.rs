@@ -0,0 +1,136 @@
+use std::process::Command;
+use std::sync::mpsc;
+
+// Windows has no way for other apps to switch Focus Assist, so the option isn't shown there
+pub const SUPPORTED: bool = !cfg!(windows);
+
+// Turns the system's Do Not Disturb on while tracking, and back to how it was before
+// once tracking stops. Also put back when the app quits with it still on. The system
+// tools can take a while, so they run on a thread of their own and the outcome is polled.
+#[derive(Default)]
+pub struct DoNotDisturb {
+    previous: Option<String>, // The system setting from before, while turned on
+    switching: Option<(bool, mpsc::Receiver<Switched>)>, // Whether it's being turned on or off
+}
+
+// The setting to put back afterwards, if it's on now, and any problem switching
+type Switched = (Option<String>, Result<(), String>);
+
+impl DoNotDisturb {
+    // On, or being turned on
+    pub fn is_on(&self) -> bool {
+        match &self.switching {
+            Some((on, _)) => *on,
+            None => self.previous.is_some(),
+        }
+    }
+
+    // Starts turning it on or off, unless a switch is still going
+    pub fn switch(&mut self, on: bool) {
+        if self.switching.is_some() || on == self.previous.is_some() {
+            return;
+        }
+        let previous = self.previous.clone();
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            let _ = sender.send(switch(previous));
+        });
+        self.switching = Some((on, receiver));
+    }
+
+    // How the last switch went, once it's done
+    pub fn poll(&mut self) -> Option<Result<(), String>> {
+        let (_, receiver) = self.switching.as_ref()?;
+        let (previous, result) = match receiver.try_recv() {
+            Ok(switched) => switched,
+            Err(mpsc::TryRecvError::Empty) => return None,
+            Err(mpsc::TryRecvError::Disconnected) => (None, Err("switching stopped before finishing".to_string())),
+        };
+        self.switching = None;
+        self.previous = previous;
+        Some(result)
+    }
+}
+
+impl Drop for DoNotDisturb {
+    fn drop(&mut self) {
+        if let Some((_, receiver)) = self.switching.take() {
+            if let Ok((previous, _)) = receiver.recv() {
+                self.previous = previous;
+            }
+        }
+        // Nowhere left to report a failure while quitting
+        if let Some(previous) = self.previous.take() {
+            let _ = restore(&previous);
+        }
+    }
+}
+
+// Turns it on when there's nothing to put back, otherwise puts that back
+fn switch(previous: Option<String>) -> Switched {
+    match previous {
+        None => match turn_on() {
+            Ok(previous) => (Some(previous), Ok(())),
+            Err(e) => (None, Err(e)),
+        },
+        Some(previous) => (None, restore(&previous)),
+    }
+}
+
+fn run(command: &mut Command) -> Result<String, String> {
+    let output = command.output().map_err(|e| e.to_string())?;
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
+
+// macOS has no command for Focus, so it goes through two shortcuts the user creates
+// in the Shortcuts app. Whether a Focus was already on can't be read.
+#[cfg(target_os = "macos")]
+const SHORTCUT_ON: &str = "Work Timer Focus On";
+#[cfg(target_os = "macos")]
+const SHORTCUT_OFF: &str = "Work Timer Focus Off";
+
+#[cfg(target_os = "macos")]
+fn turn_on() -> Result<String, String> {
+    run(Command::new("shortcuts").args(["run", SHORTCUT_ON]))
+        .map_err(|e| format!("running the \"{}\" shortcut failed: {}", SHORTCUT_ON, e))?;
+    Ok(String::new())
+}
+
+#[cfg(target_os = "macos")]
+fn restore(_previous: &str) -> Result<(), String> {
+    run(Command::new("shortcuts").args(["run", SHORTCUT_OFF]))
+        .map(|_| ())
+        .map_err(|e| format!("running the \"{}\" shortcut failed: {}", SHORTCUT_OFF, e))
+}
+
+// Focus Assist can only be switched from the Windows settings, see SUPPORTED
+#[cfg(windows)]
+fn turn_on() -> Result<String, String> {
+    Err("Windows doesn't let other apps switch Focus Assist".to_string())
+}
+
+#[cfg(windows)]
+fn restore(_previous: &str) -> Result<(), String> {
+    Ok(())
+}
+
+// GNOME's Do Not Disturb is the notification banners setting
+#[cfg(not(any(target_os = "macos", windows)))]
+const GNOME_SCHEMA: &str = "org.gnome.desktop.notifications";
+
+#[cfg(not(any(target_os = "macos", windows)))]
+fn turn_on() -> Result<String, String> {
+    let previous = run(Command::new("gsettings").args(["get", GNOME_SCHEMA, "show-banners"]))
+        .map_err(|e| format!("only GNOME is supported ({})", e))?;
+    run(Command::new("gsettings").args(["set", GNOME_SCHEMA, "show-banners", "false"]))?;
+    Ok(previous)
+}
+
+#[cfg(not(any(target_os = "macos", windows)))]
+fn restore(previous: &str) -> Result<(), String> {
+    run(Command::new("gsettings").args(["set", GNOME_SCHEMA, "show-banners", previous])).map(|_| ())
+}
@@ -0,0 +1,145 @@
+use chrono::{DateTime, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+const BASE_URL: &str = "https://api.harvestapp.com/v2";
+
+// Key used in `TimeEntry::synced` for entries pushed to Harvest
+pub const SYNC_KEY: &str = "harvest";
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct HarvestConfig {
+    pub access_token: String, // Personal access token or OAuth2 access token
+    pub account_id: String,
+    #[serde(default)]
+    pub mappings: HashMap<String, HarvestMapping>, // Folder name -> Harvest project/task
+}
+
+impl HarvestConfig {
+    pub fn is_configured(&self) -> bool {
+        !self.access_token.trim().is_empty() && !self.account_id.trim().is_empty()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct HarvestMapping {
+    pub project_id: u64,
+    pub project_name: String,
+    pub task_id: u64,
+    pub task_name: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct HarvestProject {
+    pub id: u64,
+    pub name: String,
+    pub tasks: Vec<(u64, String)>,
+}
+
+// One task's tracked time for one day, pushed as a single Harvest time entry
+#[derive(Debug, Clone)]
+pub struct DailyEntry {
+    pub task_id: String,
+    pub date: NaiveDate,
+    pub hours: f64,
+    pub notes: String,
+    pub mapping: HarvestMapping,
+    pub entry_starts: Vec<DateTime<Local>>, // Starts of the local sessions folded into this entry
+}
+
+#[derive(Deserialize)]
+struct ProjectAssignmentsPage {
+    project_assignments: Vec<ProjectAssignment>,
+    next_page: Option<u32>,
+}
+
+#[derive(Deserialize)]
+struct ProjectAssignment {
+    project: NamedRef,
+    task_assignments: Vec<TaskAssignment>,
+}
+
+#[derive(Deserialize)]
+struct TaskAssignment {
+    task: NamedRef,
+}
+
+#[derive(Deserialize)]
+struct NamedRef {
+    id: u64,
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct CreatedTimeEntry {
+    id: u64,
+}
+
+fn request(config: &HarvestConfig, method: &str, path: &str) -> ureq::Request {
+    ureq::request(method, &format!("{}{}", BASE_URL, path))
+        .set("Authorization", &format!("Bearer {}", config.access_token.trim()))
+        .set("Harvest-Account-Id", config.account_id.trim())
+        .set("User-Agent", "Work Timer")
+}
+
+fn describe_error(error: ureq::Error) -> String {
+    match error {
+        ureq::Error::Status(code, response) => {
+            format!("HTTP {}: {}", code, response.into_string().unwrap_or_default())
+        }
+        ureq::Error::Transport(transport) => transport.to_string(),
+    }
+}
+
+// Projects and tasks the authenticated user can track time against
+pub fn fetch_projects(config: &HarvestConfig) -> Result<Vec<HarvestProject>, String> {
+    let mut projects = Vec::new();
+    let mut page = 1;
+
+    loop {
+        let response: ProjectAssignmentsPage = request(config, "GET", "/users/me/project_assignments")
+            .query("page", &page.to_string())
+            .call()
+            .map_err(describe_error)?
+            .into_json()
+            .map_err(|e| e.to_string())?;
+
+        for assignment in response.project_assignments {
+            projects.push(HarvestProject {
+                id: assignment.project.id,
+                name: assignment.project.name,
+                tasks: assignment
+                    .task_assignments
+                    .into_iter()
+                    .map(|task_assignment| (task_assignment.task.id, task_assignment.task.name))
+                    .collect(),
+            });
+        }
+
+        match response.next_page {
+            Some(next_page) => page = next_page,
+            None => break,
+        }
+    }
+
+    projects.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(projects)
+}
+
+// Creates the time entry in Harvest and returns its id
+pub fn push_entry(config: &HarvestConfig, entry: &DailyEntry) -> Result<u64, String> {
+    let body = serde_json::json!({
+        "project_id": entry.mapping.project_id,
+        "task_id": entry.mapping.task_id,
+        "spent_date": entry.date.to_string(),
+        "hours": (entry.hours * 100.0).round() / 100.0,
+        "notes": entry.notes,
+    });
+
+    let created: CreatedTimeEntry = request(config, "POST", "/time_entries")
+        .send_json(body)
+        .map_err(describe_error)?
+        .into_json()
+        .map_err(|e| e.to_string())?;
+    Ok(created.id)
+}
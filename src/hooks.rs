@@ -0,0 +1,43 @@
+use serde::{Deserialize, Serialize};
+use std::process::Command;
+
+// Shell commands run on timer events. Details are passed in WORK_TIMER_* environment variables.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct HookConfig {
+    #[serde(default)]
+    pub on_task_started: String,
+    #[serde(default)]
+    pub on_task_stopped: String,
+    #[serde(default)]
+    pub on_task_completed: String,
+    #[serde(default)]
+    pub on_day_ended: String,
+}
+
+// Starts the command through the platform shell without waiting for it to finish
+pub fn run(command: &str, env: &[(&str, String)]) -> Result<(), String> {
+    let command = command.trim();
+    if command.is_empty() {
+        return Ok(());
+    }
+
+    let mut shell = if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.args(["/C", command]);
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.args(["-c", command]);
+        shell
+    };
+    for (key, value) in env {
+        shell.env(key, value);
+    }
+
+    let mut child = shell.spawn().map_err(|e| e.to_string())?;
+    // Reap the process in the background so it doesn't linger as a zombie
+    std::thread::spawn(move || {
+        let _ = child.wait();
+    });
+    Ok(())
+}
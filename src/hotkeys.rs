@@ -0,0 +1,130 @@
+use eframe::egui;
+use global_hotkey::hotkey::HotKey;
+use global_hotkey::{GlobalHotKeyEvent, GlobalHotKeyManager, HotKeyState};
+use serde::{Deserialize, Serialize};
+use std::sync::mpsc;
+
+// System-wide hotkeys that work while another app has focus. Keys are written like
+// Ctrl+Alt+P, where CmdOrCtrl is Cmd on macOS and Ctrl elsewhere. On Linux this only works
+// under X11, Wayland doesn't let apps take keys from the others.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum GlobalAction {
+    ToggleTimer, // Pause the running timers, or resume the last task
+    QuickAdd,
+    ShowWindow,
+}
+
+impl GlobalAction {
+    pub const ALL: [GlobalAction; 3] = [GlobalAction::ToggleTimer, GlobalAction::QuickAdd, GlobalAction::ShowWindow];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            GlobalAction::ToggleTimer => "Pause / resume timer",
+            GlobalAction::QuickAdd => "Add a task",
+            GlobalAction::ShowWindow => "Show Work Timer",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GlobalHotkeyConfig {
+    pub enabled: bool,
+    pub toggle_timer: String, // Empty for no hotkey
+    pub quick_add: String,
+    pub show_window: String,
+}
+
+impl Default for GlobalHotkeyConfig {
+    fn default() -> Self {
+        GlobalHotkeyConfig {
+            enabled: false,
+            toggle_timer: "CmdOrCtrl+Alt+P".to_string(),
+            quick_add: "CmdOrCtrl+Alt+N".to_string(),
+            show_window: "CmdOrCtrl+Alt+W".to_string(),
+        }
+    }
+}
+
+impl GlobalHotkeyConfig {
+    pub fn keys_mut(&mut self, action: GlobalAction) -> &mut String {
+        match action {
+            GlobalAction::ToggleTimer => &mut self.toggle_timer,
+            GlobalAction::QuickAdd => &mut self.quick_add,
+            GlobalAction::ShowWindow => &mut self.show_window,
+        }
+    }
+
+    fn keys(&self, action: GlobalAction) -> &str {
+        match action {
+            GlobalAction::ToggleTimer => &self.toggle_timer,
+            GlobalAction::QuickAdd => &self.quick_add,
+            GlobalAction::ShowWindow => &self.show_window,
+        }
+    }
+}
+
+// The event handler can only be set once, so there is one of these for the whole run and
+// turning the hotkeys off unregisters them
+pub struct Hotkeys {
+    manager: GlobalHotKeyManager,
+    registered: Vec<(HotKey, GlobalAction)>,
+    events: mpsc::Receiver<GlobalHotKeyEvent>,
+}
+
+impl Hotkeys {
+    // Hotkeys wake up the UI, so they work while the window is hidden or minimized
+    pub fn new(ctx: &egui::Context) -> Result<Self, String> {
+        let manager = GlobalHotKeyManager::new().map_err(|e| e.to_string())?;
+        let (sender, events) = mpsc::channel();
+        let ctx = ctx.clone();
+        GlobalHotKeyEvent::set_event_handler(Some(move |event: GlobalHotKeyEvent| {
+            if event.state() == HotKeyState::Pressed {
+                let _ = sender.send(event);
+                ctx.request_repaint();
+            }
+        }));
+        Ok(Hotkeys { manager, registered: Vec::new(), events })
+    }
+
+    // Registers the configured keys in place of the previous ones. Returns what couldn't be
+    // registered, e.g. keys another app already uses.
+    pub fn apply(&mut self, config: &GlobalHotkeyConfig) -> Vec<String> {
+        for (hotkey, _) in self.registered.drain(..) {
+            let _ = self.manager.unregister(hotkey);
+        }
+        if !config.enabled {
+            return Vec::new();
+        }
+        let mut problems = Vec::new();
+        for action in GlobalAction::ALL {
+            let keys = config.keys(action).trim();
+            if keys.is_empty() {
+                continue;
+            }
+            let hotkey = match keys.parse::<HotKey>() {
+                Ok(hotkey) => hotkey,
+                Err(e) => {
+                    problems.push(format!("{}: {}", action.label(), e));
+                    continue;
+                }
+            };
+            match self.manager.register(hotkey) {
+                Ok(()) => self.registered.push((hotkey, action)),
+                Err(e) => problems.push(format!("{} ({}): {}", action.label(), keys, e)),
+            }
+        }
+        problems
+    }
+
+    pub fn pressed(&self) -> Vec<GlobalAction> {
+        self.events
+            .try_iter()
+            .filter_map(|event| {
+                self.registered
+                    .iter()
+                    .find(|(hotkey, _)| hotkey.id() == event.id())
+                    .map(|(_, action)| *action)
+            })
+            .collect()
+    }
+}
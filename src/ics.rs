@@ -0,0 +1,358 @@
+use chrono::{DateTime, Datelike, Local, NaiveDate, NaiveDateTime, TimeZone, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+// Reads meetings from a subscribed calendar, i.e. the secret ICS address Google Calendar,
+// Outlook and most others offer. Times with a TZID are taken as local time, and only the
+// common recurrence rules are understood (daily, weekly on given days, monthly and yearly
+// on the same date).
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CalendarGapConfig {
+    pub ics_url: String, // Empty to not read a calendar
+    pub min_gap_minutes: u32,
+    pub weekdays: Vec<Weekday>, // Days to suggest focus time on
+}
+
+impl Default for CalendarGapConfig {
+    fn default() -> Self {
+        CalendarGapConfig {
+            ics_url: String::new(),
+            min_gap_minutes: 30,
+            weekdays: vec![Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu, Weekday::Fri],
+        }
+    }
+}
+
+impl CalendarGapConfig {
+    pub fn is_configured(&self) -> bool {
+        !self.ics_url.trim().is_empty()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Meeting {
+    pub start: DateTime<Local>,
+    pub end: DateTime<Local>,
+}
+
+// Timed meetings on the date, earliest first. All-day, cancelled and free events are left out.
+pub fn fetch_meetings(url: &str, date: NaiveDate) -> Result<Vec<Meeting>, String> {
+    Ok(meetings_on(&fetch(url)?, date))
+}
+
+// Days covered by all-day events, with the event's title, e.g. from a vacation calendar or a
+// public holiday feed. Recurring all-day events like birthdays are left out.
+pub fn fetch_days_off(source: &str) -> Result<Vec<(NaiveDate, String)>, String> {
+    let mut days = Vec::new();
+    for event in parse(&fetch(source)?) {
+        let Some(first) = event.all_day_start.filter(|_| !event.cancelled && event.rule.is_none()) else {
+            continue;
+        };
+        // The end date is the day after the event
+        let end = event.all_day_end.unwrap_or(first + chrono::Duration::days(1));
+        days.extend(first.iter_days().take_while(|day| *day < end).map(|day| (day, event.summary.clone())));
+    }
+    days.sort();
+    Ok(days)
+}
+
+// An .ics file on disk or a calendar address
+fn fetch(source: &str) -> Result<String, String> {
+    let source = source.trim();
+    if std::path::Path::new(source).is_file() {
+        return std::fs::read_to_string(source).map_err(|e| e.to_string());
+    }
+    // webcal:// is how calendar apps spell a subscription, the feed itself is plain HTTP
+    let url = match source.strip_prefix("webcal://") {
+        Some(rest) => format!("https://{}", rest),
+        None => source.to_string(),
+    };
+    ureq::get(&url)
+        .timeout(std::time::Duration::from_secs(30))
+        .call()
+        .map_err(|e| e.to_string())?
+        .into_string()
+        .map_err(|e| e.to_string())
+}
+
+#[derive(Default)]
+struct Event {
+    uid: String,
+    start: Option<NaiveDateTime>,
+    end: Option<NaiveDateTime>,
+    duration: Option<chrono::Duration>,
+    rule: Option<HashMap<String, String>>,
+    exceptions: Vec<NaiveDate>,
+    recurrence_id: Option<NaiveDate>,
+    skipped: bool, // All-day, cancelled or marked free
+    cancelled: bool,
+    summary: String,
+    all_day_start: Option<NaiveDate>,
+    all_day_end: Option<NaiveDate>, // Exclusive
+}
+
+fn meetings_on(text: &str, date: NaiveDate) -> Vec<Meeting> {
+    let events = parse(text);
+    // Single occurrences of a series that were moved or changed replace the series on that day
+    let overridden: HashSet<(&str, NaiveDate)> = events
+        .iter()
+        .filter_map(|event| Some((event.uid.as_str(), event.recurrence_id?)))
+        .collect();
+
+    let mut meetings: Vec<Meeting> = events
+        .iter()
+        .filter(|event| !event.skipped)
+        .filter_map(|event| {
+            let start = event.start?;
+            let length = match (event.end, event.duration) {
+                (Some(end), _) => end - start,
+                (None, Some(duration)) => duration,
+                (None, None) => return None,
+            };
+            let occurs = match &event.rule {
+                Some(rule) if event.recurrence_id.is_none() => {
+                    !event.exceptions.contains(&date)
+                        && !overridden.contains(&(event.uid.as_str(), date))
+                        && occurs_on(rule, start.date(), date)
+                }
+                _ => start.date() == date,
+            };
+            if !occurs {
+                return None;
+            }
+            let start = local(date.and_time(start.time()))?;
+            Some(Meeting { start, end: start + length })
+        })
+        .collect();
+    meetings.sort_by_key(|meeting| meeting.start);
+    meetings
+}
+
+fn parse(text: &str) -> Vec<Event> {
+    // Long lines continue on the next line after a space or tab
+    let mut lines: Vec<String> = Vec::new();
+    for line in text.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ => lines.push(line.trim_end_matches('\r').to_string()),
+        }
+    }
+
+    let mut events = Vec::new();
+    let mut current: Option<Event> = None;
+    for line in &lines {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let (name, params) = name.split_once(';').unwrap_or((name, ""));
+        match (name, current.as_mut()) {
+            ("BEGIN", _) if value == "VEVENT" => current = Some(Event::default()),
+            ("END", Some(_)) if value == "VEVENT" => events.extend(current.take()),
+            ("UID", Some(event)) => event.uid = value.to_string(),
+            ("DTSTART", Some(event)) => {
+                event.start = parse_time(value, params);
+                event.skipped |= event.start.is_none();
+                event.all_day_start = parse_date(value, params);
+            }
+            ("DTEND", Some(event)) => {
+                event.end = parse_time(value, params);
+                event.all_day_end = parse_date(value, params);
+            }
+            ("SUMMARY", Some(event)) => event.summary = unescape(value),
+            ("DURATION", Some(event)) => event.duration = parse_duration(value),
+            ("RRULE", Some(event)) => {
+                event.rule = Some(
+                    value
+                        .split(';')
+                        .filter_map(|part| part.split_once('='))
+                        .map(|(key, value)| (key.to_string(), value.to_string()))
+                        .collect(),
+                )
+            }
+            ("EXDATE", Some(event)) => {
+                event.exceptions.extend(value.split(',').filter_map(|value| parse_time(value, params)).map(|time| time.date()))
+            }
+            ("RECURRENCE-ID", Some(event)) => event.recurrence_id = parse_time(value, params).map(|time| time.date()),
+            ("STATUS", Some(event)) if value == "CANCELLED" => {
+                event.skipped = true;
+                event.cancelled = true;
+            }
+            ("TRANSP", Some(event)) if value == "TRANSPARENT" => event.skipped = true,
+            _ => {}
+        }
+    }
+    events
+}
+
+// Text values escape commas, semicolons and backslashes with a backslash, and line breaks as \n.
+// Titles are kept on one line.
+fn unescape(value: &str) -> String {
+    let mut text = String::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match (c, chars.clone().next()) {
+            ('\\', Some('n' | 'N')) => {
+                chars.next();
+                text.push(' ');
+            }
+            ('\\', Some(escaped @ (',' | ';' | '\\'))) => {
+                chars.next();
+                text.push(escaped);
+            }
+            _ => text.push(c),
+        }
+    }
+    text
+}
+
+// Local date and time of 20261016T090000Z (UTC) or 20261016T090000 (local or TZID).
+// Dates without a time are all-day events and give None.
+fn parse_time(value: &str, params: &str) -> Option<NaiveDateTime> {
+    if params.contains("VALUE=DATE") && !params.contains("VALUE=DATE-TIME") {
+        return None;
+    }
+    match value.strip_suffix('Z') {
+        Some(utc) => {
+            let utc = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok()?;
+            Some(Utc.from_utc_datetime(&utc).with_timezone(&Local).naive_local())
+        }
+        None => NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok(),
+    }
+}
+
+// The date of an all-day event, None for timed ones
+fn parse_date(value: &str, params: &str) -> Option<NaiveDate> {
+    if !params.contains("VALUE=DATE") || params.contains("VALUE=DATE-TIME") {
+        return None;
+    }
+    NaiveDate::parse_from_str(value, "%Y%m%d").ok()
+}
+
+fn local(time: NaiveDateTime) -> Option<DateTime<Local>> {
+    Local.from_local_datetime(&time).earliest()
+}
+
+// PT1H30M, P1D and the like
+fn parse_duration(value: &str) -> Option<chrono::Duration> {
+    let mut seconds = 0;
+    let mut number = String::new();
+    for c in value.trim_start_matches(['+', 'P']).chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'T' => {}
+            'W' | 'D' | 'H' | 'M' | 'S' => {
+                let unit = match c {
+                    'W' => 7 * 24 * 3600,
+                    'D' => 24 * 3600,
+                    'H' => 3600,
+                    'M' => 60,
+                    _ => 1,
+                };
+                seconds += number.parse::<i64>().ok()? * unit;
+                number.clear();
+            }
+            _ => return None,
+        }
+    }
+    Some(chrono::Duration::seconds(seconds))
+}
+
+fn weekday(code: &str) -> Option<Weekday> {
+    match code {
+        "MO" => Some(Weekday::Mon),
+        "TU" => Some(Weekday::Tue),
+        "WE" => Some(Weekday::Wed),
+        "TH" => Some(Weekday::Thu),
+        "FR" => Some(Weekday::Fri),
+        "SA" => Some(Weekday::Sat),
+        "SU" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+fn occurs_on(rule: &HashMap<String, String>, first: NaiveDate, date: NaiveDate) -> bool {
+    if date < first {
+        return false;
+    }
+    let interval: i64 = rule.get("INTERVAL").and_then(|interval| interval.parse().ok()).unwrap_or(1).max(1);
+    let until = rule.get("UNTIL").and_then(|until| {
+        parse_time(until, "")
+            .map(|time| time.date())
+            .or_else(|| NaiveDate::parse_from_str(until, "%Y%m%d").ok())
+    });
+    if until.is_some_and(|until| date > until) {
+        return false;
+    }
+    let by_day: Vec<Weekday> = match rule.get("BYDAY") {
+        // Positions like 2TU (second Tuesday) aren't supported
+        Some(days) if days.split(',').any(|day| day.len() != 2) => return false,
+        Some(days) => days.split(',').filter_map(weekday).collect(),
+        None => vec![first.weekday()],
+    };
+
+    let matches = |day: NaiveDate| -> bool {
+        match rule.get("FREQ").map(String::as_str) {
+            Some("DAILY") => (day - first).num_days() % interval == 0,
+            Some("WEEKLY") => {
+                let monday = |date: NaiveDate| date - chrono::Duration::days(date.weekday().num_days_from_monday() as i64);
+                ((monday(day) - monday(first)).num_days() / 7) % interval == 0 && by_day.contains(&day.weekday())
+            }
+            Some("MONTHLY") if !rule.contains_key("BYDAY") => {
+                let months = (day.year() - first.year()) as i64 * 12 + day.month() as i64 - first.month() as i64;
+                day.day() == first.day() && months % interval == 0
+            }
+            Some("YEARLY") if !rule.contains_key("BYDAY") => {
+                day.month() == first.month() && day.day() == first.day() && (day.year() - first.year()) as i64 % interval == 0
+            }
+            _ => false,
+        }
+    };
+    if !matches(date) {
+        return false;
+    }
+    match rule.get("COUNT").and_then(|count| count.parse::<usize>().ok()) {
+        Some(count) => first.iter_days().take_while(|day| *day <= date).filter(|day| matches(*day)).count() <= count,
+        None => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::central_european;
+
+    fn calendar(event: &str) -> String {
+        format!("BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nUID:1\r\n{}\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n", event.replace('\n', "\r\n"))
+    }
+
+    #[test]
+    fn folded_lines_are_joined() {
+        let events = parse(&calendar("SUMMARY:Weekly planning with the\n  design team\nDTSTART:20240506T090000\nDTEND:2024050\n\t6T100000"));
+        assert_eq!(events[0].summary, "Weekly planning with the design team");
+        assert_eq!(events[0].end, NaiveDate::from_ymd_opt(2024, 5, 6).unwrap().and_hms_opt(10, 0, 0));
+    }
+
+    #[test]
+    fn start_times_in_utc_a_time_zone_or_all_day() {
+        let nine = central_european("2024-05-06 09:00").naive_local();
+        let start = |line: &str| {
+            let event = parse(&calendar(line)).remove(0);
+            (event.start, event.all_day_start, event.skipped)
+        };
+        // 07:00 UTC is 09:00 in Central European summer time
+        assert_eq!(start("DTSTART:20240506T070000Z"), (Some(nine), None, false));
+        // Times with a TZID are taken as local time
+        assert_eq!(start("DTSTART;TZID=Europe/Berlin:20240506T090000"), (Some(nine), None, false));
+        assert_eq!(start("DTSTART;VALUE=DATE:20240506"), (None, Some(nine.date()), true));
+        assert_eq!(start("DTSTART;VALUE=DATE-TIME:20240506T090000"), (Some(nine), None, false));
+    }
+
+    #[test]
+    fn summaries_are_unescaped() {
+        let summary = |line: &str| parse(&calendar(line)).remove(0).summary;
+        assert_eq!(summary("SUMMARY:Lunch\\, then review\\; bring notes"), "Lunch, then review; bring notes");
+        assert_eq!(summary("SUMMARY:Offsite\\nDay 1"), "Offsite Day 1");
+        assert_eq!(summary("SUMMARY:C:\\\\shared\\\\notes"), "C:\\shared\\notes");
+        assert_eq!(summary("SUMMARY:Trailing\\"), "Trailing\\");
+    }
+}
@@ -0,0 +1,94 @@
+#[cfg(not(windows))]
+use std::process::Command;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+// Seconds since the last keyboard or mouse input anywhere on the system. Polled on a
+// background thread because on macOS and Linux asking the OS means spawning a helper process.
+pub struct IdleMonitor {
+    idle_seconds: Arc<AtomicI64>, // -1 while unknown
+}
+
+impl IdleMonitor {
+    pub fn start() -> Self {
+        let idle_seconds = Arc::new(AtomicI64::new(-1));
+        let shared = Arc::downgrade(&idle_seconds);
+        std::thread::spawn(move || {
+            // Stops once the monitor is dropped
+            while let Some(idle_seconds) = shared.upgrade() {
+                idle_seconds.store(query().map_or(-1, |seconds| seconds as i64), Ordering::Relaxed);
+                drop(idle_seconds);
+                std::thread::sleep(POLL_INTERVAL);
+            }
+        });
+        IdleMonitor { idle_seconds }
+    }
+
+    // None until the first poll, or when the system can't tell
+    pub fn idle_seconds(&self) -> Option<u64> {
+        u64::try_from(self.idle_seconds.load(Ordering::Relaxed)).ok()
+    }
+}
+
+#[cfg(not(windows))]
+fn output(command: &mut Command) -> Option<String> {
+    let output = command.output().ok()?;
+    output.status.success().then(|| String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+// "HIDIdleTime" = 1234567890 (nanoseconds)
+#[cfg(target_os = "macos")]
+fn query() -> Option<u64> {
+    let text = output(Command::new("ioreg").args(["-c", "IOHIDSystem", "-d", "4"]))?;
+    let line = text.lines().find(|line| line.contains("\"HIDIdleTime\""))?;
+    let nanoseconds: u64 = line.split('=').nth(1)?.trim().parse().ok()?;
+    Some(nanoseconds / 1_000_000_000)
+}
+
+// Windows tells directly, in milliseconds since boot
+#[cfg(windows)]
+fn query() -> Option<u64> {
+    use windows_sys::Win32::System::SystemInformation::GetTickCount;
+    use windows_sys::Win32::UI::Input::KeyboardAndMouse::{GetLastInputInfo, LASTINPUTINFO};
+    let mut info = LASTINPUTINFO {
+        cbSize: std::mem::size_of::<LASTINPUTINFO>() as u32,
+        dwTime: 0,
+    };
+    // SAFETY: info is a LASTINPUTINFO with its size filled in, as the call expects
+    if unsafe { GetLastInputInfo(&mut info) } == 0 {
+        return None;
+    }
+    // SAFETY: takes no arguments. The tick count wraps around after 49 days, so does dwTime.
+    let now = unsafe { GetTickCount() };
+    Some(u64::from(now.wrapping_sub(info.dwTime)) / 1000)
+}
+
+// xprintidle on X11, or the GNOME idle monitor (which also works on Wayland)
+#[cfg(not(any(target_os = "macos", windows)))]
+fn query() -> Option<u64> {
+    if let Some(milliseconds) = output(&mut Command::new("xprintidle")).and_then(|text| text.trim().parse::<u64>().ok()) {
+        return Some(milliseconds / 1000);
+    }
+    // Prints "(uint64 12345,)"
+    let text = output(Command::new("gdbus").args([
+        "call",
+        "--session",
+        "--dest",
+        "org.gnome.Mutter.IdleMonitor",
+        "--object-path",
+        "/org/gnome/Mutter/IdleMonitor/Core",
+        "--method",
+        "org.gnome.Mutter.IdleMonitor.GetIdletime",
+    ]))?;
+    let milliseconds: u64 = text
+        .trim()
+        .trim_start_matches("(uint64")
+        .trim_end_matches(",)")
+        .trim()
+        .parse()
+        .ok()?;
+    Some(milliseconds / 1000)
+}
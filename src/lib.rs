@@ -0,0 +1,462 @@
+//! Reads and writes Work Timer's data folder from other programs.
+//!
+//! The app keeps its tasks in `tasks.json` inside its data folder. This crate opens that folder,
+//! starts and stops timers the same way the app does, and sums up recorded time:
+//!
+//! ```no_run
+//! use work_timer::{Report, Workspace};
+//!
+//! let mut workspace = Workspace::open("/path/to/work_timer")?;
+//! let id = workspace.add_task("Write the release notes", Some("Docs")).id().to_string();
+//! workspace.task(&id).unwrap().start()?;
+//! // ...
+//! workspace.task(&id).unwrap().stop()?;
+//!
+//! let today = chrono::Local::now().date_naive();
+//! println!("{} s today", Report::range(&workspace, today, today).total_seconds());
+//! # Ok::<(), work_timer::Error>(())
+//! ```
+//!
+//! The app overwrites `tasks.json` whenever it saves, so don't write while it is running. Use its
+//! local HTTP API (Settings → Integrations) to control a running app instead.
+//!
+//! Fields this crate doesn't know about are kept as they are, so files written by a newer app
+//! version survive a round trip. See the README for what is covered by semver.
+
+use chrono::{DateTime, Duration, Local, NaiveDate, TimeZone};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+const TASKS_FILE: &str = "tasks.json";
+
+// Recorded as the source of sessions timed through this crate
+const SOURCE: &str = "Api";
+
+/// Failure to read or write the data folder.
+#[derive(Debug)]
+pub enum Error {
+    /// The file couldn't be read or written.
+    Io(PathBuf, io::Error),
+    /// The file isn't valid JSON or doesn't have the layout of a Work Timer data file.
+    Json(PathBuf, serde_json::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io(path, e) => write!(f, "{}: {}", path.display(), e),
+            Error::Json(path, e) => write!(f, "{} can't be read: {}", path.display(), e),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(_, e) => Some(e),
+            Error::Json(_, e) => Some(e),
+        }
+    }
+}
+
+/// A task as stored in `tasks.json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Task {
+    id: String,
+    description: String,
+    folder: Option<String>,
+    start_time: Option<DateTime<Local>>,
+    is_paused: bool,
+    #[serde(default)]
+    entries: Vec<Entry>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    created_at: Option<DateTime<Local>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    last_active: Option<DateTime<Local>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    completed_at: Option<DateTime<Local>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    running_source: Option<String>,
+    #[serde(flatten)]
+    other: Map<String, Value>,
+}
+
+impl Task {
+    /// The id the app and its exports use for this task.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    /// `None` for Uncategorized.
+    pub fn folder(&self) -> Option<&str> {
+        self.folder.as_deref()
+    }
+
+    /// When the running session started, `None` while the timer is stopped.
+    pub fn started_at(&self) -> Option<DateTime<Local>> {
+        self.start_time
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.start_time.is_some()
+    }
+
+    /// Finished sessions, oldest first.
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+}
+
+/// A finished session of a task.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Entry {
+    start: DateTime<Local>,
+    end: DateTime<Local>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    source: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    timezone: Option<String>,
+    #[serde(flatten)]
+    other: Map<String, Value>,
+}
+
+impl Entry {
+    pub fn start(&self) -> DateTime<Local> {
+        self.start
+    }
+
+    pub fn end(&self) -> DateTime<Local> {
+        self.end
+    }
+
+    pub fn duration(&self) -> Duration {
+        self.end - self.start
+    }
+
+    /// How the session was recorded, e.g. `"Cli"` or `"Api"`. `None` for sessions timed in the app.
+    pub fn source(&self) -> Option<&str> {
+        self.source.as_deref()
+    }
+}
+
+/// The tasks of one Work Timer data folder.
+#[derive(Debug)]
+pub struct Workspace {
+    dir: PathBuf,
+    tasks: BTreeMap<String, Task>,
+}
+
+impl Workspace {
+    /// Opens the data folder at `dir`. A folder without `tasks.json` opens with no tasks.
+    pub fn open(dir: impl AsRef<Path>) -> Result<Workspace, Error> {
+        let dir = dir.as_ref().to_path_buf();
+        let path = dir.join(TASKS_FILE);
+        let tasks = match fs::read_to_string(&path) {
+            Ok(data) => serde_json::from_str(&data).map_err(|e| Error::Json(path, e))?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(e) => return Err(Error::Io(path, e)),
+        };
+        Ok(Workspace { dir, tasks })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// All tasks, including archived ones, in no particular order.
+    pub fn tasks(&self) -> impl Iterator<Item = &Task> {
+        self.tasks.values()
+    }
+
+    pub fn task(&mut self, id: &str) -> Option<TaskHandle<'_>> {
+        self.tasks.contains_key(id).then(|| TaskHandle { workspace: self, id: id.to_string() })
+    }
+
+    /// The first task with exactly this description.
+    pub fn find_task(&mut self, description: &str) -> Option<TaskHandle<'_>> {
+        let id = self.tasks.values().find(|task| task.description == description)?.id.clone();
+        Some(TaskHandle { workspace: self, id })
+    }
+
+    /// Adds a stopped task. It is written to disk with the next start, stop or [`Workspace::save`].
+    pub fn add_task(&mut self, description: &str, folder: Option<&str>) -> TaskHandle<'_> {
+        let id = uuid::Uuid::new_v4().to_string();
+        let task = Task {
+            id: id.clone(),
+            description: description.to_string(),
+            folder: folder.map(str::to_string),
+            start_time: None,
+            is_paused: false,
+            entries: Vec::new(),
+            created_at: Some(Local::now()),
+            last_active: None,
+            completed_at: None,
+            running_source: None,
+            other: Map::new(),
+        };
+        self.tasks.insert(id.clone(), task);
+        TaskHandle { workspace: self, id }
+    }
+
+    /// Writes `tasks.json`. The previous file is kept as `tasks.json.bak`, like the app does.
+    pub fn save(&self) -> Result<(), Error> {
+        let path = self.dir.join(TASKS_FILE);
+        let data = serde_json::to_string(&self.tasks).map_err(|e| Error::Json(path.clone(), e))?;
+        let temp = self.dir.join(format!("{}.tmp", TASKS_FILE));
+        fs::write(&temp, data)
+            .and_then(|_| {
+                if path.exists() {
+                    fs::rename(&path, self.dir.join(format!("{}.bak", TASKS_FILE)))?;
+                }
+                fs::rename(&temp, &path)
+            })
+            .map_err(|e| Error::Io(path, e))
+    }
+
+    fn stop_at(&mut self, id: &str, end: DateTime<Local>) -> Option<Entry> {
+        let task = self.tasks.get_mut(id)?;
+        let start = task.start_time.take()?;
+        let entry = Entry {
+            start,
+            end: end.max(start),
+            source: task.running_source.clone(),
+            timezone: system_timezone(),
+            other: Map::new(),
+        };
+        task.entries.push(entry.clone());
+        task.is_paused = true;
+        task.last_active = Some(entry.end);
+        Some(entry)
+    }
+}
+
+/// One task of a [`Workspace`], for starting and stopping its timer.
+pub struct TaskHandle<'a> {
+    workspace: &'a mut Workspace,
+    id: String,
+}
+
+impl TaskHandle<'_> {
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn task(&self) -> &Task {
+        &self.workspace.tasks[&self.id]
+    }
+
+    /// Starts the timer and saves. Like in the app, a timer running on another task is stopped
+    /// first. Returns `false` when this task was already running.
+    pub fn start(&mut self) -> Result<bool, Error> {
+        if self.task().is_running() {
+            return Ok(false);
+        }
+        let now = Local::now();
+        let running: Vec<String> = self.workspace.tasks.values().filter(|task| task.is_running()).map(|task| task.id.clone()).collect();
+        for id in running {
+            self.workspace.stop_at(&id, now);
+        }
+        let task = self.workspace.tasks.get_mut(&self.id).expect("handle of a removed task");
+        task.start_time = Some(now);
+        task.is_paused = false;
+        task.last_active = Some(now);
+        task.completed_at = None;
+        task.running_source = Some(SOURCE.to_string());
+        self.workspace.save()?;
+        Ok(true)
+    }
+
+    /// Stops the timer, records the session and saves. Returns `None` when it wasn't running.
+    pub fn stop(&mut self) -> Result<Option<Entry>, Error> {
+        let entry = self.workspace.stop_at(&self.id, Local::now());
+        if entry.is_some() {
+            self.workspace.save()?;
+        }
+        Ok(entry)
+    }
+}
+
+/// Time recorded per task between two days.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct Report {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    /// Tasks with time in the range, most time first.
+    pub rows: Vec<ReportRow>,
+}
+
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct ReportRow {
+    pub task_id: String,
+    pub description: String,
+    pub folder: Option<String>,
+    pub seconds: i64,
+}
+
+impl Report {
+    /// Sums the sessions between the start of `from` and the end of `to`, both in local time.
+    /// Sessions crossing the range are cut at its edges, and a running timer counts up to now.
+    /// Time without sessions, like totals set by hand, has no date and is left out.
+    pub fn range(workspace: &Workspace, from: NaiveDate, to: NaiveDate) -> Report {
+        let range_start = start_of(from);
+        let range_end = start_of(to + Duration::days(1));
+        let now = Local::now();
+        let mut rows: Vec<ReportRow> = workspace
+            .tasks
+            .values()
+            .filter_map(|task| {
+                let running = task.start_time.map(|start| (start, now));
+                let seconds: i64 = task
+                    .entries
+                    .iter()
+                    .map(|entry| (entry.start, entry.end))
+                    .chain(running)
+                    .map(|(start, end)| (end.min(range_end) - start.max(range_start)).num_seconds().max(0))
+                    .sum();
+                (seconds > 0).then(|| ReportRow {
+                    task_id: task.id.clone(),
+                    description: task.description.clone(),
+                    folder: task.folder.clone(),
+                    seconds,
+                })
+            })
+            .collect();
+        rows.sort_by(|a, b| b.seconds.cmp(&a.seconds).then_with(|| a.description.cmp(&b.description)));
+        Report { from, to, rows }
+    }
+
+    pub fn total_seconds(&self) -> i64 {
+        self.rows.iter().map(|row| row.seconds).sum()
+    }
+}
+
+// Midnight, or the first time after it on days that skip it for daylight saving
+fn start_of(day: NaiveDate) -> DateTime<Local> {
+    let midnight = day.and_hms_opt(0, 0, 0).unwrap();
+    Local
+        .from_local_datetime(&midnight)
+        .earliest()
+        .or_else(|| Local.from_local_datetime(&(midnight + Duration::hours(1))).earliest())
+        .unwrap_or_else(|| Local.from_utc_datetime(&midnight))
+}
+
+// Same zone name the app records on its sessions
+fn system_timezone() -> Option<String> {
+    std::env::var("TZ")
+        .ok()
+        .map(|name| name.trim_start_matches(':').to_string())
+        .filter(|name| name.parse::<chrono_tz::Tz>().is_ok())
+        .or_else(|| iana_time_zone::get_timezone().ok().filter(|name| name.parse::<chrono_tz::Tz>().is_ok()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A fresh data folder in the system's temporary folder, removed when dropped
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new() -> Self {
+            let dir = std::env::temp_dir().join(format!("work_timer_test_{}", uuid::Uuid::new_v4()));
+            fs::create_dir(&dir).unwrap();
+            TempDir(dir)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn time(text: &str) -> DateTime<Local> {
+        DateTime::parse_from_rfc3339(text).unwrap().with_timezone(&Local)
+    }
+
+    #[test]
+    fn a_missing_tasks_file_opens_empty() {
+        let dir = TempDir::new();
+        assert_eq!(Workspace::open(&dir.0).unwrap().tasks().count(), 0);
+    }
+
+    #[test]
+    fn start_and_stop_record_a_session_that_reopens() {
+        let dir = TempDir::new();
+        let mut workspace = Workspace::open(&dir.0).unwrap();
+        let id = workspace.add_task("Review", None).id().to_string();
+        assert!(workspace.task(&id).unwrap().start().unwrap());
+        assert!(!workspace.task(&id).unwrap().start().unwrap());
+        let entry = workspace.task(&id).unwrap().stop().unwrap().unwrap();
+        assert_eq!(entry.source(), Some("Api"));
+        assert!(workspace.task(&id).unwrap().stop().unwrap().is_none());
+
+        let mut reopened = Workspace::open(&dir.0).unwrap();
+        let task = reopened.find_task("Review").unwrap();
+        assert!(!task.task().is_running());
+        assert_eq!(task.task().entries().len(), 1);
+    }
+
+    #[test]
+    fn starting_a_task_stops_the_running_one() {
+        let dir = TempDir::new();
+        let mut workspace = Workspace::open(&dir.0).unwrap();
+        let first = workspace.add_task("First", None).id().to_string();
+        let second = workspace.add_task("Second", None).id().to_string();
+        workspace.task(&first).unwrap().start().unwrap();
+        workspace.task(&second).unwrap().start().unwrap();
+        let first = workspace.task(&first).unwrap();
+        assert!(!first.task().is_running());
+        assert_eq!(first.task().entries().len(), 1);
+        assert!(workspace.task(&second).unwrap().task().is_running());
+    }
+
+    #[test]
+    fn fields_of_the_app_survive_a_save() {
+        let dir = TempDir::new();
+        let data = r#"{"a":{"id":"a","description":"Task","folder":"Work","start_time":null,"is_paused":false,
+            "billable":true,"tags":["x"],"entries":[{"start":"2024-03-01T09:00:00Z","end":"2024-03-01T10:00:00Z",
+            "annotation":"notes","synced":{"Harvest":"7"}}]}}"#;
+        fs::write(dir.0.join(TASKS_FILE), data).unwrap();
+        let mut workspace = Workspace::open(&dir.0).unwrap();
+        workspace.task("a").unwrap().start().unwrap();
+
+        let saved: Value = serde_json::from_str(&fs::read_to_string(dir.0.join(TASKS_FILE)).unwrap()).unwrap();
+        assert_eq!(saved["a"]["billable"], true);
+        assert_eq!(saved["a"]["tags"][0], "x");
+        assert_eq!(saved["a"]["entries"][0]["annotation"], "notes");
+        assert_eq!(saved["a"]["entries"][0]["synced"]["Harvest"], "7");
+        assert_eq!(saved["a"]["running_source"], "Api");
+        assert!(dir.0.join("tasks.json.bak").exists());
+    }
+
+    #[test]
+    fn report_cuts_sessions_at_the_edges_of_the_range() {
+        let dir = TempDir::new();
+        let mut workspace = Workspace::open(&dir.0).unwrap();
+        let id = workspace.add_task("Night shift", None).id().to_string();
+        let day = time("2024-03-05T12:00:00Z").date_naive();
+        let midnight = start_of(day);
+        let task = workspace.tasks.get_mut(&id).unwrap();
+        for (start, end) in [(midnight - Duration::hours(1), midnight + Duration::hours(2)), (midnight + Duration::hours(10), midnight + Duration::hours(11))] {
+            task.entries.push(Entry { start, end, source: None, timezone: None, other: Map::new() });
+        }
+
+        let report = Report::range(&workspace, day, day);
+        assert_eq!(report.rows.len(), 1);
+        assert_eq!(report.total_seconds(), 3 * 3600);
+        assert_eq!(Report::range(&workspace, day - Duration::days(1), day).total_seconds(), 4 * 3600);
+        assert!(Report::range(&workspace, day + Duration::days(1), day + Duration::days(1)).rows.is_empty());
+    }
+}
@@ -0,0 +1,161 @@
+use chrono::{DateTime, Local};
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::sync::Mutex;
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{Builder, Rotation};
+use tracing_subscriber::filter::Targets;
+use tracing_subscriber::fmt::format::Writer;
+use tracing_subscriber::fmt::time::FormatTime;
+use tracing_subscriber::layer::{Context, SubscriberExt};
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::Layer;
+
+// Written to this folder next to the data files, a file a day like work_timer.2024-05-06.log.
+// The files of the last week are kept.
+pub const LOG_DIR: &str = "logs";
+const LOG_PREFIX: &str = "work_timer";
+const KEPT_LOGS: usize = 7;
+const RECENT_PROBLEMS: usize = 20;
+
+#[derive(Clone)]
+pub struct LogRecord {
+    pub time: DateTime<Local>,
+    pub level: Level,
+    pub message: String,
+}
+
+// Outcome of the most recent write of a data file
+#[derive(Clone)]
+pub struct SaveStatus {
+    pub time: DateTime<Local>,
+    pub path: String,
+    pub error: Option<String>,
+}
+
+static PROBLEMS: Mutex<VecDeque<LogRecord>> = Mutex::new(VecDeque::new());
+static LAST_SAVE: Mutex<Option<SaveStatus>> = Mutex::new(None);
+
+// Warnings and errors logged since the app started, newest last
+pub fn recent_problems() -> Vec<LogRecord> {
+    PROBLEMS.lock().map(|problems| problems.iter().cloned().collect()).unwrap_or_default()
+}
+
+pub fn record_save(path: &str, error: Option<String>) {
+    if let Ok(mut last_save) = LAST_SAVE.lock() {
+        *last_save = Some(SaveStatus {
+            time: Local::now(),
+            path: path.to_string(),
+            error,
+        });
+    }
+}
+
+pub fn last_save() -> Option<SaveStatus> {
+    LAST_SAVE.lock().ok().and_then(|last_save| last_save.clone())
+}
+
+// Sends the app's tracing events to the log files. Events from dependencies are ignored.
+// Lines are written on a thread of their own, so a slow disk doesn't hold up the UI. Keep
+// the guard until the app exits, dropping it writes out what is still queued.
+pub fn init() -> Option<WorkerGuard> {
+    let appender = Builder::new()
+        .rotation(Rotation::DAILY)
+        .filename_prefix(LOG_PREFIX)
+        .filename_suffix("log")
+        .max_log_files(KEPT_LOGS)
+        .build(LOG_DIR);
+    let (writer, guard) = match appender {
+        Ok(appender) => tracing_appender::non_blocking(appender),
+        Err(e) => {
+            eprintln!("Not writing a log, {} isn't available: {}", LOG_DIR, e);
+            return None;
+        }
+    };
+    let result = tracing_subscriber::registry()
+        .with(tracing_subscriber::fmt::layer().with_writer(writer).with_ansi(false).with_timer(LocalTime))
+        .with(RecentProblems)
+        .with(Targets::new().with_target("work_timer", Level::INFO))
+        .try_init();
+    if result.is_err() {
+        eprintln!("Logging was already set up");
+    }
+    Some(guard)
+}
+
+// Times in the log are local, like everywhere else in the app
+struct LocalTime;
+
+impl FormatTime for LocalTime {
+    fn format_time(&self, writer: &mut Writer<'_>) -> std::fmt::Result {
+        write!(writer, "{}", Local::now().format("%Y-%m-%d %H:%M:%S%.3f"))
+    }
+}
+
+// Collects the message and any extra fields of an event into one line
+#[derive(Default)]
+struct LineVisitor {
+    message: String,
+    fields: String,
+}
+
+impl Visit for LineVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message.push_str(value);
+        } else {
+            let _ = write!(self.fields, " {}={:?}", field.name(), value);
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.message, "{:?}", value);
+        } else {
+            let _ = write!(self.fields, " {}={:?}", field.name(), value);
+        }
+    }
+}
+
+// Keeps the latest warnings and errors for the diagnostics panel
+struct RecentProblems;
+
+impl<S: Subscriber> Layer<S> for RecentProblems {
+    fn on_event(&self, event: &Event<'_>, _context: Context<'_, S>) {
+        let level = *event.metadata().level();
+        if level > Level::WARN {
+            return;
+        }
+        let mut visitor = LineVisitor::default();
+        event.record(&mut visitor);
+        if let Ok(mut problems) = PROBLEMS.lock() {
+            if problems.len() == RECENT_PROBLEMS {
+                problems.pop_front();
+            }
+            problems.push_back(LogRecord {
+                time: Local::now(),
+                level,
+                message: format!("{}{}", visitor.message, visitor.fields),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_warnings_and_errors_with_their_fields() {
+        let subscriber = tracing_subscriber::registry().with(RecentProblems);
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info!("Not a problem");
+            tracing::warn!(file = "tasks.json", "Failed to save");
+        });
+        let problems = recent_problems();
+        assert!(problems.iter().all(|problem| problem.message != "Not a problem"));
+        assert!(problems.iter().any(|problem| problem.level == Level::WARN && problem.message == "Failed to save file=\"tasks.json\""));
+    }
+}
@@ -0,0 +1,78 @@
+use lettre::{
+    message::{header::ContentType, Attachment, MultiPart, SinglePart},
+    transport::smtp::authentication::Credentials,
+    Message, SmtpTransport, Transport,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct SmtpConfig {
+    pub host: String,
+    #[serde(default)]
+    pub port: u16, // 0 uses the submission port 587
+    pub username: String,
+    pub password: String,
+    pub from: String, // e.g. Work Timer <me@example.com>
+}
+
+impl SmtpConfig {
+    pub fn is_configured(&self) -> bool {
+        !self.host.trim().is_empty() && !self.from.trim().is_empty()
+    }
+}
+
+// A file sent along with a mail
+pub struct MailAttachment {
+    pub filename: String,
+    pub content_type: &'static str, // e.g. text/csv
+    pub data: Vec<u8>,
+}
+
+fn builder(config: &SmtpConfig, to: &str, subject: &str) -> Result<lettre::message::MessageBuilder, String> {
+    Ok(Message::builder()
+        .from(config.from.trim().parse().map_err(|e| format!("invalid sender: {}", e))?)
+        .to(to.trim().parse().map_err(|e| format!("invalid recipient: {}", e))?)
+        .subject(subject))
+}
+
+// Sends a plain text mail over STARTTLS
+pub fn send(config: &SmtpConfig, to: &str, subject: &str, body: String) -> Result<(), String> {
+    let message = builder(config, to, subject)?
+        .header(ContentType::TEXT_PLAIN)
+        .body(body)
+        .map_err(|e| e.to_string())?;
+    deliver(config, &message)
+}
+
+pub fn send_with_attachment(
+    config: &SmtpConfig,
+    to: &str,
+    subject: &str,
+    body: String,
+    attachment: MailAttachment,
+) -> Result<(), String> {
+    let content_type = ContentType::parse(attachment.content_type).map_err(|e| e.to_string())?;
+    let message = builder(config, to, subject)?
+        .multipart(
+            MultiPart::mixed()
+                .singlepart(SinglePart::plain(body))
+                .singlepart(Attachment::new(attachment.filename).body(attachment.data, content_type)),
+        )
+        .map_err(|e| e.to_string())?;
+    deliver(config, &message)
+}
+
+fn deliver(config: &SmtpConfig, message: &Message) -> Result<(), String> {
+    let mut transport = SmtpTransport::starttls_relay(config.host.trim())
+        .map_err(|e| e.to_string())?
+        .port(if config.port == 0 { 587 } else { config.port });
+    if !config.username.trim().is_empty() {
+        transport = transport.credentials(Credentials::new(
+            config.username.trim().to_string(),
+            config.password.clone(),
+        ));
+    }
+
+    transport.build().send(message).map_err(|e| e.to_string())?;
+    Ok(())
+}
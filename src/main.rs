@@ -1,9 +1,42 @@
-use chrono::{DateTime, Local};
+mod activity;
+mod api;
+mod attachments;
+mod audit;
+mod backup;
+mod billing;
+mod cli;
+mod clipboard;
+mod desktop;
+mod focus;
+mod harvest;
+mod hooks;
+mod hotkeys;
+mod ics;
+mod idle;
+mod logging;
+mod mail;
+mod notifications;
+mod notion;
+mod paths;
+mod plugins;
+mod pomodoro;
+mod redmine;
+mod reports;
+mod schema;
+mod screenshots;
+mod task_import;
+mod timezones;
+#[cfg(feature = "tray")]
+mod tray;
+
+use chrono::{DateTime, Datelike, Local, Months, NaiveDate, NaiveTime, Timelike};
 use csv;
 use eframe::egui;
 use egui_phosphor::fill;
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, fs, path::Path};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::{collections::HashMap, fs, path::Path, sync::mpsc};
 use uuid::Uuid;
 
 #[derive(Clone)]
@@ -13,6 +46,236 @@ enum TaskAction {
     Resume,
     Delete,
     Complete,
+    ToggleBillable,
+    ShowDetails,
+    Duplicate,
+    DuplicateInto(Option<String>), // Target folder, None for Uncategorized
+    Rename,
+    MoveTo(Option<String>), // Target folder, None for Uncategorized
+    SetColorLabel(Option<ColorLabel>),
+    Export,
+}
+
+// Things that happen to tasks, passed on to plugins and shell hooks
+enum TimerEvent {
+    TaskStarted(String),
+    TaskStopped(String),
+    TaskCompleted(String),
+    DayEnded(NaiveDate),
+}
+
+// Keyboard shortcuts. The input handling and the shortcuts window both read these bindings.
+#[derive(Clone, Copy, PartialEq)]
+enum Shortcut {
+    NewTask,
+    NewFolder,
+    ToggleDarkMode,
+    TogglePrivacy,
+    ExportAll,
+    Statistics,
+    Settings,
+    PlayPause,
+    DeleteFocused,
+    MoveUp,
+    MoveDown,
+    ExpandFolder,
+    CollapseFolder,
+    CloseDialog,
+    Confirm,
+    Search,
+    FocusFolder,
+    AddTaskToFolder,
+}
+
+// Number keys for the first nine folders, in list order
+const FOLDER_KEYS: [egui::Key; 9] = [
+    egui::Key::Num1,
+    egui::Key::Num2,
+    egui::Key::Num3,
+    egui::Key::Num4,
+    egui::Key::Num5,
+    egui::Key::Num6,
+    egui::Key::Num7,
+    egui::Key::Num8,
+    egui::Key::Num9,
+];
+
+impl Shortcut {
+    const ALL: [Shortcut; 18] = [
+        Shortcut::NewTask,
+        Shortcut::NewFolder,
+        Shortcut::ToggleDarkMode,
+        Shortcut::TogglePrivacy,
+        Shortcut::ExportAll,
+        Shortcut::Statistics,
+        Shortcut::Settings,
+        Shortcut::PlayPause,
+        Shortcut::DeleteFocused,
+        Shortcut::MoveUp,
+        Shortcut::MoveDown,
+        Shortcut::ExpandFolder,
+        Shortcut::CollapseFolder,
+        Shortcut::CloseDialog,
+        Shortcut::Confirm,
+        Shortcut::Search,
+        Shortcut::FocusFolder,
+        Shortcut::AddTaskToFolder,
+    ];
+
+    fn label(&self) -> &'static str {
+        match self {
+            Shortcut::NewTask => "New Task",
+            Shortcut::NewFolder => "New Folder",
+            Shortcut::ToggleDarkMode => "Toggle Dark/Light Mode",
+            Shortcut::TogglePrivacy => "Toggle Privacy Mode",
+            Shortcut::ExportAll => "Export All Tasks",
+            Shortcut::Statistics => "Show Statistics",
+            Shortcut::Settings => "Show Settings",
+            Shortcut::PlayPause => "Start/Pause Focused Task",
+            Shortcut::DeleteFocused => "Delete Focused Task/Folder",
+            Shortcut::MoveUp => "Focus Previous",
+            Shortcut::MoveDown => "Focus Next",
+            Shortcut::ExpandFolder => "Expand Focused Folder",
+            Shortcut::CollapseFolder => "Collapse Focused Folder",
+            Shortcut::CloseDialog => "Close Dialog",
+            Shortcut::Confirm => "Create Task/Folder",
+            Shortcut::Search => "Search Everything",
+            Shortcut::FocusFolder => "Focus Folder 1–9",
+            Shortcut::AddTaskToFolder => "Add Task to Folder 1–9",
+        }
+    }
+
+    // Any of these triggers the action
+    fn bindings(&self) -> Vec<egui::KeyboardShortcut> {
+        use egui::{Key, KeyboardShortcut, Modifiers};
+        let command = |key| KeyboardShortcut::new(Modifiers::COMMAND, key);
+        let plain = |key| KeyboardShortcut::new(Modifiers::NONE, key);
+        match self {
+            Shortcut::NewTask => vec![command(Key::T)],
+            Shortcut::NewFolder => vec![command(Key::N)],
+            Shortcut::ToggleDarkMode => vec![command(Key::D)],
+            Shortcut::TogglePrivacy => vec![KeyboardShortcut::new(Modifiers::COMMAND | Modifiers::SHIFT, Key::P)],
+            Shortcut::ExportAll => vec![command(Key::E)],
+            Shortcut::Statistics => vec![command(Key::S)],
+            Shortcut::Settings => vec![command(Key::Comma)],
+            Shortcut::PlayPause => vec![plain(Key::Space)],
+            Shortcut::DeleteFocused => vec![command(Key::Backspace), command(Key::Delete)],
+            Shortcut::MoveUp => vec![plain(Key::ArrowUp)],
+            Shortcut::MoveDown => vec![plain(Key::ArrowDown)],
+            Shortcut::ExpandFolder => vec![plain(Key::ArrowRight)],
+            Shortcut::CollapseFolder => vec![plain(Key::ArrowLeft)],
+            Shortcut::CloseDialog => vec![plain(Key::Escape), command(Key::W)],
+            Shortcut::Confirm => vec![plain(Key::Enter)],
+            Shortcut::Search => vec![KeyboardShortcut::new(Modifiers::COMMAND | Modifiers::SHIFT, Key::F)],
+            Shortcut::FocusFolder => FOLDER_KEYS.iter().map(|key| command(*key)).collect(),
+            Shortcut::AddTaskToFolder => FOLDER_KEYS
+                .iter()
+                .map(|key| KeyboardShortcut::new(Modifiers::COMMAND | Modifiers::SHIFT, *key))
+                .collect(),
+        }
+    }
+
+    fn pressed(&self, ctx: &egui::Context) -> bool {
+        let bindings = self.bindings();
+        ctx.input(|i| {
+            bindings
+                .iter()
+                .any(|binding| i.key_pressed(binding.logical_key) && i.modifiers.matches_logically(binding.modifiers))
+        })
+    }
+
+    // Which of the bindings was pressed, for shortcuts with one binding per folder. Shift
+    // has to match exactly and the physical key counts too, since Shift turns 1 into !.
+    fn pressed_index(&self, ctx: &egui::Context) -> Option<usize> {
+        let bindings = self.bindings();
+        ctx.input(|i| {
+            i.events.iter().find_map(|event| match event {
+                egui::Event::Key { key, physical_key, pressed: true, modifiers, .. } => bindings.iter().position(|binding| {
+                    (*key == binding.logical_key || *physical_key == Some(binding.logical_key))
+                        && modifiers.command
+                        && !modifiers.alt
+                        && modifiers.shift == binding.modifiers.shift
+                }),
+                _ => None,
+            })
+        })
+    }
+
+    // Platform-specific text, e.g. "⌘T" on macOS and "Ctrl+T" elsewhere
+    fn keys_text(&self, ctx: &egui::Context) -> String {
+        let bindings = self.bindings();
+        if let [first, .., last] = bindings.as_slice() {
+            if bindings.len() == FOLDER_KEYS.len() {
+                return format!("{} … {}", ctx.format_shortcut(first), ctx.format_shortcut(last));
+            }
+        }
+        bindings
+            .iter()
+            .map(|binding| ctx.format_shortcut(binding))
+            .collect::<Vec<_>>()
+            .join(" / ")
+    }
+}
+
+// What the cleanup view does with a stale task
+#[derive(Clone, PartialEq, Default)]
+enum CleanupAction {
+    #[default]
+    Keep,
+    Archive,
+    Delete,
+    MergeInto(String), // Task id that receives the sessions
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+enum TaskSort {
+    #[default]
+    Created,
+    Name,
+    Recent,
+}
+
+impl TaskSort {
+    const ALL: [TaskSort; 3] = [TaskSort::Created, TaskSort::Name, TaskSort::Recent];
+
+    fn label(&self) -> &'static str {
+        match self {
+            TaskSort::Created => "Oldest first",
+            TaskSort::Name => "Name",
+            TaskSort::Recent => "Recently active",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum TaskStatus {
+    Running,
+    Paused,
+    NotStarted,
+    Completed,
+}
+
+impl TaskStatus {
+    const ALL: [TaskStatus; 4] = [TaskStatus::Running, TaskStatus::Paused, TaskStatus::NotStarted, TaskStatus::Completed];
+
+    fn label(&self) -> &'static str {
+        match self {
+            TaskStatus::Running => "running",
+            TaskStatus::Paused => "paused",
+            TaskStatus::NotStarted => "not started",
+            TaskStatus::Completed => "done",
+        }
+    }
+
+    // Same colors as the status label on task rows
+    fn color(&self) -> egui::Color32 {
+        match self {
+            TaskStatus::Running => egui::Color32::GREEN,
+            TaskStatus::Paused => egui::Color32::YELLOW,
+            TaskStatus::NotStarted => egui::Color32::GRAY,
+            TaskStatus::Completed => egui::Color32::from_rgb(0, 180, 180),
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -24,9 +287,221 @@ enum DurationEditAction {
 #[derive(Clone, Copy, PartialEq)]
 enum StatsTab {
     Overview,
+    Dashboard,
     Projects,
     Timeline,
     Details,
+    PayPeriods,
+    Plan,
+    Focus,
+    Pomodoro,
+}
+
+// Aggregates behind the current Statistics tab. Rebuilt when a data file is written, once a
+// second while a timer runs and once a minute otherwise, instead of on every frame.
+#[derive(Default)]
+struct StatsCache {
+    key: Option<(StatsTab, u64, i64)>,
+    total_time: i64,
+    task_count: usize,
+    active_tasks: usize,
+    completed_tasks: usize,
+    billable_split: Vec<(String, i64, i64)>,
+    folder_durations: Vec<(String, i64)>,
+    folder_expenses: Vec<(String, f64)>,
+    tracked_days: Vec<(NaiveDate, i64, i64)>, // Day, tracked seconds, pomodoros
+    plan_days: Vec<(NaiveDate, i64, i64)>,
+    pay_periods: Vec<PayPeriodSummary>,
+    top_tasks: Vec<(String, i64)>,
+    folder_cards: Vec<FolderCard>,
+    focus_days: Vec<(NaiveDate, i64, i64)>, // Day, deep and shallow seconds, oldest first
+    pomodoro_days: Vec<PomodoroDay>, // Oldest first
+    pomodoro_tasks: Vec<(String, usize)>, // Task id and finished pomodoros, most first
+}
+
+// One day on the Pomodoro tab, from the Pomodoro mode log
+#[derive(Default)]
+struct PomodoroDay {
+    date: NaiveDate,
+    finished: usize,
+    cut_short: usize, // Paused or switched away from before the time was up
+    breaks: usize,
+    skipped_breaks: usize, // Back to work before the break was over
+    break_seconds: i64,
+}
+
+// One folder on the dashboard
+struct FolderCard {
+    folder: String,
+    budget: Option<(i64, i64, &'static str)>, // Tracked and budgeted seconds in the budget period, period label
+    tracked: i64,
+    earnings: billing::Taxed,
+    last_active: Option<DateTime<Local>>,
+    days: Vec<i64>, // Tracked seconds of the last DASHBOARD_DAYS days, oldest first
+}
+
+const DASHBOARD_DAYS: i64 = 14;
+
+// Annual summary, computed from the recorded sessions and adjustments of the year
+struct YearReview {
+    year: i32,
+    total: i64,
+    days_tracked: usize,
+    months: [i64; 12],
+    top_folders: Vec<(String, i64)>,
+    top_tasks: Vec<(String, i64)>, // Task ID, seconds
+    busiest_weeks: Vec<(NaiveDate, i64)>, // Week start, seconds
+    longest_streak: Option<(NaiveDate, NaiveDate)>, // First and last day of the longest run of tracked days
+}
+
+impl YearReview {
+    fn average_per_day(&self) -> i64 {
+        self.total / self.days_tracked.max(1) as i64
+    }
+
+    fn streak_days(&self) -> i64 {
+        self.longest_streak.map_or(0, |(first, last)| (last - first).num_days() + 1)
+    }
+}
+const FOCUS_DAYS: i64 = 28;
+
+// Share of the classified time that was deep work, None when nothing was classified
+fn deep_share(deep: i64, shallow: i64) -> Option<f32> {
+    (deep + shallow > 0).then(|| deep as f32 / (deep + shallow) as f32)
+}
+
+const SHOWN_DIALOGS: &str = "shown_dialogs";
+
+// Button of the remove folder dialog with the keyboard focus, in egui memory:
+// 0 = remove folder only, 1 = delete folder and tasks, 2 = cancel
+const CLEAR_FOLDER_FOCUS: &str = "clear_folder_dialog_focus";
+
+// Dialog that reopens where it was last left, or centered on the main window the first
+// time. Saved positions are pulled back inside the window, e.g. when it moved to a
+// smaller monitor or the monitor it was on was disconnected.
+fn dialog_window(
+    ctx: &egui::Context,
+    positions: &HashMap<String, [f32; 2]>,
+    key: &str,
+    title: impl Into<egui::WidgetText>,
+) -> egui::Window<'static> {
+    ctx.data_mut(|data| {
+        data.get_temp_mut_or_default::<Vec<String>>(egui::Id::new(SHOWN_DIALOGS))
+            .push(key.to_string())
+    });
+    let screen = ctx.screen_rect();
+    let window = egui::Window::new(title).id(egui::Id::new(("dialog", key)));
+    match positions.get(key) {
+        Some([x, y]) => {
+            let x = x.min(screen.right() - 80.0).max(screen.left());
+            let y = y.min(screen.bottom() - 40.0).max(screen.top());
+            window.default_pos(egui::pos2(x, y))
+        }
+        None => window.pivot(egui::Align2::CENTER_CENTER).default_pos(screen.center()),
+    }
+}
+
+// "just now", "5m ago", "2h ago", "3d ago"
+fn format_relative(time: DateTime<Local>) -> String {
+    let seconds = (Local::now() - time).num_seconds().max(0);
+    if seconds < 60 {
+        "just now".to_string()
+    } else if seconds < 3600 {
+        format!("{}m ago", seconds / 60)
+    } else if seconds < 86400 {
+        format!("{}h ago", seconds / 3600)
+    } else {
+        format!("{}d ago", seconds / 86400)
+    }
+}
+
+fn parse_tags(text: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for tag in text.split(',').map(str::trim).filter(|tag| !tag.is_empty()) {
+        if !tags.iter().any(|existing| existing == tag) {
+            tags.push(tag.to_string());
+        }
+    }
+    tags
+}
+
+// Comma separated tag field. The text is kept as typed while focused and parsed when focus leaves.
+fn edit_tags(ui: &mut egui::Ui, id: egui::Id, tags: &mut Vec<String>) -> bool {
+    let buffer_id = id.with("buffer");
+    let mut text = ui
+        .data_mut(|data| data.get_temp::<String>(buffer_id))
+        .unwrap_or_else(|| tags.join(", "));
+    let response = ui.add(egui::TextEdit::singleline(&mut text).id(id).hint_text("e.g. admin, meeting"));
+    if response.has_focus() {
+        ui.data_mut(|data| data.insert_temp(buffer_id, text));
+        return false;
+    }
+    ui.data_mut(|data| data.remove::<String>(buffer_id));
+    let parsed = parse_tags(&text);
+    if response.lost_focus() && parsed != *tags {
+        *tags = parsed;
+        return true;
+    }
+    false
+}
+
+fn edit_work_kind(ui: &mut egui::Ui, id_salt: impl std::hash::Hash, kind: &mut Option<WorkKind>, unset_label: &str) -> bool {
+    let mut changed = false;
+    egui::ComboBox::from_id_salt(id_salt)
+        .selected_text(kind.map_or(unset_label, |kind| kind.label()))
+        .show_ui(ui, |ui| {
+            changed |= ui.selectable_value(kind, None, unset_label).changed();
+            for option in [WorkKind::Deep, WorkKind::Shallow] {
+                changed |= ui.selectable_value(kind, Some(option), option.label()).changed();
+            }
+        });
+    changed
+}
+
+// Number field where 0 means "not set"
+fn edit_optional_rate(ui: &mut egui::Ui, rate: &mut Option<f64>) -> bool {
+    let mut value = rate.unwrap_or(0.0);
+    let changed = ui
+        .add(egui::DragValue::new(&mut value).range(0.0..=100_000.0).speed(1.0).max_decimals(2))
+        .on_hover_text("0 for none")
+        .changed();
+    if changed {
+        *rate = (value > 0.0).then_some(value);
+    }
+    changed
+}
+
+// Tasks in this folder or with this tag get a running meeting cost
+const MEETINGS_FOLDER: &str = "Meetings";
+const MEETING_TAG: &str = "meeting";
+
+// Default length of one pomodoro, changed in the Pomodoro settings. Every full pomodoro
+// within a session counts.
+const POMODORO_MINUTES: u32 = 25;
+
+// Same as edit_optional_minutes, but counted in whole pomodoros of `length` minutes
+fn edit_optional_pomodoros(ui: &mut egui::Ui, minutes: &mut Option<u32>, length: u32) -> bool {
+    let mut value = minutes.unwrap_or(0).div_ceil(length);
+    let changed = ui
+        .add(egui::DragValue::new(&mut value).range(0..=1000).suffix(" 🍅"))
+        .on_hover_text(format!("{} minutes each, 0 for none", length))
+        .changed();
+    if changed {
+        *minutes = (value > 0).then_some(value * length);
+    }
+    changed
+}
+
+fn edit_optional_minutes(ui: &mut egui::Ui, minutes: &mut Option<u32>) -> bool {
+    let mut value = minutes.unwrap_or(0);
+    let changed = ui
+        .add(egui::DragValue::new(&mut value).range(0..=100_000).speed(5).suffix(" min"))
+        .on_hover_text("0 for none")
+        .changed();
+    if changed {
+        *minutes = (value > 0).then_some(value);
+    }
+    changed
 }
 
 fn sanitize_filename(name: &str) -> String {
@@ -36,14 +511,450 @@ fn sanitize_filename(name: &str) -> String {
         .collect()
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct TimeEntry {
+    start: DateTime<Local>,
+    end: DateTime<Local>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    synced: HashMap<String, String>, // Integration name -> id of the remote entry this session was pushed into
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    annotation: Option<String>, // What was done during this session, asked for at pause time
+    #[serde(default, skip_serializing_if = "EntrySource::is_gui")]
+    source: EntrySource,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    timezone: Option<String>, // Zone the session was recorded in, e.g. while travelling
+}
+
+impl TimeEntry {
+    fn new(start: DateTime<Local>, end: DateTime<Local>) -> Self {
+        TimeEntry {
+            start,
+            end,
+            synced: HashMap::new(),
+            annotation: None,
+            source: EntrySource::Gui,
+            timezone: timezones::system().map(str::to_string),
+        }
+    }
+}
+
+// How a session was created, so automated time can be audited or left out of exports.
+// Entries written by other tools into tasks.json can use Api and Import.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+enum EntrySource {
+    #[default]
+    Gui, // Timed in the app
+    Cli,
+    Api,
+    Import,
+    Auto, // Started without a click, e.g. the launch task or resumed after being idle
+}
+
+impl EntrySource {
+    const ALL: [EntrySource; 5] = [EntrySource::Gui, EntrySource::Cli, EntrySource::Api, EntrySource::Import, EntrySource::Auto];
+
+    fn label(&self) -> &'static str {
+        match self {
+            EntrySource::Gui => "App",
+            EntrySource::Cli => "CLI",
+            EntrySource::Api => "API",
+            EntrySource::Import => "Import",
+            EntrySource::Auto => "Auto-tracker",
+        }
+    }
+
+    fn is_gui(&self) -> bool {
+        *self == EntrySource::Gui
+    }
+}
+
+// Two recorded sessions covering the same time, which would count it twice
+#[derive(Clone)]
+struct EntryConflict {
+    first: ConflictEntry, // The one that started first
+    second: ConflictEntry,
+    overlap: i64, // Seconds
+}
+
+// Sessions are found again by task and start time, a task never has two starting at the same moment
+#[derive(Clone)]
+struct ConflictEntry {
+    task_id: String,
+    start: DateTime<Local>,
+    end: DateTime<Local>,
+}
+
+#[derive(Clone, Copy)]
+enum ConflictResolution {
+    Trim,  // The later session starts when the earlier one ends
+    Merge, // One session from the earlier start to the later end, on the earlier session's task
+    DeleteFirst,
+    DeleteSecond,
+}
+
+// Text being edited for one session in the task details
+struct EntryEdit {
+    task_id: String,
+    index: usize,
+    start: String,
+    end: String,
+    annotation: String,
+}
+
+const ENTRY_TIME_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+fn parse_entry_time(text: &str) -> Option<DateTime<Local>> {
+    use chrono::TimeZone;
+    let naive = chrono::NaiveDateTime::parse_from_str(text.trim(), ENTRY_TIME_FORMAT).ok()?;
+    Local.from_local_datetime(&naive).earliest()
+}
+
+// Everything the app keeps in the data folder, listed under Settings → Diagnostics
+const DATA_FILES: &[&str] = &[
+    "tasks.json",
+    "folders.json",
+    "folder_styles.json",
+    "settings.json",
+    "expenses.json",
+    "plan.json",
+    "allocations.json",
+    "adjustments.json",
+    "time_off.json",
+    pomodoro::LOG_FILE,
+    "report_history.json",
+    "redmine_queue.json",
+    EXPORTS_MANIFEST,
+    audit::AUDIT_LOG,
+];
+
+// CSV files the app has written. Clearing all tasks only ever removes files listed here.
+const EXPORTS_MANIFEST: &str = "exports.json";
+
+fn record_export(path: &str) {
+    let mut exports: Vec<String> = read_data_file(EXPORTS_MANIFEST);
+    if !exports.iter().any(|export| export == path) {
+        exports.push(path.to_string());
+        if let Ok(data) = serde_json::to_string(&exports) {
+            write_data_file(EXPORTS_MANIFEST, data);
+        }
+    }
+}
+
+// Exported CSV files that are still there
+fn exported_files() -> Vec<String> {
+    let exports: Vec<String> = read_data_file(EXPORTS_MANIFEST);
+    exports.into_iter().filter(|path| Path::new(path).is_file()).collect()
+}
+
+fn format_file_size(bytes: u64) -> String {
+    match bytes {
+        0..1024 => format!("{} B", bytes),
+        1024..1_048_576 => format!("{:.1} KB", bytes as f64 / 1024.0),
+        _ => format!("{:.1} MB", bytes as f64 / 1_048_576.0),
+    }
+}
+
+// Counts data file writes, so cached statistics know when to rebuild
+static DATA_VERSION: AtomicU64 = AtomicU64::new(0);
+
+// What went wrong reading data files, shown to the user once the window is up
+static DATA_FILE_PROBLEMS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+// Round-trip testing from Settings → Diagnostics: every save is read back and checked against
+// the schema before it is written, and problems are logged. The save goes ahead either way,
+// this is for finding bugs in the schemas and serialization, not for guarding the data.
+static LOG_SAVE_CHECKS: AtomicBool = AtomicBool::new(false);
+
+// Unreadable files that could not be moved aside. Saving would replace what is left of them.
+static UNSAFE_TO_SAVE: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+fn take_data_file_problems() -> Vec<String> {
+    DATA_FILE_PROBLEMS.lock().map(|mut problems| std::mem::take(&mut *problems)).unwrap_or_default()
+}
+
+fn report_data_file_problem(message: String) {
+    tracing::warn!("{}", message);
+    if let Ok(mut problems) = DATA_FILE_PROBLEMS.lock() {
+        problems.push(message);
+    }
+}
+
+// The previous save of a data file, kept for recovery
+fn data_file_backup(path: &str) -> String {
+    format!("{}.bak", path)
+}
+
+// Writes one of the JSON data files. Failures are logged and shown under Settings → Diagnostics.
+// The new contents go to a temporary file that then replaces the old one, so a crash never
+// leaves a half-written file behind.
+fn write_data_file(path: &str, data: String) {
+    DATA_VERSION.fetch_add(1, Ordering::Relaxed);
+    if UNSAFE_TO_SAVE.lock().is_ok_and(|paths| paths.iter().any(|unsafe_path| unsafe_path == path)) {
+        tracing::error!("Not saving {}: the unreadable file is still in place", path);
+        logging::record_save(path, Some("not saved, the file on disk could not be read".to_string()));
+        return;
+    }
+    if LOG_SAVE_CHECKS.load(Ordering::Relaxed) && schema::is_checked(path) {
+        for problem in schema::check_file(path, &data) {
+            tracing::error!("Round-trip check of {}: {}", path, problem);
+        }
+    }
+    let temp = format!("{}.tmp", path);
+    let result = fs::write(&temp, data).and_then(|_| {
+        if Path::new(path).exists() {
+            fs::rename(path, data_file_backup(path))?;
+        }
+        fs::rename(&temp, path)
+    });
+    if let Err(e) = &result {
+        tracing::error!("Failed to save {}: {}", path, e);
+    }
+    logging::record_save(path, result.err().map(|e| e.to_string()));
+}
+
+fn write_restored_file(name: &str, data: &[u8]) -> Result<(), String> {
+    if let Some(parent) = Path::new(name).parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        fs::create_dir_all(parent).map_err(|e| format!("{}: {}", name, e))?;
+    }
+    fs::write(name, data).map_err(|e| format!("{}: {}", name, e))
+}
+
+fn parse_data_file<T: serde::de::DeserializeOwned>(path: &str) -> Result<T, String> {
+    let data = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let Some(schema) = schema::schema(path) else {
+        return serde_json::from_str(&data).map_err(|e| e.to_string());
+    };
+    // Files written by other tools are checked against the schema, but still loaded when
+    // the app can make sense of them
+    let value: serde_json::Value = serde_json::from_str(&data).map_err(|e| e.to_string())?;
+    let problems = schema::validate(&value, &schema);
+    if let Some(first) = problems.first() {
+        report_data_file_problem(format!(
+            "{} doesn't match its schema ({}{}). Run \"work_timer validate {}\" for the full list.",
+            path,
+            first,
+            if problems.len() > 1 { format!(", and {} more", problems.len() - 1) } else { String::new() },
+            path
+        ));
+    }
+    serde_json::from_value(value).map_err(|e| e.to_string())
+}
+
+// Reads a JSON data file, falling back to the default when it is missing. A file that can't
+// be read is moved aside as <file>.corrupt-<time> and the previous save is loaded instead.
+fn read_data_file<T: serde::de::DeserializeOwned + Default>(path: &str) -> T {
+    let backup = data_file_backup(path);
+    if !Path::new(path).exists() {
+        // A save was interrupted between moving the old file away and putting the new one in place
+        if Path::new(&backup).exists() {
+            if let Ok(value) = parse_data_file(&backup) {
+                report_data_file_problem(format!("{} was missing, so the previous save in {} was loaded.", path, backup));
+                return value;
+            }
+        }
+        return T::default();
+    }
+
+    let error = match parse_data_file(path) {
+        Ok(value) => return value,
+        Err(e) => e,
+    };
+    tracing::error!("Failed to load {}: {}", path, error);
+    let corrupt = format!("{}.corrupt-{}", path, Local::now().format("%Y%m%d-%H%M%S"));
+    if let Err(e) = fs::rename(path, &corrupt) {
+        if let Ok(mut paths) = UNSAFE_TO_SAVE.lock() {
+            paths.push(path.to_string());
+        }
+        report_data_file_problem(format!(
+            "{} could not be read ({}) or moved aside ({}). Changes to it won't be saved until the app is restarted.",
+            path, error, e
+        ));
+        return T::default();
+    }
+    match parse_data_file(&backup) {
+        Ok(value) => {
+            report_data_file_problem(format!(
+                "{} could not be read ({}). It was moved to {} and the previous save was loaded instead.",
+                path, error, corrupt
+            ));
+            value
+        }
+        Err(_) => {
+            report_data_file_problem(format!(
+                "{} could not be read ({}). It was moved to {} and there was no usable earlier save, so it starts empty. A backup can be restored from Settings.",
+                path, error, corrupt
+            ));
+            T::default()
+        }
+    }
+}
+
+// Task totals used to be kept as a running sum next to the sessions. Whatever that sum holds
+// beyond the recorded sessions (time from before sessions were kept, imports, edits by hand)
+// becomes time without a session, so the totals stay the same.
+//
+// Time without a session is never negative: a total can't be set below the recorded sessions,
+// those are edited or deleted instead. A total that was lower, kept by older versions, goes
+// up to the recorded time, so totals, daily totals and reports all add up to the same.
+thread_local! {
+    // Set while reading the task list without its sessions, see read_task_list
+    static SKIP_ENTRIES: std::cell::Cell<bool> = const { std::cell::Cell::new(false) };
+}
+
+fn deserialize_entries<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Vec<TimeEntry>, D::Error> {
+    if SKIP_ENTRIES.get() {
+        serde::de::IgnoredAny::deserialize(deserializer)?;
+        return Ok(Vec::new());
+    }
+    Vec::deserialize(deserializer)
+}
+
+// The tasks without their sessions, which are most of the file and slow to parse after years
+// of use. None when the file is missing or can't be read, which read_data_file then deals with.
+fn read_task_list(path: &str) -> Option<HashMap<String, Task>> {
+    let data = fs::read_to_string(path).ok()?;
+    SKIP_ENTRIES.set(true);
+    let tasks = serde_json::from_str(&data).ok();
+    SKIP_ENTRIES.set(false);
+    tasks
+}
+
+fn migrate_tasks(mut tasks: HashMap<String, Task>) -> HashMap<String, Task> {
+    for task in tasks.values_mut() {
+        if let Some(total) = task.legacy_total_duration.take() {
+            task.untracked_duration = total - task.recorded_duration();
+        }
+        if task.untracked_duration < 0 {
+            tracing::warn!(
+                "{}: total was {} seconds below its sessions, it now matches them",
+                task.description,
+                -task.untracked_duration
+            );
+            task.untracked_duration = 0;
+        }
+    }
+    tasks
+}
+
+// Markdown table of every shortcut with this platform's key names
+fn shortcut_cheat_sheet(ctx: &egui::Context) -> String {
+    let mut text = String::from("# Work Timer Keyboard Shortcuts\n\n| Keys | Action |\n|---|---|\n");
+    for shortcut in Shortcut::ALL {
+        text.push_str(&format!("| {} | {} |\n", shortcut.keys_text(ctx).replace('|', "\\|"), shortcut.label()));
+    }
+    text
+}
+
+// Scores how well the query's characters appear in order in the text, or None when they don't.
+// Runs of consecutive characters and matches at the start of words score higher.
+fn fuzzy_score(query: &str, text: &str) -> Option<i32> {
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut position = 0;
+    let mut previous_match: Option<usize> = None;
+    for wanted in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let found = position + text[position..].iter().position(|c| *c == wanted)?;
+        score += 1;
+        if previous_match == Some(found.wrapping_sub(1)) {
+            score += 5;
+        }
+        if found == 0 || !text[found - 1].is_alphanumeric() {
+            score += 3;
+        }
+        score -= (found - position).min(10) as i32 / 2;
+        previous_match = Some(found);
+        position = found + 1;
+    }
+    Some(score)
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum SearchGroup {
+    Tasks,
+    Folders,
+    Tags,
+    Notes,
+}
+
+impl SearchGroup {
+    const ALL: [SearchGroup; 4] = [SearchGroup::Tasks, SearchGroup::Folders, SearchGroup::Tags, SearchGroup::Notes];
+
+    fn label(&self) -> &'static str {
+        match self {
+            SearchGroup::Tasks => "Tasks",
+            SearchGroup::Folders => "Folders",
+            SearchGroup::Tags => "Tags",
+            SearchGroup::Notes => "Session notes",
+        }
+    }
+}
+
+// One result of the global search
+struct SearchHit {
+    group: SearchGroup,
+    task_id: Option<String>, // None for folder results
+    folder: String,
+    title: String,
+    detail: String,
+    score: i32,
+}
+
+// Change applied to every task selected in the search window
+enum BulkEdit {
+    AddTag(String),
+    RemoveTag(String),
+    MoveTo(Option<String>), // None for Uncategorized
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 struct Task {
     id: String,
     description: String,
     folder: Option<String>,
-    total_duration: i64, // Duration in seconds
+    #[serde(default, rename = "total_duration", skip_serializing)]
+    legacy_total_duration: Option<i64>, // Files written before sessions were kept apart, see migrate_tasks
+    #[serde(default)]
+    untracked_duration: i64, // Seconds without a recorded session: imported totals and time set by hand, never negative
     start_time: Option<DateTime<Local>>,
     is_paused: bool,
+    #[serde(default)]
+    billable: bool,
+    #[serde(default, deserialize_with = "deserialize_entries")]
+    entries: Vec<TimeEntry>, // Finished sessions, recorded whenever a running timer stops
+    #[serde(default)]
+    redmine_issue_id: Option<u64>,
+    #[serde(default)]
+    issue_key: Option<String>, // Linked Jira issue, e.g. PROJ-123
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    external_ids: HashMap<String, String>, // Integration name -> id of the mirrored remote item
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    #[serde(default)]
+    hourly_rate: Option<f64>,
+    #[serde(default)]
+    estimate_minutes: Option<u32>,
+    #[serde(default)]
+    estimate_in_pomodoros: bool, // Estimate shown and edited as a number of pomodoros
+    #[serde(default)]
+    attendees: Option<u32>, // Meetings only, for the running cost
+    #[serde(default)]
+    attendee_rate: Option<f64>, // Average hourly rate of an attendee
+    #[serde(default)]
+    created_at: Option<DateTime<Local>>, // None for tasks created before this was recorded
+    #[serde(default)]
+    last_active: Option<DateTime<Local>>, // When the timer last started or stopped
+    #[serde(default)]
+    archived: bool, // Hidden from the task list but kept for statistics and exports
+    #[serde(default)]
+    completed_at: Option<DateTime<Local>>, // When it was last marked complete, for the done list
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    attachments: Vec<String>, // Copies under attachments/, see attachments::add
+    #[serde(default)]
+    work_kind: Option<WorkKind>, // Overrides the folder's
+    #[serde(default, skip_serializing_if = "EntrySource::is_gui")]
+    running_source: EntrySource, // How the running session was started, recorded on its entry
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    color_label: Option<ColorLabel>,
 }
 
 impl Task {
@@ -52,23 +963,53 @@ impl Task {
             id: Uuid::new_v4().to_string(),
             description,
             folder: None,
-            total_duration: 0,
+            legacy_total_duration: None,
+            untracked_duration: 0,
             start_time: None,
             is_paused: false,
+            billable: false,
+            entries: Vec::new(),
+            redmine_issue_id: None,
+            issue_key: None,
+            external_ids: HashMap::new(),
+            tags: Vec::new(),
+            hourly_rate: None,
+            estimate_minutes: None,
+            estimate_in_pomodoros: false,
+            attendees: None,
+            attendee_rate: None,
+            created_at: Some(Local::now()),
+            last_active: None,
+            archived: false,
+            completed_at: None,
+            attachments: Vec::new(),
+            work_kind: None,
+            running_source: EntrySource::Gui,
+            color_label: None,
         }
     }
 
     fn start(&mut self) {
         if self.start_time.is_none() && !self.is_paused {
             self.start_time = Some(Local::now());
+            self.last_active = self.start_time;
+            self.completed_at = None;
+            self.running_source = EntrySource::Gui;
         }
     }
 
     fn pause(&mut self) {
+        self.pause_at(Local::now());
+    }
+
+    // Ends the running session at the given time, e.g. when the user went idle
+    fn pause_at(&mut self, end: DateTime<Local>) {
         if let Some(start) = self.start_time {
-            self.total_duration += Local::now().signed_duration_since(start).num_seconds();
+            let end = end.max(start);
+            self.entries.push(TimeEntry { source: self.running_source, ..TimeEntry::new(start, end) });
             self.start_time = None;
             self.is_paused = true;
+            self.last_active = Some(end);
         }
     }
 
@@ -76,696 +1017,6332 @@ impl Task {
         if self.is_paused {
             self.start_time = Some(Local::now());
             self.is_paused = false;
+            self.last_active = self.start_time;
+            self.running_source = EntrySource::Gui;
+        }
+    }
+
+    // Now while running; older tasks fall back to the end of their last session
+    fn last_active_at(&self) -> Option<DateTime<Local>> {
+        if self.start_time.is_some() {
+            return Some(Local::now());
         }
+        self.last_active.or_else(|| self.entries.iter().map(|entry| entry.end).max())
+    }
+
+    fn recorded_duration(&self) -> i64 {
+        self.entries.iter().map(|entry| (entry.end - entry.start).num_seconds()).sum()
+    }
+
+    // Finished sessions plus time without sessions, not counting the running session
+    fn total_duration(&self) -> i64 {
+        self.recorded_duration() + self.untracked_duration
+    }
+
+    // The sessions stay as they are, the difference is time without a session. So the total
+    // can't go below the sessions, time is taken off by editing those. Returns the total set.
+    fn set_total_duration(&mut self, seconds: i64) -> i64 {
+        let recorded = self.recorded_duration();
+        self.untracked_duration = (seconds - recorded).max(0);
+        recorded + self.untracked_duration
     }
 
     fn get_current_duration(&self) -> i64 {
-        let mut duration = self.total_duration;
+        let mut duration = self.total_duration();
         if let Some(start) = self.start_time {
             duration += Local::now().signed_duration_since(start).num_seconds();
         }
         duration
     }
 
-    fn format_duration(&self) -> String {
-        let duration = self.get_current_duration();
-        let hours = duration / 3600;
-        let minutes = (duration % 3600) / 60;
-        let seconds = duration % 60;
-        format!("{:02}:{:02}:{:02}", hours, minutes, seconds)
+    // Seconds tracked inside [from, to), including the currently running session
+    fn tracked_between(&self, from: DateTime<Local>, to: DateTime<Local>) -> i64 {
+        let overlap = |start: DateTime<Local>, end: DateTime<Local>| {
+            (end.min(to) - start.max(from)).num_seconds().max(0)
+        };
+        let mut tracked: i64 = self.entries.iter().map(|entry| overlap(entry.start, entry.end)).sum();
+        if let Some(start) = self.start_time {
+            tracked += overlap(start, Local::now());
+        }
+        tracked
+    }
+
+    // Tasks in a "Meetings" folder or tagged meeting
+    fn is_meeting(&self) -> bool {
+        self.folder.as_deref().is_some_and(|folder| folder.eq_ignore_ascii_case(MEETINGS_FOLDER))
+            || self.tags.iter().any(|tag| tag.eq_ignore_ascii_case(MEETING_TAG))
+    }
+
+    // What the meeting has cost so far, once attendees and their rate are set
+    fn meeting_cost(&self) -> Option<f64> {
+        if !self.is_meeting() {
+            return None;
+        }
+        let people = self.attendees? as f64;
+        Some(people * self.attendee_rate? * self.get_current_duration() as f64 / 3600.0)
+    }
+
+    // Full pomodoros of `length` minutes per session, keyed by when the session started,
+    // including the running one
+    fn pomodoro_sessions(&self, length: u32) -> impl Iterator<Item = (DateTime<Local>, i64)> + '_ {
+        let running = self.start_time.map(|start| (start, Local::now()));
+        self.entries
+            .iter()
+            .map(|entry| (entry.start, entry.end))
+            .chain(running)
+            .map(move |(start, end)| (start, (end - start).num_minutes() / length as i64))
+    }
+
+    fn pomodoros(&self, length: u32) -> i64 {
+        self.pomodoro_sessions(length).map(|(_, count)| count).sum()
+    }
+
+    // Pomodoros of the sessions that started inside [from, to)
+    fn pomodoros_between(&self, from: DateTime<Local>, to: DateTime<Local>, length: u32) -> i64 {
+        self.pomodoro_sessions(length)
+            .filter(|(start, _)| *start >= from && *start < to)
+            .map(|(_, count)| count)
+            .sum()
+    }
+
+    // Tracked seconds per day, with sessions split at the day boundary. Time without a recorded
+    // session (tracked before sessions existed, imported or set by hand) is returned under None.
+    fn daily_durations(&self, calendar: Calendar) -> Vec<(Option<NaiveDate>, i64)> {
+        let mut days: std::collections::BTreeMap<Option<NaiveDate>, i64> = std::collections::BTreeMap::new();
+        for entry in &self.entries {
+            for (date, seconds) in calendar.split_by_day(entry.start, entry.end) {
+                *days.entry(Some(date)).or_default() += seconds;
+            }
+        }
+        if self.untracked_duration > 0 {
+            *days.entry(None).or_default() += self.untracked_duration;
+        }
+        if let Some(start) = self.start_time {
+            for (date, seconds) in calendar.split_by_day(start, Local::now()) {
+                *days.entry(Some(date)).or_default() += seconds;
+            }
+        }
+        days.into_iter().collect()
+    }
+
+    // Tracked seconds per hour of day over all sessions, split at hour boundaries
+    fn hourly_distribution(&self) -> [i64; 24] {
+        let mut hours = [0; 24];
+        let running = self.start_time.map(|start| (start, Local::now()));
+        let sessions = self.entries.iter().map(|entry| (entry.start, entry.end)).chain(running);
+        for (start, end) in sessions {
+            let mut cursor = start;
+            while cursor < end {
+                let into_hour = cursor.minute() as i64 * 60 + cursor.second() as i64;
+                let chunk_end = (cursor + chrono::Duration::seconds(3600 - into_hour)).min(end);
+                hours[cursor.hour() as usize] += (chunk_end - cursor).num_seconds();
+                cursor = chunk_end;
+            }
+        }
+        hours
+    }
+
+    fn status(&self) -> TaskStatus {
+        if self.start_time.is_some() {
+            TaskStatus::Running
+        } else if self.is_paused {
+            TaskStatus::Paused
+        } else if self.total_duration() == 0 {
+            TaskStatus::NotStarted
+        } else {
+            TaskStatus::Completed
+        }
+    }
+
+    fn status_label(&self) -> &'static str {
+        if self.start_time.is_some() {
+            "Running"
+        } else if self.is_paused {
+            "Paused"
+        } else {
+            "Stopped"
+        }
+    }
+
+    fn first_tracked_date(&self, calendar: Calendar) -> Option<NaiveDate> {
+        self.entries
+            .iter()
+            .map(|entry| entry.start)
+            .chain(self.start_time)
+            .min()
+            .map(|start| calendar.day_of(start))
+    }
+
+    fn format_duration(&self, format: DurationFormat) -> String {
+        format.format(self.get_current_duration())
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 struct FolderStyle {
     name: String,
+    #[serde(default)]
+    ask_annotation: bool, // Ask "What did you just do?" when pausing a task in this folder
+    #[serde(default)]
+    task_defaults: TaskDefaults,
+    #[serde(default)]
+    budget: Option<FolderBudget>,
+    #[serde(default)]
+    color: Option<[u8; 3]>, // Tint for the folder name in the task list
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    ticket_patterns: Vec<String>, // Clipboard patterns for this folder's tickets, see clipboard::recognize
+    #[serde(default)]
+    work_kind: Option<WorkKind>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    export_dir: String, // Where this folder's exports go, e.g. the client's share. Empty for the app folder.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    auto_complete_days: Option<u32>, // Complete tasks left paused this long, None to leave them
 }
 
-impl Default for StatsTab {
-    fn default() -> Self {
-        StatsTab::Overview
+// Deep work needs long stretches of focus, shallow work is email, meetings and admin
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+enum WorkKind {
+    Deep,
+    Shallow,
+}
+
+impl WorkKind {
+    fn label(&self) -> &'static str {
+        match self {
+            WorkKind::Deep => "Deep work",
+            WorkKind::Shallow => "Shallow work",
+        }
     }
 }
 
-#[derive(Default)]
-struct WorkTimer {
-    tasks: HashMap<String, Task>,
-    folders: Vec<String>,
-    folder_styles: HashMap<String, FolderStyle>,
-    data_file: String,
-    new_task_input: String,
-    new_folder_input: String,
-    selected_folder: Option<String>,
-    show_new_folder_dialog: bool,
-    show_clear_folders_confirm: bool,
-    dragged_task: Option<String>,
-    show_clear_confirm: bool,
-    show_clear_folder_confirm: Option<String>,
-    show_delete_task_confirm: Option<String>,
-    export_message: Option<(String, f32)>,
-    dark_mode: bool,
-    show_shortcuts: bool,
-    show_settings: bool,
-    show_statistics: bool,
-    selected_stats_tab: StatsTab,
-    ui_scale: f32,
-    temporary_ui_scale: f32,
-    focus_new_task: bool,
-    focus_new_folder: bool,
-    show_add_task_dialog: bool,
-    add_task_to_folder: Option<String>,
-    new_task_in_folder: String,
-    dragged_folder: Option<String>,
-    focused_folder_index: Option<usize>,
-    focused_task_index: Option<usize>,
-    editing_duration_task_id: Option<String>,
-    editing_duration_value: String,
+// Label for a single task like the Finder's, shown as a strip at the start of its row
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+enum ColorLabel {
+    Red,
+    Orange,
+    Yellow,
+    Green,
+    Blue,
+    Purple,
+    Gray,
 }
 
-impl WorkTimer {
-    fn new() -> Self {
-        let data_file = "tasks.json".to_string();
-        let tasks = if Path::new(&data_file).exists() {
-            let data = fs::read_to_string(&data_file).unwrap_or_default();
-            serde_json::from_str(&data).unwrap_or_default()
-        } else {
-            HashMap::new()
-        };
-
-        // Load folders from file
-        let folders = if Path::new("folders.json").exists() {
-            let data = fs::read_to_string("folders.json").unwrap_or_default();
-            serde_json::from_str(&data).unwrap_or_default()
-        } else {
-            Vec::new()
-        };
-
-        // Load folder styles from file
-        let folder_styles = if Path::new("folder_styles.json").exists() {
-            let data = fs::read_to_string("folder_styles.json").unwrap_or_default();
-            serde_json::from_str(&data).unwrap_or_default()
-        } else {
-            HashMap::new()
-        };
-
-        let selected_folder = folders.first().cloned();
-        let default_scale = 2.0;
-        let focused_folder_index = if !folders.is_empty() { Some(0) } else { None };
-        let focused_task_index = None;
+impl ColorLabel {
+    const ALL: [ColorLabel; 7] = [
+        ColorLabel::Red,
+        ColorLabel::Orange,
+        ColorLabel::Yellow,
+        ColorLabel::Green,
+        ColorLabel::Blue,
+        ColorLabel::Purple,
+        ColorLabel::Gray,
+    ];
+
+    fn label(&self) -> &'static str {
+        match self {
+            ColorLabel::Red => "Red",
+            ColorLabel::Orange => "Orange",
+            ColorLabel::Yellow => "Yellow",
+            ColorLabel::Green => "Green",
+            ColorLabel::Blue => "Blue",
+            ColorLabel::Purple => "Purple",
+            ColorLabel::Gray => "Gray",
+        }
+    }
 
-        WorkTimer {
-            tasks,
-            folders,
-            folder_styles,
-            data_file,
-            new_task_input: String::new(),
-            new_folder_input: String::new(),
-            selected_folder,
-            show_new_folder_dialog: false,
-            show_clear_folders_confirm: false,
-            dragged_task: None,
-            show_clear_confirm: false,
-            show_clear_folder_confirm: None,
-            show_delete_task_confirm: None,
-            export_message: None,
-            dark_mode: true,
-            show_shortcuts: false,
-            show_settings: false,
-            show_statistics: false,
-            selected_stats_tab: StatsTab::Overview,
-            ui_scale: default_scale,
-            temporary_ui_scale: default_scale,
-            focus_new_task: false,
-            focus_new_folder: false,
-            show_add_task_dialog: false,
-            add_task_to_folder: None,
-            new_task_in_folder: String::new(),
-            dragged_folder: None,
-            focused_folder_index,
-            focused_task_index,
-            editing_duration_task_id: None,
-            editing_duration_value: String::new(),
+    fn color(&self) -> egui::Color32 {
+        match self {
+            ColorLabel::Red => egui::Color32::from_rgb(235, 80, 75),
+            ColorLabel::Orange => egui::Color32::from_rgb(245, 150, 50),
+            ColorLabel::Yellow => egui::Color32::from_rgb(240, 205, 50),
+            ColorLabel::Green => egui::Color32::from_rgb(90, 190, 80),
+            ColorLabel::Blue => egui::Color32::from_rgb(60, 140, 240),
+            ColorLabel::Purple => egui::Color32::from_rgb(170, 100, 220),
+            ColorLabel::Gray => egui::Color32::from_rgb(150, 150, 150),
         }
     }
 
-    fn add_task(&mut self, description: String) -> String {
-        let mut task = Task::new(description);
-        task.folder = self.selected_folder.clone();
-        let id = task.id.clone();
-        self.tasks.insert(id.clone(), task);
-        self.save_tasks();
-        id
+    // Name with a dot, in the label's color, for menus
+    fn text(&self) -> egui::RichText {
+        egui::RichText::new(format!("● {}", self.label())).color(self.color())
     }
+}
 
-    fn add_folder(&mut self, name: String) {
-        if !name.is_empty() && !self.folders.contains(&name) {
-            let style = FolderStyle { name: name.clone() };
-            self.folder_styles.insert(name.clone(), style);
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+enum BudgetPeriod {
+    Weekly,
+    Monthly,
+    Total,
+}
 
-            self.folders.push(name.clone());
-            self.folders.sort();
-            if self.selected_folder.is_none() {
-                self.selected_folder = Some(name.clone());
-            }
-            // Find the index of the newly added folder after sorting
-            if let Some(new_folder_idx) = self.folders.iter().position(|f| f == &name) {
-                self.focused_folder_index = Some(new_folder_idx);
-                self.focused_task_index = None; // Reset task focus when switching folders
-            }
-            self.save_tasks();
-            self.save_folder_styles();
+impl BudgetPeriod {
+    fn label(&self) -> &'static str {
+        match self {
+            BudgetPeriod::Weekly => "per week",
+            BudgetPeriod::Monthly => "per month",
+            BudgetPeriod::Total => "in total",
         }
     }
 
-    fn move_task_to_folder(&mut self, task_id: &str, folder: Option<String>) {
-        if let Some(task) = self.tasks.get_mut(task_id) {
-            task.folder = folder;
-            self.save_tasks();
+    // Start of the period containing `now`, None for no start
+    fn start(&self, now: DateTime<Local>, calendar: Calendar) -> Option<DateTime<Local>> {
+        let today = calendar.day_of(now);
+        match self {
+            BudgetPeriod::Weekly => Some(calendar.day_start(calendar.week_start(today))),
+            BudgetPeriod::Monthly => today.with_day(1).map(|date| calendar.day_start(date)),
+            BudgetPeriod::Total => None,
         }
     }
+}
 
-    fn save_tasks(&self) {
-        if let Ok(data) = serde_json::to_string(&self.tasks) {
-            let _ = fs::write(&self.data_file, data);
-        }
-        // Save folders to a separate file
-        if let Ok(data) = serde_json::to_string(&self.folders) {
-            let _ = fs::write("folders.json", data);
+// Hours a client folder may use, with alerts when tracked time crosses a share of it
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct FolderBudget {
+    hours: f64,
+    period: BudgetPeriod,
+    alert_percents: Vec<u32>,
+}
+
+impl Default for FolderBudget {
+    fn default() -> Self {
+        FolderBudget {
+            hours: 40.0,
+            period: BudgetPeriod::Monthly,
+            alert_percents: vec![80, 100],
         }
     }
+}
 
-    fn get_projects(&self) -> Vec<String> {
-        let mut projects: Vec<String> = self
-            .tasks
-            .values()
-            .filter_map(|task| task.folder.clone())
-            .collect::<std::collections::HashSet<_>>()
-            .into_iter()
-            .collect();
-        if projects.is_empty() {
-            projects.push("Default".to_string());
+// A day away from work. Days off don't count against goals, streaks or contracted hours.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct TimeOff {
+    date: NaiveDate,
+    kind: TimeOffKind,
+    #[serde(default)]
+    note: String, // e.g. the event title when imported from a calendar
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+enum TimeOffKind {
+    #[default]
+    Vacation,
+    Sick,
+    Holiday,
+}
+
+impl TimeOffKind {
+    const ALL: [TimeOffKind; 3] = [TimeOffKind::Vacation, TimeOffKind::Sick, TimeOffKind::Holiday];
+
+    fn label(&self) -> &'static str {
+        match self {
+            TimeOffKind::Vacation => "Vacation",
+            TimeOffKind::Sick => "Sick",
+            TimeOffKind::Holiday => "Holiday",
         }
-        projects.sort();
-        projects
     }
+}
 
-    fn clear_all_tasks(&mut self) {
-        self.tasks.clear();
-        self.save_tasks();
-        
-        // Clean up CSV files
-        let _ = fs::remove_file("work_timer_export.csv"); // Remove main export file
-        
-        // Remove individual task exports
-        if let Ok(entries) = fs::read_dir(".") {
-            for entry in entries.flatten() {
-                if let Ok(file_name) = entry.file_name().into_string() {
-                    if file_name.ends_with(".csv") {
-                        let _ = fs::remove_file(&file_name);
-                    }
-                }
+// Applied to tasks created inside a folder
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+struct TaskDefaults {
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    billable: bool,
+    #[serde(default)]
+    hourly_rate: Option<f64>,
+    #[serde(default)]
+    estimate_minutes: Option<u32>,
+    #[serde(default)]
+    auto_start: bool, // Start the timer as soon as the task is created
+}
+
+impl TaskDefaults {
+    fn apply_to(&self, task: &mut Task) {
+        for tag in &self.tags {
+            if !task.tags.contains(tag) {
+                task.tags.push(tag.clone());
             }
         }
+        task.billable |= self.billable;
+        task.hourly_rate = task.hourly_rate.or(self.hourly_rate);
+        task.estimate_minutes = task.estimate_minutes.or(self.estimate_minutes);
     }
+}
 
-    fn get_unique_filename(&self, base_name: &str) -> String {
-        let sanitized_name = sanitize_filename(base_name);
-        let mut filename = format!("{}.csv", sanitized_name);
-        let mut counter = 1;
+// Time added to or taken off a day without belonging to any task, e.g. forgotten admin work.
+// Counted in daily totals and reports, edited in the day review.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct Adjustment {
+    id: String,
+    date: NaiveDate,
+    seconds: i64, // Negative to take time off
+    reason: String,
+}
 
-        while Path::new(&filename).exists() {
-            filename = format!("{}_{}.csv", sanitized_name, counter);
-            counter += 1;
-        }
+// Hours set aside for a folder in one week, from the planner
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct WeekAllocation {
+    week_start: NaiveDate,
+    folder: String,
+    hours: f64,
+}
 
-        filename
+// A block of time planned for a task on a given day
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct PlannedBlock {
+    id: String,
+    task_id: String,
+    date: NaiveDate,
+    start: NaiveTime,
+    minutes: u32,
+}
+
+impl PlannedBlock {
+    fn window(&self) -> (DateTime<Local>, DateTime<Local>) {
+        let start = local_midnight(self.date) + (self.start - NaiveTime::MIN);
+        (start, start + chrono::Duration::minutes(self.minutes as i64))
     }
+}
 
-    fn export_task_to_csv(&self, task: &Task) -> Result<String, Box<dyn std::error::Error>> {
-        let filename = self.get_unique_filename(&task.description);
-        let file = fs::File::create(&filename)?;
-        let mut writer = csv::Writer::from_writer(file);
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct Expense {
+    id: String,
+    description: String,
+    amount: f64,
+    date: NaiveDate,
+    folder: Option<String>,
+}
 
-        // Write header
-        writer.write_record(&["Task", "Project", "Duration (HH:MM:SS)", "Status"])?;
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+enum PayPeriodKind {
+    #[default]
+    Weekly,
+    BiWeekly,
+    Monthly,
+}
 
-        // Write task
-        let status = if task.start_time.is_some() {
-            "Running"
-        } else if task.is_paused {
-            "Paused"
-        } else {
-            "Stopped"
-        };
+impl PayPeriodKind {
+    fn label(&self) -> &'static str {
+        match self {
+            PayPeriodKind::Weekly => "Weekly",
+            PayPeriodKind::BiWeekly => "Bi-weekly",
+            PayPeriodKind::Monthly => "Monthly",
+        }
+    }
+}
 
-        writer.write_record(&[
-            &task.description,
-            task.folder.as_deref().unwrap_or("Uncategorized"),
-            &task.format_duration(),
-            status
-        ])?;
-        writer.flush()?;
-        Ok(filename)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct PayPeriodConfig {
+    kind: PayPeriodKind,
+    contracted_hours: f64, // Contracted hours per pay period
+    anchor_date: NaiveDate, // First day of any bi-weekly period
+}
+
+impl Default for PayPeriodConfig {
+    fn default() -> Self {
+        PayPeriodConfig {
+            kind: PayPeriodKind::Weekly,
+            contracted_hours: 40.0,
+            anchor_date: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+        }
+    }
+}
+
+impl PayPeriodConfig {
+    fn period_start(&self, date: NaiveDate, calendar: Calendar) -> NaiveDate {
+        match self.kind {
+            PayPeriodKind::Weekly => calendar.week_start(date),
+            PayPeriodKind::BiWeekly => {
+                let days = (date - self.anchor_date).num_days();
+                self.anchor_date + chrono::Duration::days(days.div_euclid(14) * 14)
+            }
+            PayPeriodKind::Monthly => date.with_day(1).unwrap_or(date),
+        }
     }
 
-    fn export_to_csv(&self) -> Result<String, Box<dyn std::error::Error>> {
-        let filename = "work_timer_export.csv";
-        let file = fs::File::create(filename)?;
-        let mut writer = csv::Writer::from_writer(file);
+    fn next_period_start(&self, start: NaiveDate) -> NaiveDate {
+        match self.kind {
+            PayPeriodKind::Weekly => start + chrono::Duration::days(7),
+            PayPeriodKind::BiWeekly => start + chrono::Duration::days(14),
+            PayPeriodKind::Monthly => start
+                .checked_add_months(Months::new(1))
+                .unwrap_or(start + chrono::Duration::days(31)),
+        }
+    }
+}
 
-        // Write header
-        writer.write_record(&["Task", "Project", "Duration (HH:MM:SS)", "Status"])?;
+#[derive(Clone)]
+struct PayPeriodSummary {
+    start: NaiveDate,
+    end: NaiveDate, // Last day of the period (inclusive)
+    tracked: i64,
+    contracted: i64,
+    carry_over: i64, // Running balance of tracked minus contracted time up to and including this period
+}
 
-        // Write tasks
-        for task in self.tasks.values() {
-            let status = if task.start_time.is_some() {
-                "Running"
-            } else if task.is_paused {
-                "Paused"
-            } else {
-                "Stopped"
-            };
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+enum ExportColumn {
+    Task,
+    Project,
+    Date,
+    Duration,
+    DecimalHours,
+    Status,
+    Billable,
+    Amount, // Billable amount before tax
+    Tax,
+    Total, // Billable amount with tax
+    Annotations,
+    Source, // How the sessions were created
+    TimeZone, // Zone the dates are in, and where a session was recorded if elsewhere
+}
 
-            writer.write_record(&[
-                &task.description,
-                task.folder.as_deref().unwrap_or("Uncategorized"),
-                &task.format_duration(),
-                status
-            ])?;
+impl ExportColumn {
+    const ALL: [ExportColumn; 13] = [
+        ExportColumn::Task,
+        ExportColumn::Project,
+        ExportColumn::Date,
+        ExportColumn::Duration,
+        ExportColumn::DecimalHours,
+        ExportColumn::Status,
+        ExportColumn::Billable,
+        ExportColumn::Amount,
+        ExportColumn::Tax,
+        ExportColumn::Total,
+        ExportColumn::Annotations,
+        ExportColumn::Source,
+        ExportColumn::TimeZone,
+    ];
+
+    fn label(&self) -> &'static str {
+        match self {
+            ExportColumn::Task => "Task",
+            ExportColumn::Project => "Project",
+            ExportColumn::Date => "Date",
+            ExportColumn::Duration => "Duration",
+            ExportColumn::DecimalHours => "Hours",
+            ExportColumn::Status => "Status",
+            ExportColumn::Billable => "Billable",
+            ExportColumn::Amount => "Amount",
+            ExportColumn::Tax => "Tax",
+            ExportColumn::Total => "Total",
+            ExportColumn::Annotations => "Notes",
+            ExportColumn::Source => "Source",
+            ExportColumn::TimeZone => "Time zone",
         }
+    }
 
-        writer.flush()?;
-        Ok(filename.to_string())
+    // Exports with one of these also list the folder's expenses
+    fn is_amount(&self) -> bool {
+        matches!(self, ExportColumn::Amount | ExportColumn::Tax | ExportColumn::Total)
     }
+}
 
-    fn export_folder_to_csv(
-        &self,
-        folder_name: &str,
-    ) -> Result<String, Box<dyn std::error::Error>> {
-        let filename = format!("folder_{}.csv", sanitize_filename(folder_name));
-        let file = fs::File::create(&filename)?;
-        let mut writer = csv::Writer::from_writer(file);
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+enum ExportGrouping {
+    Task,
+    TaskAndDay,
+    Entry,
+}
 
-        // Write header
-        writer.write_record(&["Task", "Project", "Duration (HH:MM:SS)", "Status"])?;
+impl ExportGrouping {
+    fn label(&self) -> &'static str {
+        match self {
+            ExportGrouping::Task => "One row per task",
+            ExportGrouping::TaskAndDay => "One row per task and day",
+            ExportGrouping::Entry => "One row per session",
+        }
+    }
+}
 
-        // Write tasks in this folder
-        for task in self.tasks.values() {
-            if task.folder.as_deref() == Some(folder_name) {
-                let status = if task.start_time.is_some() {
-                    "Running"
-                } else if task.is_paused {
-                    "Paused"
-                } else {
-                    "Stopped"
-                };
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+enum ExportFormat {
+    Csv,
+    Tsv,
+}
 
-                writer.write_record(&[
-                    &task.description,
-                    folder_name,
-                    &task.format_duration(),
-                    status
-                ])?;
-            }
+impl ExportFormat {
+    fn label(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "CSV (comma separated)",
+            ExportFormat::Tsv => "TSV (tab separated)",
         }
-
-        writer.flush()?;
-        Ok(filename)
     }
+}
 
-    fn clear_folder(&mut self, folder_name: &str) {
-        // Remove the folder's CSV export if it exists
-        let folder_csv = format!("folder_{}.csv", sanitize_filename(folder_name));
-        let _ = fs::remove_file(&folder_csv);
-
-        // Remove individual task CSV files for tasks in this folder and the tasks themselves
-        self.tasks.retain(|_, task| {
-            if task.folder.as_deref() == Some(folder_name) {
-                // Remove the task's CSV file if it exists
-                let _ = fs::remove_file(format!("{}.csv", sanitize_filename(&task.description)));
-                false // Remove this task
-            } else {
-                true // Keep tasks from other folders
-            }
-        });
+// How durations are written, in the app and in exports
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+enum DurationFormat {
+    #[default]
+    Clock, // 30:15:00
+    Days, // 1d 6h 15m
+    HoursMinutes, // 30h 15m
+    Decimal, // 30.25
+}
 
-        // Remove the folder from the folders list
-        if let Some(index) = self.folders.iter().position(|f| f == folder_name) {
-            self.folders.remove(index);
-            self.folder_styles.remove(folder_name);
-            // If this was the selected folder, clear the selection
-            if self.selected_folder.as_deref() == Some(folder_name) {
-                self.selected_folder = self.folders.first().cloned();
-            }
-            // Update focused folder index if needed
-            if let Some(focused_idx) = self.focused_folder_index {
-                if focused_idx >= self.folders.len() {
-                    self.focused_folder_index = if self.folders.is_empty() {
-                        None
-                    } else {
-                        Some(self.folders.len() - 1)
-                    };
-                }
-            }
-            self.save_tasks();
-            self.save_folder_styles();
+impl DurationFormat {
+    const ALL: [DurationFormat; 4] = [
+        DurationFormat::Clock,
+        DurationFormat::Days,
+        DurationFormat::HoursMinutes,
+        DurationFormat::Decimal,
+    ];
+
+    fn label(&self) -> &'static str {
+        match self {
+            DurationFormat::Clock => "Clock (30:15:00)",
+            DurationFormat::Days => "Days (1d 6h 15m)",
+            DurationFormat::HoursMinutes => "Hours (30h 15m)",
+            DurationFormat::Decimal => "Decimal hours (30.25)",
         }
     }
 
-    fn save_folder_styles(&self) {
-        if let Ok(data) = serde_json::to_string(&self.folder_styles) {
-            let _ = fs::write("folder_styles.json", data);
+    // Column header for a duration column in this format
+    fn header(&self) -> &'static str {
+        match self {
+            DurationFormat::Clock => "Duration (HH:MM:SS)",
+            DurationFormat::Days | DurationFormat::HoursMinutes => "Duration",
+            DurationFormat::Decimal => "Duration (hours)",
         }
     }
 
-    fn configure_theme(&self, ctx: &egui::Context) {
-        let mut visuals = if self.dark_mode {
-            egui::Visuals::dark()
-        } else {
-            egui::Visuals::light()
-        };
-        
-        // Customize colors based on theme
-        if self.dark_mode {
-            visuals.override_text_color = Some(egui::Color32::from_rgb(230, 230, 230));
-            visuals.widgets.noninteractive.bg_fill = egui::Color32::from_rgb(32, 33, 36);
-            visuals.widgets.inactive.bg_fill = egui::Color32::from_rgb(45, 45, 48);
-            visuals.widgets.hovered.bg_fill = egui::Color32::from_rgb(55, 55, 58);
-            visuals.widgets.active.bg_fill = egui::Color32::from_rgb(48, 48, 51);
-            visuals.window_fill = egui::Color32::from_rgb(32, 33, 36);
-            visuals.panel_fill = egui::Color32::from_rgb(32, 33, 36);
-        } else {
-            visuals.override_text_color = Some(egui::Color32::from_rgb(25, 25, 25));
-            visuals.widgets.noninteractive.bg_fill = egui::Color32::from_rgb(252, 252, 252);
-            visuals.widgets.inactive.bg_fill = egui::Color32::from_rgb(248, 248, 248);
-            visuals.widgets.hovered.bg_fill = egui::Color32::from_rgb(240, 240, 240);
-            visuals.widgets.active.bg_fill = egui::Color32::from_rgb(235, 235, 235);
-            visuals.window_fill = egui::Color32::from_rgb(252, 252, 252);
-            visuals.panel_fill = egui::Color32::from_rgb(252, 252, 252);
+    fn format(&self, seconds: i64) -> String {
+        let hours = seconds / 3600;
+        let minutes = (seconds % 3600) / 60;
+        match self {
+            DurationFormat::Clock => format!("{:02}:{:02}:{:02}", hours, minutes, seconds % 60),
+            DurationFormat::Days => {
+                let days = hours / 24;
+                let hours = hours % 24;
+                let mut parts = Vec::new();
+                if days > 0 {
+                    parts.push(format!("{}d", days));
+                }
+                if hours > 0 {
+                    parts.push(format!("{}h", hours));
+                }
+                if minutes > 0 || parts.is_empty() {
+                    parts.push(format!("{}m", minutes));
+                }
+                parts.join(" ")
+            }
+            DurationFormat::HoursMinutes if hours > 0 => format!("{}h {}m", hours, minutes),
+            DurationFormat::HoursMinutes => format!("{}m", minutes),
+            DurationFormat::Decimal => format!("{:.2}", seconds as f64 / 3600.0),
         }
-        
-        // Apply the styles
-        ctx.set_visuals(visuals);
-        ctx.set_pixels_per_point(self.ui_scale);
     }
+}
 
-    fn get_folders(&self) -> Vec<String> {
-        self.folders.clone()
-    }
+// How tightly the task list is packed
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+enum Density {
+    #[default]
+    Comfortable,
+    Compact, // Smaller paddings, and colored dots instead of status text
+}
 
-    fn get_tasks_by_folder(&self) -> HashMap<String, Vec<String>> {
-        let mut tasks_by_folder: HashMap<String, Vec<String>> = HashMap::new();
-        for (id, task) in self.tasks.iter() {
-            let folder_name = task
-                .folder
-                .clone()
-                .unwrap_or_else(|| "Uncategorized".to_string());
-            tasks_by_folder
-                .entry(folder_name)
-                .or_default()
-                .push(id.clone());
+impl Density {
+    fn label(&self) -> &'static str {
+        match self {
+            Density::Comfortable => "Comfortable",
+            Density::Compact => "Compact",
         }
-        tasks_by_folder
     }
+}
 
-    fn handle_duration_edit(&mut self, task_id: &str, action: DurationEditAction) {
-        match action {
-            DurationEditAction::StartEdit(current_value) => {
-                self.editing_duration_task_id = Some(task_id.to_string());
-                self.editing_duration_value = current_value;
-            }
-            DurationEditAction::StopEdit(new_duration) => {
-                if let Some(duration) = new_duration {
-                    self.update_task_duration(task_id, duration);
-                }
-                self.editing_duration_task_id = None;
-                self.editing_duration_value.clear();
-            }
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct ExportTemplate {
+    id: String,
+    name: String,
+    folder: Option<String>, // Client folder this template belongs to, None for every folder
+    columns: Vec<ExportColumn>,
+    rounding_minutes: u32, // Round each row up to this many minutes, 0 to keep exact seconds
+    grouping: ExportGrouping,
+    format: ExportFormat,
+    #[serde(default)]
+    duration_format: Option<DurationFormat>, // None to follow the app setting
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    excluded_sources: Vec<EntrySource>, // Sessions left out, with one row per session
+}
+
+impl ExportTemplate {
+    fn new(name: String, folder: Option<String>) -> Self {
+        ExportTemplate {
+            id: Uuid::new_v4().to_string(),
+            name,
+            folder,
+            columns: vec![ExportColumn::Task, ExportColumn::Project, ExportColumn::Duration, ExportColumn::Status],
+            rounding_minutes: 0,
+            grouping: ExportGrouping::Task,
+            format: ExportFormat::Csv,
+            duration_format: None,
+            excluded_sources: Vec::new(),
         }
     }
 
-    fn display_task(
-        &mut self,
-        ui: &mut egui::Ui,
-        task_id: String,
-        description: String,
-        duration: i64,
-        start_time: Option<DateTime<Local>>,
-        is_paused: bool,
-    ) -> (Option<TaskAction>, Option<String>) {
-        let mut action = None;
-        let mut export_error = None;
-        let is_editing = Some(&task_id) == self.editing_duration_task_id.as_ref();
-        
-        ui.horizontal(|ui| {
-            // Complete button (checkbox style) on the left
-            let is_completed = duration > 0 && start_time.is_none() && !is_paused;
-            let complete_icon = if is_completed {
-                fill::CHECK_SQUARE
-            } else {
-                fill::SQUARE
-            };
-            if ui.button(complete_icon).clicked() {
-                action = Some(TaskAction::Complete);
-            }
-            
-            ui.label(&description);
-            
-            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                // Delete button
-                if ui.button(fill::TRASH).clicked() {
-                    action = Some(TaskAction::Delete);
-                }
+    fn applies_to(&self, folder_name: &str) -> bool {
+        self.folder.as_deref().is_none_or(|folder| folder == folder_name)
+    }
+}
 
-                // Export single task button
-                if ui.button(fill::EXPORT).clicked() {
-                    export_error = Some(format!("Error exporting task: Task export not implemented in closure"));
-                }
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+struct Settings {
+    #[serde(default)]
+    pay_period: PayPeriodConfig,
+    #[serde(default)]
+    task_sort: TaskSort,
+    #[serde(default)]
+    export_templates: Vec<ExportTemplate>,
+    #[serde(default)]
+    reports: Vec<reports::ReportDefinition>,
+    #[serde(default)]
+    harvest: harvest::HarvestConfig,
+    #[serde(default)]
+    redmine: redmine::RedmineConfig,
+    #[serde(default)]
+    api: api::ApiConfig,
+    #[serde(default)]
+    notion: notion::NotionConfig,
+    #[serde(default)]
+    obsidian: ObsidianConfig,
+    #[serde(default)]
+    task_import: task_import::TaskImportConfig,
+    #[serde(default)]
+    activity: activity::ActivityConfig,
+    #[serde(default)]
+    disabled_plugins: Vec<String>,
+    #[serde(default)]
+    hooks: hooks::HookConfig,
+    #[serde(default)]
+    day_review: DayReviewConfig,
+    #[serde(default)]
+    weekly_summary: WeeklySummaryConfig,
+    #[serde(default)]
+    smtp: mail::SmtpConfig,
+    #[serde(default)]
+    work_hours: WorkHours,
+    #[serde(default)]
+    launch_task: Option<String>, // Task started automatically when the app opens during work hours
+    #[serde(default)]
+    pinned_tasks: Vec<String>, // Task IDs shown as quick-start buttons under the top bar
+    #[serde(default)]
+    idle_reminder_minutes: u32, // Ask what I'm working on after this long without a timer, 0 to never ask
+    #[serde(default)]
+    calendar_gaps: ics::CalendarGapConfig,
+    #[serde(default)]
+    idle_pause: IdlePauseConfig,
+    #[serde(default)]
+    daily_goal_hours: f64, // Shown as a progress ring in the top bar, 0 for no goal
+    #[serde(default)]
+    deep_work_target_percent: u32, // Share of classified time meant to be deep work, 0 for no target
+    #[serde(default)]
+    day_start_hour: u32, // Hour at which a new day starts for daily totals, e.g. 4 to count late nights as the day before
+    #[serde(default)]
+    duration_format: DurationFormat,
+    #[serde(default)]
+    density: Density,
+    #[serde(default)]
+    week_starts_on_sunday: bool, // Weeks in statistics, reports and goals start on Monday otherwise
+    #[serde(default)]
+    overlay: OverlayConfig,
+    #[serde(default)]
+    tray: TrayConfig,
+    #[serde(default)]
+    global_hotkeys: hotkeys::GlobalHotkeyConfig,
+    #[serde(default)]
+    do_not_disturb: DoNotDisturbConfig,
+    #[serde(default)]
+    screenshots: screenshots::ScreenshotConfig,
+    #[serde(default)]
+    clipboard_tickets: bool, // Offer to create a task for ticket ids copied to the clipboard
+    #[serde(default)]
+    pomodoro: pomodoro::PomodoroConfig,
+    #[serde(default)]
+    notifications: notifications::NotificationConfig,
+    #[serde(default)]
+    billing: billing::BillingConfig,
+    #[serde(default)]
+    window_positions: HashMap<String, [f32; 2]>, // Dialog -> top left corner, relative to the main window
+    #[serde(default)]
+    appearance: AppearanceConfig,
+    #[serde(default)]
+    selected_folder: Option<String>, // Selected again on the next start if it still exists
+}
 
-                // Only show play/pause button if task is not completed
-                if !is_completed {
-                    let button_text = if start_time.is_some() {
-                        fill::PAUSE // Pause icon
-                    } else if is_paused {
-                        fill::PLAY // Play icon
-                    } else {
-                        fill::PLAY // Play icon
-                    };
+// Theme and zoom as last set, restored on the next start. The main window's size and
+// position are kept by eframe.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct AppearanceConfig {
+    dark_mode: bool,
+    ui_scale: f32,
+}
 
-                    if ui.button(button_text).clicked() {
-                        action = Some(if start_time.is_some() {
-                            TaskAction::Pause
-                        } else if is_paused {
-                            TaskAction::Resume
-                        } else {
-                            TaskAction::Start
-                        });
-                    }
-                }
+impl Default for AppearanceConfig {
+    fn default() -> Self {
+        AppearanceConfig { dark_mode: true, ui_scale: 2.0 }
+    }
+}
 
-                // Duration display/edit
-                if is_editing {
-                    let mut edit_value = self.editing_duration_value.clone();
-                    let response = ui.text_edit_singleline(&mut edit_value);
-                    if response.lost_focus() || ui.input(|i| i.key_pressed(egui::Key::Enter)) {
-                        let new_duration = self.parse_duration_input(&edit_value);
-                        if let Some(duration) = new_duration {
-                            self.update_task_duration(&task_id, duration);
-                        }
-                        self.editing_duration_task_id = None;
-                        self.editing_duration_value.clear();
-                    } else if ui.input(|i| i.key_pressed(egui::Key::Escape)) {
-                        self.editing_duration_task_id = None;
-                        self.editing_duration_value.clear();
-                    } else {
-                        self.editing_duration_value = edit_value;
-                    }
-                } else {
-                    let formatted_duration = Self::format_duration(duration);
-                    let duration_label = ui.label(&formatted_duration);
-                    if duration_label.double_clicked() {
-                        self.editing_duration_task_id = Some(task_id.clone());
-                        self.editing_duration_value = formatted_duration;
-                    }
-                }
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+enum ScreenCorner {
+    TopLeft,
+    #[default]
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
 
-                let status_text = if start_time.is_some() {
-                    egui::RichText::new("Running").color(egui::Color32::GREEN)
-                } else if is_paused {
-                    egui::RichText::new("Paused").color(egui::Color32::YELLOW)
-                } else if duration == 0 && !is_paused {
-                    egui::RichText::new("Not Started").color(egui::Color32::GRAY)
-                } else {
-                    egui::RichText::new("Completed").color(egui::Color32::from_rgb(0, 180, 180))
-                };
-                ui.label(status_text);
-            });
-        });
+impl ScreenCorner {
+    const ALL: [ScreenCorner; 4] = [ScreenCorner::TopLeft, ScreenCorner::TopRight, ScreenCorner::BottomLeft, ScreenCorner::BottomRight];
 
-        (action, export_error)
+    fn label(&self) -> &'static str {
+        match self {
+            ScreenCorner::TopLeft => "Top left",
+            ScreenCorner::TopRight => "Top right",
+            ScreenCorner::BottomLeft => "Bottom left",
+            ScreenCorner::BottomRight => "Bottom right",
+        }
     }
+}
 
-    fn handle_task_action(&mut self, task_id: &str, action: TaskAction) {
-        match action {
-            TaskAction::Delete => {
-                self.show_delete_task_confirm = Some(task_id.to_string());
-            }
-            TaskAction::Complete => {
-                if let Some(task) = self.tasks.get_mut(task_id) {
-                    let is_completed = task.total_duration > 0 && task.start_time.is_none() && !task.is_paused;
-                    if is_completed {
-                        // If task is completed, mark it as incomplete by setting is_paused to true
-                        task.is_paused = true;
-                    } else {
-                        // If task is not completed, mark it as completed
-                        if task.start_time.is_some() {
-                            task.pause(); // Stop the timer if it's running
-                        }
-                        task.is_paused = false; // Mark as not paused
-                    }
-                    self.save_tasks();
-                }
-            }
-            _ => {
-                if let Some(task) = self.tasks.get_mut(task_id) {
-                    match action {
-                        TaskAction::Start => task.start(),
-                        TaskAction::Pause => task.pause(),
-                        TaskAction::Resume => task.resume(),
-                        TaskAction::Delete | TaskAction::Complete => unreachable!(),
-                    }
-                }
-            }
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+enum IdleReturn {
+    #[default]
+    Resume,
+    Ask,
+    StayPaused,
+}
+
+impl IdleReturn {
+    fn label(&self) -> &'static str {
+        match self {
+            IdleReturn::Resume => "resume the timer",
+            IdleReturn::Ask => "ask whether to keep the time away",
+            IdleReturn::StayPaused => "keep it paused",
         }
     }
+}
 
-    fn clear_all_folders(&mut self) {
-        self.folders.clear();
-        self.folder_styles.clear();
-        self.selected_folder = None;
-        // Reset focus but don't set to None - it will be set to Some(0) when a new folder is added
-        self.focused_folder_index = None;
-        self.focused_task_index = None;
-        self.save_tasks();
-        self.save_folder_styles();
+// Pause running timers when there is no keyboard or mouse input, ending the
+// session when the inactivity began so no idle time is recorded unless it is kept
+// when asked on return
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct IdlePauseConfig {
+    enabled: bool,
+    minutes: u32,
+    on_return: IdleReturn,
+}
+
+impl Default for IdlePauseConfig {
+    fn default() -> Self {
+        IdlePauseConfig {
+            enabled: false,
+            minutes: 10,
+            on_return: IdleReturn::Ask,
+        }
     }
+}
 
-    fn calculate_folder_durations(&self) -> Vec<(String, i64)> {
-        let mut durations: HashMap<String, i64> = HashMap::new();
-        
-        for task in self.tasks.values() {
-            let folder = task.folder.clone().unwrap_or_else(|| "Uncategorized".to_string());
-            *durations.entry(folder).or_default() += task.get_current_duration();
+// Tag for tasks that turn on Do Not Disturb when it is limited to deep work
+const DEEP_WORK_TAG: &str = "deep-work";
+
+// Turn on the system's Do Not Disturb while a timer runs
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+struct DoNotDisturbConfig {
+    #[serde(default)]
+    enabled: bool,
+    #[serde(default)]
+    deep_work_only: bool, // Only for tasks tagged deep-work
+}
+
+// Small always-on-top window with only the running time, no task or client names
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+struct OverlayConfig {
+    #[serde(default)]
+    enabled: bool,
+    #[serde(default)]
+    corner: ScreenCorner,
+}
+
+// Tray or menu bar icon with the running task and quick timer controls
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct TrayConfig {
+    enabled: bool,
+    close_to_tray: bool, // Closing the window hides it, Quit in the tray menu exits
+}
+
+impl Default for TrayConfig {
+    fn default() -> Self {
+        TrayConfig { enabled: true, close_to_tray: false }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+struct DayReviewConfig {
+    review_at: Option<NaiveTime>, // Open the review automatically at this time
+    #[serde(default)]
+    on_quit: bool,
+    #[serde(default)]
+    reviewed_days: std::collections::BTreeSet<NaiveDate>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct WeeklySummaryConfig {
+    enabled: bool,
+    weekday: chrono::Weekday, // Generate last week's summary on this day...
+    at: NaiveTime,            // ...once this time has passed
+    goal_hours: f64,          // 0 for no goal
+    save_dir: String,         // Write the summary here too, empty to only show it
+    email_to: String,         // Mail it through the SMTP account, empty to not send
+    last_generated: Option<NaiveDate>, // Start of the last summarized week
+}
+
+impl Default for WeeklySummaryConfig {
+    fn default() -> Self {
+        WeeklySummaryConfig {
+            enabled: false,
+            weekday: chrono::Weekday::Mon,
+            at: NaiveTime::from_hms_opt(9, 0, 0).unwrap(),
+            goal_hours: 0.0,
+            save_dir: String::new(),
+            email_to: String::new(),
+            last_generated: None,
         }
+    }
+}
 
-        let mut result: Vec<_> = durations.into_iter().collect();
-        result.sort_by_key(|(_, duration)| std::cmp::Reverse(*duration));
-        result
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct WorkHours {
+    start: NaiveTime,
+    end: NaiveTime,
+    days: Vec<chrono::Weekday>,
+}
+
+impl Default for WorkHours {
+    fn default() -> Self {
+        WorkHours {
+            start: NaiveTime::from_hms_opt(9, 0, 0).unwrap(),
+            end: NaiveTime::from_hms_opt(17, 0, 0).unwrap(),
+            days: vec![
+                chrono::Weekday::Mon,
+                chrono::Weekday::Tue,
+                chrono::Weekday::Wed,
+                chrono::Weekday::Thu,
+                chrono::Weekday::Fri,
+            ],
+        }
     }
+}
 
-    fn calculate_average_task_duration(&self) -> i64 {
-        if self.tasks.is_empty() {
-            return 0;
+impl WorkHours {
+    fn contains(&self, time: DateTime<Local>) -> bool {
+        self.days.contains(&time.weekday()) && time.time() >= self.start && time.time() < self.end
+    }
+}
+
+// Sessions at least this long are flagged in the day review
+const LONG_SESSION_SECONDS: i64 = 4 * 3600;
+
+const MAX_PINNED_TASKS: usize = 5;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+struct ObsidianConfig {
+    path_pattern: String, // Daily note path, `{date}` is replaced with YYYY-MM-DD
+    auto_append_at: Option<NaiveTime>, // Append today's summary automatically at this time
+    last_appended: Option<NaiveDate>,
+}
+
+impl ObsidianConfig {
+    fn note_path(&self, date: NaiveDate) -> String {
+        self.path_pattern.trim().replace("{date}", &date.format("%Y-%m-%d").to_string())
+    }
+}
+
+enum IntegrationJobResult {
+    HarvestProjects(Result<Vec<harvest::HarvestProject>, String>),
+    HarvestPushed(Vec<(harvest::DailyEntry, Result<u64, String>)>),
+    RedmineActivities(Result<Vec<redmine::RedmineActivity>, String>),
+    RedminePushed(Vec<(redmine::RedmineEntry, Result<u64, String>)>),
+    NotionSynced(Vec<(String, Result<String, String>)>),
+    TasksImported {
+        source: task_import::ImportSource,
+        folder: String,
+        result: Result<Vec<task_import::RemoteTask>, String>,
+    },
+    ActivityLoaded(Result<Vec<activity::DailyActivity>, String>),
+    SummaryEmailed(Result<(), String>),
+    ReportEmailed(reports::SentReport),
+}
+
+fn local_midnight(date: NaiveDate) -> DateTime<Local> {
+    local_time(date.and_hms_opt(0, 0, 0).unwrap())
+}
+
+// A time on the clock. The first one when the clocks go back, and the hour after when
+// they go forward past it.
+fn local_time(naive: chrono::NaiveDateTime) -> DateTime<Local> {
+    naive
+        .and_local_timezone(Local)
+        .earliest()
+        .or_else(|| (naive + chrono::Duration::hours(1)).and_local_timezone(Local).earliest())
+        .unwrap_or_else(|| naive.and_utc().with_timezone(&Local))
+}
+
+// How days and weeks are counted everywhere: days roll over at `day_start_hour` instead of
+// midnight, and weeks start on `week_start`. The hour is on the clock, so it stays the same
+// on days the clocks change.
+#[derive(Clone, Copy)]
+struct Calendar {
+    day_start_hour: u32,
+    week_start: chrono::Weekday,
+}
+
+impl Calendar {
+    fn offset(&self) -> chrono::Duration {
+        chrono::Duration::hours(self.day_start_hour as i64)
+    }
+
+    // The day a moment counts towards
+    fn day_of(&self, time: DateTime<Local>) -> NaiveDate {
+        (time.naive_local() - self.offset()).date()
+    }
+
+    fn today(&self) -> NaiveDate {
+        self.day_of(Local::now())
+    }
+
+    fn day_start(&self, date: NaiveDate) -> DateTime<Local> {
+        local_time(date.and_hms_opt(0, 0, 0).unwrap() + self.offset())
+    }
+
+    fn day_range(&self, date: NaiveDate) -> (DateTime<Local>, DateTime<Local>) {
+        (self.day_start(date), self.day_start(date + chrono::Duration::days(1)))
+    }
+
+    // First day of the week containing `date`
+    fn week_start(&self, date: NaiveDate) -> NaiveDate {
+        date - chrono::Duration::days(self.days_into_week(date.weekday()) as i64)
+    }
+
+    fn days_into_week(&self, weekday: chrono::Weekday) -> u32 {
+        (weekday.num_days_from_monday() + 7 - self.week_start.num_days_from_monday()) % 7
+    }
+
+    // The days of the week in display order
+    fn weekdays(&self) -> [chrono::Weekday; 7] {
+        let mut weekdays = [self.week_start; 7];
+        for index in 1..7 {
+            weekdays[index] = weekdays[index - 1].succ();
         }
-        let total: i64 = self.tasks.values().map(|t| t.get_current_duration()).sum();
-        total / self.tasks.len() as i64
+        weekdays
     }
 
-    fn format_duration(seconds: i64) -> String {
-        let hours = seconds / 3600;
-        let minutes = (seconds % 3600) / 60;
-        let seconds = seconds % 60;
-        format!("{:02}:{:02}:{:02}", hours, minutes, seconds)
+    // Whether a daily clock time has passed for the current day, counting times before
+    // the day boundary as the end of the previous day
+    fn time_reached(&self, now: DateTime<Local>, at: NaiveTime) -> bool {
+        (now.naive_local() - self.offset()).time() >= at - self.offset()
     }
 
-    fn is_any_dialog_open(&self) -> bool {
-        self.show_new_folder_dialog || 
-        self.show_clear_folders_confirm || 
-        self.show_clear_confirm || 
-        self.show_clear_folder_confirm.is_some() || 
-        self.show_delete_task_confirm.is_some() || 
-        self.show_shortcuts || 
-        self.show_settings || 
-        self.show_add_task_dialog ||
-        self.show_statistics
+    // Seconds of a session on each day it touches, so one running past the day boundary counts towards both days
+    fn split_by_day(&self, start: DateTime<Local>, end: DateTime<Local>) -> Vec<(NaiveDate, i64)> {
+        let mut days = Vec::new();
+        let mut cursor = start;
+        while cursor < end {
+            let date = self.day_of(cursor);
+            let day_end = self.day_start(date + chrono::Duration::days(1)).min(end);
+            days.push((date, (day_end - cursor).num_seconds()));
+            cursor = day_end;
+        }
+        days
     }
+}
 
-    fn parse_duration_input(&self, input: &str) -> Option<i64> {
-        // Try to parse HH:MM:SS format
-        let parts: Vec<&str> = input.split(':').collect();
-        if parts.len() != 3 {
-            return None;
+impl Settings {
+    fn calendar(&self) -> Calendar {
+        Calendar {
+            day_start_hour: self.day_start_hour,
+            week_start: if self.week_starts_on_sunday { chrono::Weekday::Sun } else { chrono::Weekday::Mon },
         }
+    }
+}
 
-        let hours = parts[0].parse::<i64>().ok()?;
-        let minutes = parts[1].parse::<i64>().ok()?;
-        let seconds = parts[2].parse::<i64>().ok()?;
+impl Default for StatsTab {
+    fn default() -> Self {
+        StatsTab::Overview
+    }
+}
 
-        if minutes >= 60 || seconds >= 60 || hours < 0 || minutes < 0 || seconds < 0 {
-            return None;
+#[derive(Default)]
+struct WorkTimer {
+    tasks: HashMap<String, Task>,
+    folders: Vec<String>,
+    folder_styles: HashMap<String, FolderStyle>,
+    data_file: String,
+    new_task_input: String,
+    new_folder_input: String,
+    selected_folder: Option<String>,
+    show_new_folder_dialog: bool,
+    show_clear_folders_confirm: bool,
+    dragged_task: Option<String>,
+    show_clear_confirm: bool,
+    clear_exports: Option<Vec<String>>, // Exported files to delete along with the tasks, when opted in
+    show_clear_folder_confirm: Option<String>,
+    clear_folder_target: Option<String>, // Where "Remove folder only" moves the tasks, None for Uncategorized
+    status_filters: HashMap<String, TaskStatus>, // Folder name -> only show tasks with this status
+    color_filter: Option<ColorLabel>, // Only show tasks with this color label
+    privacy_mode: bool, // Show placeholders instead of task and folder names; not saved
+    idle_monitor: Option<idle::IdleMonitor>, // Running while auto-pause is enabled
+    editing_entry: Option<EntryEdit>,
+    entry_undo: Option<(String, Vec<TimeEntry>)>, // Task id and sessions before the last session edit
+    moving_entry: Option<(String, usize)>, // Task id and session index picked for "Move entry to…"
+    move_entry_query: String,
+    show_search: bool,
+    search_query: String,
+    search_selected: std::collections::HashSet<String>, // Task ids picked for a bulk edit
+    search_tag: String, // Tag to add to or remove from the selected tasks
+    renaming_task: Option<(String, String)>, // Task id and the new name being typed in its row
+    renaming_folder: Option<(String, String)>, // Current and new folder name
+    scroll_to_focused: bool, // Bring the focused folder or task into view on the next frame
+    idle_paused: Vec<String>, // Tasks paused because the user went idle
+    idle_since: Option<DateTime<Local>>,
+    show_idle_return: bool,
+    show_cleanup: bool,
+    stale_weeks: u32,
+    cleanup_actions: HashMap<String, CleanupAction>, // Task id -> chosen action in the cleanup view
+    show_delete_task_confirm: Option<String>,
+    export_message: Option<(String, f32)>,
+    exported_file: Option<(String, String)>, // Toast message and the file it refers to
+    dark_mode: bool,
+    show_shortcuts: bool,
+    show_settings: bool,
+    show_statistics: bool,
+    selected_stats_tab: StatsTab,
+    billable_range: reports::DateRange, // Of the billable split on the Overview tab
+    ui_scale: f32,
+    temporary_ui_scale: f32,
+    focus_new_task: bool,
+    focus_new_folder: bool,
+    show_add_task_dialog: bool,
+    add_task_to_folder: Option<String>,
+    new_task_in_folder: String,
+    dragged_folder: Option<String>,
+    focused_folder_index: Option<usize>,
+    focused_task_index: Option<usize>,
+    editing_duration_task_id: Option<String>,
+    editing_duration_value: String,
+    expenses: Vec<Expense>,
+    show_expenses: bool,
+    new_expense_description: String,
+    new_expense_amount: String,
+    new_expense_date: String,
+    new_expense_folder: Option<String>,
+    plan: Vec<PlannedBlock>,
+    allocations: Vec<WeekAllocation>,
+    adjustments: Vec<Adjustment>,
+    time_off: Vec<TimeOff>,
+    pomodoro_log: Option<Vec<pomodoro::Interval>>, // Read when first needed, see pomodoro_log()
+    pomodoro: Option<pomodoro::Current>, // Interval in progress while Pomodoro mode is on
+    report_history: Option<Vec<reports::SentReport>>, // Read when first needed, see report_history()
+    new_adjustment_minutes: i64,
+    new_adjustment_reason: String,
+    new_time_off: (String, String, TimeOffKind, String), // From and to date, kind, note
+    time_off_source: String, // ICS file or address to import days off from
+    time_off_import: Option<mpsc::Receiver<Result<Vec<TimeOff>, String>>>,
+    capacity_week: NaiveDate, // Week shown under Weekly capacity in the planner
+    show_planner: bool,
+    planner_date: NaiveDate,
+    new_block_task: Option<String>,
+    new_block_start: NaiveTime,
+    new_block_minutes: u32,
+    settings: Settings,
+    show_export_templates: bool,
+    selected_export_template: Option<String>,
+    show_reports: bool,
+    selected_report: Option<String>,
+    report_preview: Option<(String, reports::ReportTable)>, // Report id -> table from its last run
+    show_harvest: bool,
+    harvest_projects: Vec<harvest::HarvestProject>,
+    integration_job: Option<mpsc::Receiver<IntegrationJobResult>>,
+    sessions_loading: Option<mpsc::Receiver<HashMap<String, Task>>>, // Until the background load is done
+    listed_without_sessions: bool, // The task list was read before its sessions, see take_sessions
+    tasks_save_pending: std::cell::Cell<bool>, // Saved while the sessions were loading
+    saved_conflicts: std::cell::Cell<usize>, // Overlapping sessions in the file, see save_problems
+    show_redmine: bool,
+    redmine_activities: Vec<redmine::RedmineActivity>,
+    redmine_queue: Vec<redmine::FailedPush>,
+    show_task_details: Option<String>,
+    attachment_path: String,
+    attachment_thumbnails: HashMap<String, Option<egui::TextureHandle>>, // Attachment path -> preview, None for non-images
+    pending_annotation: Option<(String, DateTime<Local>)>, // Task id and start of the session to annotate
+    annotation_input: String,
+    show_day_review: Option<NaiveDate>,
+    day_review_prompted: Option<NaiveDate>,
+    quit_after_review: bool,
+    weekly_summary: Option<(NaiveDate, String)>, // Week start and text of the summary being shown
+    year_review: Option<YearReview>, // Shown while set
+    conflicts: Option<Vec<EntryConflict>>, // Shown while set
+    conflict_count: Option<(u64, usize)>, // Data version it was counted at
+    last_notion_sync: Option<std::time::Instant>,
+    show_task_import: bool,
+    show_csv_import: bool,
+    csv_import_path: String,
+    show_backup: bool,
+    backup_path: String,
+    import_source: task_import::ImportSource,
+    import_container_id: String,
+    import_folder: Option<String>,
+    activity_overlay: Vec<activity::DailyActivity>,
+    plugin_host: plugins::PluginHost,
+    show_plugins: bool,
+    current_day: NaiveDate, // Detects the day rollover while the app is open
+    auto_completed_on: Option<NaiveDate>, // Day paused tasks were last checked for auto-completion
+    launch_checked: bool,
+    auto_started_task: Option<String>, // Offered for reassignment until dismissed
+    last_timer_running: Option<DateTime<Local>>, // Last time a timer was seen running, or the reminder was snoozed
+    show_idle_reminder: bool,
+    meetings: Vec<ics::Meeting>, // Today's, from the subscribed calendar
+    meetings_loaded: Option<(NaiveDate, std::time::Instant)>,
+    meetings_job: Option<mpsc::Receiver<Result<Vec<ics::Meeting>, String>>>,
+    meetings_error: Option<String>,
+    free_time_prompt: Option<DateTime<Local>>, // Start of the next meeting while focus time is suggested
+    free_time_prompted: Option<DateTime<Local>>, // Only suggested once per gap
+    frame_times: Option<std::collections::VecDeque<f32>>, // Milliseconds per recent frame while the overlay is on
+    stats: StatsCache,
+    data_file_problems: Vec<String>,
+    do_not_disturb: focus::DoNotDisturb,
+    last_screenshot: Option<std::time::Instant>, // While proof-of-work screenshots are being taken
+    screenshot_job: Option<mpsc::Receiver<Result<(), String>>>,
+    clipboard_watch: Option<mpsc::Receiver<String>>, // While clipboard tickets are turned on
+    clipboard_ticket: Option<clipboard::Ticket>, // Offered until dismissed or another one is copied
+    api_port: Option<u16>, // Port the API is listening on
+    api_requests: Option<mpsc::Receiver<api::Request>>, // Commands from API clients, while it listens
+    api_checked: bool, // Started on the first frame when turned on
+    #[cfg(feature = "tray")]
+    tray: Option<tray::Tray>, // Created once when first turned on, hidden when turned off
+    quitting: bool, // Quit was picked in the tray menu, so closing the window exits
+    hotkeys: Option<hotkeys::Hotkeys>, // Created once when first turned on
+    hotkeys_registered: bool, // The current hotkey settings are registered
+    hotkey_problems: Vec<String>, // Hotkeys that couldn't be registered
+    // Running task -> start of its session and whether it ran long, None until the timers
+    // running at launch have been seen
+    notified_timers: Option<HashMap<String, (DateTime<Local>, bool)>>,
+}
+
+impl WorkTimer {
+    fn new() -> Self {
+        let data_file = "tasks.json".to_string();
+
+        // Years of sessions make the tasks slow to parse. The list is read without them so it
+        // shows right away, and the sessions load off the UI thread.
+        let tasks = read_task_list(&data_file);
+        let listed_without_sessions = tasks.is_some();
+        let (sender, sessions_loading) = mpsc::channel();
+        let path = data_file.clone();
+        std::thread::spawn(move || {
+            let _ = sender.send(migrate_tasks(read_data_file(&path)));
+        });
+
+        // Load folders from file
+        let folders: Vec<String> = read_data_file("folders.json");
+
+        // Load folder styles from file
+        let folder_styles = read_data_file("folder_styles.json");
+
+        // Load expenses from file
+        let expenses = read_data_file("expenses.json");
+
+        // Load planned time blocks and weekly allocations from file
+        let plan = read_data_file("plan.json");
+        let allocations = read_data_file("allocations.json");
+        let adjustments = read_data_file("adjustments.json");
+        let time_off = read_data_file("time_off.json");
+
+        // Load settings from file
+        let settings: Settings = read_data_file("settings.json");
+        let current_day = settings.calendar().today();
+
+        // Load failed Redmine pushes waiting to be retried
+        let redmine_queue = read_data_file("redmine_queue.json");
+
+        let selected_folder = settings
+            .selected_folder
+            .clone()
+            .filter(|folder| folders.contains(folder))
+            .or_else(|| folders.first().cloned());
+        let ui_scale = settings.appearance.ui_scale.clamp(1.0, 2.5);
+        let focused_folder_index = selected_folder.as_ref().and_then(|folder| folders.iter().position(|f| f == folder));
+        let focused_task_index = None;
+
+        WorkTimer {
+            tasks: tasks.unwrap_or_default(),
+            sessions_loading: Some(sessions_loading),
+            listed_without_sessions,
+            tasks_save_pending: std::cell::Cell::new(false),
+            saved_conflicts: std::cell::Cell::new(0),
+            folders,
+            folder_styles,
+            data_file,
+            new_task_input: String::new(),
+            new_folder_input: String::new(),
+            selected_folder,
+            show_new_folder_dialog: false,
+            show_clear_folders_confirm: false,
+            dragged_task: None,
+            show_clear_confirm: false,
+            clear_exports: None,
+            show_clear_folder_confirm: None,
+            clear_folder_target: None,
+            status_filters: HashMap::new(),
+            color_filter: None,
+            privacy_mode: false,
+            idle_monitor: None,
+            editing_entry: None,
+            entry_undo: None,
+            moving_entry: None,
+            move_entry_query: String::new(),
+            show_search: false,
+            search_query: String::new(),
+            search_selected: std::collections::HashSet::new(),
+            search_tag: String::new(),
+            renaming_task: None,
+            renaming_folder: None,
+            scroll_to_focused: false,
+            idle_paused: Vec::new(),
+            idle_since: None,
+            show_idle_return: false,
+            show_cleanup: false,
+            stale_weeks: 8,
+            cleanup_actions: HashMap::new(),
+            show_delete_task_confirm: None,
+            export_message: None,
+            exported_file: None,
+            dark_mode: settings.appearance.dark_mode,
+            show_shortcuts: false,
+            show_settings: false,
+            show_statistics: false,
+            selected_stats_tab: StatsTab::Overview,
+            billable_range: reports::DateRange::AllTime,
+            ui_scale,
+            temporary_ui_scale: ui_scale,
+            focus_new_task: false,
+            focus_new_folder: false,
+            show_add_task_dialog: false,
+            add_task_to_folder: None,
+            new_task_in_folder: String::new(),
+            dragged_folder: None,
+            focused_folder_index,
+            focused_task_index,
+            editing_duration_task_id: None,
+            editing_duration_value: String::new(),
+            expenses,
+            show_expenses: false,
+            new_expense_description: String::new(),
+            new_expense_amount: String::new(),
+            new_expense_date: Local::now().date_naive().to_string(),
+            new_expense_folder: None,
+            plan,
+            show_planner: false,
+            planner_date: Local::now().date_naive(),
+            allocations,
+            adjustments,
+            time_off,
+            pomodoro_log: None,
+            pomodoro: None,
+            report_history: None,
+            new_adjustment_minutes: 60,
+            new_adjustment_reason: String::new(),
+            new_time_off: (String::new(), String::new(), TimeOffKind::default(), String::new()),
+            time_off_source: String::new(),
+            time_off_import: None,
+            capacity_week: settings.calendar().week_start(current_day),
+            new_block_task: None,
+            new_block_start: NaiveTime::from_hms_opt(9, 0, 0).unwrap(),
+            new_block_minutes: 60,
+            settings,
+            show_export_templates: false,
+            selected_export_template: None,
+            show_reports: false,
+            year_review: None,
+            conflicts: None,
+            conflict_count: None,
+            selected_report: None,
+            report_preview: None,
+            show_harvest: false,
+            harvest_projects: Vec::new(),
+            integration_job: None,
+            show_redmine: false,
+            redmine_activities: Vec::new(),
+            redmine_queue,
+            show_task_details: None,
+            attachment_path: String::new(),
+            attachment_thumbnails: HashMap::new(),
+            pending_annotation: None,
+            annotation_input: String::new(),
+            show_day_review: None,
+            day_review_prompted: None,
+            quit_after_review: false,
+            weekly_summary: None,
+            last_notion_sync: None,
+            show_task_import: false,
+            show_csv_import: false,
+            csv_import_path: "work_timer_export.csv".to_string(),
+            show_backup: false,
+            backup_path: String::new(),
+            import_source: task_import::ImportSource::Asana,
+            import_container_id: String::new(),
+            import_folder: None,
+            activity_overlay: Vec::new(),
+            plugin_host: plugins::PluginHost::load(),
+            show_plugins: false,
+            current_day,
+            auto_completed_on: None,
+            launch_checked: false,
+            auto_started_task: None,
+            last_timer_running: None,
+            show_idle_reminder: false,
+            meetings: Vec::new(),
+            meetings_loaded: None,
+            meetings_job: None,
+            meetings_error: None,
+            free_time_prompt: None,
+            free_time_prompted: None,
+            frame_times: None,
+            stats: StatsCache::default(),
+            data_file_problems: Vec::new(),
+            do_not_disturb: focus::DoNotDisturb::default(),
+            last_screenshot: None,
+            screenshot_job: None,
+            clipboard_watch: None,
+            clipboard_ticket: None,
+            api_port: None,
+            api_requests: None,
+            api_checked: false,
+            #[cfg(feature = "tray")]
+            tray: None,
+            quitting: false,
+            hotkeys: None,
+            hotkeys_registered: false,
+            hotkey_problems: Vec::new(),
+            notified_timers: None,
         }
+    }
 
-        Some(hours * 3600 + minutes * 60 + seconds)
+    fn add_task(&mut self, description: String, folder: Option<String>) -> String {
+        let mut task = Task::new(description);
+        let defaults = folder
+            .as_ref()
+            .and_then(|folder| self.folder_styles.get(folder))
+            .map(|style| style.task_defaults.clone())
+            .unwrap_or_default();
+        defaults.apply_to(&mut task);
+        task.folder = folder;
+        let id = task.id.clone();
+        audit::log("created", &task, 0);
+        self.tasks.insert(id.clone(), task);
+        self.save_tasks();
+        if defaults.auto_start {
+            self.handle_task_action(&id, TaskAction::Start);
+        }
+        id
     }
 
-    fn update_task_duration(&mut self, task_id: &str, new_duration: i64) {
-        if let Some(task) = self.tasks.get_mut(task_id) {
-            // If task is running, we need to account for the current running time
-            if task.start_time.is_some() {
-                task.pause();
+    // Copies a task's settings but not its tracked time
+    fn duplicate_task(&mut self, task_id: &str, folder: Option<String>) {
+        let Some(original) = self.tasks.get(task_id) else {
+            return;
+        };
+        let mut task = Task::new(original.description.clone());
+        task.folder = folder;
+        task.billable = original.billable;
+        task.tags = original.tags.clone();
+        task.hourly_rate = original.hourly_rate;
+        task.estimate_minutes = original.estimate_minutes;
+        task.estimate_in_pomodoros = original.estimate_in_pomodoros;
+        task.attendees = original.attendees;
+        task.attendee_rate = original.attendee_rate;
+        task.work_kind = original.work_kind;
+        task.color_label = original.color_label;
+        task.issue_key = original.issue_key.clone();
+        task.redmine_issue_id = original.redmine_issue_id;
+        audit::log("created", &task, 0);
+        self.tasks.insert(task.id.clone(), task);
+        self.save_tasks();
+    }
+
+    // Tasks without activity for the given number of weeks, least recently active first.
+    // Tasks that were never started count from their creation date.
+    fn stale_tasks(&self, weeks: u32) -> Vec<String> {
+        let cutoff = Local::now() - chrono::Duration::weeks(weeks as i64);
+        let mut stale: Vec<_> = self
+            .tasks
+            .values()
+            .filter(|task| !task.archived && task.start_time.is_none())
+            .map(|task| (task.last_active_at().or(task.created_at), task))
+            .filter(|(last, _)| last.is_none_or(|last| last < cutoff))
+            .collect();
+        stale.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| a.1.description.cmp(&b.1.description)));
+        stale.into_iter().map(|(_, task)| task.id.clone()).collect()
+    }
+
+    // Moves the sessions and tracked time of one task into another and removes it
+    fn merge_task(&mut self, from_id: &str, into_id: &str) {
+        if from_id == into_id || !self.tasks.contains_key(into_id) {
+            return;
+        }
+        let Some(from) = self.tasks.remove(from_id) else {
+            return;
+        };
+        audit::log("merged away", &from, -from.total_duration());
+        if let Some(into) = self.tasks.get_mut(into_id) {
+            audit::log("merged into", into, from.total_duration());
+            into.untracked_duration += from.untracked_duration;
+            into.entries.extend(from.entries);
+            into.entries.sort_by_key(|entry| entry.start);
+            for tag in from.tags {
+                if !into.tags.contains(&tag) {
+                    into.tags.push(tag);
+                }
             }
-            task.total_duration = new_duration;
-            self.save_tasks();
+            into.created_at = match (into.created_at, from.created_at) {
+                (Some(a), Some(b)) => Some(a.min(b)),
+                (a, b) => a.or(b),
+            };
+            into.last_active = into.last_active.max(from.last_active);
+            into.attachments.extend(from.attachments);
+        }
+        let mut plan_changed = false;
+        for block in self.plan.iter_mut().filter(|block| block.task_id == from_id) {
+            block.task_id = into_id.to_string();
+            plan_changed = true;
         }
+        if plan_changed {
+            self.save_plan();
+        }
+        self.replace_in_settings(from_id, Some(into_id));
     }
-}
-
-impl eframe::App for WorkTimer {
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        self.configure_theme(ctx);
 
-        // Handle global shortcuts that should work even when dialogs are open
-        if ctx.input(|i| i.modifiers.command && i.key_pressed(egui::Key::D)) {
-            self.dark_mode = !self.dark_mode;
+    // Points the pinned tasks and the launch task at another task, or drops them with None
+    fn replace_in_settings(&mut self, task_id: &str, replacement: Option<&str>) {
+        let settings = &mut self.settings;
+        let was_pinned = settings.pinned_tasks.iter().any(|id| id == task_id);
+        let was_launch_task = settings.launch_task.as_deref() == Some(task_id);
+        if !was_pinned && !was_launch_task {
+            return;
+        }
+        if let Some(index) = settings.pinned_tasks.iter().position(|id| id == task_id) {
+            match replacement {
+                Some(into) if !settings.pinned_tasks.iter().any(|id| id == into) => settings.pinned_tasks[index] = into.to_string(),
+                _ => {
+                    settings.pinned_tasks.remove(index);
+                }
+            }
+        }
+        if was_launch_task {
+            settings.launch_task = replacement.map(str::to_string);
         }
+        self.save_settings();
+    }
 
-        // Handle dialog closing with Escape or Cmd+W
-        if ctx.input(|i| i.key_pressed(egui::Key::Escape) || (i.modifiers.command && i.key_pressed(egui::Key::W))) {
-            if self.show_new_folder_dialog {
-                self.show_new_folder_dialog = false;
-                self.new_folder_input.clear();
-            } else if self.show_clear_folders_confirm {
-                self.show_clear_folders_confirm = false;
-            } else if self.show_clear_confirm {
-                self.show_clear_confirm = false;
-            } else if self.show_clear_folder_confirm.is_some() {
-                self.show_clear_folder_confirm = None;
-            } else if self.show_delete_task_confirm.is_some() {
-                self.show_delete_task_confirm = None;
-            } else if self.show_shortcuts {
-                self.show_shortcuts = false;
-            } else if self.show_settings {
-                self.temporary_ui_scale = self.ui_scale; // Reset temporary scale
-                self.show_settings = false;
-            } else if self.show_add_task_dialog {
-                self.show_add_task_dialog = false;
-                self.add_task_to_folder = None;
-                self.new_task_in_folder.clear();
-            } else if self.show_statistics {
-                self.show_statistics = false;
+    // Applies the chosen cleanup actions. Merges into a task that is itself being
+    // deleted or merged are skipped. Returns (archived, deleted, merged, skipped).
+    fn apply_cleanup(&mut self) -> (usize, usize, usize, usize) {
+        let actions = std::mem::take(&mut self.cleanup_actions);
+        let (mut archived, mut deleted, mut merged, mut skipped) = (0, 0, 0, 0);
+        for (task_id, action) in &actions {
+            match action {
+                CleanupAction::Keep => {}
+                CleanupAction::Archive => {
+                    if let Some(task) = self.tasks.get_mut(task_id) {
+                        task.archived = true;
+                        audit::log("archived", task, 0);
+                        archived += 1;
+                    }
+                }
+                CleanupAction::Delete => {
+                    if let Some(task) = self.tasks.remove(task_id) {
+                        audit::log("deleted", &task, -task.total_duration());
+                        task.attachments.iter().for_each(|path| attachments::remove(path));
+                        screenshots::remove_for(&task.id);
+                        self.replace_in_settings(task_id, None);
+                        deleted += 1;
+                    }
+                }
+                CleanupAction::MergeInto(into_id) => {
+                    let target_removed = actions
+                        .get(into_id)
+                        .is_some_and(|action| matches!(action, CleanupAction::Delete | CleanupAction::MergeInto(_)));
+                    if target_removed || !self.tasks.contains_key(into_id) {
+                        skipped += 1;
+                    } else {
+                        self.merge_task(task_id, into_id);
+                        merged += 1;
+                    }
+                }
             }
         }
+        self.save_tasks();
+        (archived, deleted, merged, skipped)
+    }
 
-        // Handle keyboard shortcuts and navigation
-        if !self.is_any_dialog_open() {
-            // Handle space bar for play/pause
-            if ctx.input(|i| i.key_pressed(egui::Key::Space)) {
-                let folders = self.get_folders();
-                if let Some(current_folder_idx) = self.focused_folder_index {
-                    let folder_name = &folders[current_folder_idx];
-                    let folder_id = egui::Id::new(format!("folder_{}", folder_name));
-                    let is_open = ctx.memory(|mem| mem.data.get_temp::<bool>(folder_id).unwrap_or(true));
-                    
+    // Pauses every running task in the folder, or starts every paused and not yet
+    // started one. Completed and archived tasks are left alone.
+    fn set_folder_running(&mut self, folder_name: &str, run: bool) {
+        let actions: Vec<_> = self
+            .tasks
+            .values()
+            .filter(|task| !task.archived && task.folder.as_deref().unwrap_or("Uncategorized") == folder_name)
+            .filter_map(|task| match (run, task.status()) {
+                (false, TaskStatus::Running) => Some((task.id.clone(), TaskAction::Pause)),
+                (true, TaskStatus::Paused) => Some((task.id.clone(), TaskAction::Resume)),
+                (true, TaskStatus::NotStarted) => Some((task.id.clone(), TaskAction::Start)),
+                _ => None,
+            })
+            .collect();
+        for (task_id, action) in actions {
+            self.handle_task_action(&task_id, action);
+        }
+        self.save_tasks();
+    }
+
+    // (tracked, budgeted) seconds in the folder's current budget period
+    fn folder_budget_usage(&self, folder_name: &str) -> Option<(i64, i64)> {
+        let budget = self.folder_styles.get(folder_name)?.budget.as_ref()?;
+        let now = Local::now();
+        let from = budget.period.start(now, self.calendar());
+        let tracked = self
+            .tasks
+            .values()
+            .filter(|task| task.folder.as_deref() == Some(folder_name))
+            .map(|task| match from {
+                Some(from) => task.tracked_between(from, now),
+                None => task.get_current_duration(),
+            })
+            .sum();
+        Some((tracked, (budget.hours * 3600.0) as i64))
+    }
+
+    // Called when a session ends: warns when it pushed the folder past an alert threshold
+    fn check_budget_alerts(&mut self, task_id: &str) {
+        let Some(task) = self.tasks.get(task_id) else {
+            return;
+        };
+        let (Some(folder), Some(entry)) = (task.folder.clone(), task.entries.last()) else {
+            return;
+        };
+        let session = (entry.end - entry.start).num_seconds();
+        let Some((tracked, budgeted)) = self.folder_budget_usage(&folder) else {
+            return;
+        };
+        let Some(budget) = self.folder_styles.get(&folder).and_then(|style| style.budget.as_ref()) else {
+            return;
+        };
+        let before = tracked - session;
+        let crossed = budget
+            .alert_percents
+            .iter()
+            .filter(|percent| {
+                let threshold = budgeted * **percent as i64 / 100;
+                before < threshold && tracked >= threshold
+            })
+            .max();
+        if let Some(percent) = crossed {
+            let message = format!(
+                "{} reached {}% ({} of {} {})",
+                folder,
+                percent,
+                Self::format_hours_minutes(tracked),
+                Self::format_hours_minutes(budgeted),
+                budget.period.label()
+            );
+            self.notify(notifications::Event::BudgetAlert, "Budget alert", &message);
+            self.export_message = Some((format!("Budget: {}", message), 8.0));
+        }
+    }
+
+    fn add_folder(&mut self, name: String) {
+        if !name.is_empty() && !self.folders.contains(&name) {
+            let style = FolderStyle {
+                name: name.clone(),
+                ..Default::default()
+            };
+            self.folder_styles.insert(name.clone(), style);
+
+            self.folders.push(name.clone());
+            self.folders.sort();
+            if self.selected_folder.is_none() {
+                self.selected_folder = Some(name.clone());
+            }
+            // Find the index of the newly added folder after sorting
+            if let Some(new_folder_idx) = self.folders.iter().position(|f| f == &name) {
+                self.focused_folder_index = Some(new_folder_idx);
+                self.focused_task_index = None; // Reset task focus when switching folders
+            }
+            self.save_tasks();
+            self.save_folder_styles();
+        }
+    }
+
+    fn rename_task(&mut self, task_id: &str, description: &str) {
+        let description = description.trim();
+        if description.is_empty() {
+            return;
+        }
+        if let Some(task) = self.tasks.get_mut(task_id) {
+            task.description = description.to_string();
+            audit::log("renamed", task, 0);
+            self.save_tasks();
+        }
+    }
+
+    fn move_task_to_folder(&mut self, task_id: &str, folder: Option<String>) {
+        if let Some(task) = self.tasks.get_mut(task_id) {
+            task.folder = folder;
+            self.save_tasks();
+        }
+    }
+
+    fn save_tasks(&self) {
+        // Without its sessions the list must not replace the file, it is saved once they are in
+        if self.sessions_loading.is_some() {
+            self.tasks_save_pending.set(true);
+        } else if let Some(problems) = self.save_problems() {
+            // Kept in memory only, so the file still has the last good save
+            tracing::error!("Not saving {}: {}", self.data_file, problems);
+            logging::record_save(&self.data_file, Some(format!("not saved, {}", problems)));
+            report_data_file_problem(format!("Changes to the tasks weren't saved: {}", problems));
+        } else if let Ok(data) = serde_json::to_string(&self.tasks) {
+            write_data_file(&self.data_file, data);
+            self.saved_conflicts.set(self.find_conflicts().len());
+        }
+        // Save folders to a separate file
+        if let Ok(data) = serde_json::to_string(&self.folders) {
+            write_data_file("folders.json", data);
+        }
+    }
+
+    // What makes the tasks unfit to save: a session ending before it starts, negative time, or
+    // overlapping sessions beyond the ones already saved, which are left to the Conflicts window
+    fn save_problems(&self) -> Option<String> {
+        let mut problems = Vec::new();
+        for task in self.tasks.values() {
+            if task.untracked_duration < 0 {
+                problems.push(format!("\"{}\" has negative time", task.description));
+            }
+            if let Some(entry) = task.entries.iter().find(|entry| entry.end < entry.start) {
+                problems.push(format!("a session of \"{}\" ends before it starts at {}", task.description, entry.start.format("%Y-%m-%d %H:%M")));
+            }
+        }
+        problems.sort();
+        let conflicts = self.find_conflicts().len();
+        if conflicts > self.saved_conflicts.get() {
+            problems.push(format!("{} more overlapping sessions, see Conflicts", conflicts - self.saved_conflicts.get()));
+        }
+        (!problems.is_empty()).then(|| problems.join("; "))
+    }
+
+    fn get_projects(&self) -> Vec<String> {
+        let mut projects: Vec<String> = self
+            .tasks
+            .values()
+            .filter_map(|task| task.folder.clone())
+            .collect::<std::collections::HashSet<_>>()
+            .into_iter()
+            .collect();
+        if projects.is_empty() {
+            projects.push("Default".to_string());
+        }
+        projects.sort();
+        projects
+    }
+
+    fn clear_all_tasks(&mut self, delete_exports: bool) {
+        for task in self.tasks.values() {
+            audit::log("deleted", task, -task.get_current_duration());
+            task.attachments.iter().for_each(|path| attachments::remove(path));
+            screenshots::remove_for(&task.id);
+        }
+        self.tasks.clear();
+        self.save_tasks();
+
+        // Only files the app exported itself, never other CSV files lying around
+        if delete_exports {
+            for path in exported_files() {
+                if let Err(e) = fs::remove_file(&path) {
+                    tracing::warn!("Failed to delete {}: {}", path, e);
+                }
+            }
+            write_data_file(EXPORTS_MANIFEST, "[]".to_string());
+        }
+    }
+
+    fn get_unique_filename(&self, base_name: &str) -> String {
+        let sanitized_name = sanitize_filename(base_name);
+        let mut filename = format!("{}.csv", sanitized_name);
+        let mut counter = 1;
+
+        while Path::new(&filename).exists() {
+            filename = format!("{}_{}.csv", sanitized_name, counter);
+            counter += 1;
+        }
+
+        filename
+    }
+
+    fn export_task_to_csv(&self, task: &Task) -> Result<String, Box<dyn std::error::Error>> {
+        let filename = self.get_unique_filename(&task.description);
+        let file = fs::File::create(&filename)?;
+        let mut writer = csv::Writer::from_writer(file);
+
+        // Write header
+        let duration_format = self.settings.duration_format;
+        writer.write_record(&["Task", "Project", duration_format.header(), "Status"])?;
+
+        // Write task
+        let status = if task.start_time.is_some() {
+            "Running"
+        } else if task.is_paused {
+            "Paused"
+        } else {
+            "Stopped"
+        };
+
+        writer.write_record(&[
+            &task.description,
+            task.folder.as_deref().unwrap_or("Uncategorized"),
+            &task.format_duration(duration_format),
+            status
+        ])?;
+        writer.flush()?;
+        record_export(&filename);
+        Self::bundle_screenshots(&filename, &[task.id.as_str()])?;
+        Ok(filename)
+    }
+
+    fn export_to_csv(&self) -> Result<String, Box<dyn std::error::Error>> {
+        let filename = "work_timer_export.csv";
+        let file = fs::File::create(filename)?;
+        let mut writer = csv::Writer::from_writer(file);
+
+        // Write header. The columns after Status let import_from_csv rebuild the tasks.
+        let duration_format = self.settings.duration_format;
+        writer.write_record(["Task", "Project", duration_format.header(), "Status", "Task ID", "Seconds", "Billable", "Tags"])?;
+
+        // Write tasks
+        for task in self.tasks.values() {
+            let status = if task.start_time.is_some() {
+                "Running"
+            } else if task.is_paused {
+                "Paused"
+            } else {
+                "Stopped"
+            };
+
+            writer.write_record([
+                task.description.as_str(),
+                task.folder.as_deref().unwrap_or("Uncategorized"),
+                &task.format_duration(duration_format),
+                status,
+                &task.id,
+                &task.get_current_duration().to_string(),
+                if task.billable { "Yes" } else { "No" },
+                &task.tags.join(", "),
+            ])?;
+        }
+
+        writer.flush()?;
+        record_export(filename);
+        Ok(filename.to_string())
+    }
+
+    // Recreates tasks from a file written by export_to_csv. Tasks whose id is already
+    // present are left alone. Returns how many were created and skipped.
+    fn import_from_csv(&mut self, path: &str) -> Result<(usize, usize), Box<dyn std::error::Error>> {
+        let mut reader = csv::Reader::from_path(path)?;
+        let headers = reader.headers()?.clone();
+        let column = |name: &str| headers.iter().position(|header| header == name);
+        let (Some(name_column), Some(seconds_column)) = (column("Task"), column("Seconds")) else {
+            return Err("not a Work Timer export, or one made before task ids were included".into());
+        };
+        let (folder_column, status_column, id_column, billable_column, tags_column) =
+            (column("Project"), column("Status"), column("Task ID"), column("Billable"), column("Tags"));
+
+        // Exports have no sessions, only the totals, which come back as one session each
+        // ending when the file was written
+        let exported_at: DateTime<Local> = fs::metadata(path).and_then(|metadata| metadata.modified()).map(DateTime::from).unwrap_or_else(|_| Local::now());
+        let mut created = 0;
+        let mut skipped = 0;
+        for (line, record) in reader.records().enumerate() {
+            let record = record?;
+            let field = |index: Option<usize>| index.and_then(|index| record.get(index)).unwrap_or("").trim();
+            let id = field(id_column);
+            if !id.is_empty() && self.tasks.contains_key(id) {
+                skipped += 1;
+                continue;
+            }
+            let seconds: i64 = field(Some(seconds_column))
+                .parse()
+                .map_err(|_| format!("row {}: invalid seconds '{}'", line + 2, field(Some(seconds_column))))?;
+
+            let mut task = Task::new(field(Some(name_column)).to_string());
+            if !id.is_empty() {
+                task.id = id.to_string();
+            }
+            task.created_at = None;
+            if seconds > 0 {
+                let start = exported_at - chrono::Duration::seconds(seconds);
+                task.entries.push(TimeEntry { source: EntrySource::Import, ..TimeEntry::new(start, exported_at) });
+            }
+            // The start time of a running timer isn't exported, so it comes back paused
+            task.is_paused = matches!(field(status_column), "Running" | "Paused");
+            task.billable = field(billable_column).eq_ignore_ascii_case("yes");
+            task.tags = parse_tags(field(tags_column));
+            let folder = field(folder_column);
+            if !folder.is_empty() && folder != "Uncategorized" {
+                self.add_folder(folder.to_string());
+                task.folder = Some(folder.to_string());
+            }
+            audit::log("imported", &task, task.total_duration());
+            self.tasks.insert(task.id.clone(), task);
+            created += 1;
+        }
+
+        self.save_tasks();
+        Ok((created, skipped))
+    }
+
+    // Builds the saved report, writes it and keeps the table for the preview
+    fn run_report(&mut self, report_id: &str) -> Result<String, Box<dyn std::error::Error>> {
+        let calendar = self.calendar();
+        let Some(definition) = self.settings.reports.iter_mut().find(|report| report.id == report_id) else {
+            return Err("report not found".into());
+        };
+        let today = definition.today(calendar);
+        let table = reports::build(definition, self.tasks.values(), &self.adjustments, &self.expenses, today, calendar, &self.settings.billing);
+        let filename = reports::write(definition, &table, today)?;
+        definition.last_run = Some(Local::now());
+        self.report_preview = Some((report_id.to_string(), table));
+        self.save_settings();
+        Ok(filename)
+    }
+
+    // Finished sessions as JSON Lines, oldest first. With `append`, only sessions that
+    // ended after the last one already in the file are added, so the file can be
+    // extended incrementally. Returns the file name and the number of lines written.
+    fn export_entries_ndjson(&self, append: bool) -> Result<(String, usize), Box<dyn std::error::Error>> {
+        use std::io::Write;
+
+        let filename = "time_entries.jsonl";
+        let mut exported_until: Option<DateTime<Local>> = None;
+        if append {
+            if let Ok(existing) = fs::read_to_string(filename) {
+                exported_until = existing
+                    .lines()
+                    .filter_map(|line| serde_json::from_str::<serde_json::Value>(line).ok())
+                    .filter_map(|entry| entry["end"].as_str().and_then(|end| DateTime::parse_from_rfc3339(end).ok()))
+                    .map(|end| end.with_timezone(&Local))
+                    .max();
+            }
+        }
+
+        let mut entries: Vec<_> = self
+            .tasks
+            .values()
+            .flat_map(|task| task.entries.iter().map(move |entry| (task, entry)))
+            .filter(|(_, entry)| exported_until.is_none_or(|until| entry.end > until))
+            .collect();
+        entries.sort_by_key(|(_, entry)| entry.start);
+
+        let timezone = timezones::workspace();
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .write(true)
+            .append(append)
+            .truncate(!append)
+            .open(filename)?;
+        for (task, entry) in &entries {
+            let line = serde_json::json!({
+                "task_id": task.id,
+                "task": task.description,
+                "folder": task.folder,
+                "start": entry.start.to_rfc3339(),
+                "end": entry.end.to_rfc3339(),
+                "timezone": timezone,
+                "original_timezone": entry.timezone,
+                "duration_seconds": (entry.end - entry.start).num_seconds(),
+                "billable": task.billable,
+                "tags": task.tags,
+                "color_label": task.color_label,
+                "annotation": entry.annotation,
+                "source": entry.source,
+            });
+            writeln!(file, "{}", line)?;
+        }
+        Ok((filename.to_string(), entries.len()))
+    }
+
+    // Path for an export of one folder, in the folder's export directory if it has one
+    fn folder_export_path(&self, folder_name: &str, filename: String) -> Result<String, String> {
+        let Some(dir) = self
+            .folder_styles
+            .get(folder_name)
+            .map(|style| style.export_dir.trim())
+            .filter(|dir| !dir.is_empty())
+        else {
+            return Ok(filename);
+        };
+        // Not created when missing, that usually means a share that isn't mounted
+        if !Path::new(dir).is_dir() {
+            return Err(format!("the export folder {} of {} isn't available", dir, folder_name));
+        }
+        Ok(Path::new(dir).join(filename).to_string_lossy().to_string())
+    }
+
+    fn export_folder_to_csv(
+        &self,
+        folder_name: &str,
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        let filename = self.folder_export_path(folder_name, format!("folder_{}.csv", sanitize_filename(folder_name)))?;
+        let file = fs::File::create(&filename)?;
+        let mut writer = csv::Writer::from_writer(file);
+
+        // Write header
+        let duration_format = self.settings.duration_format;
+        writer.write_record(&["Task", "Project", duration_format.header(), "Status"])?;
+
+        // Write tasks in this folder
+        for task in self.tasks.values() {
+            if task.folder.as_deref() == Some(folder_name) {
+                let status = if task.start_time.is_some() {
+                    "Running"
+                } else if task.is_paused {
+                    "Paused"
+                } else {
+                    "Stopped"
+                };
+
+                writer.write_record(&[
+                    &task.description,
+                    folder_name,
+                    &task.format_duration(duration_format),
+                    status
+                ])?;
+            }
+        }
+
+        writer.flush()?;
+        record_export(&filename);
+        let task_ids: Vec<&str> = self
+            .tasks
+            .values()
+            .filter(|task| task.folder.as_deref() == Some(folder_name))
+            .map(|task| task.id.as_str())
+            .collect();
+        Self::bundle_screenshots(&filename, &task_ids)?;
+        Ok(filename)
+    }
+
+    // Printable timesheet for this week with the folders' open tasks as rows and empty day
+    // columns, for clients that want hours written down and signed on paper
+    fn export_blank_timesheet(&self, folder: Option<&str>) -> Result<String, Box<dyn std::error::Error>> {
+        let escape = |text: &str| text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;");
+        let calendar = self.calendar();
+        let week_start = calendar.week_start(calendar.today());
+        let days: Vec<NaiveDate> = week_start.iter_days().take(7).collect();
+        let folders: Vec<&str> = match folder {
+            Some(folder) => vec![folder],
+            None => self.folders.iter().map(String::as_str).collect(),
+        };
+
+        let mut html = String::new();
+        html.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+        html.push_str(&format!("<title>Timesheet, week of {}</title>\n", week_start.format("%b %d, %Y")));
+        html.push_str(
+            "<style>\n\
+             body { font-family: sans-serif; margin: 2em; }\n\
+             table { border-collapse: collapse; width: 100%; }\n\
+             th, td { border: 1px solid #444; padding: 6px; height: 1.6em; }\n\
+             td.hours { width: 8%; }\n\
+             th.folder { text-align: left; background: #eee; }\n\
+             .signatures { display: flex; gap: 4em; margin-top: 4em; }\n\
+             .signatures div { flex: 1; border-top: 1px solid #444; padding-top: 4px; }\n\
+             @media print { body { margin: 0; } }\n\
+             </style>\n</head>\n<body>\n",
+        );
+        html.push_str(&format!("<h1>Timesheet, week of {}</h1>\n", week_start.format("%b %d, %Y")));
+        html.push_str("<p>Name: ______________________________</p>\n<table>\n<tr><th>Task</th>");
+        for day in &days {
+            html.push_str(&format!("<th>{}</th>", day.format("%a %d")));
+        }
+        html.push_str("<th>Total</th></tr>\n");
+
+        let blank_cells = "<td class=\"hours\"></td>".repeat(days.len() + 1);
+        for folder in folders {
+            let mut tasks: Vec<&Task> = self
+                .tasks
+                .values()
+                .filter(|task| task.folder.as_deref() == Some(folder))
+                .filter(|task| !task.archived && task.status() != TaskStatus::Completed)
+                .collect();
+            tasks.sort_by(|a, b| a.description.cmp(&b.description));
+            html.push_str(&format!("<tr><th class=\"folder\" colspan=\"{}\">{}</th></tr>\n", days.len() + 2, escape(folder)));
+            for task in tasks {
+                html.push_str(&format!("<tr><td>{}</td>{}</tr>\n", escape(&task.description), blank_cells));
+            }
+            // Room for work that isn't a task yet
+            html.push_str(&format!("<tr><td></td>{}</tr>\n", blank_cells));
+        }
+        html.push_str(&format!("<tr><th>Total</th>{}</tr>\n</table>\n", blank_cells));
+        html.push_str(
+            "<div class=\"signatures\">\n<div>Signature and date</div>\n<div>Approved by (client) and date</div>\n</div>\n</body>\n</html>\n",
+        );
+
+        let filename = match folder {
+            Some(folder) => self.folder_export_path(folder, format!("timesheet_{}_{}.html", sanitize_filename(folder), week_start))?,
+            None => format!("timesheet_{}.html", week_start),
+        };
+        fs::write(&filename, html)?;
+        Ok(filename)
+    }
+
+    // Overlapping recorded sessions, across all tasks, earliest first
+    fn find_conflicts(&self) -> Vec<EntryConflict> {
+        let mut entries: Vec<ConflictEntry> = self
+            .tasks
+            .values()
+            .flat_map(|task| {
+                task.entries.iter().map(|entry| ConflictEntry { task_id: task.id.clone(), start: entry.start, end: entry.end })
+            })
+            .collect();
+        entries.sort_by_key(|entry| entry.start);
+        let mut conflicts = Vec::new();
+        for (index, first) in entries.iter().enumerate() {
+            for second in entries[index + 1..].iter().take_while(|second| second.start < first.end) {
+                let overlap = (first.end.min(second.end) - second.start).num_seconds();
+                if overlap > 0 {
+                    conflicts.push(EntryConflict { first: first.clone(), second: second.clone(), overlap });
+                }
+            }
+        }
+        conflicts
+    }
+
+    // Counted again only after data was saved
+    fn conflict_count(&mut self) -> usize {
+        let version = DATA_VERSION.load(Ordering::Relaxed);
+        match self.conflict_count {
+            Some((counted_at, count)) if counted_at == version => count,
+            _ => {
+                let count = self.find_conflicts().len();
+                self.conflict_count = Some((version, count));
+                count
+            }
+        }
+    }
+
+    fn resolve_conflict(&mut self, conflict: &EntryConflict, resolution: ConflictResolution) {
+        let (first, second) = (&conflict.first, &conflict.second);
+        match resolution {
+            ConflictResolution::Trim => {
+                self.edit_entry(&second.task_id, second.start, |entry| entry.start = first.end);
+            }
+            ConflictResolution::Merge => {
+                let removed = self.remove_entry(&second.task_id, second.start);
+                self.edit_entry(&first.task_id, first.start, |entry| {
+                    entry.end = entry.end.max(second.end);
+                    if let Some(note) = removed.and_then(|removed| removed.annotation) {
+                        entry.annotation = Some(match entry.annotation.take() {
+                            Some(annotation) => format!("{}; {}", annotation, note),
+                            None => note,
+                        });
+                    }
+                });
+            }
+            ConflictResolution::DeleteFirst => {
+                self.remove_entry(&first.task_id, first.start);
+            }
+            ConflictResolution::DeleteSecond => {
+                self.remove_entry(&second.task_id, second.start);
+            }
+        }
+        self.save_tasks();
+    }
+
+    fn remove_entry(&mut self, task_id: &str, start: DateTime<Local>) -> Option<TimeEntry> {
+        let task = self.tasks.get_mut(task_id)?;
+        let index = task.entries.iter().position(|entry| entry.start == start)?;
+        let entry = task.entries.remove(index);
+        audit::log("entry deleted", task, -(entry.end - entry.start).num_seconds());
+        Some(entry)
+    }
+
+    fn edit_entry(&mut self, task_id: &str, start: DateTime<Local>, edit: impl FnOnce(&mut TimeEntry)) {
+        let Some(task) = self.tasks.get_mut(task_id) else {
+            return;
+        };
+        let Some(entry) = task.entries.iter_mut().find(|entry| entry.start == start) else {
+            return;
+        };
+        let before = (entry.end - entry.start).num_seconds();
+        edit(entry);
+        let delta = (entry.end - entry.start).num_seconds() - before;
+        task.entries.sort_by_key(|entry| entry.start);
+        audit::log("entry edited", task, delta);
+    }
+
+    fn year_review(&self, year: i32) -> YearReview {
+        let calendar = self.calendar();
+        let mut days: std::collections::BTreeMap<NaiveDate, i64> = std::collections::BTreeMap::new();
+        let mut folders: HashMap<String, i64> = HashMap::new();
+        let mut tasks: Vec<(String, i64)> = Vec::new();
+        for task in self.tasks.values() {
+            let mut task_total = 0;
+            for (date, seconds) in task.daily_durations(calendar) {
+                let Some(date) = date.filter(|date| date.year() == year) else {
+                    continue;
+                };
+                *days.entry(date).or_default() += seconds;
+                task_total += seconds;
+            }
+            if task_total > 0 {
+                *folders.entry(task.folder.clone().unwrap_or_else(|| "Uncategorized".to_string())).or_default() += task_total;
+                tasks.push((task.id.clone(), task_total));
+            }
+        }
+        for adjustment in self.adjustments.iter().filter(|adjustment| adjustment.date.year() == year) {
+            *days.entry(adjustment.date).or_default() += adjustment.seconds;
+        }
+        days.retain(|_, seconds| *seconds > 0);
+
+        let mut months = [0; 12];
+        let mut weeks: HashMap<NaiveDate, i64> = HashMap::new();
+        let mut longest_streak: Option<(NaiveDate, NaiveDate)> = None;
+        let mut streak: Option<(NaiveDate, NaiveDate)> = None;
+        for (date, seconds) in &days {
+            months[date.month0() as usize] += seconds;
+            *weeks.entry(calendar.week_start(*date)).or_default() += seconds;
+            // Days off in between don't break a streak
+            let current = match streak {
+                Some((first, last)) if last.iter_days().skip(1).take_while(|day| day < date).all(|day| self.day_off(day).is_some()) => {
+                    (first, *date)
+                }
+                _ => (*date, *date),
+            };
+            streak = Some(current);
+            if longest_streak.is_none_or(|(first, last)| current.1 - current.0 > last - first) {
+                longest_streak = Some(current);
+            }
+        }
+
+        let top = |totals: Vec<(String, i64)>| {
+            let mut totals = totals;
+            totals.sort_by_key(|(_, seconds)| std::cmp::Reverse(*seconds));
+            totals.truncate(5);
+            totals
+        };
+        let mut busiest_weeks: Vec<(NaiveDate, i64)> = weeks.into_iter().collect();
+        busiest_weeks.sort_by_key(|(_, seconds)| std::cmp::Reverse(*seconds));
+        busiest_weeks.truncate(3);
+        YearReview {
+            year,
+            total: days.values().sum(),
+            days_tracked: days.len(),
+            months,
+            top_folders: top(folders.into_iter().collect()),
+            top_tasks: top(tasks),
+            busiest_weeks,
+            longest_streak,
+        }
+    }
+
+    fn export_year_review(&self, review: &YearReview) -> Result<String, Box<dyn std::error::Error>> {
+        let escape = |text: &str| text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;");
+        let hours = |seconds: i64| Self::format_hours_minutes(seconds);
+        let mut html = String::new();
+        html.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+        html.push_str(&format!("<title>{} in review</title>\n", review.year));
+        html.push_str(
+            "<style>\n\
+             body { font-family: sans-serif; margin: 2em; max-width: 48em; }\n\
+             .figures { display: flex; gap: 2em; flex-wrap: wrap; }\n\
+             .figures div { font-size: 0.9em; color: #555; }\n\
+             .figures strong { display: block; font-size: 1.8em; color: #000; }\n\
+             table { border-collapse: collapse; }\n\
+             td { padding: 3px 12px 3px 0; }\n\
+             .bar { background: #00b4b4; height: 1em; }\n\
+             </style>\n</head>\n<body>\n",
+        );
+        html.push_str(&format!("<h1>{} in review</h1>\n<div class=\"figures\">\n", review.year));
+        let streak = match review.longest_streak {
+            Some((first, last)) => format!("{} days, {} to {}", review.streak_days(), first.format("%b %d"), last.format("%b %d")),
+            None => "-".to_string(),
+        };
+        for (label, value) in [
+            ("Total", hours(review.total)),
+            ("Days tracked", review.days_tracked.to_string()),
+            ("Average per tracked day", hours(review.average_per_day())),
+            ("Longest streak", streak),
+        ] {
+            html.push_str(&format!("<div><strong>{}</strong>{}</div>\n", escape(&value), label));
+        }
+        html.push_str("</div>\n<h2>By month</h2>\n<table>\n");
+        let busiest_month = review.months.iter().copied().max().unwrap_or(0).max(1);
+        for (month, seconds) in review.months.iter().enumerate() {
+            let name = NaiveDate::from_ymd_opt(review.year, month as u32 + 1, 1).map(|date| date.format("%B").to_string()).unwrap_or_default();
+            html.push_str(&format!(
+                "<tr><td>{}</td><td>{}</td><td style=\"width: 20em\"><div class=\"bar\" style=\"width: {:.0}%\"></div></td></tr>\n",
+                name,
+                hours(*seconds),
+                *seconds as f64 / busiest_month as f64 * 100.0
+            ));
+        }
+        html.push_str("</table>\n<h2>Top projects</h2>\n<table>\n");
+        for (folder, seconds) in &review.top_folders {
+            html.push_str(&format!("<tr><td>{}</td><td>{}</td></tr>\n", escape(folder), hours(*seconds)));
+        }
+        html.push_str("</table>\n<h2>Top tasks</h2>\n<table>\n");
+        for (task_id, seconds) in &review.top_tasks {
+            let name = self.tasks.get(task_id).map_or("(deleted task)", |task| task.description.as_str());
+            html.push_str(&format!("<tr><td>{}</td><td>{}</td></tr>\n", escape(name), hours(*seconds)));
+        }
+        html.push_str("</table>\n<h2>Busiest weeks</h2>\n<table>\n");
+        for (week_start, seconds) in &review.busiest_weeks {
+            html.push_str(&format!("<tr><td>Week of {}</td><td>{}</td></tr>\n", week_start.format("%b %d"), hours(*seconds)));
+        }
+        html.push_str("</table>\n</body>\n</html>\n");
+
+        let filename = format!("year_in_review_{}.html", review.year);
+        fs::write(&filename, html)?;
+        Ok(filename)
+    }
+
+    // Proof-of-work screenshots go into a zip named after the export
+    fn bundle_screenshots(export: &str, task_ids: &[&str]) -> Result<(), Box<dyn std::error::Error>> {
+        let zip = format!("{}_screenshots.zip", export.trim_end_matches(".csv"));
+        screenshots::bundle(task_ids, &zip)?;
+        Ok(())
+    }
+
+    fn export_folder_with_template(
+        &self,
+        folder_name: &str,
+        template: &ExportTemplate,
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        let (delimiter, extension) = match template.format {
+            ExportFormat::Csv => (b',', "csv"),
+            ExportFormat::Tsv => (b'\t', "tsv"),
+        };
+        let filename = self.folder_export_path(
+            folder_name,
+            format!("{}_{}.{}", sanitize_filename(folder_name), sanitize_filename(&template.name), extension),
+        )?;
+        let file = fs::File::create(&filename)?;
+        let mut writer = csv::WriterBuilder::new().delimiter(delimiter).from_writer(file);
+
+        // Write header
+        let duration_format = template.duration_format.unwrap_or(self.settings.duration_format);
+        writer.write_record(template.columns.iter().map(|column| match column {
+            ExportColumn::Duration => duration_format.header(),
+            column => column.label(),
+        }))?;
+        let calendar = self.calendar();
+        let timezone = timezones::workspace();
+        let billing = &self.settings.billing;
+
+        let round = |seconds: i64| {
+            let step = template.rounding_minutes as i64 * 60;
+            if step > 0 && seconds > 0 {
+                (seconds + step - 1) / step * step
+            } else {
+                seconds
+            }
+        };
+
+        let mut tasks: Vec<_> = self
+            .tasks
+            .values()
+            .filter(|task| task.folder.as_deref() == Some(folder_name))
+            .collect();
+        tasks.sort_by(|a, b| a.description.cmp(&b.description));
+
+        for task in tasks {
+            // Sessions that ran on the given day, or all sessions
+            let sessions = |date: Option<NaiveDate>| -> Vec<&TimeEntry> {
+                task.entries
+                    .iter()
+                    .filter(|entry| {
+                        date.is_none_or(|date| {
+                            calendar.split_by_day(entry.start, entry.end).iter().any(|(day, _)| *day == date)
+                        })
+                    })
+                    .collect()
+            };
+            let annotations = |sessions: &[&TimeEntry]| {
+                sessions.iter().filter_map(|entry| entry.annotation.as_deref()).collect::<Vec<_>>().join("; ")
+            };
+            let sources = |sessions: &[&TimeEntry]| {
+                let mut labels: Vec<&str> = sessions.iter().map(|entry| entry.source.label()).collect();
+                labels.sort();
+                labels.dedup();
+                labels.join(", ")
+            };
+            let zone = |entry: &TimeEntry| match &entry.timezone {
+                Some(recorded) if *recorded != timezone => format!("{} (recorded in {})", timezone, recorded),
+                _ => timezone.clone(),
+            };
+            let rows: Vec<(Option<NaiveDate>, i64, String, String, String)> = match template.grouping {
+                ExportGrouping::Task => {
+                    let all = sessions(None);
+                    vec![(None, task.get_current_duration(), annotations(&all), sources(&all), timezone.clone())]
+                }
+                ExportGrouping::TaskAndDay => task
+                    .daily_durations(calendar)
+                    .into_iter()
+                    .map(|(date, seconds)| {
+                        let day = date.map(|date| sessions(Some(date))).unwrap_or_default();
+                        (date, seconds, annotations(&day), sources(&day), timezone.clone())
+                    })
+                    .collect(),
+                // A session past the day boundary becomes one row per day
+                ExportGrouping::Entry => task
+                    .entries
+                    .iter()
+                    .filter(|entry| !template.excluded_sources.contains(&entry.source))
+                    .flat_map(|entry| {
+                        calendar.split_by_day(entry.start, entry.end).into_iter().map(|(date, seconds)| {
+                            let notes = entry.annotation.clone().unwrap_or_default();
+                            (Some(date), seconds, notes, entry.source.label().to_string(), zone(entry))
+                        })
+                    })
+                    .collect(),
+            };
+
+            for (date, seconds, notes, source, zone) in rows {
+                let seconds = round(seconds);
+                let rate = if task.billable { task.hourly_rate.unwrap_or(0.0) } else { 0.0 };
+                let amount = billing.taxed(seconds as f64 / 3600.0 * rate, Some(folder_name));
+                let record: Vec<String> = template
+                    .columns
+                    .iter()
+                    .map(|column| match column {
+                        ExportColumn::Task => task.description.clone(),
+                        ExportColumn::Project => folder_name.to_string(),
+                        ExportColumn::Date => date.map(|d| d.to_string()).unwrap_or_default(),
+                        ExportColumn::Duration => duration_format.format(seconds),
+                        ExportColumn::DecimalHours => format!("{:.2}", seconds as f64 / 3600.0),
+                        ExportColumn::Status => task.status_label().to_string(),
+                        ExportColumn::Billable => if task.billable { "Yes" } else { "No" }.to_string(),
+                        ExportColumn::Amount => billing.format(amount.subtotal),
+                        ExportColumn::Tax => billing.format(amount.tax),
+                        ExportColumn::Total => billing.format(amount.total),
+                        ExportColumn::Annotations => notes.clone(),
+                        ExportColumn::Source => source.clone(),
+                        ExportColumn::TimeZone => zone.clone(),
+                    })
+                    .collect();
+                writer.write_record(&record)?;
+            }
+        }
+
+        // Costs go on the invoice along with the hours
+        if template.columns.iter().any(ExportColumn::is_amount) {
+            let mut expenses: Vec<&Expense> =
+                self.expenses.iter().filter(|expense| expense.folder.as_deref() == Some(folder_name)).collect();
+            expenses.sort_by_key(|expense| expense.date);
+            for expense in expenses {
+                writer.write_record(Self::expense_record(&template.columns, expense, billing))?;
+            }
+        }
+
+        writer.flush()?;
+        record_export(&filename);
+        Ok(filename)
+    }
+
+    // An expense as an export row, taxed like the folder's hours
+    fn expense_record(columns: &[ExportColumn], expense: &Expense, billing: &billing::BillingConfig) -> Vec<String> {
+        let amount = billing.taxed(expense.amount, expense.folder.as_deref());
+        columns
+            .iter()
+            .map(|column| match column {
+                ExportColumn::Task => expense.description.clone(),
+                ExportColumn::Project => expense.folder.clone().unwrap_or_else(|| "Uncategorized".to_string()),
+                ExportColumn::Date => expense.date.to_string(),
+                ExportColumn::Status | ExportColumn::Source => "Expense".to_string(),
+                ExportColumn::Billable => "Yes".to_string(),
+                ExportColumn::Amount => billing.format(amount.subtotal),
+                ExportColumn::Tax => billing.format(amount.tax),
+                ExportColumn::Total => billing.format(amount.total),
+                ExportColumn::Duration
+                | ExportColumn::DecimalHours
+                | ExportColumn::Annotations
+                | ExportColumn::TimeZone => String::new(),
+            })
+            .collect()
+    }
+
+    // Finished sessions not yet pushed to Harvest, folded into one entry per task and day
+    fn harvest_unpushed_entries(&self) -> Vec<harvest::DailyEntry> {
+        let mut daily: HashMap<(String, NaiveDate), harvest::DailyEntry> = HashMap::new();
+
+        for (task_id, task) in &self.tasks {
+            let Some(mapping) = task.folder.as_ref().and_then(|folder| self.settings.harvest.mappings.get(folder)) else {
+                continue;
+            };
+            for entry in task.entries.iter().filter(|entry| !entry.synced.contains_key(harvest::SYNC_KEY)) {
+                let date = self.calendar().day_of(entry.start);
+                let daily_entry = daily.entry((task_id.clone(), date)).or_insert_with(|| harvest::DailyEntry {
+                    task_id: task_id.clone(),
+                    date,
+                    hours: 0.0,
+                    notes: task.description.clone(),
+                    mapping: mapping.clone(),
+                    entry_starts: Vec::new(),
+                });
+                daily_entry.hours += (entry.end - entry.start).num_seconds() as f64 / 3600.0;
+                daily_entry.entry_starts.push(entry.start);
+            }
+        }
+
+        let mut entries: Vec<_> = daily.into_values().collect();
+        entries.sort_by(|a, b| a.date.cmp(&b.date).then_with(|| a.notes.cmp(&b.notes)));
+        entries
+    }
+
+    // Per task and day over the last 30 days: (date, task, hours, pushed sessions, total sessions)
+    fn harvest_daily_status(&self) -> Vec<(NaiveDate, String, f64, usize, usize)> {
+        let since = self.calendar().today() - chrono::Duration::days(30);
+        let mut daily: HashMap<(NaiveDate, String), (f64, usize, usize)> = HashMap::new();
+
+        for task in self.tasks.values() {
+            if !task.folder.as_ref().is_some_and(|folder| self.settings.harvest.mappings.contains_key(folder)) {
+                continue;
+            }
+            for entry in &task.entries {
+                let date = self.calendar().day_of(entry.start);
+                if date < since {
+                    continue;
+                }
+                let status = daily.entry((date, task.description.clone())).or_default();
+                status.0 += (entry.end - entry.start).num_seconds() as f64 / 3600.0;
+                if entry.synced.contains_key(harvest::SYNC_KEY) {
+                    status.1 += 1;
+                }
+                status.2 += 1;
+            }
+        }
+
+        let mut result: Vec<_> = daily
+            .into_iter()
+            .map(|((date, description), (hours, pushed, total))| (date, description, hours, pushed, total))
+            .collect();
+        result.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+        result
+    }
+
+    fn start_harvest_fetch(&mut self) {
+        let config = self.settings.harvest.clone();
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            let _ = sender.send(IntegrationJobResult::HarvestProjects(harvest::fetch_projects(&config)));
+        });
+        self.integration_job = Some(receiver);
+    }
+
+    fn start_harvest_push(&mut self) {
+        let entries = self.harvest_unpushed_entries();
+        if entries.is_empty() {
+            self.export_message = Some(("Nothing to push to Harvest".to_string(), 3.0));
+            return;
+        }
+
+        let config = self.settings.harvest.clone();
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            let results = entries
+                .into_iter()
+                .map(|entry| {
+                    let result = harvest::push_entry(&config, &entry);
+                    (entry, result)
+                })
+                .collect();
+            let _ = sender.send(IntegrationJobResult::HarvestPushed(results));
+        });
+        self.integration_job = Some(receiver);
+    }
+
+    // Waits for the background load, for the command line, which has nothing to show meanwhile
+    fn finish_loading(&mut self) {
+        let Some(receiver) = self.sessions_loading.take() else {
+            return;
+        };
+        match receiver.recv() {
+            Ok(tasks) => self.take_sessions(tasks),
+            Err(_) => tracing::error!("Loading {} stopped before finishing", self.data_file),
+        }
+    }
+
+    // Takes the sessions from the background load once it is done. False while it is still
+    // running.
+    fn poll_sessions_loading(&mut self) -> bool {
+        let Some(receiver) = &self.sessions_loading else {
+            return true;
+        };
+        match receiver.try_recv() {
+            Ok(tasks) => {
+                self.sessions_loading = None;
+                self.take_sessions(tasks);
+            }
+            Err(mpsc::TryRecvError::Empty) => return false,
+            Err(mpsc::TryRecvError::Disconnected) => {
+                tracing::error!("Loading {} stopped before finishing", self.data_file);
+                self.sessions_loading = None;
+            }
+        }
+        true
+    }
+
+    // Adds the loaded sessions to the task list. Changes made meanwhile are kept: a task
+    // missing from the list was deleted, and sessions recorded meanwhile come last.
+    fn take_sessions(&mut self, mut loaded: HashMap<String, Task>) {
+        if self.listed_without_sessions {
+            for (id, task) in self.tasks.iter_mut() {
+                if let Some(loaded) = loaded.remove(id) {
+                    let recorded = std::mem::replace(&mut task.entries, loaded.entries);
+                    task.entries.extend(recorded);
+                }
+            }
+            self.tasks = migrate_tasks(std::mem::take(&mut self.tasks));
+        } else {
+            // Nothing was listed, so every task in the file is new to the app
+            loaded.extend(std::mem::take(&mut self.tasks));
+            self.tasks = loaded;
+        }
+        self.saved_conflicts.set(self.find_conflicts().len());
+        DATA_VERSION.fetch_add(1, Ordering::Relaxed);
+        if self.tasks_save_pending.take() {
+            self.save_tasks();
+        }
+    }
+
+    // Only read once a pomodoro ends or the Focus statistics open
+    fn pomodoro_log(&mut self) -> &mut Vec<pomodoro::Interval> {
+        self.pomodoro_log.get_or_insert_with(|| read_data_file(pomodoro::LOG_FILE))
+    }
+
+    fn log_pomodoro(&mut self, interval: pomodoro::Interval) {
+        self.pomodoro_log().push(interval);
+        if let Ok(data) = serde_json::to_string(&self.pomodoro_log) {
+            write_data_file(pomodoro::LOG_FILE, data);
+        }
+    }
+
+    // Only read once a report is sent or the reports window opens
+    fn report_history(&mut self) -> &mut Vec<reports::SentReport> {
+        self.report_history.get_or_insert_with(|| read_data_file("report_history.json"))
+    }
+
+    fn poll_integration_job(&mut self) {
+        let Some(receiver) = &self.integration_job else {
+            return;
+        };
+        let result = match receiver.try_recv() {
+            Ok(result) => result,
+            Err(mpsc::TryRecvError::Empty) => return,
+            Err(mpsc::TryRecvError::Disconnected) => {
+                self.integration_job = None;
+                return;
+            }
+        };
+        self.integration_job = None;
+
+        match result {
+            IntegrationJobResult::HarvestProjects(Ok(projects)) => {
+                self.export_message = Some((format!("Loaded {} Harvest projects", projects.len()), 3.0));
+                self.harvest_projects = projects;
+            }
+            IntegrationJobResult::HarvestProjects(Err(e)) => {
+                self.export_message = Some((format!("Error loading Harvest projects: {}", e), 3.0));
+            }
+            IntegrationJobResult::HarvestPushed(results) => {
+                let mut pushed = 0;
+                let mut last_error = None;
+                for (daily_entry, result) in results {
+                    match result {
+                        Ok(remote_id) => {
+                            pushed += 1;
+                            if let Some(task) = self.tasks.get_mut(&daily_entry.task_id) {
+                                for entry in task.entries.iter_mut().filter(|e| daily_entry.entry_starts.contains(&e.start)) {
+                                    entry.synced.insert(harvest::SYNC_KEY.to_string(), remote_id.to_string());
+                                }
+                            }
+                        }
+                        Err(e) => last_error = Some(e),
+                    }
+                }
+                self.save_tasks();
+                self.export_message = Some(match last_error {
+                    Some(e) => (format!("Error pushing to Harvest ({} pushed): {}", pushed, e), 3.0),
+                    None => (format!("Pushed {} entries to Harvest", pushed), 3.0),
+                });
+            }
+            IntegrationJobResult::RedmineActivities(Ok(activities)) => {
+                self.export_message = Some((format!("Loaded {} Redmine activities", activities.len()), 3.0));
+                self.redmine_activities = activities;
+            }
+            IntegrationJobResult::RedmineActivities(Err(e)) => {
+                self.export_message = Some((format!("Error loading Redmine activities: {}", e), 3.0));
+            }
+            IntegrationJobResult::RedminePushed(results) => self.handle_redmine_push_results(results),
+            IntegrationJobResult::TasksImported { source, folder, result } => match result {
+                Ok(remote_tasks) => self.apply_imported_tasks(source, &folder, remote_tasks),
+                Err(e) => {
+                    self.export_message = Some((format!("Error importing from {}: {}", source.label(), e), 3.0));
+                }
+            },
+            IntegrationJobResult::SummaryEmailed(result) => {
+                self.export_message = Some(match result {
+                    Ok(()) => ("Weekly summary sent".to_string(), 3.0),
+                    Err(e) => (format!("Error sending weekly summary: {}", e), 3.0),
+                });
+            }
+            IntegrationJobResult::ReportEmailed(sent) => {
+                self.export_message = Some(match &sent.error {
+                    None => (format!("Report sent to {}", sent.to), 3.0),
+                    Some(e) => (format!("Error sending report: {}", e), 3.0),
+                });
+                self.report_history().push(sent);
+                if let Ok(data) = serde_json::to_string(&self.report_history) {
+                    write_data_file("report_history.json", data);
+                }
+            }
+            IntegrationJobResult::ActivityLoaded(Ok(days)) => self.activity_overlay = days,
+            IntegrationJobResult::ActivityLoaded(Err(e)) => {
+                self.export_message = Some((
+                    format!("Error loading {} data: {}", self.settings.activity.source.label(), e),
+                    3.0,
+                ));
+            }
+            IntegrationJobResult::NotionSynced(results) => {
+                let mut synced = 0;
+                let mut last_error = None;
+                for (task_id, result) in results {
+                    match result {
+                        Ok(page_id) => {
+                            synced += 1;
+                            if let Some(task) = self.tasks.get_mut(&task_id) {
+                                task.external_ids.insert(notion::EXTERNAL_ID_KEY.to_string(), page_id);
+                            }
+                        }
+                        Err(e) => last_error = Some(e),
+                    }
+                }
+                self.save_tasks();
+                if let Some(e) = last_error {
+                    self.export_message = Some((format!("Error syncing to Notion ({} synced): {}", synced, e), 3.0));
+                }
+            }
+        }
+    }
+
+    // Finished sessions of tasks linked to an issue that are neither pushed nor waiting
+    // in the error queue, folded into one entry per task and day
+    fn redmine_unpushed_entries(&self) -> Vec<redmine::RedmineEntry> {
+        let queued: Vec<DateTime<Local>> = self
+            .redmine_queue
+            .iter()
+            .flat_map(|failed| failed.entry.entry_starts.iter().copied())
+            .collect();
+        let mut daily: HashMap<(String, NaiveDate), redmine::RedmineEntry> = HashMap::new();
+
+        for (task_id, task) in &self.tasks {
+            let Some(issue_id) = task.redmine_issue_id else {
+                continue;
+            };
+            let activity_id = self.settings.redmine.activity_for(task.folder.as_deref()).map(|a| a.id);
+            for entry in task
+                .entries
+                .iter()
+                .filter(|entry| !entry.synced.contains_key(redmine::SYNC_KEY) && !queued.contains(&entry.start))
+            {
+                let date = self.calendar().day_of(entry.start);
+                let redmine_entry = daily.entry((task_id.clone(), date)).or_insert_with(|| redmine::RedmineEntry {
+                    task_id: task_id.clone(),
+                    issue_id,
+                    date,
+                    hours: 0.0,
+                    comments: task.description.clone(),
+                    activity_id,
+                    entry_starts: Vec::new(),
+                });
+                redmine_entry.hours += (entry.end - entry.start).num_seconds() as f64 / 3600.0;
+                redmine_entry.entry_starts.push(entry.start);
+            }
+        }
+
+        let mut entries: Vec<_> = daily.into_values().collect();
+        entries.sort_by(|a, b| a.date.cmp(&b.date).then_with(|| a.comments.cmp(&b.comments)));
+        entries
+    }
+
+    fn save_redmine_queue(&self) {
+        if let Ok(data) = serde_json::to_string(&self.redmine_queue) {
+            write_data_file("redmine_queue.json", data);
+        }
+    }
+
+    fn start_redmine_fetch(&mut self) {
+        let config = self.settings.redmine.clone();
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            let _ = sender.send(IntegrationJobResult::RedmineActivities(redmine::fetch_activities(&config)));
+        });
+        self.integration_job = Some(receiver);
+    }
+
+    fn start_redmine_push(&mut self, entries: Vec<redmine::RedmineEntry>) {
+        if entries.is_empty() {
+            self.export_message = Some(("Nothing to push to Redmine".to_string(), 3.0));
+            return;
+        }
+
+        let config = self.settings.redmine.clone();
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            let results = entries
+                .into_iter()
+                .map(|entry| {
+                    let result = redmine::push_entry(&config, &entry);
+                    (entry, result)
+                })
+                .collect();
+            let _ = sender.send(IntegrationJobResult::RedminePushed(results));
+        });
+        self.integration_job = Some(receiver);
+    }
+
+    fn handle_redmine_push_results(&mut self, results: Vec<(redmine::RedmineEntry, Result<u64, String>)>) {
+        let mut pushed = 0;
+        let mut failed = 0;
+
+        for (redmine_entry, result) in results {
+            let queue_index = self.redmine_queue.iter().position(|queued| {
+                queued.entry.task_id == redmine_entry.task_id && queued.entry.entry_starts == redmine_entry.entry_starts
+            });
+
+            match result {
+                Ok(remote_id) => {
+                    pushed += 1;
+                    if let Some(index) = queue_index {
+                        self.redmine_queue.remove(index);
+                    }
+                    if let Some(task) = self.tasks.get_mut(&redmine_entry.task_id) {
+                        for entry in task.entries.iter_mut().filter(|e| redmine_entry.entry_starts.contains(&e.start)) {
+                            entry.synced.insert(redmine::SYNC_KEY.to_string(), remote_id.to_string());
+                        }
+                    }
+                }
+                Err(error) => {
+                    failed += 1;
+                    match queue_index {
+                        Some(index) => {
+                            let queued = &mut self.redmine_queue[index];
+                            queued.error = error;
+                            queued.attempts += 1;
+                            queued.last_attempt = Local::now();
+                        }
+                        None => self.redmine_queue.push(redmine::FailedPush {
+                            entry: redmine_entry,
+                            error,
+                            attempts: 1,
+                            last_attempt: Local::now(),
+                        }),
+                    }
+                }
+            }
+        }
+
+        self.save_tasks();
+        self.save_redmine_queue();
+        self.export_message = Some(if failed > 0 {
+            (format!("Error pushing to Redmine: {} failed, {} pushed", failed, pushed), 3.0)
+        } else {
+            (format!("Pushed {} entries to Redmine", pushed), 3.0)
+        });
+    }
+
+    // Worklogs in the layout Tempo's CSV importer accepts, one row per linked task and day.
+    // Returns the filename and how many tasks were skipped for lacking an issue key.
+    fn export_tempo_worklogs(&self) -> Result<(String, usize), Box<dyn std::error::Error>> {
+        let filename = "tempo_worklogs.csv";
+        let file = fs::File::create(filename)?;
+        let mut writer = csv::Writer::from_writer(file);
+
+        // Write header
+        writer.write_record(["Issue Key", "Date", "Hours", "Description"])?;
+
+        let mut tasks: Vec<_> = self.tasks.values().filter(|task| task.get_current_duration() > 0).collect();
+        tasks.sort_by(|a, b| a.description.cmp(&b.description));
+
+        let mut skipped = 0;
+        for task in tasks {
+            let Some(issue_key) = task.issue_key.as_deref() else {
+                skipped += 1;
+                continue;
+            };
+            // Time without a recorded session has no date and cannot become a worklog
+            for (date, seconds) in task.daily_durations(self.calendar()) {
+                if let Some(date) = date {
+                    writer.write_record([
+                        issue_key.to_string(),
+                        date.to_string(),
+                        format!("{:.2}", seconds as f64 / 3600.0),
+                        task.description.clone(),
+                    ])?;
+                }
+            }
+        }
+
+        writer.flush()?;
+        record_export(filename);
+        Ok((filename.to_string(), skipped))
+    }
+
+    fn notion_rows(&self) -> Vec<notion::NotionRow> {
+        let calendar = self.calendar();
+        let today = calendar.today();
+        let week_ago = today - chrono::Duration::days(6);
+
+        self.tasks
+            .iter()
+            .map(|(task_id, task)| {
+                let daily = task
+                    .daily_durations(self.calendar())
+                    .into_iter()
+                    .rev()
+                    .filter_map(|(date, seconds)| date.filter(|date| *date >= week_ago).map(|date| (date, seconds)))
+                    .map(|(date, seconds)| format!("{}: {:.2}h", date, seconds as f64 / 3600.0))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                let today_seconds = task.tracked_between(calendar.day_start(today), Local::now() + chrono::Duration::seconds(1));
+
+                notion::NotionRow {
+                    task_id: task_id.clone(),
+                    page_id: task.external_ids.get(notion::EXTERNAL_ID_KEY).cloned(),
+                    title: task.description.clone(),
+                    folder: task.folder.clone().unwrap_or_else(|| "Uncategorized".to_string()),
+                    status: task.status_label().to_string(),
+                    total_hours: task.get_current_duration() as f64 / 3600.0,
+                    today_hours: today_seconds as f64 / 3600.0,
+                    daily,
+                }
+            })
+            .collect()
+    }
+
+    fn start_notion_sync(&mut self) {
+        let rows = self.notion_rows();
+        let config = self.settings.notion.clone();
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            let results = rows
+                .iter()
+                .map(|row| (row.task_id.clone(), notion::sync_row(&config, row)))
+                .collect();
+            let _ = sender.send(IntegrationJobResult::NotionSynced(results));
+        });
+        self.integration_job = Some(receiver);
+        self.last_notion_sync = Some(std::time::Instant::now());
+    }
+
+    // Starts a scheduled Notion sync when the configured interval has passed
+    fn check_notion_schedule(&mut self) {
+        let config = &self.settings.notion;
+        if !config.is_configured() || config.sync_interval_minutes == 0 || self.integration_job.is_some() {
+            return;
+        }
+        let interval = std::time::Duration::from_secs(config.sync_interval_minutes as u64 * 60);
+        if self.last_notion_sync.is_none_or(|last| last.elapsed() >= interval) {
+            self.start_notion_sync();
+        }
+    }
+
+    fn format_hours_minutes(seconds: i64) -> String {
+        DurationFormat::HoursMinutes.format(seconds)
+    }
+
+    // "ClientA – API refactor: 3h 20m today, 14h 0m total"
+    fn task_summary_line(&self, task_id: &str) -> Option<String> {
+        let task = self.tasks.get(task_id)?;
+        let (from, to) = self.calendar().day_range(self.calendar().today());
+        let today_seconds = task.tracked_between(from, to);
+        let name = match &task.folder {
+            Some(folder) => format!("{} – {}", folder, task.description),
+            None => task.description.clone(),
+        };
+        Some(format!(
+            "{}: {} today, {} total",
+            name,
+            Self::format_hours_minutes(today_seconds),
+            Self::format_hours_minutes(task.get_current_duration())
+        ))
+    }
+
+    // Markdown table of a folder's tasks with today's and total time
+    fn folder_summary_markdown(&self, folder_name: &str) -> String {
+        let (from, to) = self.calendar().day_range(self.calendar().today());
+        let mut tasks: Vec<_> = self
+            .tasks
+            .values()
+            .filter(|task| !task.archived && task.folder.as_deref().unwrap_or("Uncategorized") == folder_name)
+            .collect();
+        tasks.sort_by(|a, b| a.description.cmp(&b.description));
+
+        let mut markdown = format!("### {}\n\n| Task | Today | Total |\n|---|---:|---:|\n", folder_name);
+        let (mut today_total, mut total) = (0, 0);
+        for task in tasks {
+            let today_seconds = task.tracked_between(from, to);
+            let seconds = task.get_current_duration();
+            today_total += today_seconds;
+            total += seconds;
+            markdown.push_str(&format!(
+                "| {} | {} | {} |\n",
+                task.description.replace('|', "\\|"),
+                Self::format_hours_minutes(today_seconds),
+                Self::format_hours_minutes(seconds)
+            ));
+        }
+        markdown.push_str(&format!(
+            "| **Total** | **{}** | **{}** |\n",
+            Self::format_hours_minutes(today_total),
+            Self::format_hours_minutes(total)
+        ));
+        markdown
+    }
+
+    // Markdown section summarizing the tasks worked on during the given day
+    fn daily_note_summary(&self, date: NaiveDate) -> String {
+        let (from, to) = self.calendar().day_range(date);
+
+        let mut rows: Vec<_> = self
+            .tasks
+            .values()
+            .map(|task| (task, task.tracked_between(from, to)))
+            .filter(|(_, seconds)| *seconds > 0)
+            .collect();
+        rows.sort_by_key(|(_, seconds)| std::cmp::Reverse(*seconds));
+
+        let mut summary = String::from("\n## Work Timer\n\n");
+        if rows.is_empty() {
+            summary.push_str("No time tracked.\n");
+            return summary;
+        }
+
+        summary.push_str("| Task | Project | Time |\n|---|---|---|\n");
+        for (task, seconds) in &rows {
+            summary.push_str(&format!(
+                "| {} | {} | {} |\n",
+                task.description.replace('|', "\\|"),
+                task.folder.as_deref().unwrap_or("Uncategorized").replace('|', "\\|"),
+                Self::format_hours_minutes(*seconds)
+            ));
+        }
+        let total: i64 = rows.iter().map(|(_, seconds)| seconds).sum();
+        summary.push_str(&format!("\n**Total:** {}\n", Self::format_hours_minutes(total)));
+        summary
+    }
+
+    // Plain text summary of the week starting at week_start
+    fn weekly_summary_text(&self, week_start: NaiveDate) -> String {
+        let week_end = week_start + chrono::Duration::days(7);
+        let from = self.calendar().day_start(week_start);
+        let to = self.calendar().day_start(week_end);
+
+        let mut per_project: HashMap<String, i64> = HashMap::new();
+        for task in self.tasks.values() {
+            let seconds = task.tracked_between(from, to);
+            if seconds > 0 {
+                let folder = task.folder.clone().unwrap_or_else(|| "Uncategorized".to_string());
+                *per_project.entry(folder).or_default() += seconds;
+            }
+        }
+        let mut per_project: Vec<_> = per_project.into_iter().collect();
+        per_project.sort_by_key(|(_, seconds)| std::cmp::Reverse(*seconds));
+        let total: i64 = per_project.iter().map(|(_, seconds)| seconds).sum();
+
+        let mut summary = format!(
+            "Week of {} – {}\n\nTotal: {}\n",
+            week_start.format("%b %d"),
+            (week_end - chrono::Duration::days(1)).format("%b %d, %Y"),
+            Self::format_hours_minutes(total)
+        );
+        let last_day = week_end - chrono::Duration::days(1);
+        let share = self.work_day_share(week_start, last_day, last_day);
+        let goal = (self.settings.weekly_summary.goal_hours * 3600.0 * share) as i64;
+        if goal > 0 {
+            let status = if total >= goal {
+                format!("met, {} over", Self::format_hours_minutes(total - goal))
+            } else {
+                format!("missed, {} short", Self::format_hours_minutes(goal - total))
+            };
+            summary.push_str(&format!("Goal: {} ({})\n", Self::format_hours_minutes(goal), status));
+        }
+
+        summary.push_str("\nPer project:\n");
+        if per_project.is_empty() {
+            summary.push_str("  No time tracked.\n");
+        }
+        for (folder, seconds) in &per_project {
+            summary.push_str(&format!("  {}: {}\n", folder, Self::format_hours_minutes(*seconds)));
+        }
+        summary
+    }
+
+    fn save_weekly_summary(&self, week_start: NaiveDate, summary: &str) -> Result<String, Box<dyn std::error::Error>> {
+        let dir = Path::new(self.settings.weekly_summary.save_dir.trim());
+        fs::create_dir_all(dir)?;
+        let path = dir.join(format!("week_{}.txt", week_start.format("%Y-%m-%d")));
+        fs::write(&path, summary)?;
+        Ok(path.display().to_string())
+    }
+
+    fn start_summary_email(&mut self, week_start: NaiveDate, summary: String) {
+        let config = self.settings.smtp.clone();
+        let to = self.settings.weekly_summary.email_to.clone();
+        let subject = format!("Work Timer: week of {}", week_start.format("%b %d, %Y"));
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            let result = mail::send(&config, &to, &subject, summary);
+            let _ = sender.send(IntegrationJobResult::SummaryEmailed(result));
+        });
+        self.integration_job = Some(receiver);
+    }
+
+    // Runs the report and mails the file to the report's recipient
+    fn start_report_email(&mut self, report_id: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let filename = self.run_report(report_id)?;
+        let Some(definition) = self.settings.reports.iter().find(|report| report.id == report_id) else {
+            return Err("report not found".into());
+        };
+        let attachment = mail::MailAttachment {
+            filename: filename.clone(),
+            content_type: definition.format.content_type(),
+            data: fs::read(&filename)?,
+        };
+        let config = self.settings.smtp.clone();
+        let to = definition.email_to.trim().to_string();
+        let subject = format!("Work Timer: {}", definition.name);
+        let body = format!("{} ({}) is attached.\n", definition.name, definition.range.label().to_lowercase());
+        let report_id = report_id.to_string();
+
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            let error = mail::send_with_attachment(&config, &to, &subject, body, attachment).err();
+            let _ = sender.send(IntegrationJobResult::ReportEmailed(reports::SentReport {
+                report_id,
+                to,
+                filename,
+                sent_at: Local::now(),
+                error,
+            }));
+        });
+        self.integration_job = Some(receiver);
+        Ok(())
+    }
+
+    // Mails reports that are due this week, catching up when the app wasn't open on their day
+    fn check_report_email_schedule(&mut self) {
+        if !self.settings.smtp.is_configured() || self.integration_job.is_some() {
+            return;
+        }
+        let calendar = self.calendar();
+        let today = calendar.today();
+        let week_start = calendar.week_start(today);
+        let due = self.settings.reports.iter_mut().find_map(|report| {
+            let weekday = report.email_weekday?;
+            let due_on = week_start + chrono::Duration::days(calendar.days_into_week(weekday) as i64);
+            let due = !report.email_to.trim().is_empty() && today >= due_on && report.last_scheduled_send < Some(due_on);
+            // Marked before sending so a failing mail server isn't retried every frame
+            due.then(|| {
+                report.last_scheduled_send = Some(due_on);
+                report.id.clone()
+            })
+        });
+        if let Some(report_id) = due {
+            if let Err(e) = self.start_report_email(&report_id) {
+                self.export_message = Some((format!("Error sending report: {}", e), 3.0));
+            }
+            self.save_settings();
+        }
+    }
+
+    fn last_week_start(&self) -> NaiveDate {
+        let calendar = self.calendar();
+        calendar.week_start(calendar.today()) - chrono::Duration::days(7)
+    }
+
+    fn check_weekly_summary_schedule(&mut self) {
+        let calendar = self.calendar();
+        let config = &self.settings.weekly_summary;
+        let now = Local::now();
+        let week_start = self.last_week_start();
+        let today = calendar.today().weekday();
+        // Catch up later in the week when the app wasn't open on the configured day
+        let due = calendar.days_into_week(today) > calendar.days_into_week(config.weekday)
+            || (today == config.weekday && calendar.time_reached(now, config.at));
+        if !config.enabled || !due || config.last_generated >= Some(week_start) || self.integration_job.is_some() {
+            return;
+        }
+
+        let summary = self.weekly_summary_text(week_start);
+        self.settings.weekly_summary.last_generated = Some(week_start);
+        self.save_settings();
+
+        if !self.settings.weekly_summary.save_dir.trim().is_empty() {
+            if let Err(e) = self.save_weekly_summary(week_start, &summary) {
+                self.export_message = Some((format!("Error saving weekly summary: {}", e), 3.0));
+            }
+        }
+        if !self.settings.weekly_summary.email_to.trim().is_empty() && self.settings.smtp.is_configured() {
+            self.start_summary_email(week_start, summary.clone());
+        }
+        self.weekly_summary = Some((week_start, summary));
+    }
+
+    fn append_daily_note(&mut self, date: NaiveDate) -> Result<String, Box<dyn std::error::Error>> {
+        if self.settings.obsidian.path_pattern.trim().is_empty() {
+            return Err("no daily note path configured".into());
+        }
+        let path = self.settings.obsidian.note_path(date);
+        if let Some(parent) = Path::new(&path).parent() {
+            if !parent.as_os_str().is_empty() {
+                fs::create_dir_all(parent)?;
+            }
+        }
+
+        use std::io::Write;
+        let mut file = fs::OpenOptions::new().create(true).append(true).open(&path)?;
+        file.write_all(self.daily_note_summary(date).as_bytes())?;
+
+        self.settings.obsidian.last_appended = Some(date);
+        self.save_settings();
+        Ok(path)
+    }
+
+    // Appends today's summary once the configured end-of-day time has passed
+    fn check_daily_note_schedule(&mut self) {
+        let Some(append_at) = self.settings.obsidian.auto_append_at else {
+            return;
+        };
+        let now = Local::now();
+        let today = self.calendar().today();
+        if !self.calendar().time_reached(now, append_at) || self.settings.obsidian.last_appended == Some(today) {
+            return;
+        }
+        match self.append_daily_note(today) {
+            Ok(path) => self.show_exported_file(format!("Daily summary appended to {}", path), &path),
+            Err(e) => {
+                // Don't retry every frame after a failure
+                self.settings.obsidian.last_appended = Some(today);
+                self.export_message = Some((format!("Error appending daily note: {}", e), 3.0));
+            }
+        }
+    }
+
+    fn has_sessions_on(&self, date: NaiveDate) -> bool {
+        let (from, to) = self.calendar().day_range(date);
+        self.tasks.values().any(|task| task.tracked_between(from, to) > 0)
+    }
+
+    fn check_day_review_schedule(&mut self) {
+        let Some(review_at) = self.settings.day_review.review_at else {
+            return;
+        };
+        let now = Local::now();
+        let today = self.calendar().today();
+        if !self.calendar().time_reached(now, review_at)
+            || self.day_review_prompted == Some(today)
+            || self.settings.day_review.reviewed_days.contains(&today)
+        {
+            return;
+        }
+        self.day_review_prompted = Some(today);
+        if self.has_sessions_on(today) {
+            self.show_day_review = Some(today);
+        }
+    }
+
+    // Holds the window open for the review when quitting with an unreviewed day
+    fn check_day_review_on_quit(&mut self, ctx: &egui::Context) {
+        if !ctx.input(|i| i.viewport().close_requested())
+            || !self.settings.day_review.on_quit
+            || self.quit_after_review
+            || self.closes_to_tray()
+        {
+            return;
+        }
+        let today = self.calendar().today();
+        if self.settings.day_review.reviewed_days.contains(&today) || !self.has_sessions_on(today) {
+            return;
+        }
+        ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
+        self.show_day_review = Some(today);
+        self.quit_after_review = true;
+    }
+
+    fn start_activity_fetch(&mut self, from: NaiveDate, to: NaiveDate) {
+        let config = self.settings.activity.clone();
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            let result = activity::fetch_daily_activity(&config, from, to);
+            let _ = sender.send(IntegrationJobResult::ActivityLoaded(result));
+        });
+        self.integration_job = Some(receiver);
+    }
+
+    fn start_task_import(&mut self, folder: String) {
+        let config = self.settings.task_import.clone();
+        let source = self.import_source;
+        let container_id = self.import_container_id.clone();
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            let result = task_import::fetch_open_tasks(&config, source, &container_id);
+            let _ = sender.send(IntegrationJobResult::TasksImported { source, folder, result });
+        });
+        self.integration_job = Some(receiver);
+    }
+
+    // Adds remote tasks to the folder; tasks imported before are renamed in place instead
+    fn apply_imported_tasks(&mut self, source: task_import::ImportSource, folder: &str, remote_tasks: Vec<task_import::RemoteTask>) {
+        let key = source.external_id_key();
+        let mut created = 0;
+        let mut updated = 0;
+
+        for remote_task in remote_tasks {
+            let existing = self
+                .tasks
+                .values_mut()
+                .find(|task| task.external_ids.get(key) == Some(&remote_task.id));
+            match existing {
+                Some(task) => {
+                    if task.description != remote_task.name {
+                        task.description = remote_task.name;
+                        updated += 1;
+                    }
+                }
+                None => {
+                    let mut task = Task::new(remote_task.name);
+                    if let Some(style) = self.folder_styles.get(folder) {
+                        style.task_defaults.apply_to(&mut task);
+                    }
+                    task.folder = Some(folder.to_string());
+                    task.external_ids.insert(key.to_string(), remote_task.id);
+                    self.tasks.insert(task.id.clone(), task);
+                    created += 1;
+                }
+            }
+        }
+
+        self.save_tasks();
+        self.export_message = Some((
+            format!("Imported {} new tasks into '{}', updated {}", created, folder, updated),
+            3.0,
+        ));
+    }
+
+    fn clear_folder(&mut self, folder_name: &str) {
+        // Remove the folder's CSV export if it exists
+        let folder_csv = format!("folder_{}.csv", sanitize_filename(folder_name));
+        let _ = fs::remove_file(&folder_csv);
+
+        // Remove individual task CSV files for tasks in this folder and the tasks themselves
+        self.tasks.retain(|_, task| {
+            if task.folder.as_deref() == Some(folder_name) {
+                audit::log("deleted", task, -task.get_current_duration());
+                // Remove the task's CSV file if it exists
+                let _ = fs::remove_file(format!("{}.csv", sanitize_filename(&task.description)));
+                task.attachments.iter().for_each(|path| attachments::remove(path));
+                screenshots::remove_for(&task.id);
+                false // Remove this task
+            } else {
+                true // Keep tasks from other folders
+            }
+        });
+
+        // Remove expenses logged against this folder
+        let expense_count = self.expenses.len();
+        self.expenses.retain(|expense| expense.folder.as_deref() != Some(folder_name));
+        if self.expenses.len() != expense_count {
+            self.save_expenses();
+        }
+
+        self.remove_folder_entry(folder_name);
+    }
+
+    // Moves every task (and expense) of the folder elsewhere. Returns how many tasks moved.
+    fn move_folder_tasks(&mut self, folder_name: &str, target: Option<String>) -> usize {
+        let mut moved = 0;
+        for task in self.tasks.values_mut().filter(|task| task.folder.as_deref() == Some(folder_name)) {
+            task.folder = target.clone();
+            moved += 1;
+        }
+
+        let mut expenses_moved = false;
+        for expense in self.expenses.iter_mut().filter(|expense| expense.folder.as_deref() == Some(folder_name)) {
+            expense.folder = target.clone();
+            expenses_moved = true;
+        }
+        if expenses_moved {
+            self.save_expenses();
+        }
+
+        self.save_tasks();
+        moved
+    }
+
+    // Deletes the folder but keeps its tasks, which move to the target folder or Uncategorized
+    fn remove_folder_keep_tasks(&mut self, folder_name: &str, target: Option<String>) {
+        self.move_folder_tasks(folder_name, target);
+        self.remove_folder_entry(folder_name);
+    }
+
+    // Renames the folder everywhere it is referenced: tasks, expenses, styles, templates and reports
+    // Opens on Cancel every time, so Enter never removes a folder by accident
+    fn open_clear_folder_confirm(&mut self, ctx: &egui::Context, folder_name: String) {
+        ctx.memory_mut(|mem| mem.data.remove::<u8>(egui::Id::new(CLEAR_FOLDER_FOCUS)));
+        self.show_clear_folder_confirm = Some(folder_name);
+    }
+
+    fn rename_folder(&mut self, old_name: &str, new_name: &str) -> Result<(), String> {
+        let new_name = new_name.trim();
+        if new_name == old_name {
+            return Ok(());
+        }
+        if new_name.is_empty() || new_name == "Uncategorized" {
+            return Err("choose another name".to_string());
+        }
+        if self.folders.iter().any(|folder| folder == new_name) {
+            return Err(format!("{} already exists", new_name));
+        }
+        let Some(index) = self.folders.iter().position(|folder| folder == old_name) else {
+            return Err(format!("{} no longer exists", old_name));
+        };
+
+        self.folders[index] = new_name.to_string();
+        self.move_folder_tasks(old_name, Some(new_name.to_string()));
+        if let Some(mut style) = self.folder_styles.remove(old_name) {
+            style.name = new_name.to_string();
+            self.folder_styles.insert(new_name.to_string(), style);
+            self.save_folder_styles();
+        }
+        if let Some(status) = self.status_filters.remove(old_name) {
+            self.status_filters.insert(new_name.to_string(), status);
+        }
+        if self.selected_folder.as_deref() == Some(old_name) {
+            self.selected_folder = Some(new_name.to_string());
+        }
+        let renamed = |folder: &mut Option<String>| {
+            if folder.as_deref() == Some(old_name) {
+                *folder = Some(new_name.to_string());
+            }
+        };
+        self.settings.export_templates.iter_mut().for_each(|template| renamed(&mut template.folder));
+        self.settings.reports.iter_mut().for_each(|report| renamed(&mut report.folder));
+        if let Some(percent) = self.settings.billing.folder_tax_percents.remove(old_name) {
+            self.settings.billing.folder_tax_percents.insert(new_name.to_string(), percent);
+        }
+        self.save_settings();
+        if self.allocations.iter().any(|allocation| allocation.folder == old_name) {
+            self.allocations
+                .iter_mut()
+                .filter(|allocation| allocation.folder == old_name)
+                .for_each(|allocation| allocation.folder = new_name.to_string());
+            self.save_allocations();
+        }
+        Ok(())
+    }
+
+    // Archives every task in the folder, stopping running timers first. Returns how many were archived.
+    fn archive_folder_tasks(&mut self, folder_name: &str) -> usize {
+        let task_ids: Vec<String> = self
+            .tasks
+            .values()
+            .filter(|task| !task.archived && task.folder.as_deref().unwrap_or("Uncategorized") == folder_name)
+            .map(|task| task.id.clone())
+            .collect();
+        for task_id in &task_ids {
+            if self.tasks.get(task_id).is_some_and(|task| task.start_time.is_some()) {
+                self.handle_task_action(task_id, TaskAction::Pause);
+            }
+            if let Some(task) = self.tasks.get_mut(task_id) {
+                task.archived = true;
+                audit::log("archived", task, 0);
+            }
+        }
+        self.save_tasks();
+        task_ids.len()
+    }
+
+    // Everything that can be done to a folder, from its overflow button or a right-click on its header
+    fn folder_menu(&mut self, ui: &mut egui::Ui, folder_name: &str) {
+        let folder_name = folder_name.to_string();
+        let folder_tasks: Vec<&Task> = self
+            .tasks
+            .values()
+            .filter(|task| !task.archived && task.folder.as_deref().unwrap_or("Uncategorized") == folder_name)
+            .collect();
+        let running = folder_tasks.iter().filter(|task| task.status() == TaskStatus::Running).count();
+        let stopped = folder_tasks
+            .iter()
+            .filter(|task| matches!(task.status(), TaskStatus::Paused | TaskStatus::NotStarted))
+            .count();
+        let task_count = folder_tasks.len();
+
+        if running > 0 && ui.button(format!("{} Pause All ({})", fill::PAUSE, running)).clicked() {
+            self.set_folder_running(&folder_name, false);
+            ui.close_menu();
+        }
+        if stopped > 0 && ui.button(format!("{} Start All ({})", fill::PLAY, stopped)).clicked() {
+            self.set_folder_running(&folder_name, true);
+            ui.close_menu();
+        }
+        if running > 0 || stopped > 0 {
+            ui.separator();
+        }
+
+        if ui.button(format!("{} Rename", fill::PENCIL_SIMPLE)).clicked() {
+            self.renaming_folder = Some((folder_name.clone(), folder_name.clone()));
+            ui.close_menu();
+        }
+        ui.menu_button(format!("{} Color", fill::PALETTE), |ui| {
+            let style = self.folder_styles.entry(folder_name.clone()).or_insert_with(|| FolderStyle {
+                name: folder_name.clone(),
+                ..Default::default()
+            });
+            let mut color = style.color.unwrap_or([120, 160, 220]);
+            let mut color_changed = false;
+            if ui.color_edit_button_srgb(&mut color).changed() {
+                style.color = Some(color);
+                color_changed = true;
+            }
+            if style.color.is_some() && ui.button("No color").clicked() {
+                style.color = None;
+                color_changed = true;
+                ui.close_menu();
+            }
+            if color_changed {
+                self.save_folder_styles();
+            }
+        });
+        ui.menu_button(format!("{} Move All Tasks to", fill::FOLDER), |ui| {
+            let targets = self.folders.iter().filter(|folder| **folder != folder_name).map(|folder| Some(folder.clone()));
+            for target in targets.chain([None]).collect::<Vec<_>>() {
+                if ui.button(target.as_deref().unwrap_or("Uncategorized")).clicked() {
+                    let moved = self.move_folder_tasks(&folder_name, target.clone());
+                    self.export_message = Some((
+                        format!("Moved {} tasks to {}", moved, target.as_deref().unwrap_or("Uncategorized")),
+                        3.0,
+                    ));
+                    ui.close_menu();
+                }
+            }
+        });
+        if ui
+            .add_enabled(task_count > 0, egui::Button::new(format!("{} Archive All Tasks", fill::ARCHIVE)))
+            .clicked()
+        {
+            let archived = self.archive_folder_tasks(&folder_name);
+            self.export_message = Some((format!("Archived {} tasks in {}", archived, folder_name), 3.0));
+            ui.close_menu();
+        }
+        ui.separator();
+
+        ui.menu_button(format!("{} Export", fill::EXPORT), |ui| {
+            if ui.button("CSV").clicked() {
+                match self.export_folder_to_csv(&folder_name) {
+                    Ok(filename) => self.show_exported_file(format!("Folder exported to {}", filename), &filename),
+                    Err(e) => self.export_message = Some((format!("Error exporting folder: {}", e), 3.0)),
+                }
+                ui.close_menu();
+            }
+            let templates: Vec<_> = self
+                .settings
+                .export_templates
+                .iter()
+                .filter(|template| template.applies_to(&folder_name))
+                .cloned()
+                .collect();
+            for template in &templates {
+                if ui.button(&template.name).clicked() {
+                    match self.export_folder_with_template(&folder_name, template) {
+                        Ok(filename) => self.show_exported_file(format!("Folder exported to {}", filename), &filename),
+                        Err(e) => self.export_message = Some((format!("Error exporting folder: {}", e), 3.0)),
+                    }
+                    ui.close_menu();
+                }
+            }
+            if ui.button("Blank timesheet (HTML)").on_hover_text("This week, to print and fill in by hand").clicked() {
+                match self.export_blank_timesheet(Some(&folder_name)) {
+                    Ok(filename) => self.show_exported_file(format!("Timesheet saved to {}", filename), &filename),
+                    Err(e) => self.export_message = Some((format!("Error saving timesheet: {}", e), 3.0)),
+                }
+                ui.close_menu();
+            }
+            if ui.button("Copy Markdown table").clicked() {
+                ui.ctx().copy_text(self.folder_summary_markdown(&folder_name));
+                self.export_message = Some((format!("Copied summary of {}", folder_name), 3.0));
+                ui.close_menu();
+            }
+            ui.separator();
+            if ui.button("Manage templates…").clicked() {
+                self.show_export_templates = true;
+                ui.close_menu();
+            }
+        });
+        ui.menu_button(format!("{} Statistics", fill::CHART_BAR), |ui| {
+            let calendar = self.calendar();
+            let today = calendar.today();
+            let (day_from, day_to) = calendar.day_range(today);
+            let week_from = calendar.day_start(calendar.week_start(today));
+            let tasks = self
+                .tasks
+                .values()
+                .filter(|task| task.folder.as_deref().unwrap_or("Uncategorized") == folder_name);
+            let (mut total, mut week, mut day, mut billable) = (0, 0, 0, 0);
+            for task in tasks {
+                total += task.get_current_duration();
+                week += task.tracked_between(week_from, day_to);
+                day += task.tracked_between(day_from, day_to);
+                if task.billable {
+                    billable += task.get_current_duration();
+                }
+            }
+            egui::Grid::new(("folder_stats", &folder_name)).num_columns(2).show(ui, |ui| {
+                for (label, value) in [
+                    ("Active tasks", task_count.to_string()),
+                    ("Today", self.format_duration(day)),
+                    ("This week", self.format_duration(week)),
+                    ("All time", self.format_duration(total)),
+                    ("Billable", self.format_duration(billable)),
+                ] {
+                    ui.label(label);
+                    ui.label(value);
+                    ui.end_row();
+                }
+            });
+            if let Some((tracked, budgeted)) = self.folder_budget_usage(&folder_name) {
+                ui.label(egui::RichText::new(format!(
+                    "Budget: {} of {} used",
+                    Self::format_hours_minutes(tracked),
+                    Self::format_hours_minutes(budgeted)
+                )).weak());
+            }
+            let (allocated, tracked, projected) = self.capacity_usage(calendar.week_start(today), &folder_name);
+            if allocated > 0 {
+                let text = format!(
+                    "Allocated this week: {} of {} used",
+                    Self::format_hours_minutes(tracked),
+                    Self::format_hours_minutes(allocated)
+                );
+                if projected.is_some_and(|projected| projected > allocated) {
+                    ui.colored_label(egui::Color32::from_rgb(220, 80, 80), text)
+                        .on_hover_text("On pace to go over the allocation");
+                } else {
+                    ui.label(egui::RichText::new(text).weak());
+                }
+            }
+        });
+        ui.menu_button(format!("{} Settings", fill::GEAR), |ui| {
+            let budget_usage = self.folder_budget_usage(&folder_name);
+            let style = self.folder_styles.entry(folder_name.clone()).or_insert_with(|| FolderStyle {
+                name: folder_name.clone(),
+                ..Default::default()
+            });
+            let mut style_changed = ui
+                .checkbox(&mut style.ask_annotation, "Ask what I did when pausing")
+                .changed();
+            ui.horizontal(|ui| {
+                ui.label("Work type:");
+                style_changed |= edit_work_kind(ui, ("folder_work_kind", &folder_name), &mut style.work_kind, "Not set");
+            });
+            ui.horizontal(|ui| {
+                let mut auto_complete = style.auto_complete_days.is_some();
+                if ui
+                    .checkbox(&mut auto_complete, "Complete tasks paused for")
+                    .on_hover_text("Marks tasks completed once they haven't run for this many days")
+                    .changed()
+                {
+                    style.auto_complete_days = auto_complete.then_some(30);
+                    style_changed = true;
+                }
+                let mut days = style.auto_complete_days.unwrap_or(30);
+                ui.add_enabled_ui(auto_complete, |ui| {
+                    if ui.add(egui::DragValue::new(&mut days).range(1..=365).suffix(" days")).changed() {
+                        style.auto_complete_days = Some(days);
+                        style_changed = true;
+                    }
+                });
+            });
+            ui.horizontal(|ui| {
+                ui.label("Export to:");
+                style_changed |= ui
+                    .add(egui::TextEdit::singleline(&mut style.export_dir).hint_text("App folder").desired_width(180.0))
+                    .on_hover_text("Directory for this folder's CSV, template and timesheet exports, e.g. a shared drive")
+                    .changed();
+            });
+            if self.settings.clipboard_tickets {
+                ui.horizontal(|ui| {
+                    ui.label("Ticket patterns:");
+                    style_changed |= edit_tags(ui, ui.id().with(("ticket_patterns", &folder_name)), &mut style.ticket_patterns);
+                })
+                .response
+                .on_hover_text("Copied text matching one of these starts a task here. # stands for a number and * for any text, e.g. SUPPORT-#");
+            }
+
+            ui.separator();
+            ui.label("New tasks in this folder:");
+            let defaults = &mut style.task_defaults;
+            egui::Grid::new(("folder_task_defaults", &folder_name))
+                .num_columns(2)
+                .spacing([10.0, 4.0])
+                .show(ui, |ui| {
+                    ui.label("Tags:");
+                    let tags_id = ui.id().with(("folder_default_tags", &folder_name));
+                    style_changed |= edit_tags(ui, tags_id, &mut defaults.tags);
+                    ui.end_row();
+
+                    ui.label("Billable:");
+                    style_changed |= ui.checkbox(&mut defaults.billable, "").changed();
+                    ui.end_row();
+
+                    ui.label("Hourly rate:");
+                    style_changed |= edit_optional_rate(ui, &mut defaults.hourly_rate);
+                    ui.end_row();
+
+                    ui.label("Estimate:");
+                    style_changed |= edit_optional_minutes(ui, &mut defaults.estimate_minutes);
+                    ui.end_row();
+
+                    ui.label("Start timer:");
+                    style_changed |= ui.checkbox(&mut defaults.auto_start, "when created").changed();
+                    ui.end_row();
+                });
+
+            // Kept with the billing settings, so reports from the command line and the API use it too
+            let tax_percents = &mut self.settings.billing.folder_tax_percents;
+            let mut own_tax = tax_percents.contains_key(&folder_name);
+            let mut tax_changed = false;
+            ui.horizontal(|ui| {
+                if ui
+                    .checkbox(&mut own_tax, "Tax rate")
+                    .on_hover_text("For this client instead of the one in Settings, e.g. 0 % for a client abroad")
+                    .changed()
+                {
+                    if own_tax {
+                        tax_percents.insert(folder_name.clone(), 0.0);
+                    } else {
+                        tax_percents.remove(&folder_name);
+                    }
+                    tax_changed = true;
+                }
+                if let Some(percent) = tax_percents.get_mut(&folder_name) {
+                    tax_changed |= ui
+                        .add(egui::DragValue::new(percent).range(0.0..=100.0).speed(0.5).max_decimals(2).suffix(" %"))
+                        .changed();
+                }
+            });
+
+            ui.separator();
+            let mut has_budget = style.budget.is_some();
+            if ui.checkbox(&mut has_budget, "Time budget").changed() {
+                style.budget = has_budget.then(FolderBudget::default);
+                style_changed = true;
+            }
+            if let Some(budget) = &mut style.budget {
+                ui.horizontal(|ui| {
+                    style_changed |= ui
+                        .add(egui::DragValue::new(&mut budget.hours).range(0.0..=10_000.0).speed(1.0).suffix(" h"))
+                        .changed();
+                    egui::ComboBox::from_id_salt(("budget_period", &folder_name))
+                        .selected_text(budget.period.label())
+                        .show_ui(ui, |ui| {
+                            for period in [BudgetPeriod::Weekly, BudgetPeriod::Monthly, BudgetPeriod::Total] {
+                                style_changed |= ui
+                                    .selectable_value(&mut budget.period, period, period.label())
+                                    .changed();
+                            }
+                        });
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Alert at (%):");
+                    let mut percents: Vec<String> = budget.alert_percents.iter().map(|p| p.to_string()).collect();
+                    if edit_tags(ui, ui.id().with(("budget_alerts", &folder_name)), &mut percents) {
+                        budget.alert_percents = percents.iter().filter_map(|p| p.trim_end_matches('%').parse().ok()).collect();
+                        budget.alert_percents.sort();
+                        style_changed = true;
+                    }
+                });
+                if let Some((tracked, budgeted)) = budget_usage {
+                    ui.label(egui::RichText::new(format!(
+                        "{} of {} used ({:.0}%)",
+                        Self::format_hours_minutes(tracked),
+                        Self::format_hours_minutes(budgeted),
+                        tracked as f64 / budgeted.max(1) as f64 * 100.0
+                    )).weak());
+                }
+            }
+            if style_changed {
+                self.save_folder_styles();
+                self.auto_completed_on = None;
+            }
+            if tax_changed {
+                self.save_settings();
+            }
+        });
+        ui.separator();
+
+        if ui.button(format!("{} Delete…", fill::TRASH)).clicked() {
+            self.open_clear_folder_confirm(ui.ctx(), folder_name.clone());
+            ui.close_menu();
+        }
+    }
+
+    fn remove_folder_entry(&mut self, folder_name: &str) {
+        if let Some(index) = self.folders.iter().position(|f| f == folder_name) {
+            self.folders.remove(index);
+            self.folder_styles.remove(folder_name);
+            // If this was the selected folder, clear the selection
+            if self.selected_folder.as_deref() == Some(folder_name) {
+                self.selected_folder = self.folders.first().cloned();
+            }
+            // Update focused folder index if needed
+            if let Some(focused_idx) = self.focused_folder_index {
+                if focused_idx >= self.folders.len() {
+                    self.focused_folder_index = if self.folders.is_empty() {
+                        None
+                    } else {
+                        Some(self.folders.len() - 1)
+                    };
+                }
+            }
+            self.save_tasks();
+            self.save_folder_styles();
+        }
+    }
+
+    fn add_expense(&mut self, description: String, amount: f64, date: NaiveDate, folder: Option<String>) {
+        self.expenses.push(Expense {
+            id: Uuid::new_v4().to_string(),
+            description,
+            amount,
+            date,
+            folder,
+        });
+        self.expenses.sort_by_key(|expense| std::cmp::Reverse(expense.date));
+        self.save_expenses();
+    }
+
+    fn delete_expense(&mut self, expense_id: &str) {
+        self.expenses.retain(|expense| expense.id != expense_id);
+        self.save_expenses();
+    }
+
+    fn save_expenses(&self) {
+        if let Ok(data) = serde_json::to_string(&self.expenses) {
+            write_data_file("expenses.json", data);
+        }
+    }
+
+    fn save_plan(&self) {
+        if let Ok(data) = serde_json::to_string(&self.plan) {
+            write_data_file("plan.json", data);
+        }
+    }
+
+    fn save_allocations(&self) {
+        if let Ok(data) = serde_json::to_string(&self.allocations) {
+            write_data_file("allocations.json", data);
+        }
+    }
+
+    fn save_adjustments(&self) {
+        if let Ok(data) = serde_json::to_string(&self.adjustments) {
+            write_data_file("adjustments.json", data);
+        }
+    }
+
+    // Net adjustment of the days in [from, to]
+    fn adjusted_between(&self, from: NaiveDate, to: NaiveDate) -> i64 {
+        self.adjustments
+            .iter()
+            .filter(|adjustment| adjustment.date >= from && adjustment.date <= to)
+            .map(|adjustment| adjustment.seconds)
+            .sum()
+    }
+
+    fn save_time_off(&self) {
+        if let Ok(data) = serde_json::to_string(&self.time_off) {
+            write_data_file("time_off.json", data);
+        }
+    }
+
+    fn pomodoro_minutes(&self) -> u32 {
+        self.settings.pomodoro.work_minutes.max(1)
+    }
+
+    fn day_off(&self, date: NaiveDate) -> Option<&TimeOff> {
+        self.time_off.iter().find(|day| day.date == date)
+    }
+
+    // Share of the work days in [from, to] that aren't days off, for goals and contracted hours.
+    // Only the days up to `until` count, for the part of a period that has passed.
+    fn work_day_share(&self, from: NaiveDate, to: NaiveDate, until: NaiveDate) -> f64 {
+        let work_days: Vec<NaiveDate> = from
+            .iter_days()
+            .take_while(|date| *date <= to)
+            .filter(|date| self.settings.work_hours.days.contains(&date.weekday()))
+            .collect();
+        if work_days.is_empty() {
+            return 1.0;
+        }
+        let working = work_days.iter().filter(|date| **date <= until && self.day_off(**date).is_none()).count();
+        working as f64 / work_days.len() as f64
+    }
+
+    // Days that are already marked keep their kind and note
+    fn add_time_off(&mut self, days: Vec<TimeOff>) -> usize {
+        let mut added = 0;
+        for day in days {
+            if self.day_off(day.date).is_none() {
+                self.time_off.push(day);
+                added += 1;
+            }
+        }
+        self.time_off.sort_by_key(|day| day.date);
+        self.save_time_off();
+        added
+    }
+
+    fn poll_time_off_import(&mut self) {
+        let Some(job) = &self.time_off_import else {
+            return;
+        };
+        let result = match job.try_recv() {
+            Ok(result) => result,
+            Err(mpsc::TryRecvError::Empty) => return,
+            Err(mpsc::TryRecvError::Disconnected) => Err("import stopped".to_string()),
+        };
+        self.time_off_import = None;
+        self.export_message = Some(match result {
+            Ok(days) => {
+                let found = days.len();
+                let added = self.add_time_off(days);
+                (format!("Imported {} days off ({} already marked)", added, found - added), 3.0)
+            }
+            Err(e) => (format!("Error importing days off: {}", e), 5.0),
+        });
+    }
+
+    fn allocated_hours(&self, week_start: NaiveDate, folder: &str) -> f64 {
+        self.allocations
+            .iter()
+            .find(|allocation| allocation.week_start == week_start && allocation.folder == folder)
+            .map_or(0.0, |allocation| allocation.hours)
+    }
+
+    // 0 hours removes the allocation
+    fn set_allocation(&mut self, week_start: NaiveDate, folder: &str, hours: f64) {
+        self.allocations.retain(|allocation| allocation.week_start != week_start || allocation.folder != folder);
+        if hours > 0.0 {
+            self.allocations.push(WeekAllocation { week_start, folder: folder.to_string(), hours });
+        }
+        self.save_allocations();
+    }
+
+    // Allocated and tracked seconds for the folder in the week. While the week is under way,
+    // also where it ends up if the pace so far continues.
+    fn capacity_usage(&self, week_start: NaiveDate, folder: &str) -> (i64, i64, Option<i64>) {
+        let calendar = self.calendar();
+        let from = calendar.day_start(week_start);
+        let to = calendar.day_start(week_start + chrono::Duration::days(7));
+        let tracked: i64 = self
+            .tasks
+            .values()
+            .filter(|task| task.folder.as_deref().unwrap_or("Uncategorized") == folder)
+            .map(|task| task.tracked_between(from, to))
+            .sum();
+        let now = Local::now();
+        let projected = (from < now && now < to).then(|| {
+            let elapsed = (now - from).num_seconds() as f64 / (to - from).num_seconds() as f64;
+            (tracked as f64 / elapsed) as i64
+        });
+        let allocated = (self.allocated_hours(week_start, folder) * 3600.0) as i64;
+        (allocated, tracked, projected)
+    }
+
+    fn add_planned_block(&mut self, task_id: String, date: NaiveDate, start: NaiveTime, minutes: u32) {
+        self.plan.push(PlannedBlock {
+            id: Uuid::new_v4().to_string(),
+            task_id,
+            date,
+            start,
+            minutes,
+        });
+        self.plan.sort_by_key(|block| (block.date, block.start));
+        self.save_plan();
+    }
+
+    fn delete_planned_block(&mut self, block_id: &str) {
+        self.plan.retain(|block| block.id != block_id);
+        self.save_plan();
+    }
+
+    // Seconds tracked on the block's task inside the block
+    fn planned_block_actual(&self, block: &PlannedBlock) -> i64 {
+        let (from, to) = block.window();
+        self.tasks
+            .get(&block.task_id)
+            .map_or(0, |task| task.tracked_between(from, to))
+    }
+
+    // Planned seconds and the part of them actually spent on the planned task
+    fn plan_adherence(&self, date: NaiveDate) -> Option<(i64, i64)> {
+        let blocks: Vec<_> = self.plan.iter().filter(|block| block.date == date).collect();
+        if blocks.is_empty() {
+            return None;
+        }
+        let planned = blocks.iter().map(|block| block.minutes as i64 * 60).sum();
+        let on_plan = blocks.iter().map(|block| self.planned_block_actual(block)).sum();
+        Some((planned, on_plan))
+    }
+
+    fn calculate_folder_expenses(&self) -> HashMap<String, f64> {
+        let mut totals: HashMap<String, f64> = HashMap::new();
+        for expense in &self.expenses {
+            let folder = expense.folder.clone().unwrap_or_else(|| "Uncategorized".to_string());
+            *totals.entry(folder).or_default() += expense.amount;
+        }
+        totals
+    }
+
+    fn save_settings(&self) {
+        if let Ok(data) = serde_json::to_string_pretty(&self.settings) {
+            write_data_file("settings.json", data);
+        }
+    }
+
+    // Pay periods from the first tracked day through the one containing `today`. The current
+    // period only expects the contracted hours of its work days so far, so the carry-over
+    // doesn't count days still to come as missed.
+    fn calculate_pay_periods(&self, today: NaiveDate) -> Vec<PayPeriodSummary> {
+        let config = &self.settings.pay_period;
+        let Some(first_date) = self.tasks.values().filter_map(|task| task.first_tracked_date(self.calendar())).min() else {
+            return Vec::new();
+        };
+
+        let calendar = self.calendar();
+        let mut periods = Vec::new();
+        let mut carry_over = 0;
+        let mut start = config.period_start(first_date, calendar);
+
+        while start <= today {
+            let next_start = config.next_period_start(start);
+            let from = calendar.day_start(start);
+            let to = calendar.day_start(next_start);
+            let end = next_start.pred_opt().unwrap_or(next_start);
+            let tracked = self.tasks.values().map(|task| task.tracked_between(from, to)).sum::<i64>()
+                + self.adjusted_between(start, end);
+            let contracted = (config.contracted_hours * 3600.0 * self.work_day_share(start, end, today)).round() as i64;
+            carry_over += tracked - contracted;
+
+            periods.push(PayPeriodSummary {
+                start,
+                end,
+                tracked,
+                contracted,
+                carry_over,
+            });
+            start = next_start;
+        }
+
+        periods
+    }
+
+    fn export_pay_periods_to_csv(&self) -> Result<String, Box<dyn std::error::Error>> {
+        let filename = "pay_periods.csv";
+        let file = fs::File::create(filename)?;
+        let mut writer = csv::Writer::from_writer(file);
+
+        // Write header
+        writer.write_record(["Period Start", "Period End", "Tracked Hours", "Contracted Hours", "Overtime Hours", "Carry-over Hours"])?;
+
+        let hours = |seconds: i64| format!("{:.2}", seconds as f64 / 3600.0);
+        for period in self.calculate_pay_periods(self.calendar().today()) {
+            writer.write_record([
+                period.start.to_string(),
+                period.end.to_string(),
+                hours(period.tracked),
+                hours(period.contracted),
+                hours(period.tracked - period.contracted),
+                hours(period.carry_over),
+            ])?;
+        }
+
+        writer.flush()?;
+        record_export(filename);
+        Ok(filename.to_string())
+    }
+
+    fn save_folder_styles(&self) {
+        if let Ok(data) = serde_json::to_string(&self.folder_styles) {
+            write_data_file("folder_styles.json", data);
+        }
+    }
+
+    fn configure_theme(&self, ctx: &egui::Context) {
+        let mut visuals = if self.dark_mode {
+            egui::Visuals::dark()
+        } else {
+            egui::Visuals::light()
+        };
+        
+        // Customize colors based on theme
+        if self.dark_mode {
+            visuals.override_text_color = Some(egui::Color32::from_rgb(230, 230, 230));
+            visuals.widgets.noninteractive.bg_fill = egui::Color32::from_rgb(32, 33, 36);
+            visuals.widgets.inactive.bg_fill = egui::Color32::from_rgb(45, 45, 48);
+            visuals.widgets.hovered.bg_fill = egui::Color32::from_rgb(55, 55, 58);
+            visuals.widgets.active.bg_fill = egui::Color32::from_rgb(48, 48, 51);
+            visuals.window_fill = egui::Color32::from_rgb(32, 33, 36);
+            visuals.panel_fill = egui::Color32::from_rgb(32, 33, 36);
+        } else {
+            visuals.override_text_color = Some(egui::Color32::from_rgb(25, 25, 25));
+            visuals.widgets.noninteractive.bg_fill = egui::Color32::from_rgb(252, 252, 252);
+            visuals.widgets.inactive.bg_fill = egui::Color32::from_rgb(248, 248, 248);
+            visuals.widgets.hovered.bg_fill = egui::Color32::from_rgb(240, 240, 240);
+            visuals.widgets.active.bg_fill = egui::Color32::from_rgb(235, 235, 235);
+            visuals.window_fill = egui::Color32::from_rgb(252, 252, 252);
+            visuals.panel_fill = egui::Color32::from_rgb(252, 252, 252);
+        }
+        
+        // Apply the styles
+        ctx.set_visuals(visuals);
+        ctx.style_mut(|style| {
+            style.spacing = egui::style::Spacing::default();
+            if self.settings.density == Density::Compact {
+                style.spacing.item_spacing = egui::vec2(6.0, 1.0);
+                style.spacing.button_padding = egui::vec2(3.0, 0.0);
+                style.spacing.interact_size.y = 14.0;
+                style.spacing.indent = 12.0;
+            }
+        });
+        ctx.set_pixels_per_point(self.ui_scale);
+    }
+
+    fn get_folders(&self) -> Vec<String> {
+        self.folders.clone()
+    }
+
+    // Visible tasks per folder, honoring the folder's status filter
+    fn get_tasks_by_folder(&self) -> HashMap<String, Vec<String>> {
+        let mut tasks_by_folder: HashMap<String, Vec<String>> = HashMap::new();
+        for (id, task) in self.tasks.iter().filter(|(_, task)| !task.archived) {
+            let folder_name = task
+                .folder
+                .clone()
+                .unwrap_or_else(|| "Uncategorized".to_string());
+            if self.status_filters.get(&folder_name).is_some_and(|status| *status != task.status()) {
+                continue;
+            }
+            if self.color_filter.is_some() && task.color_label != self.color_filter {
+                continue;
+            }
+            tasks_by_folder
+                .entry(folder_name)
+                .or_default()
+                .push(id.clone());
+        }
+        for task_ids in tasks_by_folder.values_mut() {
+            self.sort_task_ids(task_ids);
+        }
+        tasks_by_folder
+    }
+
+    fn sort_task_ids(&self, task_ids: &mut [String]) {
+        let tasks = &self.tasks;
+        match self.settings.task_sort {
+            TaskSort::Created => task_ids.sort_by_key(|id| tasks[id].created_at),
+            TaskSort::Name => task_ids.sort_by_key(|id| tasks[id].description.to_lowercase()),
+            TaskSort::Recent => task_ids.sort_by_key(|id| std::cmp::Reverse(tasks[id].last_active_at())),
+        }
+    }
+
+    fn handle_duration_edit(&mut self, task_id: &str, action: DurationEditAction) {
+        match action {
+            DurationEditAction::StartEdit(current_value) => {
+                self.editing_duration_task_id = Some(task_id.to_string());
+                self.editing_duration_value = current_value;
+            }
+            DurationEditAction::StopEdit(new_duration) => {
+                if let Some(duration) = new_duration {
+                    self.update_task_duration(task_id, duration);
+                }
+                self.editing_duration_task_id = None;
+                self.editing_duration_value.clear();
+            }
+        }
+    }
+
+    fn display_task(
+        &mut self,
+        ui: &mut egui::Ui,
+        task_id: String,
+        description: String,
+        duration: i64,
+        start_time: Option<DateTime<Local>>,
+        is_paused: bool,
+    ) -> (Option<TaskAction>, Option<String>) {
+        let mut action = None;
+        let export_error = None;
+        let is_editing = Some(&task_id) == self.editing_duration_task_id.as_ref();
+        
+        ui.horizontal(|ui| {
+            // Complete button (checkbox style) on the left
+            let is_completed = duration > 0 && start_time.is_none() && !is_paused;
+            let complete_icon = if is_completed {
+                fill::CHECK_SQUARE
+            } else {
+                fill::SQUARE
+            };
+            if ui.button(complete_icon).clicked() {
+                action = Some(TaskAction::Complete);
+            }
+            
+            ui.label(&description);
+            
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                // Delete button
+                if ui.button(fill::TRASH).clicked() {
+                    action = Some(TaskAction::Delete);
+                }
+
+                // Export single task button
+                if ui.button(fill::EXPORT).clicked() {
+                    action = Some(TaskAction::Export);
+                }
+
+                // Only show play/pause button if task is not completed
+                if !is_completed {
+                    let button_text = if start_time.is_some() {
+                        fill::PAUSE // Pause icon
+                    } else if is_paused {
+                        fill::PLAY // Play icon
+                    } else {
+                        fill::PLAY // Play icon
+                    };
+
+                    if ui.button(button_text).clicked() {
+                        action = Some(if start_time.is_some() {
+                            TaskAction::Pause
+                        } else if is_paused {
+                            TaskAction::Resume
+                        } else {
+                            TaskAction::Start
+                        });
+                    }
+                }
+
+                // Duration display/edit
+                if is_editing {
+                    let mut edit_value = self.editing_duration_value.clone();
+                    let response = ui.text_edit_singleline(&mut edit_value);
+                    if response.lost_focus() || ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                        let new_duration = self.parse_duration_input(&edit_value);
+                        if let Some(duration) = new_duration {
+                            self.update_task_duration(&task_id, duration);
+                        }
+                        self.editing_duration_task_id = None;
+                        self.editing_duration_value.clear();
+                    } else if ui.input(|i| i.key_pressed(egui::Key::Escape)) {
+                        self.editing_duration_task_id = None;
+                        self.editing_duration_value.clear();
+                    } else {
+                        self.editing_duration_value = edit_value;
+                    }
+                } else {
+                    let duration_label = ui.label(self.format_duration(duration));
+                    if duration_label.double_clicked() {
+                        // Edited as HH:MM:SS whatever the display format
+                        self.editing_duration_task_id = Some(task_id.clone());
+                        self.editing_duration_value = DurationFormat::Clock.format(duration);
+                    }
+                }
+
+                let status_text = if start_time.is_some() {
+                    egui::RichText::new("Running").color(egui::Color32::GREEN)
+                } else if is_paused {
+                    egui::RichText::new("Paused").color(egui::Color32::YELLOW)
+                } else if duration == 0 && !is_paused {
+                    egui::RichText::new("Not Started").color(egui::Color32::GRAY)
+                } else {
+                    egui::RichText::new("Completed").color(egui::Color32::from_rgb(0, 180, 180))
+                };
+                ui.label(status_text);
+            });
+        });
+
+        (action, export_error)
+    }
+
+    fn handle_task_action(&mut self, task_id: &str, action: TaskAction) {
+        match action {
+            TaskAction::Delete => {
+                self.show_delete_task_confirm = Some(task_id.to_string());
+            }
+            TaskAction::Complete => {
+                let mut events = Vec::new();
+                let mut completed = false;
+                if let Some(task) = self.tasks.get_mut(task_id) {
+                    let is_completed = task.total_duration() > 0 && task.start_time.is_none() && !task.is_paused;
+                    if is_completed {
+                        // If task is completed, mark it as incomplete by setting is_paused to true
+                        task.is_paused = true;
+                        task.completed_at = None;
+                    } else {
+                        // If task is not completed, mark it as completed
+                        if task.start_time.is_some() {
+                            task.pause(); // Stop the timer if it's running
+                            events.push(TimerEvent::TaskStopped(task_id.to_string()));
+                        }
+                        task.is_paused = false; // Mark as not paused
+                        task.completed_at = Some(Local::now());
+                        completed = true;
+                        events.push(TimerEvent::TaskCompleted(task_id.to_string()));
+                    }
+                    self.save_tasks();
+                }
+                if let Some(task) = self.tasks.get(task_id).filter(|_| completed) {
+                    let done_today = self.done_on(self.calendar().today()).len();
+                    self.export_message = Some((
+                        format!("{} Done: {} ({} done today)", fill::CONFETTI, self.shown_task_name(task), done_today),
+                        3.0,
+                    ));
+                }
+                for event in events {
+                    self.emit_event(event);
+                }
+            }
+            TaskAction::ToggleBillable => {
+                if let Some(task) = self.tasks.get_mut(task_id) {
+                    task.billable = !task.billable;
+                    self.save_tasks();
+                }
+            }
+            TaskAction::ShowDetails => {
+                self.show_task_details = Some(task_id.to_string());
+            }
+            TaskAction::Duplicate => {
+                if let Some(folder) = self.tasks.get(task_id).map(|task| task.folder.clone()) {
+                    self.duplicate_task(task_id, folder);
+                }
+            }
+            TaskAction::DuplicateInto(folder) => {
+                self.duplicate_task(task_id, folder);
+            }
+            TaskAction::Rename => {
+                if let Some(task) = self.tasks.get(task_id) {
+                    self.renaming_task = Some((task_id.to_string(), task.description.clone()));
+                }
+            }
+            TaskAction::MoveTo(folder) => {
+                self.move_task_to_folder(task_id, folder);
+            }
+            TaskAction::SetColorLabel(color_label) => {
+                if let Some(task) = self.tasks.get_mut(task_id) {
+                    task.color_label = color_label;
+                    self.save_tasks();
+                }
+            }
+            TaskAction::Export => {
+                let result = self.tasks.get(task_id).map(|task| self.export_task_to_csv(task));
+                match result {
+                    Some(Ok(filename)) => {
+                        self.show_exported_file(format!("Task exported to {}", filename), &filename);
+                    }
+                    Some(Err(e)) => {
+                        tracing::error!("Failed to export task: {}", e);
+                        self.export_message = Some((format!("Error exporting task: {}", e), 3.0));
+                    }
+                    None => {}
+                }
+            }
+            _ => {
+                if let Some(task) = self.tasks.get_mut(task_id) {
+                    let was_running = task.start_time.is_some();
+                    match action {
+                        TaskAction::Start => task.start(),
+                        TaskAction::Pause => task.pause(),
+                        TaskAction::Resume => task.resume(),
+                        TaskAction::Delete
+                        | TaskAction::Complete
+                        | TaskAction::ToggleBillable
+                        | TaskAction::ShowDetails
+                        | TaskAction::Duplicate
+                        | TaskAction::DuplicateInto(_)
+                        | TaskAction::Rename
+                        | TaskAction::MoveTo(_)
+                        | TaskAction::SetColorLabel(_)
+                        | TaskAction::Export => unreachable!(),
+                    }
+                    let is_running = task.start_time.is_some();
+                    if let (TaskAction::Pause, Some(entry)) = (&action, task.entries.last()) {
+                        let ask_annotation = task
+                            .folder
+                            .as_ref()
+                            .and_then(|folder| self.folder_styles.get(folder))
+                            .is_some_and(|style| style.ask_annotation);
+                        if ask_annotation && was_running {
+                            self.pending_annotation = Some((task_id.to_string(), entry.start));
+                            self.annotation_input.clear();
+                        }
+                    }
+                    if is_running && !was_running {
+                        self.emit_event(TimerEvent::TaskStarted(task_id.to_string()));
+                    } else if was_running && !is_running {
+                        self.emit_event(TimerEvent::TaskStopped(task_id.to_string()));
+                    }
+                }
+            }
+        }
+    }
+
+    fn emit_event(&mut self, event: TimerEvent) {
+        let mut budget_task = None;
+        let (hook, args, command, mut env) = match &event {
+            TimerEvent::TaskStarted(task_id) | TimerEvent::TaskStopped(task_id) | TimerEvent::TaskCompleted(task_id) => {
+                let Some(task) = self.tasks.get(task_id) else {
+                    return;
+                };
+                let (hook, command) = match event {
+                    TimerEvent::TaskStarted(_) => (plugins::ON_TASK_STARTED, &self.settings.hooks.on_task_started),
+                    TimerEvent::TaskStopped(_) => (plugins::ON_TASK_STOPPED, &self.settings.hooks.on_task_stopped),
+                    _ => (plugins::ON_TASK_COMPLETED, &self.settings.hooks.on_task_completed),
+                };
+                let mut env = vec![
+                    ("WORK_TIMER_TASK_ID", task.id.clone()),
+                    ("WORK_TIMER_TASK", task.description.clone()),
+                    ("WORK_TIMER_FOLDER", task.folder.clone().unwrap_or_default()),
+                    ("WORK_TIMER_DURATION", task.get_current_duration().to_string()),
+                ];
+                match (&event, task.entries.last()) {
+                    (TimerEvent::TaskStopped(_), Some(entry)) => {
+                        let session = (entry.end - entry.start).num_seconds();
+                        env.push(("WORK_TIMER_SESSION_DURATION", session.to_string()));
+                        audit::log("stopped", task, session);
+                        budget_task = Some(task.id.clone());
+                    }
+                    (TimerEvent::TaskStarted(_), _) => audit::log("started", task, 0),
+                    (TimerEvent::TaskCompleted(_), _) => audit::log("completed", task, 0),
+                    _ => {}
+                }
+                (hook, vec![plugins::task_map(task)], command.clone(), env)
+            }
+            TimerEvent::DayEnded(date) => {
+                let (from, to) = self.calendar().day_range(*date);
+                let worked_on = self.tasks.values().filter(|task| task.tracked_between(from, to) > 0);
+                let day_total: i64 = worked_on.clone().map(|task| task.tracked_between(from, to)).sum();
+                let env = vec![
+                    ("WORK_TIMER_DATE", date.to_string()),
+                    ("WORK_TIMER_DURATION", day_total.to_string()),
+                ];
+                (
+                    plugins::ON_DAY_ENDED,
+                    vec![date.to_string().into(), plugins::task_array(worked_on)],
+                    self.settings.hooks.on_day_ended.clone(),
+                    env,
+                )
+            }
+        };
+
+        let mut messages = self.plugin_host.dispatch(hook, args, &self.settings.disabled_plugins);
+
+        env.push(("WORK_TIMER_EVENT", hook.trim_start_matches("on_").to_string()));
+        if let Err(e) = hooks::run(&command, &env) {
+            messages.push(format!("Error running {} hook: {}", hook, e));
+        }
+
+        for message in messages.iter().filter(|message| message.starts_with("Error")) {
+            tracing::warn!("{}", message);
+        }
+        if let Some(message) = messages.into_iter().last() {
+            self.export_message = Some((message, 3.0));
+        }
+        if let Some(task_id) = budget_task {
+            self.check_budget_alerts(&task_id);
+        }
+    }
+
+    // One-click context switch: pauses whatever else is running and starts the task,
+    // resuming it when it was paused
+    fn switch_to_task(&mut self, task_id: &str) {
+        let running: Vec<String> = self
+            .tasks
+            .values()
+            .filter(|task| task.start_time.is_some() && task.id != task_id)
+            .map(|task| task.id.clone())
+            .collect();
+        for id in running {
+            self.handle_task_action(&id, TaskAction::Pause);
+        }
+        let action = if self.tasks.get(task_id).is_some_and(|task| task.is_paused) {
+            TaskAction::Resume
+        } else {
+            TaskAction::Start
+        };
+        self.handle_task_action(task_id, action);
+        self.save_tasks();
+    }
+
+    fn check_launch_task(&mut self) {
+        if self.launch_checked {
+            return;
+        }
+        self.launch_checked = true;
+
+        let Some(task_id) = self.settings.launch_task.clone() else {
+            return;
+        };
+        let any_running = self.tasks.values().any(|task| task.start_time.is_some());
+        if any_running || !self.tasks.contains_key(&task_id) || !self.settings.work_hours.contains(Local::now()) {
+            return;
+        }
+        if let Some(task) = self.tasks.get_mut(&task_id) {
+            // A paused or completed task is started again as a new session
+            task.is_paused = false;
+        }
+        self.handle_task_action(&task_id, TaskAction::Start);
+        if let Some(task) = self.tasks.get_mut(&task_id) {
+            task.running_source = EntrySource::Auto;
+        }
+        self.save_tasks();
+        self.auto_started_task = Some(task_id);
+    }
+
+    // Hands the running session of one task over to another, keeping its start time
+    fn reassign_running_session(&mut self, from_id: &str, to_id: &str) {
+        let Some(from_task) = self.tasks.get_mut(from_id) else {
+            return;
+        };
+        let Some(start) = from_task.start_time.take() else {
+            return;
+        };
+        // Leave tracked tasks paused rather than looking completed
+        from_task.is_paused = from_task.total_duration() > 0;
+        let source = std::mem::take(&mut from_task.running_source);
+        if let Some(task) = self.tasks.get_mut(to_id) {
+            task.running_source = source;
+            if let Some(running_since) = task.start_time {
+                // The target was running too; keep the earlier start
+                task.start_time = Some(running_since.min(start));
+            } else {
+                task.start_time = Some(start);
+            }
+            task.is_paused = false;
+        }
+        self.save_tasks();
+        self.emit_event(TimerEvent::TaskStarted(to_id.to_string()));
+    }
+
+    fn check_idle_pause(&mut self, ctx: &egui::Context) {
+        let config = self.settings.idle_pause.clone();
+        if !config.enabled {
+            self.idle_monitor = None;
+            return;
+        }
+        let Some(idle_seconds) = self.idle_monitor.get_or_insert_with(idle::IdleMonitor::start).idle_seconds() else {
+            return;
+        };
+        let threshold = config.minutes.max(1) as u64 * 60;
+
+        if self.idle_since.is_none() {
+            if idle_seconds < threshold {
+                return;
+            }
+            let idle_since = Local::now() - chrono::Duration::seconds(idle_seconds as i64);
+            let running: Vec<String> = self
+                .tasks
+                .values()
+                .filter(|task| task.start_time.is_some())
+                .map(|task| task.id.clone())
+                .collect();
+            if running.is_empty() {
+                return;
+            }
+            for task_id in &running {
+                if let Some(task) = self.tasks.get_mut(task_id) {
+                    task.pause_at(idle_since);
+                }
+                self.emit_event(TimerEvent::TaskStopped(task_id.clone()));
+            }
+            self.save_tasks();
+            self.idle_paused = running;
+            self.idle_since = Some(idle_since);
+        } else if idle_seconds < 30 {
+            // Input again since the last poll
+            self.idle_since = None;
+            self.idle_paused.retain(|task_id| {
+                self.tasks.get(task_id).is_some_and(|task| task.is_paused && task.start_time.is_none())
+            });
+            match config.on_return {
+                IdleReturn::Resume => self.resume_idle_paused(EntrySource::Auto),
+                IdleReturn::Ask if !self.idle_paused.is_empty() => {
+                    self.show_idle_return = true;
+                    ctx.send_viewport_cmd(egui::ViewportCommand::RequestUserAttention(
+                        egui::UserAttentionType::Informational,
+                    ));
+                }
+                _ => self.idle_paused.clear(),
+            }
+        }
+    }
+
+    // Undoes the pause of timers paused while the user was away, so the time away counts
+    fn keep_idle_time(&mut self) {
+        for task_id in std::mem::take(&mut self.idle_paused) {
+            let Some(task) = self.tasks.get_mut(&task_id) else {
+                continue;
+            };
+            if task.start_time.is_some() || !task.is_paused {
+                continue;
+            }
+            let Some(entry) = task.entries.pop() else {
+                continue;
+            };
+            task.start_time = Some(entry.start);
+            task.is_paused = false;
+            task.running_source = entry.source;
+            task.last_active = Some(Local::now());
+            self.emit_event(TimerEvent::TaskStarted(task_id));
+        }
+        self.save_tasks();
+    }
+
+    fn resume_idle_paused(&mut self, source: EntrySource) {
+        for task_id in std::mem::take(&mut self.idle_paused) {
+            self.handle_task_action(&task_id, TaskAction::Resume);
+            if let Some(task) = self.tasks.get_mut(&task_id) {
+                task.running_source = source;
+            }
+        }
+        self.save_tasks();
+    }
+
+    // Follows the running task through Pomodoro mode: a work interval starts with the timer and
+    // pauses it when it is up, then the break lasts until a timer runs again
+    fn check_pomodoro(&mut self, ctx: &egui::Context) {
+        let config = self.settings.pomodoro.clone();
+        if !config.enabled {
+            self.pomodoro = None;
+            return;
+        }
+        let now = Local::now();
+        let running = self.tasks.values().find(|task| task.start_time.is_some()).map(|task| task.id.clone());
+        let Some(current) = self.pomodoro.as_mut() else {
+            self.pomodoro = running.map(|task_id| config.work(task_id, now, 0));
+            return;
+        };
+        ctx.request_repaint_after(std::time::Duration::from_secs(1));
+
+        if current.phase.is_break() {
+            if running.is_none() && current.remaining(now) < -pomodoro::ABANDONED_BREAK_MINUTES * 60 {
+                // Counted as the planned break, not the whole evening
+                let interval = current.finish(current.due);
+                self.pomodoro = None;
+                self.log_pomodoro(interval);
+            } else if let Some(task_id) = running {
+                // Back to work, the break ends here
+                let interval = current.finish(now);
+                let streak = current.streak;
+                self.pomodoro = Some(config.work(task_id, now, streak));
+                self.log_pomodoro(interval);
+            } else if now >= current.due && !current.announced {
+                current.announced = true;
+                let task_id = current.task_id.clone();
+                if config.auto_resume && self.tasks.get(&task_id).is_some_and(|task| task.is_paused) {
+                    self.handle_task_action(&task_id, TaskAction::Resume);
+                    if let Some(task) = self.tasks.get_mut(&task_id) {
+                        task.running_source = EntrySource::Auto;
+                    }
+                    self.save_tasks();
+                } else {
+                    self.notify(notifications::Event::Pomodoro, "Break is over", "Time to get back to work");
+                    self.export_message = Some(("☕ Break is over".to_string(), 5.0));
+                    ctx.send_viewport_cmd(egui::ViewportCommand::RequestUserAttention(egui::UserAttentionType::Informational));
+                }
+            }
+            return;
+        }
+
+        if running.as_ref() != Some(&current.task_id) {
+            // Paused or switched to another task before the pomodoro was up
+            let interval = current.finish(now);
+            let streak = current.streak;
+            self.pomodoro = running.map(|task_id| config.work(task_id, now, streak));
+            self.log_pomodoro(interval);
+        } else if now >= current.due {
+            let interval = current.finish(now);
+            let next = config.break_after(current.task_id.clone(), now, current.streak + 1);
+            self.handle_task_action(&interval.task_id, TaskAction::Pause);
+            self.save_tasks();
+            let message = format!("Take a {}-minute {}", (next.due - now).num_minutes(), next.phase.label().to_lowercase());
+            self.notify(notifications::Event::Pomodoro, "Pomodoro done", &message);
+            self.export_message = Some((format!("🍅 Pomodoro done, {}", message.to_lowercase()), 5.0));
+            ctx.send_viewport_cmd(egui::ViewportCommand::RequestUserAttention(egui::UserAttentionType::Informational));
+            self.pomodoro = Some(next);
+            self.log_pomodoro(interval);
+        }
+    }
+
+    fn notify(&self, event: notifications::Event, title: &str, body: &str) {
+        if self.settings.notifications.wants(event) {
+            notifications::send(title, body);
+        }
+    }
+
+    // Notifies when a timer starts, however it was started, and once when a session runs
+    // past the limit
+    fn check_notifications(&mut self) {
+        let now = Local::now();
+        let limit = chrono::Duration::minutes(self.settings.notifications.long_running_minutes.max(1) as i64);
+        let running: Vec<(String, DateTime<Local>)> =
+            self.tasks.values().filter_map(|task| Some((task.id.clone(), task.start_time?))).collect();
+        let Some(notified) = self.notified_timers.as_mut() else {
+            // Timers still running from the last time the app was open aren't news
+            self.notified_timers = Some(running.into_iter().map(|(task_id, start)| (task_id, (start, now - start >= limit))).collect());
+            return;
+        };
+        notified.retain(|task_id, _| running.iter().any(|(running_id, _)| running_id == task_id));
+
+        let mut events = Vec::new();
+        for (task_id, start) in running {
+            match notified.get_mut(&task_id) {
+                Some((seen_start, ran_long)) if *seen_start == start => {
+                    if !*ran_long && now - start >= limit {
+                        *ran_long = true;
+                        events.push((notifications::Event::LongRunning, task_id));
+                    }
+                }
+                _ => {
+                    notified.insert(task_id.clone(), (start, false));
+                    events.push((notifications::Event::TimerStarted, task_id));
+                }
+            }
+        }
+        for (event, task_id) in events {
+            let Some(task) = self.tasks.get(&task_id) else {
+                continue;
+            };
+            let name = self.shown_task_name(task);
+            match event {
+                notifications::Event::LongRunning => {
+                    let body = format!("{} has been running for {}", name, self.format_duration(limit.num_seconds()));
+                    self.notify(event, "Timer still running", &body);
+                }
+                _ => self.notify(event, "Timer started", &name),
+            }
+        }
+    }
+
+    // Keeps Do Not Disturb on exactly while a qualifying timer runs
+    fn check_do_not_disturb(&mut self) {
+        let config = &self.settings.do_not_disturb;
+        let wanted = focus::SUPPORTED
+            && config.enabled
+            && self.tasks.values().any(|task| {
+                task.start_time.is_some()
+                    && (!config.deep_work_only || task.tags.iter().any(|tag| tag.eq_ignore_ascii_case(DEEP_WORK_TAG)))
+            });
+        if let Some(Err(e)) = self.do_not_disturb.poll() {
+            tracing::warn!("Failed to switch Do Not Disturb: {}", e);
+            self.export_message = Some((format!("Couldn't switch Do Not Disturb: {}", e), 5.0));
+            if wanted {
+                // Don't try again on every frame
+                self.settings.do_not_disturb.enabled = false;
+                self.save_settings();
+            }
+            return;
+        }
+        if wanted != self.do_not_disturb.is_on() {
+            self.do_not_disturb.switch(wanted);
+        }
+    }
+
+    // Session folders of the running tasks that need proof-of-work screenshots
+    fn screenshot_sessions(&self) -> Vec<std::path::PathBuf> {
+        if !self.settings.screenshots.enabled {
+            return Vec::new();
+        }
+        self.tasks
+            .values()
+            .filter(|task| task.tags.iter().any(|tag| tag.eq_ignore_ascii_case(screenshots::PROOF_OF_WORK_TAG)))
+            .filter_map(|task| Some(screenshots::session_dir(&task.id, task.start_time?)))
+            .collect()
+    }
+
+    // Takes a screenshot right when a tagged task starts and then every interval while it runs
+    fn check_screenshots(&mut self) {
+        if let Some(job) = &self.screenshot_job {
+            match job.try_recv() {
+                Ok(Err(e)) => {
+                    tracing::warn!("Failed to take a screenshot: {}", e);
+                    self.export_message = Some((format!("Couldn't take a screenshot: {}", e), 5.0));
+                }
+                Err(mpsc::TryRecvError::Empty) => return,
+                _ => {}
+            }
+            self.screenshot_job = None;
+        }
+
+        let sessions = self.screenshot_sessions();
+        if sessions.is_empty() {
+            self.last_screenshot = None;
+            return;
+        }
+        let interval = std::time::Duration::from_secs(self.settings.screenshots.interval_minutes.max(1) as u64 * 60);
+        if self.last_screenshot.is_some_and(|last| last.elapsed() < interval) {
+            return;
+        }
+        self.last_screenshot = Some(std::time::Instant::now());
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            let _ = sender.send(screenshots::capture(&sessions));
+        });
+        self.screenshot_job = Some(receiver);
+    }
+
+    fn check_clipboard(&mut self, ctx: &egui::Context) {
+        if !self.settings.clipboard_tickets {
+            self.clipboard_watch = None;
+            self.clipboard_ticket = None;
+            return;
+        }
+        let watch = self.clipboard_watch.get_or_insert_with(|| clipboard::watch(ctx.clone()));
+        let Some(text) = watch.try_iter().last() else {
+            return;
+        };
+        // Folder patterns are tried in the order the folders are listed
+        let patterns: Vec<(String, String)> = self
+            .folders
+            .iter()
+            .filter_map(|folder| self.folder_styles.get(folder))
+            .flat_map(|style| style.ticket_patterns.iter().map(|pattern| (style.name.clone(), pattern.clone())))
+            .collect();
+        if let Some(ticket) = clipboard::recognize(&text, &patterns) {
+            self.clipboard_ticket = Some(ticket);
+        }
+    }
+
+    // Starts the task for a copied ticket, creating it unless there already is one
+    fn start_clipboard_ticket(&mut self, ticket: clipboard::Ticket) {
+        let existing = self
+            .tasks
+            .values()
+            .find(|task| !task.archived && (task.issue_key.as_deref() == Some(&ticket.key) || task.description == ticket.key))
+            .map(|task| task.id.clone());
+        let task_id = existing.unwrap_or_else(|| {
+            let folder = ticket.folder.clone().or_else(|| self.selected_folder.clone());
+            let task_id = self.add_task(ticket.key.clone(), folder);
+            if clipboard::is_issue_key(&ticket.key) {
+                if let Some(task) = self.tasks.get_mut(&task_id) {
+                    task.issue_key = Some(ticket.key.clone());
+                }
+                self.save_tasks();
+            }
+            task_id
+        });
+        self.switch_to_task(&task_id);
+    }
+
+    // Keeps the tray icon up to date, carries out what was picked in its menu and hides
+    // the window instead of closing it when set to
+    #[cfg(feature = "tray")]
+    fn check_tray(&mut self, ctx: &egui::Context) {
+        if self.tray.is_none() && self.settings.tray.enabled {
+            match tray::Tray::new(ctx) {
+                Ok(tray) => self.tray = Some(tray),
+                Err(e) => {
+                    tracing::warn!("Failed to create the tray icon: {}", e);
+                    self.export_message = Some((format!("No tray icon: {}", e), 5.0));
+                    self.settings.tray.enabled = false;
+                    return;
+                }
+            }
+        }
+        if self.tray.is_none() {
+            return;
+        }
+
+        let mut running: Vec<&Task> = self.tasks.values().filter(|task| task.start_time.is_some()).collect();
+        running.sort_by_key(|task| task.start_time);
+        let mut recent: Vec<&Task> = self.tasks.values().filter(|task| !task.archived && task.start_time.is_none()).collect();
+        recent.sort_by_key(|task| std::cmp::Reverse(task.last_active_at()));
+        let recent: Vec<(String, String)> = recent
+            .into_iter()
+            .take(5)
+            .map(|task| (task.id.clone(), task.description.clone()))
+            .collect();
+        let status = tray::Status {
+            visible: self.settings.tray.enabled,
+            running: running.first().map(|task| {
+                let elapsed = task.start_time.map_or(0, |start| (Local::now() - start).num_seconds());
+                format!("{} {}", task.description, self.format_duration(elapsed))
+            }),
+            resumable: !recent.is_empty(),
+            recent: recent.clone(),
+        };
+        let Some(tray) = self.tray.as_mut() else {
+            return;
+        };
+        tray.update(status);
+
+        for command in tray.commands() {
+            match command {
+                tray::Command::Toggle => self.toggle_timers(),
+                tray::Command::Start(task_id) => {
+                    if self.tasks.contains_key(&task_id) {
+                        self.switch_to_task(&task_id);
+                    }
+                }
+                tray::Command::Show => Self::bring_to_front(ctx),
+                tray::Command::Quit => {
+                    // Shown again in case the day review holds the window open
+                    self.quitting = true;
+                    ctx.send_viewport_cmd(egui::ViewportCommand::Visible(true));
+                    ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+                }
+            }
+        }
+
+        if self.closes_to_tray() && ctx.input(|i| i.viewport().close_requested()) {
+            ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
+            ctx.send_viewport_cmd(egui::ViewportCommand::Visible(false));
+        }
+    }
+
+    // Pauses whatever runs, or resumes the task that ran last when nothing does
+    fn toggle_timers(&mut self) {
+        let running: Vec<String> = self
+            .tasks
+            .values()
+            .filter(|task| task.start_time.is_some())
+            .map(|task| task.id.clone())
+            .collect();
+        if running.is_empty() {
+            let last = self
+                .tasks
+                .values()
+                .filter(|task| !task.archived)
+                .max_by_key(|task| task.last_active_at())
+                .map(|task| task.id.clone());
+            if let Some(task_id) = last {
+                self.switch_to_task(&task_id);
+            }
+            return;
+        }
+        for task_id in &running {
+            self.handle_task_action(task_id, TaskAction::Pause);
+        }
+        self.save_tasks();
+    }
+
+    fn start_api(&mut self, ctx: &egui::Context) -> Result<(), String> {
+        let (requests, receiver) = mpsc::channel();
+        api::start(self.settings.api.port, requests, ctx.clone())?;
+        self.api_port = Some(self.settings.api.port);
+        self.api_requests = Some(receiver);
+        Ok(())
+    }
+
+    // Starts the API when it is turned on and carries out what its clients asked for
+    fn check_api(&mut self, ctx: &egui::Context) {
+        if !self.api_checked {
+            self.api_checked = true;
+            if self.settings.api.enabled {
+                if let Err(e) = self.start_api(ctx) {
+                    tracing::warn!("Failed to start the API: {}", e);
+                }
+            }
+        }
+        let Some(requests) = &self.api_requests else {
+            return;
+        };
+        let requests: Vec<api::Request> = requests.try_iter().collect();
+        for request in requests {
+            let result = match request.command {
+                api::Command::Start(task_id) => match self.tasks.get(&task_id) {
+                    Some(task) if task.archived => Err("The task is archived".to_string()),
+                    Some(_) => {
+                        self.switch_to_task(&task_id);
+                        if let Some(task) = self.tasks.get_mut(&task_id) {
+                            task.running_source = EntrySource::Api;
+                        }
+                        self.save_tasks();
+                        Ok(())
+                    }
+                    None => Err(format!("No task {}", task_id)),
+                },
+                api::Command::Stop(task_id) => match self.tasks.get(&task_id) {
+                    Some(task) if task.start_time.is_some() => {
+                        self.handle_task_action(&task_id, TaskAction::Pause);
+                        self.save_tasks();
+                        Ok(())
+                    }
+                    Some(_) => Ok(()),
+                    None => Err(format!("No task {}", task_id)),
+                },
+            };
+            let _ = request.reply.send(result);
+        }
+    }
+
+    fn bring_to_front(ctx: &egui::Context) {
+        ctx.send_viewport_cmd(egui::ViewportCommand::Visible(true));
+        ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(false));
+        ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+    }
+
+    // Registers the global hotkeys when they are turned on or changed, and carries out
+    // the ones that were pressed
+    fn check_hotkeys(&mut self, ctx: &egui::Context) {
+        if self.hotkeys.is_none() {
+            if !self.settings.global_hotkeys.enabled {
+                return;
+            }
+            match hotkeys::Hotkeys::new(ctx) {
+                Ok(hotkeys) => self.hotkeys = Some(hotkeys),
+                Err(e) => {
+                    tracing::warn!("Failed to set up global hotkeys: {}", e);
+                    self.hotkey_problems = vec![e];
+                    self.settings.global_hotkeys.enabled = false;
+                    return;
+                }
+            }
+        }
+        let Some(hotkeys) = self.hotkeys.as_mut() else {
+            return;
+        };
+        if !self.hotkeys_registered {
+            self.hotkey_problems = hotkeys.apply(&self.settings.global_hotkeys);
+            for problem in &self.hotkey_problems {
+                tracing::warn!("Global hotkey not registered: {}", problem);
+            }
+            self.hotkeys_registered = true;
+        }
+        for action in hotkeys.pressed() {
+            match action {
+                hotkeys::GlobalAction::ToggleTimer => self.toggle_timers(),
+                hotkeys::GlobalAction::QuickAdd => {
+                    Self::bring_to_front(ctx);
+                    self.focus_new_task = true;
+                }
+                hotkeys::GlobalAction::ShowWindow => Self::bring_to_front(ctx),
+            }
+        }
+    }
+
+    fn closes_to_tray(&self) -> bool {
+        #[cfg(feature = "tray")]
+        let has_tray = self.tray.is_some();
+        #[cfg(not(feature = "tray"))]
+        let has_tray = false;
+        has_tray && self.settings.tray.enabled && self.settings.tray.close_to_tray && !self.quitting
+    }
+
+    fn check_idle_reminder(&mut self, ctx: &egui::Context) {
+        let now = Local::now();
+        // Auto-paused timers count as running until the user is back
+        if self.tasks.values().any(|task| task.start_time.is_some()) || self.idle_since.is_some() {
+            self.last_timer_running = Some(now);
+            self.show_idle_reminder = false;
+            return;
+        }
+        let last_running = *self.last_timer_running.get_or_insert(now);
+        let minutes = self.settings.idle_reminder_minutes;
+        if minutes == 0 || self.show_idle_reminder || !self.settings.work_hours.contains(now) {
+            return;
+        }
+        if now - last_running >= chrono::Duration::minutes(minutes as i64) {
+            self.show_idle_reminder = true;
+            self.notify(
+                notifications::Event::IdleReminder,
+                "What are you working on?",
+                &format!("No timer has been running for {} minutes.", minutes),
+            );
+            ctx.send_viewport_cmd(egui::ViewportCommand::RequestUserAttention(
+                egui::UserAttentionType::Informational,
+            ));
+        }
+    }
+
+    // Reads today's meetings from the subscribed calendar in the background, again every
+    // half hour and when the day changes
+    fn refresh_meetings(&mut self, force: bool) {
+        if let Some(job) = &self.meetings_job {
+            match job.try_recv() {
+                Ok(Ok(meetings)) => {
+                    self.meetings = meetings;
+                    self.meetings_error = None;
+                }
+                Ok(Err(e)) => {
+                    tracing::warn!("Failed to read the calendar: {}", e);
+                    self.meetings_error = Some(e);
+                }
+                Err(mpsc::TryRecvError::Empty) => return,
+                Err(mpsc::TryRecvError::Disconnected) => {}
+            }
+            self.meetings_job = None;
+        }
+
+        let today = self.calendar().today();
+        let stale = self
+            .meetings_loaded
+            .is_none_or(|(date, at)| date != today || at.elapsed() >= std::time::Duration::from_secs(30 * 60));
+        if !self.settings.calendar_gaps.is_configured() || !(stale || force) {
+            return;
+        }
+        self.meetings_loaded = Some((today, std::time::Instant::now()));
+        let url = self.settings.calendar_gaps.ics_url.clone();
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            let _ = sender.send(ics::fetch_meetings(&url, today));
+        });
+        self.meetings_job = Some(receiver);
+    }
+
+    // Suggests starting a focus task when nothing runs and the next meeting is a while away
+    fn check_calendar_gaps(&mut self, ctx: &egui::Context) {
+        self.refresh_meetings(false);
+        let config = &self.settings.calendar_gaps;
+        let now = Local::now();
+        if self.tasks.values().any(|task| task.start_time.is_some()) || self.idle_since.is_some() {
+            self.free_time_prompt = None;
+            return;
+        }
+        if !config.is_configured() || !config.weekdays.contains(&now.weekday()) || self.free_time_prompt.is_some() {
+            return;
+        }
+        // Only between two meetings, not before the first or after the last one
+        let in_meeting = self.meetings.iter().any(|meeting| meeting.start <= now && now < meeting.end);
+        let had_meeting = self.meetings.iter().any(|meeting| meeting.end <= now);
+        let Some(next) = self.meetings.iter().map(|meeting| meeting.start).filter(|start| *start > now).min() else {
+            return;
+        };
+        if in_meeting || !had_meeting || self.free_time_prompted == Some(next) {
+            return;
+        }
+        if next - now >= chrono::Duration::minutes(config.min_gap_minutes as i64) {
+            self.free_time_prompt = Some(next);
+            self.free_time_prompted = Some(next);
+            ctx.send_viewport_cmd(egui::ViewportCommand::RequestUserAttention(
+                egui::UserAttentionType::Informational,
+            ));
+        }
+    }
+
+    // Tasks worked on most recently, newest first
+    fn recent_tasks(&self, limit: usize) -> Vec<&Task> {
+        let mut tasks: Vec<_> = self
+            .tasks
+            .values()
+            .filter(|task| !task.archived)
+            .filter(|task| task.folder.as_ref().is_none_or(|folder| self.folders.contains(folder)))
+            .filter_map(|task| task.last_active_at().map(|last| (last, task)))
+            .collect();
+        tasks.sort_by_key(|(last, _)| std::cmp::Reverse(*last));
+        tasks.into_iter().take(limit).map(|(_, task)| task).collect()
+    }
+
+    // Once a day, completes tasks in folders with auto-completion that have been paused
+    // for longer than the folder allows
+    fn check_auto_complete(&mut self) {
+        let today = self.calendar().today();
+        if self.auto_completed_on == Some(today) {
+            return;
+        }
+        self.auto_completed_on = Some(today);
+
+        let now = Local::now();
+        let stale: Vec<String> = self
+            .tasks
+            .values()
+            .filter(|task| task.is_paused && task.start_time.is_none() && !task.archived)
+            .filter(|task| {
+                let days = task
+                    .folder
+                    .as_ref()
+                    .and_then(|folder| self.folder_styles.get(folder))
+                    .and_then(|style| style.auto_complete_days);
+                match (days, task.last_active_at()) {
+                    (Some(days), Some(last_active)) => now - last_active > chrono::Duration::days(days as i64),
+                    _ => false,
+                }
+            })
+            .map(|task| task.id.clone())
+            .collect();
+        if stale.is_empty() {
+            return;
+        }
+        for task_id in &stale {
+            if let Some(task) = self.tasks.get_mut(task_id) {
+                task.is_paused = false;
+                task.completed_at = Some(now);
+            }
+        }
+        self.save_tasks();
+        for task_id in &stale {
+            self.emit_event(TimerEvent::TaskCompleted(task_id.clone()));
+        }
+        tracing::info!("Completed {} tasks that were left paused", stale.len());
+        self.export_message = Some((
+            format!("{} Completed {} tasks that were left paused", fill::CHECK_CIRCLE, stale.len()),
+            5.0,
+        ));
+    }
+
+    fn check_day_rollover(&mut self) {
+        let today = self.calendar().today();
+        if today != self.current_day {
+            let ended = std::mem::replace(&mut self.current_day, today);
+            self.emit_event(TimerEvent::DayEnded(ended));
+        }
+    }
+
+    fn calendar(&self) -> Calendar {
+        self.settings.calendar()
+    }
+
+    // Time until the next running timer shows another second. Durations only change once a
+    // second, so there is no need to lay the window out on every frame in between.
+    fn next_timer_tick(&self) -> Option<std::time::Duration> {
+        let now = Local::now();
+        self.tasks
+            .values()
+            .filter_map(|task| task.start_time)
+            .map(|start| 1000 - (now - start).num_milliseconds().rem_euclid(1000))
+            .min()
+            .map(|millis| std::time::Duration::from_millis(millis as u64 + 5))
+    }
+
+    // Toast for a file that was just written, with buttons to open it or show it in its folder
+    fn show_exported_file(&mut self, message: String, path: &str) {
+        self.notify(notifications::Event::ExportFinished, "Export finished", &message);
+        self.export_message = Some((message.clone(), 8.0));
+        self.exported_file = Some((message, path.to_string()));
+    }
+
+    // Data files that go into a backup. The logs only matter on the machine that wrote them.
+    fn backup_files() -> impl Iterator<Item = &'static str> {
+        DATA_FILES.iter().copied()
+    }
+
+    fn backup_to_zip(&self, path: &str) -> Result<usize, String> {
+        let entries = backup::collect(&Self::backup_files().collect::<Vec<_>>())?;
+        backup::write(path, &entries)?;
+        tracing::info!("Backed up {} files to {}", entries.len(), path);
+        Ok(entries.len())
+    }
+
+    // Replaces the workspace with the archive, or with `merge` only adds the tasks, folders,
+    // expenses, plan blocks, adjustments, days off, allocations and attachments that aren't
+    // here yet; settings and history stay as they are. Replacing first saves the current
+    // workspace next to the data files, so it can be undone by restoring that.
+    fn restore_from_zip(&mut self, path: &str, merge: bool) -> Result<String, String> {
+        let entries = backup::read(path)?;
+        if !entries.iter().any(|entry| entry.name == "tasks.json") {
+            return Err("the archive has no tasks.json".to_string());
+        }
+        if merge {
+            let parse = |name: &str| -> Option<serde_json::Value> {
+                let entry = entries.iter().find(|entry| entry.name == name)?;
+                serde_json::from_slice(&entry.data).ok()
+            };
+            let tasks = migrate_tasks(parse("tasks.json").and_then(|value| serde_json::from_value(value).ok()).unwrap_or_default());
+            let folders: Vec<String> = parse("folders.json").and_then(|value| serde_json::from_value(value).ok()).unwrap_or_default();
+            let folder_styles: HashMap<String, FolderStyle> =
+                parse("folder_styles.json").and_then(|value| serde_json::from_value(value).ok()).unwrap_or_default();
+            let expenses: Vec<Expense> = parse("expenses.json").and_then(|value| serde_json::from_value(value).ok()).unwrap_or_default();
+            let plan: Vec<PlannedBlock> = parse("plan.json").and_then(|value| serde_json::from_value(value).ok()).unwrap_or_default();
+            let adjustments: Vec<Adjustment> =
+                parse("adjustments.json").and_then(|value| serde_json::from_value(value).ok()).unwrap_or_default();
+            let time_off: Vec<TimeOff> = parse("time_off.json").and_then(|value| serde_json::from_value(value).ok()).unwrap_or_default();
+            let allocations: Vec<WeekAllocation> =
+                parse("allocations.json").and_then(|value| serde_json::from_value(value).ok()).unwrap_or_default();
+
+            let mut added_tasks = 0;
+            for (id, task) in tasks {
+                if let std::collections::hash_map::Entry::Vacant(slot) = self.tasks.entry(id) {
+                    slot.insert(task);
+                    added_tasks += 1;
+                }
+            }
+            for folder in folders {
+                if !self.folders.contains(&folder) {
+                    self.folders.push(folder);
+                }
+            }
+            for (folder, style) in folder_styles {
+                self.folder_styles.entry(folder).or_insert(style);
+            }
+            for expense in expenses {
+                if !self.expenses.iter().any(|existing| existing.id == expense.id) {
+                    self.expenses.push(expense);
+                }
+            }
+            for block in plan {
+                if !self.plan.iter().any(|existing| existing.id == block.id) {
+                    self.plan.push(block);
+                }
+            }
+            for adjustment in adjustments {
+                if !self.adjustments.iter().any(|existing| existing.id == adjustment.id) {
+                    self.adjustments.push(adjustment);
+                }
+            }
+            // Days off and a week's hours already set here win over the archive's
+            self.add_time_off(time_off);
+            for allocation in allocations {
+                if !self
+                    .allocations
+                    .iter()
+                    .any(|existing| existing.week_start == allocation.week_start && existing.folder == allocation.folder)
+                {
+                    self.allocations.push(allocation);
+                }
+            }
+            for entry in entries.iter().filter(|entry| backup::is_attachment(&entry.name)) {
+                if !Path::new(&entry.name).exists() {
+                    write_restored_file(&entry.name, &entry.data)?;
+                }
+            }
+            self.save_tasks();
+            self.save_folder_styles();
+            self.save_expenses();
+            self.save_plan();
+            self.save_adjustments();
+            self.save_allocations();
+            tracing::info!("Merged {} tasks from {}", added_tasks, path);
+            return Ok(format!("Merged {} new tasks from {}", added_tasks, path));
+        }
+
+        let safety_copy = format!("work_timer_before_restore_{}.zip", Local::now().format("%Y-%m-%d_%H%M%S"));
+        self.backup_to_zip(&safety_copy)?;
+        for file in Self::backup_files() {
+            if !entries.iter().any(|entry| entry.name == file) && Path::new(file).exists() {
+                fs::remove_file(file).map_err(|e| format!("{}: {}", file, e))?;
+            }
+        }
+        let _ = fs::remove_dir_all(backup::ATTACHMENTS_DIR);
+        let mut restored = 0;
+        for entry in &entries {
+            if entry.name == "settings.json" {
+                // Backups leave out tokens and passwords, so keep the ones set here
+                let current = serde_json::to_vec(&self.settings).unwrap_or_default();
+                write_restored_file(&entry.name, &backup::with_current_secrets(&entry.data, &current))?;
+                restored += 1;
+            } else if Self::backup_files().any(|file| file == entry.name) || backup::is_attachment(&entry.name) {
+                write_restored_file(&entry.name, &entry.data)?;
+                restored += 1;
+            }
+        }
+        self.reload_data_files();
+        tracing::info!("Restored {} files from {}, previous workspace saved to {}", restored, path, safety_copy);
+        Ok(format!("Restored {} files from {}. The previous workspace was saved to {}", restored, path, safety_copy))
+    }
+
+    // Picks up data files changed on disk, e.g. after a restore
+    fn reload_data_files(&mut self) {
+        self.tasks = migrate_tasks(read_data_file(&self.data_file));
+        self.saved_conflicts.set(self.find_conflicts().len());
+        self.folders = read_data_file("folders.json");
+        self.folder_styles = read_data_file("folder_styles.json");
+        self.expenses = read_data_file("expenses.json");
+        self.plan = read_data_file("plan.json");
+        self.allocations = read_data_file("allocations.json");
+        self.adjustments = read_data_file("adjustments.json");
+        self.time_off = read_data_file("time_off.json");
+        self.pomodoro_log = None;
+        self.pomodoro = None;
+        self.report_history = None;
+        self.settings = read_data_file("settings.json");
+        self.redmine_queue = read_data_file("redmine_queue.json");
+        self.dark_mode = self.settings.appearance.dark_mode;
+        self.ui_scale = self.settings.appearance.ui_scale.clamp(1.0, 2.5);
+        self.temporary_ui_scale = self.ui_scale;
+        self.selected_folder = self
+            .settings
+            .selected_folder
+            .clone()
+            .filter(|folder| self.folders.contains(folder))
+            .or_else(|| self.folders.first().cloned());
+        self.focused_folder_index = self
+            .selected_folder
+            .as_ref()
+            .and_then(|folder| self.folders.iter().position(|f| f == folder));
+        self.focused_task_index = None;
+        self.editing_entry = None;
+        self.entry_undo = None;
+        self.moving_entry = None;
+        self.stats = StatsCache::default();
+    }
+
+    fn clear_all_folders(&mut self) {
+        self.folders.clear();
+        self.folder_styles.clear();
+        self.selected_folder = None;
+        // Reset focus but don't set to None - it will be set to Some(0) when a new folder is added
+        self.focused_folder_index = None;
+        self.focused_task_index = None;
+        self.save_tasks();
+        self.save_folder_styles();
+    }
+
+    fn calculate_folder_durations(&self) -> Vec<(String, i64)> {
+        let mut durations: HashMap<String, i64> = HashMap::new();
+        
+        for task in self.tasks.values() {
+            let folder = task.folder.clone().unwrap_or_else(|| "Uncategorized".to_string());
+            *durations.entry(folder).or_default() += task.get_current_duration();
+        }
+
+        let mut result: Vec<_> = durations.into_iter().collect();
+        result.sort_by_key(|(_, duration)| std::cmp::Reverse(*duration));
+        result
+    }
+
+    // Billable and non-billable seconds per folder, for tasks in existing folders or uncategorized.
+    // Within the first and last day given, time without a session only counts over all time.
+    fn calculate_billable_split(&self, days: Option<(NaiveDate, NaiveDate)>) -> Vec<(String, i64, i64)> {
+        let calendar = self.calendar();
+        let span = days.map(|(first, last)| (calendar.day_range(first).0, calendar.day_range(last).1));
+        let mut split: HashMap<String, (i64, i64)> = HashMap::new();
+
+        for task in self.tasks.values() {
+            if let Some(folder) = &task.folder {
+                if !self.folders.contains(folder) {
+                    continue;
+                }
+            }
+            let folder = task.folder.clone().unwrap_or_else(|| "Uncategorized".to_string());
+            let duration = match span {
+                Some((from, to)) => task.tracked_between(from, to),
+                None => task.get_current_duration(),
+            };
+            let entry = split.entry(folder).or_default();
+            if task.billable {
+                entry.0 += duration;
+            } else {
+                entry.1 += duration;
+            }
+        }
+
+        let mut result: Vec<_> = split
+            .into_iter()
+            .map(|(folder, (billable, non_billable))| (folder, billable, non_billable))
+            .collect();
+        result.sort_by(|a, b| a.0.cmp(&b.0));
+        result
+    }
+
+    fn work_kind(&self, task: &Task) -> Option<WorkKind> {
+        task.work_kind.or_else(|| self.folder_styles.get(task.folder.as_ref()?)?.work_kind)
+    }
+
+    // Brings the cached aggregates for the selected Statistics tab up to date
+    fn refresh_stats(&mut self) {
+        let now = Local::now().timestamp();
+        let tick = if self.tasks.values().any(|task| task.start_time.is_some()) { now } else { now / 60 };
+        let key = (self.selected_stats_tab, DATA_VERSION.load(Ordering::Relaxed), tick);
+        if self.stats.key == Some(key) {
+            return;
+        }
+        self.stats.key = Some(key);
+
+        // Tasks in existing folders or uncategorized
+        let current_tasks: Vec<&Task> = self
+            .tasks
+            .values()
+            .filter(|task| task.folder.as_ref().is_none_or(|folder| self.folders.contains(folder)))
+            .collect();
+        let calendar = self.calendar();
+        let today = calendar.today();
+        match self.selected_stats_tab {
+            StatsTab::Overview => {
+                self.stats.total_time = current_tasks.iter().map(|task| task.get_current_duration()).sum();
+                self.stats.task_count = current_tasks.len();
+                self.stats.active_tasks = current_tasks.iter().filter(|task| task.start_time.is_some()).count();
+                self.stats.completed_tasks = current_tasks
+                    .iter()
+                    .filter(|task| task.total_duration() > 0 && !task.is_paused && task.start_time.is_none())
+                    .count();
+                self.stats.billable_split = self.calculate_billable_split(self.billable_range.days(today, calendar));
+            }
+            StatsTab::Projects => {
+                let mut folder_expenses: Vec<_> = self.calculate_folder_expenses().into_iter().collect();
+                folder_expenses.sort_by(|a, b| a.0.cmp(&b.0));
+                self.stats.folder_durations = self.calculate_folder_durations();
+                self.stats.folder_expenses = folder_expenses;
+            }
+            StatsTab::Timeline => {
+                let first_day = today - chrono::Duration::days(6);
+                self.stats.tracked_days = first_day
+                    .iter_days()
+                    .take_while(|date| *date <= today)
+                    .map(|date| {
+                        let (from, to) = calendar.day_range(date);
+                        let tracked = current_tasks.iter().map(|task| task.tracked_between(from, to)).sum::<i64>() + self.adjusted_between(date, date);
+                        let pomodoros = current_tasks.iter().map(|task| task.pomodoros_between(from, to, self.pomodoro_minutes())).sum();
+                        (date, tracked, pomodoros)
+                    })
+                    .collect();
+            }
+            StatsTab::Plan => {
+                self.stats.plan_days = (0..14)
+                    .map(|offset| today - chrono::Duration::days(offset))
+                    .filter_map(|date| self.plan_adherence(date).map(|(planned, on_plan)| (date, planned, on_plan)))
+                    .collect();
+            }
+            StatsTab::Dashboard => {
+                let first_day = today - chrono::Duration::days(DASHBOARD_DAYS - 1);
+                let mut folders = self.folders.clone();
+                if current_tasks.iter().any(|task| task.folder.is_none()) {
+                    folders.push("Uncategorized".to_string());
+                }
+                self.stats.folder_cards = folders
+                    .into_iter()
+                    .map(|folder| {
+                        let tasks: Vec<&&Task> = current_tasks
+                            .iter()
+                            .filter(|task| task.folder.as_deref().unwrap_or("Uncategorized") == folder)
+                            .collect();
+                        let budget = self.folder_budget_usage(&folder).and_then(|(tracked, budgeted)| {
+                            let period = self.folder_styles.get(&folder)?.budget.as_ref()?.period.label();
+                            Some((tracked, budgeted, period))
+                        });
+                        let days = first_day
+                            .iter_days()
+                            .take_while(|date| *date <= today)
+                            .map(|date| {
+                                let (from, to) = calendar.day_range(date);
+                                tasks.iter().map(|task| task.tracked_between(from, to)).sum()
+                            })
+                            .collect();
+                        FolderCard {
+                            budget,
+                            tracked: tasks.iter().map(|task| task.get_current_duration()).sum(),
+                            earnings: tasks
+                                .iter()
+                                .filter(|task| task.billable)
+                                .fold(billing::Taxed::default(), |mut earnings, task| {
+                                    let amount = task.get_current_duration() as f64 / 3600.0 * task.hourly_rate.unwrap_or(0.0);
+                                    earnings += self.settings.billing.taxed(amount, task.folder.as_deref());
+                                    earnings
+                                }),
+                            last_active: tasks.iter().filter_map(|task| task.last_active_at()).max(),
+                            days,
+                            folder,
+                        }
+                    })
+                    .collect();
+            }
+            StatsTab::Focus => {
+                let first_day = today - chrono::Duration::days(FOCUS_DAYS - 1);
+                let classified: Vec<(&Task, WorkKind)> = current_tasks
+                    .iter()
+                    .filter_map(|task| Some((*task, self.work_kind(task)?)))
+                    .collect();
+                self.stats.focus_days = first_day
+                    .iter_days()
+                    .take_while(|date| *date <= today)
+                    .map(|date| {
+                        let (from, to) = calendar.day_range(date);
+                        let (mut deep, mut shallow) = (0, 0);
+                        for (task, kind) in &classified {
+                            match kind {
+                                WorkKind::Deep => deep += task.tracked_between(from, to),
+                                WorkKind::Shallow => shallow += task.tracked_between(from, to),
+                            }
+                        }
+                        (date, deep, shallow)
+                    })
+                    .collect();
+            }
+            StatsTab::Pomodoro => {
+                self.pomodoro_log();
+                let first_day = today - chrono::Duration::days(FOCUS_DAYS - 1);
+                let mut days: Vec<PomodoroDay> = first_day
+                    .iter_days()
+                    .take_while(|date| *date <= today)
+                    .map(|date| PomodoroDay { date, ..Default::default() })
+                    .collect();
+                let mut per_task: HashMap<&str, usize> = HashMap::new();
+                for interval in self.pomodoro_log.iter().flatten() {
+                    let date = calendar.day_of(interval.start);
+                    let Some(day) = days.iter_mut().find(|day| day.date == date) else {
+                        continue;
+                    };
+                    match (interval.phase, interval.completed) {
+                        (pomodoro::Phase::Work, true) => {
+                            day.finished += 1;
+                            *per_task.entry(&interval.task_id).or_default() += 1;
+                        }
+                        (pomodoro::Phase::Work, false) => day.cut_short += 1,
+                        (_, completed) => {
+                            day.breaks += 1;
+                            day.skipped_breaks += usize::from(!completed);
+                            day.break_seconds += (interval.end - interval.start).num_seconds();
+                        }
+                    }
+                }
+                let mut tasks: Vec<(String, usize)> = per_task.into_iter().map(|(task_id, count)| (task_id.to_string(), count)).collect();
+                tasks.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+                tasks.truncate(5);
+                self.stats.pomodoro_days = days;
+                self.stats.pomodoro_tasks = tasks;
+            }
+            StatsTab::PayPeriods => self.stats.pay_periods = self.calculate_pay_periods(today),
+            StatsTab::Details => {
+                let mut top_tasks: Vec<_> = current_tasks
+                    .iter()
+                    .map(|task| (task.id.clone(), task.get_current_duration()))
+                    .collect();
+                top_tasks.sort_by_key(|(_, duration)| std::cmp::Reverse(*duration));
+                top_tasks.truncate(5);
+                self.stats.top_tasks = top_tasks;
+            }
+        }
+    }
+
+    fn format_utilization(billable: i64, non_billable: i64) -> String {
+        let total = billable + non_billable;
+        if total == 0 {
+            return "-".to_string();
+        }
+        format!("{:.0}%", billable as f64 / total as f64 * 100.0)
+    }
+
+    fn calculate_average_task_duration(&self) -> i64 {
+        if self.tasks.is_empty() {
+            return 0;
+        }
+        let total: i64 = self.tasks.values().map(|t| t.get_current_duration()).sum();
+        total / self.tasks.len() as i64
+    }
+
+    fn format_duration(&self, seconds: i64) -> String {
+        self.settings.duration_format.format(seconds)
+    }
+
+    fn format_signed_duration(&self, seconds: i64) -> String {
+        let sign = if seconds < 0 { "-" } else { "+" };
+        format!("{}{}", sign, self.format_duration(seconds.abs()))
+    }
+
+    fn is_any_dialog_open(&self) -> bool {
+        self.show_new_folder_dialog || 
+        self.show_clear_folders_confirm || 
+        self.show_clear_confirm || 
+        self.show_clear_folder_confirm.is_some() || 
+        self.show_delete_task_confirm.is_some() || 
+        self.show_shortcuts || 
+        self.show_settings || 
+        self.show_add_task_dialog ||
+        self.show_statistics ||
+        self.show_expenses ||
+        self.show_planner ||
+        self.show_export_templates ||
+        self.show_reports ||
+        self.year_review.is_some() ||
+        self.conflicts.is_some() ||
+        self.show_harvest ||
+        self.show_redmine ||
+        self.show_task_details.is_some() ||
+        self.show_task_import ||
+        self.show_csv_import ||
+        self.show_backup ||
+        self.pending_annotation.is_some() ||
+        self.show_day_review.is_some() ||
+        self.weekly_summary.is_some() ||
+        self.show_idle_reminder ||
+        self.free_time_prompt.is_some() ||
+        self.show_plugins ||
+        self.show_cleanup ||
+        self.show_idle_return ||
+        self.show_search ||
+        self.renaming_task.is_some() || // Keeps Space and the arrow keys in the name field
+        self.renaming_folder.is_some() ||
+        !self.data_file_problems.is_empty()
+    }
+
+    // Matches across task names, folder names, tags and session notes, best first
+    // Tasks with the text in their name, a tag or a session note. Unlike the fuzzy search
+    // results it isn't capped, so "select all" really gets everything.
+    fn tasks_containing(&self, query: &str) -> Vec<String> {
+        let query = query.trim().to_lowercase();
+        if query.is_empty() {
+            return Vec::new();
+        }
+        let contains = |text: &str| text.to_lowercase().contains(&query);
+        self.tasks
+            .values()
+            .filter(|task| {
+                contains(&task.description)
+                    || task.tags.iter().any(|tag| contains(tag))
+                    || task.entries.iter().filter_map(|entry| entry.annotation.as_deref()).any(contains)
+            })
+            .map(|task| task.id.clone())
+            .collect()
+    }
+
+    // Returns the number of tasks that changed
+    fn bulk_edit(&mut self, task_ids: &[String], edit: &BulkEdit) -> usize {
+        let mut changed = 0;
+        for task_id in task_ids {
+            let Some(task) = self.tasks.get_mut(task_id) else {
+                continue;
+            };
+            match edit {
+                BulkEdit::AddTag(tag) if !task.tags.contains(tag) => task.tags.push(tag.clone()),
+                BulkEdit::RemoveTag(tag) if task.tags.contains(tag) => task.tags.retain(|existing| existing != tag),
+                BulkEdit::MoveTo(folder) if task.folder != *folder => task.folder = folder.clone(),
+                _ => continue,
+            }
+            changed += 1;
+        }
+        if changed > 0 {
+            self.save_tasks();
+        }
+        changed
+    }
+
+    fn search_everything(&self, query: &str) -> Vec<SearchHit> {
+        const PER_GROUP: usize = 20;
+        let mut hits = Vec::new();
+        if query.trim().is_empty() {
+            return hits;
+        }
+
+        for folder in &self.folders {
+            if let Some(score) = fuzzy_score(query, folder) {
+                hits.push(SearchHit {
+                    group: SearchGroup::Folders,
+                    task_id: None,
+                    folder: folder.clone(),
+                    title: self.shown_folder_name(folder),
+                    detail: String::new(),
+                    score,
+                });
+            }
+        }
+        for task in self.tasks.values() {
+            let folder = task.folder.clone().unwrap_or_else(|| "Uncategorized".to_string());
+            let title = self.shown_task_name(task);
+            let hit = |group, detail: String, score| SearchHit {
+                group,
+                task_id: Some(task.id.clone()),
+                folder: folder.clone(),
+                title: title.clone(),
+                detail,
+                score,
+            };
+            if let Some(score) = fuzzy_score(query, &task.description) {
+                let mut detail = self.shown_folder_name(&folder);
+                if task.archived {
+                    detail.push_str(" (archived)");
+                }
+                hits.push(hit(SearchGroup::Tasks, detail, score));
+            }
+            for tag in &task.tags {
+                if let Some(score) = fuzzy_score(query, tag) {
+                    let detail = if self.privacy_mode { String::new() } else { format!("#{}", tag) };
+                    hits.push(hit(SearchGroup::Tags, detail, score));
+                }
+            }
+            for entry in &task.entries {
+                let Some(annotation) = &entry.annotation else {
+                    continue;
+                };
+                if let Some(score) = fuzzy_score(query, annotation) {
+                    let date = entry.start.format("%b %d").to_string();
+                    let detail = if self.privacy_mode { date } else { format!("{}: {}", date, annotation) };
+                    hits.push(hit(SearchGroup::Notes, detail, score));
+                }
+            }
+        }
+
+        hits.sort_by(|a, b| b.score.cmp(&a.score).then_with(|| a.title.cmp(&b.title)));
+        let mut counts: HashMap<SearchGroup, usize> = HashMap::new();
+        hits.retain(|hit| {
+            let count = counts.entry(hit.group).or_default();
+            *count += 1;
+            *count <= PER_GROUP
+        });
+        hits
+    }
+
+    // Focuses a folder or task in the list and scrolls to it. Tasks the list doesn't
+    // show, like archived or filtered out ones, open in Task Details instead.
+    fn jump_to(&mut self, ctx: &egui::Context, folder: &str, task_id: Option<&str>) {
+        let folder_index = self.folders.iter().position(|name| name == folder);
+        let task_index = task_id.and_then(|id| {
+            let tasks = self.get_tasks_by_folder();
+            tasks.get(folder)?.iter().position(|task| task == id)
+        });
+        match (folder_index, task_id, task_index) {
+            (Some(folder_index), None, _) | (Some(folder_index), Some(_), Some(_)) => {
+                self.focused_folder_index = Some(folder_index);
+                self.focused_task_index = task_index;
+                ctx.memory_mut(|mem| mem.data.insert_temp(egui::Id::new(format!("folder_{}", folder)), true));
+                self.scroll_to_focused = true;
+            }
+            (_, Some(task_id), _) => self.show_task_details = Some(task_id.to_string()),
+            (None, None, _) => {}
+        }
+    }
+
+    // Every action for a task row, from its right-click menu. Copying happens right away,
+    // everything else is returned for handle_task_action.
+    fn task_context_menu(&mut self, ui: &mut egui::Ui, task_id: &str) -> Option<TaskAction> {
+        let task = self.tasks.get(task_id)?;
+        let status = task.status();
+        let billable = task.billable;
+        let color_label = task.color_label;
+        let current_folder = task.folder.clone();
+        let mut action = None;
+
+        let timer_action = match status {
+            TaskStatus::Running => Some((fill::PAUSE, "Pause", TaskAction::Pause)),
+            TaskStatus::Paused => Some((fill::PLAY, "Resume", TaskAction::Resume)),
+            TaskStatus::NotStarted => Some((fill::PLAY, "Start", TaskAction::Start)),
+            TaskStatus::Completed => None,
+        };
+        if let Some((icon, label, timer_action)) = timer_action {
+            if ui.button(format!("{} {}", icon, label)).clicked() {
+                action = Some(timer_action);
+            }
+        }
+        let complete_label = if status == TaskStatus::Completed { "Mark Incomplete" } else { "Mark Complete" };
+        if ui.button(format!("{} {}", fill::CHECK_SQUARE, complete_label)).clicked() {
+            action = Some(TaskAction::Complete);
+        }
+        ui.separator();
+
+        if ui.button(format!("{} Rename", fill::PENCIL_SIMPLE)).clicked() {
+            action = Some(TaskAction::Rename);
+        }
+        ui.menu_button(format!("{} Move to", fill::FOLDER), |ui| {
+            let targets = self.folders.iter().map(|folder| Some(folder.clone())).chain([None]);
+            for folder in targets.filter(|folder| *folder != current_folder) {
+                if ui.button(folder.as_deref().unwrap_or("Uncategorized")).clicked() {
+                    action = Some(TaskAction::MoveTo(folder));
+                    ui.close_menu();
+                }
+            }
+        });
+        if ui.button(format!("{} Duplicate", fill::COPY)).clicked() {
+            action = Some(TaskAction::Duplicate);
+        }
+        ui.menu_button(format!("{} Duplicate into", fill::COPY), |ui| {
+            for folder in self.folders.iter().map(|folder| Some(folder.clone())).chain([None]) {
+                if ui.button(folder.as_deref().unwrap_or("Uncategorized")).clicked() {
+                    action = Some(TaskAction::DuplicateInto(folder));
+                    ui.close_menu();
+                }
+            }
+        });
+        let billable_label = if billable { "Mark Non-billable" } else { "Mark Billable" };
+        if ui.button(format!("{} {}", fill::CURRENCY_DOLLAR, billable_label)).clicked() {
+            action = Some(TaskAction::ToggleBillable);
+        }
+        ui.menu_button(format!("{} Color Label", fill::PALETTE), |ui| {
+            for option in ColorLabel::ALL {
+                if ui.selectable_label(color_label == Some(option), option.text()).clicked() {
+                    action = Some(TaskAction::SetColorLabel(Some(option)));
+                }
+            }
+            if color_label.is_some() && ui.button("No Label").clicked() {
+                action = Some(TaskAction::SetColorLabel(None));
+            }
+        });
+        ui.separator();
+
+        if ui.button(format!("{} Export to CSV", fill::EXPORT)).clicked() {
+            action = Some(TaskAction::Export);
+        }
+        if ui.button(format!("{} Copy Summary", fill::CLIPBOARD_TEXT)).clicked() {
+            if let Some(summary) = self.task_summary_line(task_id) {
+                ui.ctx().copy_text(summary);
+                self.export_message = Some(("Copied task summary".to_string(), 3.0));
+            }
+            ui.close_menu();
+        }
+        if ui.button(format!("{} Details", fill::INFO)).clicked() {
+            action = Some(TaskAction::ShowDetails);
+        }
+        ui.separator();
+
+        if ui.button(format!("{} Delete…", fill::TRASH)).clicked() {
+            action = Some(TaskAction::Delete);
+        }
+
+        if action.is_some() {
+            ui.close_menu();
+        }
+        action
+    }
+
+    // Task name as shown on screen; a stable placeholder in privacy mode
+    // Tasks marked complete on the given day, in the order they were finished
+    fn done_on(&self, date: NaiveDate) -> Vec<&Task> {
+        let calendar = self.calendar();
+        let mut done: Vec<&Task> = self
+            .tasks
+            .values()
+            .filter(|task| task.completed_at.is_some_and(|at| calendar.day_of(at) == date))
+            .collect();
+        done.sort_by_key(|task| task.completed_at);
+        done
+    }
+
+    fn shown_task_name(&self, task: &Task) -> String {
+        if self.privacy_mode {
+            format!("Task {}", task.id.get(..4).unwrap_or_default().to_uppercase())
+        } else {
+            task.description.clone()
+        }
+    }
+
+    fn shown_folder_name(&self, folder_name: &str) -> String {
+        if !self.privacy_mode || folder_name == "Uncategorized" {
+            return folder_name.to_string();
+        }
+        match self.folders.iter().position(|folder| folder == folder_name) {
+            Some(index) => format!("Folder {}", index + 1),
+            None => "Folder".to_string(),
+        }
+    }
+
+    // Saves the theme, zoom and selected folder when they change
+    fn remember_appearance(&mut self) {
+        let appearance = &self.settings.appearance;
+        if appearance.dark_mode == self.dark_mode
+            && appearance.ui_scale == self.ui_scale
+            && self.settings.selected_folder == self.selected_folder
+        {
+            return;
+        }
+        self.settings.appearance = AppearanceConfig { dark_mode: self.dark_mode, ui_scale: self.ui_scale };
+        self.settings.selected_folder = self.selected_folder.clone();
+        self.save_settings();
+    }
+
+    // Saves where the dialogs shown this frame are, once the user has let go of them
+    fn remember_dialog_positions(&mut self, ctx: &egui::Context) {
+        let shown: Vec<String> = ctx
+            .data_mut(|data| data.remove_temp(egui::Id::new(SHOWN_DIALOGS)))
+            .unwrap_or_default();
+        if ctx.input(|i| i.pointer.any_down()) {
+            return;
+        }
+        let mut changed = false;
+        for key in shown {
+            let Some(rect) = ctx.memory(|mem| mem.area_rect(egui::Id::new(("dialog", key.as_str())))) else {
+                continue;
+            };
+            let position = [rect.min.x, rect.min.y];
+            if self.settings.window_positions.get(&key) != Some(&position) {
+                self.settings.window_positions.insert(key, position);
+                changed = true;
+            }
+        }
+        if changed {
+            self.save_settings();
+        }
+    }
+
+    // Circular progress of today's tracked time against the daily goal
+    fn daily_goal_ring(&self, ui: &mut egui::Ui) -> egui::Response {
+        let goal = (self.settings.daily_goal_hours * 3600.0) as i64;
+        let today = self.calendar().today();
+        let (from, to) = self.calendar().day_range(today);
+        let tracked: i64 = self.tasks.values().map(|task| task.tracked_between(from, to)).sum::<i64>() + self.adjusted_between(today, today);
+        let day_off = self.day_off(today).map(|day| day.kind);
+        let progress = if day_off.is_some() { 0.0 } else { (tracked as f32 / goal.max(1) as f32).clamp(0.0, 1.0) };
+
+        let (rect, response) = ui.allocate_exact_size(egui::vec2(22.0, 22.0), egui::Sense::click());
+        let center = rect.center();
+        let radius = rect.width() / 2.0 - 2.0;
+        let painter = ui.painter();
+        painter.circle_stroke(center, radius, egui::Stroke::new(3.0, ui.visuals().widgets.inactive.bg_fill));
+        if progress > 0.0 {
+            let color = if tracked >= goal {
+                egui::Color32::GREEN
+            } else {
+                ui.visuals().selection.bg_fill
+            };
+            // Clockwise from 12 o'clock
+            let steps = (progress * 48.0).ceil() as usize;
+            let points: Vec<_> = (0..=steps)
+                .map(|step| {
+                    let angle = -std::f32::consts::FRAC_PI_2 + std::f32::consts::TAU * progress * step as f32 / steps as f32;
+                    center + radius * egui::vec2(angle.cos(), angle.sin())
+                })
+                .collect();
+            painter.add(egui::Shape::line(points, egui::Stroke::new(3.0, color)));
+        }
+        if let Some(kind) = day_off {
+            return response.on_hover_text(format!("{}: no goal today, {} tracked", kind.label(), Self::format_hours_minutes(tracked)));
+        }
+        response.on_hover_text(format!(
+            "{} of {} today",
+            Self::format_hours_minutes(tracked),
+            Self::format_hours_minutes(goal)
+        ))
+    }
+
+    // Always visible line at the bottom: the running task, today's total, paused tasks and
+    // whether the last save worked
+    fn show_status_bar(&self, ctx: &egui::Context) {
+        let today = self.calendar().today();
+        let (from, to) = self.calendar().day_range(today);
+        let tracked: i64 = self.tasks.values().map(|task| task.tracked_between(from, to)).sum::<i64>() + self.adjusted_between(today, today);
+        let mut running: Vec<&Task> = self.tasks.values().filter(|task| task.start_time.is_some()).collect();
+        running.sort_by_key(|task| task.start_time);
+        let paused = self.tasks.values().filter(|task| !task.archived && task.start_time.is_none() && task.is_paused).count();
+
+        egui::TopBottomPanel::bottom("status_bar").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                match running.first() {
+                    Some(task) => {
+                        let elapsed = task.start_time.map_or(0, |start| (Local::now() - start).num_seconds());
+                        ui.label(egui::RichText::new(fill::PLAY).small().color(egui::Color32::GREEN));
+                        ui.label(egui::RichText::new(format!("{} {}", task.description, self.format_duration(elapsed))).small())
+                            .on_hover_text("Time since the timer was started");
+                        if running.len() > 1 {
+                            ui.label(egui::RichText::new(format!("+{} more", running.len() - 1)).small().weak());
+                        }
+                    }
+                    None => {
+                        ui.label(egui::RichText::new("No timer running").small().weak());
+                    }
+                }
+                ui.separator();
+                ui.label(egui::RichText::new(format!("Today {}", self.format_duration(tracked))).small());
+                ui.separator();
+                ui.label(egui::RichText::new(format!("{} paused", paused)).small());
+
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    match logging::last_save() {
+                        Some(save) => match save.error {
+                            None => ui.label(egui::RichText::new(format!("Saved {}", save.time.format("%H:%M:%S"))).small().weak())
+                                .on_hover_text(&save.path),
+                            Some(error) => ui.label(
+                                egui::RichText::new(format!("{} Save failed {}", fill::WARNING, save.time.format("%H:%M:%S")))
+                                    .small()
+                                    .color(egui::Color32::from_rgb(220, 80, 80)),
+                            )
+                            .on_hover_text(format!("{}: {}", save.path, error)),
+                        },
+                        None => ui.label(egui::RichText::new("Nothing saved yet").small().weak()),
+                    };
+                });
+            });
+        });
+    }
+
+    // Average and worst time spent building the last frames, above the status bar
+    fn show_frame_times(&mut self, ctx: &egui::Context, frame_start: std::time::Instant) {
+        const FRAMES: usize = 120;
+        let Some(frame_times) = self.frame_times.as_mut() else {
+            return;
+        };
+        if frame_times.len() == FRAMES {
+            frame_times.pop_front();
+        }
+        frame_times.push_back(frame_start.elapsed().as_secs_f32() * 1000.0);
+        let average = frame_times.iter().sum::<f32>() / frame_times.len() as f32;
+        let worst = frame_times.iter().copied().fold(0.0, f32::max);
+
+        egui::Area::new(egui::Id::new("frame_times"))
+            .anchor(egui::Align2::RIGHT_BOTTOM, egui::vec2(-8.0, -32.0))
+            .interactable(false)
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.label(egui::RichText::new(format!("frame {:.1} ms, worst {:.1} ms", average, worst)).monospace().small());
+                });
+            });
+    }
+
+    fn show_overlay(&mut self, ctx: &egui::Context) {
+        if !self.settings.overlay.enabled {
+            return;
+        }
+        const SIZE: egui::Vec2 = egui::vec2(130.0, 34.0);
+        const MARGIN: f32 = 16.0;
+
+        // Corner of the monitor the main window is on, assuming it sits at the origin
+        let monitor = ctx.input(|i| i.viewport().monitor_size).unwrap_or(egui::vec2(1280.0, 800.0));
+        let x = match self.settings.overlay.corner {
+            ScreenCorner::TopLeft | ScreenCorner::BottomLeft => MARGIN,
+            ScreenCorner::TopRight | ScreenCorner::BottomRight => monitor.x - SIZE.x - MARGIN,
+        };
+        let y = match self.settings.overlay.corner {
+            ScreenCorner::TopLeft | ScreenCorner::TopRight => MARGIN,
+            ScreenCorner::BottomLeft | ScreenCorner::BottomRight => monitor.y - SIZE.y - MARGIN * 3.0,
+        };
+
+        let running = self
+            .tasks
+            .values()
+            .filter(|task| task.start_time.is_some())
+            .max_by_key(|task| task.start_time);
+        let text = match running {
+            Some(task) => format!("⏱ {}", self.format_duration(task.get_current_duration())),
+            None => "⏱ --:--:--".to_string(),
+        };
+
+        let mut close = false;
+        ctx.show_viewport_immediate(
+            egui::ViewportId::from_hash_of("timer_overlay"),
+            egui::ViewportBuilder::default()
+                .with_title("Work Timer Overlay")
+                .with_inner_size(SIZE)
+                .with_position(egui::pos2(x, y))
+                .with_decorations(false)
+                .with_resizable(false)
+                .with_always_on_top()
+                .with_taskbar(false),
+            |ctx, _class| {
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    let response = ui
+                        .centered_and_justified(|ui| {
+                            ui.add(egui::Label::new(egui::RichText::new(&text).monospace().size(18.0)).sense(egui::Sense::click_and_drag()))
+                        })
+                        .inner;
+                    // Drag to move it somewhere else for this session
+                    if response.drag_started() {
+                        ctx.send_viewport_cmd(egui::ViewportCommand::StartDrag);
+                    }
+                    response.context_menu(|ui| {
+                        if ui.button("Hide overlay").clicked() {
+                            close = true;
+                            ui.close_menu();
+                        }
+                    });
+                });
+                if ctx.input(|i| i.viewport().close_requested()) {
+                    close = true;
+                }
+            },
+        );
+
+        if close {
+            self.settings.overlay.enabled = false;
+            self.save_settings();
+        }
+    }
+
+    fn parse_duration_input(&self, input: &str) -> Option<i64> {
+        // Try to parse HH:MM:SS format
+        let parts: Vec<&str> = input.split(':').collect();
+        if parts.len() != 3 {
+            return None;
+        }
+
+        let hours = parts[0].parse::<i64>().ok()?;
+        let minutes = parts[1].parse::<i64>().ok()?;
+        let seconds = parts[2].parse::<i64>().ok()?;
+
+        if minutes >= 60 || seconds >= 60 || hours < 0 || minutes < 0 || seconds < 0 {
+            return None;
+        }
+
+        Some(hours * 3600 + minutes * 60 + seconds)
+    }
+
+    fn update_task_duration(&mut self, task_id: &str, new_duration: i64) {
+        if let Some(task) = self.tasks.get_mut(task_id) {
+            // If task is running, we need to account for the current running time
+            if task.start_time.is_some() {
+                task.pause();
+            }
+            let recorded = task.recorded_duration();
+            if new_duration < recorded {
+                self.export_message = Some((
+                    format!(
+                        "The sessions add up to {}, shorten or delete them in the task details to go below that",
+                        Self::format_hours_minutes(recorded)
+                    ),
+                    5.0,
+                ));
+            }
+            let before = task.total_duration();
+            let total = task.set_total_duration(new_duration);
+            audit::log("duration edited", task, total - before);
+            self.save_tasks();
+        }
+    }
+}
+
+impl eframe::App for WorkTimer {
+    // Only the main window's geometry is kept by eframe, dialog positions are in the settings
+    fn persist_egui_memory(&self) -> bool {
+        false
+    }
+
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        let frame_start = std::time::Instant::now();
+        self.configure_theme(ctx);
+        // The task list shows while its sessions load. Whatever the user does, and the checks
+        // below, wait for them.
+        if !self.poll_sessions_loading() {
+            let acted = ctx.input(|input| {
+                input.events.iter().any(|event| {
+                    matches!(
+                        event,
+                        egui::Event::Key { pressed: true, .. } | egui::Event::PointerButton { pressed: true, .. } | egui::Event::Text(_) | egui::Event::Paste(_)
+                    )
+                })
+            });
+            if acted {
+                self.finish_loading();
+            } else {
+                ctx.request_repaint_after(std::time::Duration::from_millis(50));
+            }
+        }
+        if self.sessions_loading.is_none() {
+            self.data_file_problems.extend(take_data_file_problems());
+            self.poll_integration_job();
+            self.poll_time_off_import();
+            self.check_notion_schedule();
+            self.check_daily_note_schedule();
+            self.check_day_rollover();
+            self.check_auto_complete();
+            self.check_launch_task();
+            self.check_idle_pause(ctx);
+            self.check_idle_reminder(ctx);
+            self.check_calendar_gaps(ctx);
+            self.check_pomodoro(ctx);
+            self.check_notifications();
+            self.check_do_not_disturb();
+            self.check_screenshots();
+            self.check_clipboard(ctx);
+            self.check_day_review_schedule();
+            self.check_weekly_summary_schedule();
+            self.check_report_email_schedule();
+            #[cfg(feature = "tray")]
+            self.check_tray(ctx);
+            self.check_hotkeys(ctx);
+            self.check_api(ctx);
+            self.check_day_review_on_quit(ctx);
+        }
+
+        // Handle global shortcuts that should work even when dialogs are open
+        if Shortcut::ToggleDarkMode.pressed(ctx) {
+            self.dark_mode = !self.dark_mode;
+        }
+        if Shortcut::TogglePrivacy.pressed(ctx) {
+            self.privacy_mode = !self.privacy_mode;
+        }
+        if Shortcut::Search.pressed(ctx) {
+            self.show_search = true;
+            self.search_query.clear();
+        }
+
+        // Handle dialog closing with Escape or Cmd+W
+        if Shortcut::CloseDialog.pressed(ctx) {
+            if self.show_new_folder_dialog {
+                self.show_new_folder_dialog = false;
+                self.new_folder_input.clear();
+            } else if self.show_clear_folders_confirm {
+                self.show_clear_folders_confirm = false;
+            } else if self.show_clear_confirm {
+                self.show_clear_confirm = false;
+            } else if self.show_clear_folder_confirm.is_some() {
+                self.show_clear_folder_confirm = None;
+                self.clear_folder_target = None;
+            } else if self.show_delete_task_confirm.is_some() {
+                self.show_delete_task_confirm = None;
+            } else if self.show_shortcuts {
+                self.show_shortcuts = false;
+            } else if self.show_settings {
+                self.temporary_ui_scale = self.ui_scale; // Reset temporary scale
+                self.show_settings = false;
+            } else if self.show_add_task_dialog {
+                self.show_add_task_dialog = false;
+                self.add_task_to_folder = None;
+                self.new_task_in_folder.clear();
+            } else if self.show_statistics {
+                self.show_statistics = false;
+            } else if self.show_expenses {
+                self.show_expenses = false;
+            } else if self.show_planner {
+                self.show_planner = false;
+            } else if self.show_export_templates {
+                self.show_export_templates = false;
+            } else if self.show_reports {
+                self.show_reports = false;
+            } else if self.year_review.is_some() {
+                self.year_review = None;
+            } else if self.conflicts.is_some() {
+                self.conflicts = None;
+            } else if self.show_harvest {
+                self.show_harvest = false;
+            } else if self.show_redmine {
+                self.show_redmine = false;
+            } else if self.show_task_details.is_some() {
+                self.show_task_details = None;
+            } else if self.show_task_import {
+                self.show_task_import = false;
+            } else if self.show_csv_import {
+                self.show_csv_import = false;
+            } else if self.show_backup {
+                self.show_backup = false;
+            } else if self.pending_annotation.is_some() {
+                self.pending_annotation = None;
+            } else if self.show_day_review.is_some() {
+                self.show_day_review = None;
+            } else if self.weekly_summary.is_some() {
+                self.weekly_summary = None;
+            } else if self.show_idle_reminder {
+                self.show_idle_reminder = false;
+                self.last_timer_running = Some(Local::now());
+            } else if self.free_time_prompt.is_some() {
+                self.free_time_prompt = None;
+            } else if self.show_plugins {
+                self.show_plugins = false;
+            } else if self.show_cleanup {
+                self.show_cleanup = false;
+                self.cleanup_actions.clear();
+            } else if self.show_idle_return {
+                self.show_idle_return = false;
+                self.idle_paused.clear();
+            } else if self.show_search {
+                self.show_search = false;
+            } else if !self.data_file_problems.is_empty() {
+                self.data_file_problems.clear();
+            }
+        }
+
+        // Handle keyboard shortcuts and navigation
+        if !self.is_any_dialog_open() {
+            // Handle space bar for play/pause
+            if Shortcut::PlayPause.pressed(ctx) {
+                let folders = self.get_folders();
+                if let Some(current_folder_idx) = self.focused_folder_index {
+                    let folder_name = &folders[current_folder_idx];
+                    let folder_id = egui::Id::new(format!("folder_{}", folder_name));
+                    let is_open = ctx.memory(|mem| mem.data.get_temp::<bool>(folder_id).unwrap_or(true));
+                    
                     // Only handle space if we have a focused task in an open folder
                     if is_open && self.focused_task_index.is_some() {
                         let tasks = self.get_tasks_by_folder();
@@ -777,633 +7354,5230 @@ impl eframe::App for WorkTimer {
                                     } else if task.is_paused {
                                         TaskAction::Resume
                                     } else {
-                                        TaskAction::Start
-                                    };
-                                    self.handle_task_action(task_ids[task_idx].as_str(), action);
+                                        TaskAction::Start
+                                    };
+                                    self.handle_task_action(task_ids[task_idx].as_str(), action);
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+
+            // Handle Cmd+Delete for focused item
+            if Shortcut::DeleteFocused.pressed(ctx) {
+                let folders = self.get_folders();
+                if let Some(current_folder_idx) = self.focused_folder_index {
+                    let folder_name = &folders[current_folder_idx];
+                    let folder_id = egui::Id::new(format!("folder_{}", folder_name));
+                    let is_open = ctx.memory(|mem| mem.data.get_temp::<bool>(folder_id).unwrap_or(true));
+                    
+                    // If we have a focused task in an open folder, delete the task
+                    if is_open && self.focused_task_index.is_some() {
+                        let tasks = self.get_tasks_by_folder();
+                        if let Some(task_ids) = tasks.get(folder_name.as_str()) {
+                            if let Some(task_idx) = self.focused_task_index {
+                                self.show_delete_task_confirm = Some(task_ids[task_idx].clone());
+                            }
+                        }
+                    } else {
+                        // If we're on a folder header, delete the folder
+                        self.open_clear_folder_confirm(ctx, folder_name.clone());
+                    }
+                }
+            }
+
+            if Shortcut::MoveUp.pressed(ctx) {
+                let folders = self.get_folders();
+                if let Some(current_folder_idx) = self.focused_folder_index {
+                    let folder_name = &folders[current_folder_idx];
+                    let folder_id = egui::Id::new(format!("folder_{}", folder_name));
+                    let is_open = ctx.memory(|mem| mem.data.get_temp::<bool>(folder_id).unwrap_or(true));
+                    
+                    if is_open && self.focused_task_index.is_some() {
+                        // If we're focused on a task, move up through tasks
+                        if let Some(current_task_idx) = self.focused_task_index {
+                            if current_task_idx > 0 {
+                                self.focused_task_index = Some(current_task_idx - 1);
+                            } else {
+                                // If at first task, move to folder header
+                                self.focused_task_index = None;
+                            }
+                        }
+                    } else {
+                        // If we're on a folder header, move to previous folder
+                        if current_folder_idx > 0 {
+                            self.focused_folder_index = Some(current_folder_idx - 1);
+                            self.focused_task_index = None;
+                        }
+                    }
+                }
+            }
+
+            if Shortcut::MoveDown.pressed(ctx) {
+                let folders = self.get_folders();
+                if let Some(current_folder_idx) = self.focused_folder_index {
+                    let folder_name = &folders[current_folder_idx];
+                    let folder_id = egui::Id::new(format!("folder_{}", folder_name));
+                    let is_open = ctx.memory(|mem| mem.data.get_temp::<bool>(folder_id).unwrap_or(true));
+                    let tasks = self.get_tasks_by_folder();
+                    let task_ids = tasks.get(folder_name.as_str()).cloned().unwrap_or_default();
+                    
+                    if is_open && !task_ids.is_empty() {
+                        // If folder is open and has tasks
+                        if self.focused_task_index.is_none() {
+                            // If on folder header, move to first task
+                            self.focused_task_index = Some(0);
+                        } else if let Some(current_task_idx) = self.focused_task_index {
+                            // If on a task, try to move to next task
+                            if current_task_idx < task_ids.len() - 1 {
+                                self.focused_task_index = Some(current_task_idx + 1);
+                            } else {
+                                // If at last task, move to next folder
+                                if current_folder_idx < folders.len() - 1 {
+                                    self.focused_folder_index = Some(current_folder_idx + 1);
+                                    self.focused_task_index = None;
+                                }
+                            }
+                        }
+                    } else {
+                        // If folder is closed or empty, move to next folder
+                        if current_folder_idx < folders.len() - 1 {
+                            self.focused_folder_index = Some(current_folder_idx + 1);
+                            self.focused_task_index = None;
+                        }
+                    }
+                }
+            }
+        }
+
+        // Handle keyboard shortcuts only when no dialog is open
+        if !self.is_any_dialog_open() {
+            if Shortcut::NewFolder.pressed(ctx) {
+                self.show_new_folder_dialog = true;
+                self.focus_new_folder = true;
+            }
+            if Shortcut::ExportAll.pressed(ctx) {
+                if let Err(e) = self.export_to_csv() {
+                    self.export_message = Some((format!("Error exporting CSV: {}", e), 3.0));
+                }
+            }
+            if Shortcut::NewTask.pressed(ctx) {
+                if let Some(focused_idx) = self.focused_folder_index {
+                    // If a folder is focused, open the add task dialog for that folder
+                    if let Some(folder_name) = self.folders.get(focused_idx) {
+                        self.show_add_task_dialog = true;
+                        self.add_task_to_folder = Some(folder_name.clone());
+                        self.new_task_in_folder.clear();
+                    }
+                } else {
+                    // If no folder is focused, focus the quick add task input
+                    self.focus_new_task = true;
+                }
+            }
+            if let Some(index) = Shortcut::FocusFolder.pressed_index(ctx) {
+                if index < self.folders.len() {
+                    self.focused_folder_index = Some(index);
+                    self.focused_task_index = None;
+                    self.scroll_to_focused = true;
+                }
+            }
+            if let Some(index) = Shortcut::AddTaskToFolder.pressed_index(ctx) {
+                if let Some(folder_name) = self.folders.get(index) {
+                    self.focused_folder_index = Some(index);
+                    self.focused_task_index = None;
+                    self.show_add_task_dialog = true;
+                    self.add_task_to_folder = Some(folder_name.clone());
+                    self.new_task_in_folder.clear();
+                }
+            }
+            if Shortcut::Statistics.pressed(ctx) {
+                self.show_statistics = true;
+            }
+            if Shortcut::Settings.pressed(ctx) {
+                self.show_settings = true;
+            }
+        }
+
+        self.show_status_bar(ctx);
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.heading("Work Timer");
+
+            // Top bar with theme toggle, export and clear buttons
+            ui.horizontal(|ui| {
+                if ui.button(if self.dark_mode { "☀" } else { "🌙" }).clicked() {
+                    self.dark_mode = !self.dark_mode;
+                }
+
+                if ui.button("⚙").clicked() {
+                    self.show_settings = true;
+                }
+
+                if ui.button("⌨").clicked() {
+                    self.show_shortcuts = true;
+                }
+
+                if ui.button("📊").clicked() {
+                    self.show_statistics = true;
+                }
+
+                if ui.button("🔍").on_hover_text("Search everything (⇧⌘F)").clicked() {
+                    self.show_search = true;
+                    self.search_query.clear();
+                }
+
+                if ui.button("💰").on_hover_text("Expenses").clicked() {
+                    self.show_expenses = true;
+                }
+
+                if ui.button("🗓").on_hover_text("Planner").clicked() {
+                    self.show_planner = true;
+                }
+
+                if ui.button("🧹").on_hover_text("Clean up stale tasks").clicked() {
+                    self.show_cleanup = true;
+                }
+
+                if ui
+                    .selectable_label(self.privacy_mode, "🕶")
+                    .on_hover_text("Privacy mode: hide task and folder names (⇧⌘P)")
+                    .clicked()
+                {
+                    self.privacy_mode = !self.privacy_mode;
+                }
+
+                if ui
+                    .selectable_label(self.settings.overlay.enabled, "⏱")
+                    .on_hover_text("Screen-sharing overlay: shows only the running time")
+                    .clicked()
+                {
+                    self.settings.overlay.enabled = !self.settings.overlay.enabled;
+                    self.save_settings();
+                }
+
+                let mut task_sort = self.settings.task_sort;
+                egui::ComboBox::from_id_salt("task_sort")
+                    .selected_text(format!("Sort: {}", task_sort.label()))
+                    .show_ui(ui, |ui| {
+                        for sort in TaskSort::ALL {
+                            ui.selectable_value(&mut task_sort, sort, sort.label());
+                        }
+                    });
+                if task_sort != self.settings.task_sort {
+                    self.settings.task_sort = task_sort;
+                    self.focused_task_index = None;
+                    self.save_settings();
+                }
+
+                let mut color_filter = self.color_filter;
+                egui::ComboBox::from_id_salt("color_filter")
+                    .selected_text(color_filter.map_or(egui::RichText::new("Label: All"), |option| option.text()))
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut color_filter, None, "All");
+                        for option in ColorLabel::ALL {
+                            ui.selectable_value(&mut color_filter, Some(option), option.text());
+                        }
+                    });
+                if color_filter != self.color_filter {
+                    self.color_filter = color_filter;
+                    self.focused_task_index = None;
+                }
+
+                ui.separator();
+
+                if !self.tasks.is_empty() {
+                    if ui.button("📊 Export All Tasks").clicked() {
+                        match self.export_to_csv() {
+                            Ok(filename) => {
+                                self.show_exported_file(format!("Tasks exported to {}", filename), &filename);
+                            }
+                            Err(e) => {
+                                tracing::error!("Failed to export CSV: {}", e);
+                                self.export_message =
+                                    Some((format!("Error exporting CSV: {}", e), 3.0));
+                            }
+                        }
+                    }
+
+                    ui.menu_button("⋯ More Exports", |ui| {
+                        if ui.button("Activity log (CSV)").clicked() {
+                            match audit::export_csv() {
+                                Ok(filename) => self.show_exported_file(format!("Activity log exported to {}", filename), &filename),
+                                Err(e) => self.export_message = Some((format!("Error exporting activity log: {}", e), 3.0)),
+                            }
+                            ui.close_menu();
+                        }
+                        if ui.button("Reports…").clicked() {
+                            self.show_reports = true;
+                            ui.close_menu();
+                        }
+                        if ui.button("Year in review…").clicked() {
+                            self.year_review = Some(self.year_review(self.calendar().today().year()));
+                            ui.close_menu();
+                        }
+                        if ui.button("Blank timesheet (HTML)").on_hover_text("This week, to print and fill in by hand").clicked() {
+                            match self.export_blank_timesheet(None) {
+                                Ok(filename) => self.show_exported_file(format!("Timesheet saved to {}", filename), &filename),
+                                Err(e) => self.export_message = Some((format!("Error saving timesheet: {}", e), 3.0)),
+                            }
+                            ui.close_menu();
+                        }
+                        ui.separator();
+                        if ui.button("Time entries (JSON Lines)").clicked() {
+                            match self.export_entries_ndjson(false) {
+                                Ok((filename, count)) => self.show_exported_file(format!("{} entries exported to {}", count, filename), &filename),
+                                Err(e) => self.export_message = Some((format!("Error exporting entries: {}", e), 3.0)),
+                            }
+                            ui.close_menu();
+                        }
+                        if ui.button("Append new entries (JSON Lines)")
+                            .on_hover_text("Adds only sessions that ended after the last one in the file")
+                            .clicked()
+                        {
+                            match self.export_entries_ndjson(true) {
+                                Ok((filename, count)) => {
+                                    self.show_exported_file(format!("{} new entries appended to {}", count, filename), &filename)
+                                }
+                                Err(e) => self.export_message = Some((format!("Error exporting entries: {}", e), 3.0)),
+                            }
+                            ui.close_menu();
+                        }
+                        ui.separator();
+                        if ui.button("Import from CSV export…").clicked() {
+                            self.show_csv_import = true;
+                            ui.close_menu();
+                        }
+                        if ui.button("Backup & Restore (zip)…").clicked() {
+                            self.show_backup = true;
+                            if self.backup_path.is_empty() {
+                                self.backup_path = format!("work_timer_backup_{}.zip", Local::now().format("%Y-%m-%d"));
+                            }
+                            ui.close_menu();
+                        }
+                    });
+
+                    if ui.button("🗑 Clear All Tasks").clicked() {
+                        self.show_clear_confirm = true;
+                        self.clear_exports = None;
+                    }
+                }
+
+                let conflicts = self.conflict_count();
+                if conflicts > 0
+                    && ui
+                        .button(egui::RichText::new(format!("{} {} overlapping", fill::WARNING, conflicts)).color(egui::Color32::from_rgb(230, 150, 40)))
+                        .on_hover_text("Sessions covering the same time count it twice in daily totals and exports")
+                        .clicked()
+                {
+                    self.conflicts = Some(self.find_conflicts());
+                }
+
+                let recording = self.last_screenshot.is_some();
+                if self.settings.daily_goal_hours > 0.0 || recording {
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if self.settings.daily_goal_hours > 0.0 && self.daily_goal_ring(ui).clicked() {
+                            self.show_day_review = Some(self.calendar().today());
+                        }
+                        if recording {
+                            ui.label(egui::RichText::new(format!("{} REC", fill::RECORD)).strong().color(egui::Color32::from_rgb(220, 50, 50)))
+                                .on_hover_text(format!(
+                                    "Taking a screenshot every {} minutes while a task tagged {} runs",
+                                    self.settings.screenshots.interval_minutes.max(1),
+                                    screenshots::PROOF_OF_WORK_TAG
+                                ));
+                        }
+                    });
+                }
+            });
+
+            // Pinned quick-start buttons
+            let pinned: Vec<(String, String, bool, i64)> = self
+                .settings
+                .pinned_tasks
+                .iter()
+                .filter_map(|id| self.tasks.get(id))
+                .filter(|task| !task.archived)
+                .map(|task| (task.id.clone(), self.shown_task_name(task), task.start_time.is_some(), task.get_current_duration()))
+                .collect();
+            if !pinned.is_empty() {
+                let mut clicked = None;
+                ui.horizontal_wrapped(|ui| {
+                    for (task_id, name, running, seconds) in &pinned {
+                        let icon = if *running { fill::PAUSE } else { fill::PLAY };
+                        let hover = if *running { "Pause" } else { "Switch to this task, pausing anything else that is running" };
+                        if ui
+                            .selectable_label(*running, format!("{} {}  {}", icon, name, self.format_duration(*seconds)))
+                            .on_hover_text(hover)
+                            .clicked()
+                        {
+                            clicked = Some((task_id.clone(), *running));
+                        }
+                    }
+                });
+                match clicked {
+                    Some((task_id, true)) => {
+                        self.handle_task_action(&task_id, TaskAction::Pause);
+                        self.save_tasks();
+                    }
+                    Some((task_id, false)) => self.switch_to_task(&task_id),
+                    None => {}
+                }
+            }
+
+            // Show export message if exists
+            if let Some((msg, time_left)) = &mut self.export_message {
+                let color = if msg.starts_with("Error") {
+                    egui::Color32::RED
+                } else {
+                    egui::Color32::GREEN
+                };
+                let file = self.exported_file.as_ref().filter(|(message, _)| message == msg).map(|(_, path)| path.clone());
+                let toast = ui.horizontal_wrapped(|ui| {
+                    ui.label(egui::RichText::new(msg.clone()).color(color));
+                    if let Some(path) = &file {
+                        if ui.small_button(format!("{} Open", fill::ARROW_SQUARE_OUT)).clicked() {
+                            if let Err(e) = desktop::open(path) {
+                                tracing::warn!("Failed to open {}: {}", path, e);
+                            }
+                        }
+                        if ui.small_button(format!("{} Show in folder", fill::FOLDER_OPEN)).clicked() {
+                            if let Err(e) = desktop::reveal(path) {
+                                tracing::warn!("Failed to show {} in its folder: {}", path, e);
+                            }
+                        }
+                    }
+                });
+                // Keep the buttons around while the pointer is on them
+                if !toast.response.contains_pointer() {
+                    *time_left -= ui.input(|i| i.unstable_dt);
+                }
+                if *time_left <= 0.0 {
+                    self.export_message = None;
+                    self.exported_file = None;
+                }
+                ctx.request_repaint();
+            }
+
+            // Offer to move the session of the task started on launch to the right task
+            if let Some(auto_task_id) = self.auto_started_task.clone() {
+                let still_running = self.tasks.get(&auto_task_id).is_some_and(|task| task.start_time.is_some());
+                if !still_running {
+                    self.auto_started_task = None;
+                } else {
+                    let mut reassign_to = None;
+                    let mut dismiss = false;
+                    ui.horizontal(|ui| {
+                        let task_name = self.shown_task_name(&self.tasks[&auto_task_id]);
+                        ui.label(egui::RichText::new(format!("Started '{}' automatically", task_name))
+                            .color(egui::Color32::from_rgb(0, 180, 180)));
+                        let mut others: Vec<_> = self
+                            .tasks
+                            .values()
+                            .filter(|task| task.id != auto_task_id)
+                            .collect();
+                        others.sort_by(|a, b| a.description.cmp(&b.description));
+                        egui::ComboBox::from_id_salt("auto_started_reassign")
+                            .selected_text("Switch to…")
+                            .show_ui(ui, |ui| {
+                                for task in others {
+                                    if ui.selectable_label(false, self.shown_task_name(task)).clicked() {
+                                        reassign_to = Some(task.id.clone());
+                                    }
+                                }
+                            });
+                        if ui.small_button("✕").clicked() {
+                            dismiss = true;
+                        }
+                    });
+                    if let Some(task_id) = reassign_to {
+                        self.reassign_running_session(&auto_task_id, &task_id);
+                        dismiss = true;
+                    }
+                    if dismiss {
+                        self.auto_started_task = None;
+                    }
+                }
+            }
+
+            if let Some(ticket) = self.clipboard_ticket.clone() {
+                let exists = self
+                    .tasks
+                    .values()
+                    .any(|task| !task.archived && (task.issue_key.as_deref() == Some(&ticket.key) || task.description == ticket.key));
+                let mut start = false;
+                let mut dismiss = false;
+                ui.horizontal_wrapped(|ui| {
+                    ui.label(egui::RichText::new(format!("{} Copied {}", fill::CLIPBOARD_TEXT, ticket.key))
+                        .color(egui::Color32::from_rgb(0, 180, 180)));
+                    let label = if exists { "Start task" } else { "Create & start task" };
+                    if ui.small_button(format!("{} {}", label, ticket.key)).clicked() {
+                        start = true;
+                    }
+                    if ui.small_button("✕").clicked() {
+                        dismiss = true;
+                    }
+                });
+                if start {
+                    self.start_clipboard_ticket(ticket);
+                }
+                if start || dismiss {
+                    self.clipboard_ticket = None;
+                }
+            }
+
+            // Countdown while on a Pomodoro break
+            if let Some(current) = self.pomodoro.clone().filter(|current| current.phase.is_break()) {
+                let remaining = current.remaining(Local::now());
+                let mut back_to_work = false;
+                let mut stop = false;
+                ui.horizontal_wrapped(|ui| {
+                    let text = if remaining > 0 {
+                        format!("☕ {} · {} left", current.phase.label(), pomodoro::format_remaining(remaining))
+                    } else {
+                        format!("☕ {} is over", current.phase.label())
+                    };
+                    ui.label(egui::RichText::new(text).color(egui::Color32::from_rgb(0, 180, 180)));
+                    if let Some(task) = self.tasks.get(&current.task_id).filter(|task| task.is_paused) {
+                        if ui.small_button(format!("{} Back to {}", fill::PLAY, self.shown_task_name(task))).clicked() {
+                            back_to_work = true;
+                        }
+                    }
+                    if ui.small_button("✕").on_hover_text("End the break and start the next pomodoro cycle from the beginning").clicked() {
+                        stop = true;
+                    }
+                });
+                if back_to_work {
+                    self.handle_task_action(&current.task_id, TaskAction::Resume);
+                    self.save_tasks();
+                }
+                if stop {
+                    self.pomodoro = None;
+                    self.log_pomodoro(current.finish(Local::now()));
+                }
+            }
+
+            // Confirmation dialog for clearing all tasks
+            if self.show_clear_confirm {
+                dialog_window(ctx, &self.settings.window_positions, "confirm_clear_all", "Confirm Clear All")
+                    .collapsible(false)
+                    .resizable(false)
+                    .show(ctx, |ui| {
+                        ui.label(
+                            "Are you sure you want to clear all tasks? This cannot be undone.",
+                        );
+                        ui.add_space(4.0);
+
+                        // Exports are kept unless asked for, and then only the ones the app wrote
+                        let mut delete_exports = self.clear_exports.is_some();
+                        if ui.checkbox(&mut delete_exports, "Also delete exported CSV files").changed() {
+                            self.clear_exports = delete_exports.then(exported_files);
+                        }
+                        if let Some(files) = &self.clear_exports {
+                            if files.is_empty() {
+                                ui.weak("No exported files left to delete");
+                            } else {
+                                ui.label(format!("{} files will be deleted:", files.len()));
+                                egui::ScrollArea::vertical().max_height(120.0).show(ui, |ui| {
+                                    for file in files {
+                                        ui.label(egui::RichText::new(file).monospace());
+                                    }
+                                });
+                            }
+                        }
+                        ui.add_space(4.0);
+
+                        ui.horizontal(|ui| {
+                            ui.spacing_mut().item_spacing.x = 10.0;
+                            let yes_button = ui.add(egui::Button::new("Yes"));
+                            let no_button = ui.add(egui::Button::new("No"));
+                            
+                            let dialog_id = ui.id().with("clear_all_dialog");
+                            let focus_id = dialog_id.with("focus");
+                            
+                            // Initialize focus to "yes" if not set
+                            if !ui.memory(|mem| mem.data.get_temp::<bool>(focus_id).is_some()) {
+                                ui.memory_mut(|mem| mem.data.insert_temp(focus_id, true));  // true = yes focused
+                            }
+
+                            let mut yes_focused = ui.memory(|mem| mem.data.get_temp::<bool>(focus_id).unwrap_or(true));
+
+                            // Handle tab navigation
+                            if ui.input(|i| i.key_pressed(egui::Key::Tab)) {
+                                yes_focused = !yes_focused;
+                                ui.memory_mut(|mem| mem.data.insert_temp(focus_id, yes_focused));
+                            }
+
+                            // Apply focus based on memory state
+                            if yes_focused {
+                                yes_button.request_focus();
+                            } else {
+                                no_button.request_focus();
+                            }
+
+                            if yes_button.clicked() || (yes_button.has_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter))) {
+                                self.clear_all_tasks(self.clear_exports.is_some());
+                                self.show_clear_confirm = false;
+                                self.export_message = Some(("All tasks cleared".to_string(), 3.0));
+                            }
+                            if no_button.clicked() || (no_button.has_focus() && (ui.input(|i| i.key_pressed(egui::Key::Enter)) || ui.input(|i| i.key_pressed(egui::Key::Escape)))) {
+                                self.show_clear_confirm = false;
+                            }
+                        });
+                    });
+            }
+
+            // Confirmation dialog for removing a folder, with or without its tasks
+            if let Some(folder_name) = &self.show_clear_folder_confirm.clone() {
+                let folder_name = folder_name.clone();
+                let task_count = self
+                    .tasks
+                    .values()
+                    .filter(|task| task.folder.as_deref() == Some(folder_name.as_str()))
+                    .count();
+                let mut close = false;
+
+                dialog_window(ctx, &self.settings.window_positions, "remove_folder", format!("Remove Folder '{}'", folder_name))
+                    .collapsible(false)
+                    .resizable(false)
+                    .show(ctx, |ui| {
+                        let focus_id = egui::Id::new(CLEAR_FOLDER_FOCUS);
+                        let mut focus_state = ui.memory(|mem| mem.data.get_temp::<u8>(focus_id).unwrap_or(2));
+                        if ui.input(|i| i.key_pressed(egui::Key::Tab)) {
+                            focus_state = (focus_state + 1) % 3;
+                        }
+                        ui.memory_mut(|mem| mem.data.insert_temp(focus_id, focus_state));
+                        let enter_pressed = ui.input(|i| i.key_pressed(egui::Key::Enter));
+
+                        ui.strong("Remove folder only");
+                        ui.horizontal(|ui| {
+                            ui.label(format!("Keep its {} tasks and their tracked time, moving them to", task_count));
+                            egui::ComboBox::from_id_salt("clear_folder_target")
+                                .selected_text(self.clear_folder_target.as_deref().unwrap_or("Uncategorized"))
+                                .show_ui(ui, |ui| {
+                                    ui.selectable_value(&mut self.clear_folder_target, None, "Uncategorized");
+                                    for folder in self.folders.iter().filter(|folder| **folder != folder_name) {
+                                        ui.selectable_value(&mut self.clear_folder_target, Some(folder.clone()), folder);
+                                    }
+                                });
+                        });
+                        let keep_button = ui.button("Remove Folder Only");
+
+                        ui.add_space(8.0);
+                        ui.separator();
+                        ui.strong(egui::RichText::new("Delete folder and tasks").color(egui::Color32::RED));
+                        ui.label(format!(
+                            "Permanently deletes the folder, its {} tasks with all their tracked time, and its expenses. This cannot be undone.",
+                            task_count
+                        ));
+                        let delete_button = ui.button(
+                            egui::RichText::new(format!("Delete Folder and {} Tasks", task_count)).color(egui::Color32::RED),
+                        );
+
+                        ui.add_space(8.0);
+                        let cancel_button = ui.button("Cancel");
+
+                        match focus_state {
+                            0 => keep_button.request_focus(),
+                            1 => delete_button.request_focus(),
+                            _ => cancel_button.request_focus(),
+                        }
+
+                        if keep_button.clicked() || (enter_pressed && focus_state == 0) {
+                            let target = self.clear_folder_target.clone();
+                            self.remove_folder_keep_tasks(&folder_name, target.clone());
+                            self.export_message = Some((
+                                format!(
+                                    "Folder '{}' removed, tasks moved to {}",
+                                    folder_name,
+                                    target.as_deref().unwrap_or("Uncategorized")
+                                ),
+                                3.0,
+                            ));
+                            close = true;
+                        } else if delete_button.clicked() || (enter_pressed && focus_state == 1) {
+                            self.clear_folder(&folder_name);
+                            self.export_message = Some((format!("Folder '{}' deleted", folder_name), 3.0));
+                            close = true;
+                        } else if cancel_button.clicked() || (enter_pressed && focus_state == 2) {
+                            close = true;
+                        }
+
+                        if close {
+                            // Clear the focus state from memory when closing
+                            ui.memory_mut(|mem| mem.data.remove::<u8>(focus_id));
+                        }
+                    });
+
+                if close {
+                    self.show_clear_folder_confirm = None;
+                    self.clear_folder_target = None;
+                }
+            }
+
+            // Confirmation dialog for deleting a task
+            if let Some(task_id) = &self.show_delete_task_confirm.clone() {
+                let task_id = task_id.clone();
+                let task_info = self.tasks.get(&task_id).map(|task| (task.description.clone()));
+                if let Some(task_description) = task_info {
+                    dialog_window(ctx, &self.settings.window_positions, "delete_task", "Delete Task")
+                        .collapsible(false)
+                        .resizable(false)
+                        .show(ctx, |ui| {
+                            ui.label(format!(
+                                "Are you sure you want to delete task '{}'? This cannot be undone.",
+                                task_description
+                            ));
+                            ui.horizontal(|ui| {
+                                ui.spacing_mut().item_spacing.x = 10.0;
+                                let yes_button = ui.add(egui::Button::new("Yes"));
+                                let no_button = ui.add(egui::Button::new("No"));
+                                
+                                let dialog_id = ui.id().with("delete_task_dialog");
+                                let focus_id = dialog_id.with("focus");
+                                
+                                // Initialize focus to "yes" if not set
+                                if !ui.memory(|mem| mem.data.get_temp::<bool>(focus_id).is_some()) {
+                                    ui.memory_mut(|mem| mem.data.insert_temp(focus_id, true));  // true = yes focused
+                                }
+
+                                let mut yes_focused = ui.memory(|mem| mem.data.get_temp::<bool>(focus_id).unwrap_or(true));
+
+                                // Handle tab navigation
+                                if ui.input(|i| i.key_pressed(egui::Key::Tab)) {
+                                    yes_focused = !yes_focused;
+                                    ui.memory_mut(|mem| mem.data.insert_temp(focus_id, yes_focused));
+                                }
+
+                                // Apply focus based on memory state
+                                if yes_focused {
+                                    yes_button.request_focus();
+                                } else {
+                                    no_button.request_focus();
+                                }
+
+                                if yes_button.clicked() || (yes_button.has_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter))) {
+                                    if let Some(task) = self.tasks.remove(&task_id) {
+                                        audit::log("deleted", &task, -task.get_current_duration());
+                                        task.attachments.iter().for_each(|path| attachments::remove(path));
+                                        screenshots::remove_for(&task.id);
+                                    }
+                                    self.save_tasks();
+                                    self.show_delete_task_confirm = None;
+                                    self.export_message = Some((format!("Task '{}' deleted", task_description), 3.0));
+                                }
+                                if no_button.clicked() || (no_button.has_focus() && (ui.input(|i| i.key_pressed(egui::Key::Enter)) || ui.input(|i| i.key_pressed(egui::Key::Escape)))) {
+                                    self.show_delete_task_confirm = None;
+                                }
+                            });
+                        });
+                }
+            }
+
+            // Add the shortcuts popup window
+            if self.show_shortcuts {
+                dialog_window(ctx, &self.settings.window_positions, "keyboard_shortcuts", "Keyboard Shortcuts")
+                    .collapsible(false)
+                    .resizable(false)
+                    .show(ctx, |ui| {
+                        ui.label("Global Shortcuts:");
+                        ui.add_space(4.0);
+
+                        egui::Grid::new("shortcuts_grid")
+                            .num_columns(2)
+                            .spacing([40.0, 4.0])
+                            .show(ui, |ui| {
+                                for shortcut in Shortcut::ALL {
+                                    ui.label(shortcut.keys_text(ctx));
+                                    ui.label(shortcut.label());
+                                    ui.end_row();
+                                }
+                            });
+
+                        ui.add_space(8.0);
+                        ui.horizontal(|ui| {
+                            if ui.button("Close").clicked() {
+                                self.show_shortcuts = false;
+                            }
+                            if ui.button(format!("{} Copy Cheat Sheet", fill::CLIPBOARD_TEXT)).clicked() {
+                                ctx.copy_text(shortcut_cheat_sheet(ctx));
+                                self.export_message = Some(("Cheat sheet copied to clipboard".to_string(), 3.0));
+                            }
+                            if ui.button(format!("{} Save for Printing", fill::PRINTER)).clicked() {
+                                let filename = "keyboard_shortcuts.md";
+                                match fs::write(filename, shortcut_cheat_sheet(ctx)) {
+                                    Ok(()) => self.show_exported_file(format!("Cheat sheet saved to {}", filename), filename),
+                                    Err(e) => self.export_message = Some((format!("Error saving cheat sheet: {}", e), 3.0)),
+                                }
+                            }
+                        });
+                    });
+            }
+
+            // Add the settings popup window
+            if self.show_settings {
+                dialog_window(ctx, &self.settings.window_positions, "settings", "Settings")
+                    .collapsible(false)
+                    .resizable(false)
+                    .show(ctx, |ui| {
+                        ui.heading("Display");
+                        ui.add_space(4.0);
+
+                        ui.horizontal(|ui| {
+                            if ui.button("➖").clicked() && self.temporary_ui_scale > 1.0 {
+                                self.temporary_ui_scale = (self.temporary_ui_scale - 0.1).max(1.0);
+                            }
+
+                            ui.add(
+                                egui::Slider::new(&mut self.temporary_ui_scale, 1.0..=2.5)
+                                    .step_by(0.1)
+                                    .text("Scale"),
+                            );
+
+                            if ui.button("➕").clicked() && self.temporary_ui_scale < 2.5 {
+                                self.temporary_ui_scale = (self.temporary_ui_scale + 0.1).min(2.5);
+                            }
+                        });
+
+                        ui.horizontal(|ui| {
+                            ui.label("Show durations as");
+                            let mut duration_format = self.settings.duration_format;
+                            egui::ComboBox::from_id_salt("duration_format")
+                                .selected_text(duration_format.label())
+                                .show_ui(ui, |ui| {
+                                    for format in DurationFormat::ALL {
+                                        ui.selectable_value(&mut duration_format, format, format.label());
+                                    }
+                                });
+                            if duration_format != self.settings.duration_format {
+                                self.settings.duration_format = duration_format;
+                                self.save_settings();
+                            }
+                        });
+                        ui.horizontal(|ui| {
+                            ui.label("Density");
+                            let mut density = self.settings.density;
+                            for option in [Density::Comfortable, Density::Compact] {
+                                ui.radio_value(&mut density, option, option.label());
+                            }
+                            if density != self.settings.density {
+                                self.settings.density = density;
+                                self.save_settings();
+                            }
+                        });
+
+                        ui.add_space(8.0);
+                        ui.separator();
+                        ui.heading("Pay Period");
+                        ui.add_space(4.0);
+
+                        let mut settings_changed = false;
+                        egui::Grid::new("pay_period_settings_grid")
+                            .num_columns(2)
+                            .spacing([10.0, 4.0])
+                            .show(ui, |ui| {
+                                ui.label("Period:");
+                                egui::ComboBox::from_id_salt("pay_period_kind")
+                                    .selected_text(self.settings.pay_period.kind.label())
+                                    .show_ui(ui, |ui| {
+                                        for kind in [PayPeriodKind::Weekly, PayPeriodKind::BiWeekly, PayPeriodKind::Monthly] {
+                                            settings_changed |= ui
+                                                .selectable_value(&mut self.settings.pay_period.kind, kind, kind.label())
+                                                .changed();
+                                        }
+                                    });
+                                ui.end_row();
+
+                                ui.label("Contracted hours:");
+                                settings_changed |= ui
+                                    .add(egui::DragValue::new(&mut self.settings.pay_period.contracted_hours)
+                                        .range(0.0..=744.0)
+                                        .speed(0.5))
+                                    .changed();
+                                ui.end_row();
+
+                                if self.settings.pay_period.kind == PayPeriodKind::BiWeekly {
+                                    ui.label("First period starts:");
+                                    let mut anchor = self.settings.pay_period.anchor_date.to_string();
+                                    if ui.text_edit_singleline(&mut anchor).changed() {
+                                        if let Ok(date) = NaiveDate::parse_from_str(&anchor, "%Y-%m-%d") {
+                                            self.settings.pay_period.anchor_date = date;
+                                            settings_changed = true;
+                                        }
+                                    }
+                                    ui.end_row();
+                                }
+                            });
+
+                        if settings_changed {
+                            self.save_settings();
+                        }
+
+                        ui.add_space(8.0);
+                        ui.separator();
+                        ui.heading("Work Hours");
+                        ui.add_space(4.0);
+                        let mut work_hours_changed = false;
+                        ui.horizontal(|ui| {
+                            for weekday in self.calendar().weekdays() {
+                                let is_work_day = self.settings.work_hours.days.contains(&weekday);
+                                if ui.selectable_label(is_work_day, &weekday.to_string()[..2]).clicked() {
+                                    if is_work_day {
+                                        self.settings.work_hours.days.retain(|day| *day != weekday);
+                                    } else {
+                                        self.settings.work_hours.days.push(weekday);
+                                    }
+                                    work_hours_changed = true;
+                                }
+                            }
+                        });
+                        ui.horizontal(|ui| {
+                            let work_hours = &mut self.settings.work_hours;
+                            for (index, time) in [&mut work_hours.start, &mut work_hours.end].into_iter().enumerate() {
+                                if index == 1 {
+                                    ui.label("to");
+                                }
+                                let mut hour = time.hour();
+                                let mut minute = time.minute();
+                                let hour_changed = ui.add(egui::DragValue::new(&mut hour).range(0..=23)).changed();
+                                ui.label(":");
+                                let minute_changed = ui.add(egui::DragValue::new(&mut minute).range(0..=59)).changed();
+                                if hour_changed || minute_changed {
+                                    *time = NaiveTime::from_hms_opt(hour, minute, 0).unwrap_or(*time);
+                                    work_hours_changed = true;
+                                }
+                            }
+                        });
+                        ui.horizontal(|ui| {
+                            ui.label("Remind me after");
+                            work_hours_changed |= ui
+                                .add(egui::DragValue::new(&mut self.settings.idle_reminder_minutes).range(0..=480).suffix(" min"))
+                                .on_hover_text("0 turns the reminder off")
+                                .changed();
+                            ui.label("without a running timer");
+                        });
+                        ui.horizontal(|ui| {
+                            let config = &mut self.settings.idle_pause;
+                            work_hours_changed |= ui.checkbox(&mut config.enabled, "Pause timers after").changed();
+                            work_hours_changed |= ui
+                                .add(egui::DragValue::new(&mut config.minutes).range(1..=240).suffix(" min"))
+                                .changed();
+                            ui.label("without input, then");
+                            egui::ComboBox::from_id_salt("idle_return")
+                                .selected_text(config.on_return.label())
+                                .show_ui(ui, |ui| {
+                                    for on_return in [IdleReturn::Resume, IdleReturn::Ask, IdleReturn::StayPaused] {
+                                        work_hours_changed |= ui
+                                            .selectable_value(&mut config.on_return, on_return, on_return.label())
+                                            .changed();
+                                    }
+                                });
+                        });
+                        if self.settings.idle_pause.enabled
+                            && self.idle_monitor.as_ref().is_some_and(|monitor| monitor.idle_seconds().is_none())
+                        {
+                            ui.label(egui::RichText::new(
+                                "Can't read input idle time on this system (on Linux, install xprintidle)",
+                            ).weak());
+                        }
+                        ui.horizontal(|ui| {
+                            ui.label("New day starts at");
+                            work_hours_changed |= ui
+                                .add(egui::DragValue::new(&mut self.settings.day_start_hour).range(0..=23).suffix(":00"))
+                                .on_hover_text("Sessions running past this hour are split between the two days in daily totals, statistics, goals and exports")
+                                .changed();
+                        });
+                        ui.horizontal(|ui| {
+                            ui.label("Weeks start on");
+                            work_hours_changed |= ui.radio_value(&mut self.settings.week_starts_on_sunday, false, "Monday").changed();
+                            work_hours_changed |= ui.radio_value(&mut self.settings.week_starts_on_sunday, true, "Sunday").changed();
+                        });
+                        ui.horizontal(|ui| {
+                            ui.label("Daily goal:");
+                            work_hours_changed |= ui
+                                .add(egui::DragValue::new(&mut self.settings.daily_goal_hours).range(0.0..=24.0).speed(0.25).suffix(" h"))
+                                .on_hover_text("0 hides the progress ring")
+                                .changed();
+                        });
+                        ui.horizontal(|ui| {
+                            ui.label("On launch during work hours, start:");
+                            let selected = self
+                                .settings
+                                .launch_task
+                                .as_ref()
+                                .and_then(|task_id| self.tasks.get(task_id))
+                                .map_or("Nothing", |task| task.description.as_str());
+                            let mut tasks: Vec<_> = self.tasks.values().collect();
+                            tasks.sort_by(|a, b| a.description.cmp(&b.description));
+                            egui::ComboBox::from_id_salt("launch_task")
+                                .selected_text(selected)
+                                .show_ui(ui, |ui| {
+                                    work_hours_changed |= ui.selectable_value(&mut self.settings.launch_task, None, "Nothing").changed();
+                                    for task in tasks {
+                                        work_hours_changed |= ui
+                                            .selectable_value(&mut self.settings.launch_task, Some(task.id.clone()), &task.description)
+                                            .changed();
+                                    }
+                                });
+                        });
+                        if work_hours_changed {
+                            self.save_settings();
+                        }
+
+                        ui.add_space(8.0);
+                        ui.separator();
+                        ui.heading("Time Off");
+                        ui.add_space(4.0);
+                        ui.label(egui::RichText::new(
+                            "Days off don't count against goals, streaks or contracted hours"
+                        ).weak());
+                        let mut add_days = None;
+                        let mut remove_day = None;
+                        ui.horizontal(|ui| {
+                            let (from, to, kind, note) = &mut self.new_time_off;
+                            ui.add(egui::TextEdit::singleline(from).hint_text("YYYY-MM-DD").desired_width(90.0));
+                            ui.label("to");
+                            ui.add(egui::TextEdit::singleline(to).hint_text("same day").desired_width(90.0));
+                            egui::ComboBox::from_id_salt("time_off_kind")
+                                .selected_text(kind.label())
+                                .show_ui(ui, |ui| {
+                                    for option in TimeOffKind::ALL {
+                                        ui.selectable_value(kind, option, option.label());
+                                    }
+                                });
+                            ui.add(egui::TextEdit::singleline(note).hint_text("Note").desired_width(120.0));
+                            let first = NaiveDate::parse_from_str(from.trim(), "%Y-%m-%d").ok();
+                            let last = if to.trim().is_empty() {
+                                first
+                            } else {
+                                NaiveDate::parse_from_str(to.trim(), "%Y-%m-%d").ok()
+                            };
+                            let range = first.zip(last).filter(|(first, last)| first <= last);
+                            if ui.add_enabled(range.is_some(), egui::Button::new("Add")).clicked() {
+                                add_days = range;
+                            }
+                        });
+                        ui.horizontal(|ui| {
+                            ui.add(
+                                egui::TextEdit::singleline(&mut self.time_off_source)
+                                    .hint_text(".ics file or https://… address")
+                                    .desired_width(280.0),
+                            );
+                            let can_import = !self.time_off_source.trim().is_empty() && self.time_off_import.is_none();
+                            if ui
+                                .add_enabled(can_import, egui::Button::new("Import"))
+                                .on_hover_text("Marks the days of all-day events with the kind chosen above")
+                                .clicked()
+                            {
+                                let source = self.time_off_source.trim().to_string();
+                                let kind = self.new_time_off.2;
+                                let (sender, receiver) = mpsc::channel();
+                                std::thread::spawn(move || {
+                                    let days = ics::fetch_days_off(&source)
+                                        .map(|days| days.into_iter().map(|(date, note)| TimeOff { date, kind, note }).collect());
+                                    let _ = sender.send(days);
+                                });
+                                self.time_off_import = Some(receiver);
+                            }
+                            if self.time_off_import.is_some() {
+                                ui.spinner();
+                            }
+                        });
+                        let recent = self.calendar().today() - chrono::Duration::days(30);
+                        let shown: Vec<&TimeOff> = self.time_off.iter().filter(|day| day.date >= recent).collect();
+                        if !shown.is_empty() {
+                            egui::ScrollArea::vertical().id_salt("time_off_list").max_height(120.0).show(ui, |ui| {
+                                for day in shown {
+                                    ui.horizontal(|ui| {
+                                        ui.label(day.date.format("%a, %b %d, %Y").to_string());
+                                        ui.label(day.kind.label());
+                                        ui.weak(&day.note);
+                                        if ui.small_button(fill::TRASH).on_hover_text("Remove").clicked() {
+                                            remove_day = Some(day.date);
+                                        }
+                                    });
+                                }
+                            });
+                        }
+                        if let Some((first, last)) = add_days {
+                            let (_, _, kind, note) = self.new_time_off.clone();
+                            let days = first
+                                .iter_days()
+                                .take_while(|date| *date <= last)
+                                .map(|date| TimeOff { date, kind, note: note.trim().to_string() })
+                                .collect();
+                            self.add_time_off(days);
+                            self.new_time_off = (String::new(), String::new(), kind, String::new());
+                        }
+                        if let Some(date) = remove_day {
+                            self.time_off.retain(|day| day.date != date);
+                            self.save_time_off();
+                        }
+
+                        ui.add_space(8.0);
+                        ui.separator();
+                        ui.heading("Overlay");
+                        ui.add_space(4.0);
+                        let mut overlay_changed = false;
+                        ui.horizontal(|ui| {
+                            overlay_changed |= ui
+                                .checkbox(&mut self.settings.overlay.enabled, "Show the running time in a corner of the screen")
+                                .changed();
+                            egui::ComboBox::from_id_salt("overlay_corner")
+                                .selected_text(self.settings.overlay.corner.label())
+                                .show_ui(ui, |ui| {
+                                    for corner in ScreenCorner::ALL {
+                                        overlay_changed |= ui
+                                            .selectable_value(&mut self.settings.overlay.corner, corner, corner.label())
+                                            .changed();
+                                    }
+                                });
+                        });
+                        if overlay_changed {
+                            self.save_settings();
+                        }
+
+                        if cfg!(feature = "tray") {
+                            ui.add_space(8.0);
+                            ui.separator();
+                            ui.heading("Tray Icon");
+                            ui.add_space(4.0);
+                            let mut tray_changed = ui
+                                .checkbox(&mut self.settings.tray.enabled, "Show the running task in the tray")
+                                .on_hover_text("The menu pauses and resumes the timer and switches to recent tasks")
+                                .changed();
+                            ui.add_enabled_ui(self.settings.tray.enabled, |ui| {
+                                tray_changed |= ui
+                                    .checkbox(&mut self.settings.tray.close_to_tray, "Closing the window keeps the app running in the tray")
+                                    .changed();
+                            });
+                            if tray_changed {
+                                self.save_settings();
+                            }
+                        }
+
+                        ui.add_space(8.0);
+                        ui.separator();
+                        ui.heading("Global Hotkeys");
+                        ui.add_space(4.0);
+                        let mut hotkeys_changed = ui
+                            .checkbox(&mut self.settings.global_hotkeys.enabled, "Use hotkeys while other apps have focus")
+                            .on_hover_text("Not available under Wayland on Linux")
+                            .changed();
+                        ui.add_enabled_ui(self.settings.global_hotkeys.enabled, |ui| {
+                            egui::Grid::new("global_hotkeys").num_columns(2).spacing([10.0, 4.0]).show(ui, |ui| {
+                                for action in hotkeys::GlobalAction::ALL {
+                                    ui.label(action.label());
+                                    hotkeys_changed |= ui
+                                        .add(
+                                            egui::TextEdit::singleline(self.settings.global_hotkeys.keys_mut(action))
+                                                .hint_text("None")
+                                                .desired_width(160.0),
+                                        )
+                                        .on_hover_text("e.g. Ctrl+Alt+P or CmdOrCtrl+Shift+Space, CmdOrCtrl is Cmd on macOS")
+                                        .lost_focus();
+                                    ui.end_row();
+                                }
+                            });
+                        });
+                        for problem in &self.hotkey_problems {
+                            ui.colored_label(egui::Color32::from_rgb(220, 80, 80), problem);
+                        }
+                        if hotkeys_changed {
+                            self.hotkeys_registered = false;
+                            self.save_settings();
+                        }
+
+                        ui.add_space(8.0);
+                        ui.separator();
+                        ui.heading("Notifications");
+                        ui.add_space(4.0);
+                        let config = &mut self.settings.notifications;
+                        let mut notifications_changed =
+                            ui.checkbox(&mut config.enabled, "Show desktop notifications").changed();
+                        ui.add_enabled_ui(config.enabled, |ui| {
+                            notifications_changed |= ui.checkbox(&mut config.timer_started, "When a timer starts").changed();
+                            ui.horizontal(|ui| {
+                                notifications_changed |=
+                                    ui.checkbox(&mut config.long_running, "When a timer has been running for").changed();
+                                notifications_changed |= ui
+                                    .add(egui::DragValue::new(&mut config.long_running_minutes).range(1..=1440).suffix(" min"))
+                                    .changed();
+                            });
+                            notifications_changed |=
+                                ui.checkbox(&mut config.pomodoro, "When a pomodoro or a break is over").changed();
+                            notifications_changed |= ui.checkbox(&mut config.export_finished, "When an export is finished").changed();
+                            notifications_changed |=
+                                ui.checkbox(&mut config.budget_alert, "When a folder reaches a budget alert").changed();
+                            notifications_changed |=
+                                ui.checkbox(&mut config.idle_reminder, "When no timer has been running for a while").changed();
+                        });
+                        if notifications_changed {
+                            self.save_settings();
+                        }
+
+                        if focus::SUPPORTED {
+                            ui.add_space(8.0);
+                            ui.separator();
+                            ui.heading("Do Not Disturb");
+                            ui.add_space(4.0);
+                            let mut dnd_changed = ui
+                                .checkbox(&mut self.settings.do_not_disturb.enabled, "Turn on Do Not Disturb while a timer runs")
+                                .on_hover_text(if cfg!(target_os = "macos") {
+                                    "Runs the shortcuts \"Work Timer Focus On\" and \"Work Timer Focus Off\", create them in the Shortcuts app"
+                                } else {
+                                    "Put back as it was when the timer stops"
+                                })
+                                .changed();
+                            ui.add_enabled_ui(self.settings.do_not_disturb.enabled, |ui| {
+                                dnd_changed |= ui
+                                    .checkbox(&mut self.settings.do_not_disturb.deep_work_only, format!("Only for tasks tagged {}", DEEP_WORK_TAG))
+                                    .changed();
+                            });
+                            if dnd_changed {
+                                self.save_settings();
+                            }
+                        }
+
+                        ui.add_space(8.0);
+                        ui.separator();
+                        ui.heading("Pomodoro");
+                        ui.add_space(4.0);
+                        let pomodoro = &mut self.settings.pomodoro;
+                        let mut pomodoro_changed = ui
+                            .checkbox(&mut pomodoro.enabled, "Pomodoro mode")
+                            .on_hover_text("Timers pause when a work interval is up, followed by a break")
+                            .changed();
+                        ui.add_enabled_ui(pomodoro.enabled, |ui| {
+                            egui::Grid::new("pomodoro_settings_grid")
+                                .num_columns(2)
+                                .spacing([10.0, 4.0])
+                                .show(ui, |ui| {
+                                    for (label, minutes) in [
+                                        ("Work:", &mut pomodoro.work_minutes),
+                                        ("Short break:", &mut pomodoro.short_break_minutes),
+                                        ("Long break:", &mut pomodoro.long_break_minutes),
+                                    ] {
+                                        ui.label(label);
+                                        pomodoro_changed |= ui.add(egui::DragValue::new(minutes).range(1..=180).suffix(" min")).changed();
+                                        ui.end_row();
+                                    }
+                                    ui.label("Long break after:");
+                                    pomodoro_changed |= ui
+                                        .add(egui::DragValue::new(&mut pomodoro.long_break_every).range(1..=12).suffix(" pomodoros"))
+                                        .changed();
+                                    ui.end_row();
+                                });
+                            pomodoro_changed |= ui.checkbox(&mut pomodoro.auto_resume, "Resume the task when the break is over").changed();
+                        });
+                        if pomodoro_changed {
+                            self.save_settings();
+                        }
+
+                        ui.add_space(8.0);
+                        ui.separator();
+                        ui.heading("Proof-of-Work Screenshots");
+                        ui.add_space(4.0);
+                        let mut screenshots_changed = false;
+                        ui.horizontal(|ui| {
+                            screenshots_changed |= ui.checkbox(&mut self.settings.screenshots.enabled, "Take a screenshot every").changed();
+                            screenshots_changed |= ui
+                                .add(egui::DragValue::new(&mut self.settings.screenshots.interval_minutes).range(1..=120).suffix(" min"))
+                                .changed();
+                            ui.label(format!("while a task tagged {} runs", screenshots::PROOF_OF_WORK_TAG));
+                        });
+                        ui.label(egui::RichText::new(format!(
+                            "Kept per session in {}/ and zipped next to task and folder CSV exports",
+                            screenshots::SCREENSHOTS_DIR
+                        )).weak());
+                        if screenshots_changed {
+                            self.save_settings();
+                        }
+
+                        ui.add_space(8.0);
+                        ui.separator();
+                        ui.heading("Clipboard Tickets");
+                        ui.add_space(4.0);
+                        if ui
+                            .checkbox(&mut self.settings.clipboard_tickets, "Offer a task for ticket ids and issue links I copy")
+                            .changed()
+                        {
+                            self.save_settings();
+                        }
+                        ui.label(egui::RichText::new(
+                            "Recognizes keys like PROJ-1234 and issue, pull request and Jira links. \
+                             Folders can add their own patterns under their Settings.",
+                        ).weak());
+
+                        ui.add_space(8.0);
+                        ui.separator();
+                        ui.heading("Day Review");
+                        ui.add_space(4.0);
+                        let mut review_changed = false;
+                        ui.horizontal(|ui| {
+                            let mut scheduled = self.settings.day_review.review_at.is_some();
+                            if ui.checkbox(&mut scheduled, "Review the day at").changed() {
+                                self.settings.day_review.review_at =
+                                    scheduled.then(|| NaiveTime::from_hms_opt(17, 30, 0).unwrap());
+                                review_changed = true;
+                            }
+                            if let Some(review_at) = self.settings.day_review.review_at {
+                                let mut hour = review_at.hour();
+                                let mut minute = review_at.minute();
+                                let hour_changed = ui.add(egui::DragValue::new(&mut hour).range(0..=23)).changed();
+                                ui.label(":");
+                                let minute_changed = ui.add(egui::DragValue::new(&mut minute).range(0..=59)).changed();
+                                if hour_changed || minute_changed {
+                                    self.settings.day_review.review_at = NaiveTime::from_hms_opt(hour, minute, 0);
+                                    review_changed = true;
+                                }
+                            }
+                        });
+                        review_changed |= ui
+                            .checkbox(&mut self.settings.day_review.on_quit, "Review an unconfirmed day before quitting")
+                            .changed();
+                        if review_changed {
+                            self.save_settings();
+                        }
+                        if ui.button("Review Today…").clicked() {
+                            self.show_day_review = Some(self.calendar().today());
+                        }
+
+                        ui.add_space(8.0);
+                        ui.separator();
+                        ui.heading("Calendar Gaps");
+                        ui.add_space(4.0);
+                        ui.label(egui::RichText::new(
+                            "Suggests a focus task when nothing is running between two meetings"
+                        ).weak());
+                        let mut gaps_changed = false;
+                        let mut refresh_meetings = false;
+                        let weekdays = self.calendar().weekdays();
+                        egui::Grid::new("calendar_gaps_settings_grid")
+                            .num_columns(2)
+                            .spacing([10.0, 4.0])
+                            .show(ui, |ui| {
+                                let config = &mut self.settings.calendar_gaps;
+                                ui.label("Calendar address (ICS):");
+                                let response = ui.add(
+                                    egui::TextEdit::singleline(&mut config.ics_url)
+                                        .hint_text("https://… or webcal://…")
+                                        .desired_width(280.0),
+                                );
+                                gaps_changed |= response.changed();
+                                refresh_meetings |= response.lost_focus();
+                                ui.end_row();
+
+                                ui.label("Gaps of at least:");
+                                gaps_changed |= ui
+                                    .add(egui::DragValue::new(&mut config.min_gap_minutes).range(5..=240).suffix(" min"))
+                                    .changed();
+                                ui.end_row();
+
+                                ui.label("On:");
+                                ui.horizontal(|ui| {
+                                    for weekday in weekdays {
+                                        let mut enabled = config.weekdays.contains(&weekday);
+                                        if ui.checkbox(&mut enabled, weekday.to_string()).changed() {
+                                            config.weekdays.retain(|day| *day != weekday);
+                                            if enabled {
+                                                config.weekdays.push(weekday);
+                                            }
+                                            gaps_changed = true;
+                                        }
+                                    }
+                                });
+                                ui.end_row();
+                            });
+                        if self.settings.calendar_gaps.is_configured() {
+                            ui.horizontal(|ui| {
+                                match &self.meetings_error {
+                                    Some(e) => ui.colored_label(egui::Color32::from_rgb(220, 80, 80), format!("Couldn't read the calendar: {}", e)),
+                                    None if self.meetings_job.is_some() => ui.label("Reading calendar…"),
+                                    None => ui.label(format!("{} meetings today", self.meetings.len())),
+                                };
+                                if ui.small_button(fill::ARROW_CLOCKWISE).on_hover_text("Read again").clicked() {
+                                    refresh_meetings = true;
+                                }
+                            });
+                        }
+                        if gaps_changed {
+                            self.save_settings();
+                        }
+                        if refresh_meetings && self.meetings_job.is_none() {
+                            self.refresh_meetings(true);
+                        }
+
+                        ui.add_space(8.0);
+                        ui.separator();
+                        ui.heading("Weekly Summary");
+                        ui.add_space(4.0);
+                        let mut summary_changed = false;
+                        let weekdays = self.calendar().weekdays();
+                        egui::Grid::new("weekly_summary_settings_grid")
+                            .num_columns(2)
+                            .spacing([10.0, 4.0])
+                            .show(ui, |ui| {
+                                let config = &mut self.settings.weekly_summary;
+                                summary_changed |= ui.checkbox(&mut config.enabled, "Generate every").changed();
+                                ui.horizontal(|ui| {
+                                    egui::ComboBox::from_id_salt("weekly_summary_weekday")
+                                        .selected_text(config.weekday.to_string())
+                                        .show_ui(ui, |ui| {
+                                            for weekday in weekdays {
+                                                summary_changed |= ui
+                                                    .selectable_value(&mut config.weekday, weekday, weekday.to_string())
+                                                    .changed();
+                                            }
+                                        });
+                                    let mut hour = config.at.hour();
+                                    let mut minute = config.at.minute();
+                                    let hour_changed = ui.add(egui::DragValue::new(&mut hour).range(0..=23)).changed();
+                                    ui.label(":");
+                                    let minute_changed = ui.add(egui::DragValue::new(&mut minute).range(0..=59)).changed();
+                                    if hour_changed || minute_changed {
+                                        config.at = NaiveTime::from_hms_opt(hour, minute, 0).unwrap_or(config.at);
+                                        summary_changed = true;
+                                    }
+                                });
+                                ui.end_row();
+
+                                ui.label("Weekly goal (hours):");
+                                summary_changed |= ui
+                                    .add(egui::DragValue::new(&mut config.goal_hours).range(0.0..=168.0).speed(0.5))
+                                    .on_hover_text("0 for no goal")
+                                    .changed();
+                                ui.end_row();
+
+                                ui.label("Save to directory:");
+                                summary_changed |= ui
+                                    .add(egui::TextEdit::singleline(&mut config.save_dir).hint_text("Only show in app"))
+                                    .changed();
+                                ui.end_row();
+
+                                ui.label("Email to:");
+                                summary_changed |= ui
+                                    .add(egui::TextEdit::singleline(&mut config.email_to).hint_text("Uses the SMTP account below"))
+                                    .changed();
+                                ui.end_row();
+                            });
+                        if summary_changed {
+                            self.save_settings();
+                        }
+                        if ui.button("Show Last Week").clicked() {
+                            let week_start = self.last_week_start();
+                            self.weekly_summary = Some((week_start, self.weekly_summary_text(week_start)));
+                        }
+
+                        ui.add_space(8.0);
+                        ui.separator();
+                        ui.heading("Billing");
+                        ui.add_space(4.0);
+                        let billing = &mut self.settings.billing;
+                        let mut billing_changed = false;
+                        ui.horizontal(|ui| {
+                            ui.label("Tax rate:");
+                            billing_changed |= ui
+                                .add(egui::DragValue::new(&mut billing.tax_percent).range(0.0..=100.0).speed(0.5).max_decimals(2).suffix(" %"))
+                                .on_hover_text("Added to billable amounts in reports, exports and statistics, 0 for none. Client folders can have their own in the folder settings.")
+                                .changed();
+                        });
+                        billing_changed |= ui
+                            .checkbox(&mut billing.tax_inclusive, "Hourly rates include the tax")
+                            .on_hover_text("The tax is taken out of the amounts instead of added on top")
+                            .changed();
+                        ui.horizontal(|ui| {
+                            ui.label("Amounts:");
+                            egui::ComboBox::from_id_salt("number_format")
+                                .selected_text(billing.number_format.label())
+                                .show_ui(ui, |ui| {
+                                    for format in billing::NumberFormat::ALL {
+                                        billing_changed |= ui.selectable_value(&mut billing.number_format, format, format.label()).changed();
+                                    }
+                                });
+                        });
+                        if billing_changed {
+                            self.save_settings();
+                        }
+
+                        ui.add_space(8.0);
+                        ui.separator();
+                        ui.heading("Export Templates");
+                        ui.add_space(4.0);
+                        ui.horizontal(|ui| {
+                            ui.label(format!("{} saved", self.settings.export_templates.len()));
+                            if ui.button("Manage…").clicked() {
+                                self.show_export_templates = true;
+                            }
+                        });
+
+                        ui.add_space(8.0);
+                        ui.separator();
+                        ui.heading("Hooks");
+                        ui.add_space(4.0);
+                        ui.label(egui::RichText::new(
+                            "Shell commands run on events. WORK_TIMER_TASK, WORK_TIMER_FOLDER, WORK_TIMER_DURATION \
+                             (seconds) and WORK_TIMER_DATE are set in their environment."
+                        ).weak());
+
+                        let mut hooks_changed = false;
+                        egui::Grid::new("hooks_settings_grid")
+                            .num_columns(2)
+                            .spacing([10.0, 4.0])
+                            .show(ui, |ui| {
+                                for (label, command) in [
+                                    ("Task started:", &mut self.settings.hooks.on_task_started),
+                                    ("Task stopped:", &mut self.settings.hooks.on_task_stopped),
+                                    ("Task completed:", &mut self.settings.hooks.on_task_completed),
+                                    ("Day ended:", &mut self.settings.hooks.on_day_ended),
+                                ] {
+                                    ui.label(label);
+                                    hooks_changed |= ui.text_edit_singleline(command).changed();
+                                    ui.end_row();
+                                }
+                            });
+                        if hooks_changed {
+                            self.save_settings();
+                        }
+
+                        ui.add_space(8.0);
+                        ui.separator();
+                        ui.heading("Integrations");
+                        ui.add_space(4.0);
+
+                        let mut integrations_changed = false;
+                        egui::Grid::new("integrations_settings_grid")
+                            .num_columns(2)
+                            .spacing([10.0, 4.0])
+                            .show(ui, |ui| {
+                                ui.label("Harvest access token:");
+                                integrations_changed |= ui
+                                    .add(egui::TextEdit::singleline(&mut self.settings.harvest.access_token).password(true))
+                                    .changed();
+                                ui.end_row();
+
+                                ui.label("Harvest account ID:");
+                                integrations_changed |= ui
+                                    .text_edit_singleline(&mut self.settings.harvest.account_id)
+                                    .changed();
+                                ui.end_row();
+
+                                ui.label("Redmine URL:");
+                                integrations_changed |= ui
+                                    .text_edit_singleline(&mut self.settings.redmine.base_url)
+                                    .changed();
+                                ui.end_row();
+
+                                ui.label("Redmine API key:");
+                                integrations_changed |= ui
+                                    .add(egui::TextEdit::singleline(&mut self.settings.redmine.api_key).password(true))
+                                    .changed();
+                                ui.end_row();
+
+                                ui.label("Notion token:");
+                                integrations_changed |= ui
+                                    .add(egui::TextEdit::singleline(&mut self.settings.notion.token).password(true))
+                                    .changed();
+                                ui.end_row();
+
+                                ui.label("Notion database ID:");
+                                integrations_changed |= ui
+                                    .text_edit_singleline(&mut self.settings.notion.database_id)
+                                    .changed();
+                                ui.end_row();
+
+                                ui.label("Notion sync every (min):");
+                                integrations_changed |= ui
+                                    .add(egui::DragValue::new(&mut self.settings.notion.sync_interval_minutes).range(0..=1440))
+                                    .on_hover_text("0 syncs only when you click Sync to Notion")
+                                    .changed();
+                                ui.end_row();
+
+                                ui.label("Asana access token:");
+                                integrations_changed |= ui
+                                    .add(egui::TextEdit::singleline(&mut self.settings.task_import.asana_token).password(true))
+                                    .changed();
+                                ui.end_row();
+
+                                ui.label("Trello API key:");
+                                integrations_changed |= ui
+                                    .text_edit_singleline(&mut self.settings.task_import.trello_api_key)
+                                    .changed();
+                                ui.end_row();
+
+                                ui.label("Trello token:");
+                                integrations_changed |= ui
+                                    .add(egui::TextEdit::singleline(&mut self.settings.task_import.trello_token).password(true))
+                                    .changed();
+                                ui.end_row();
+
+                                ui.label("Computer activity:");
+                                ui.horizontal(|ui| {
+                                    for source in [
+                                        activity::ActivitySource::None,
+                                        activity::ActivitySource::RescueTime,
+                                        activity::ActivitySource::ActivityWatch,
+                                    ] {
+                                        if ui.selectable_value(&mut self.settings.activity.source, source, source.label()).changed() {
+                                            self.activity_overlay.clear();
+                                            integrations_changed = true;
+                                        }
+                                    }
+                                });
+                                ui.end_row();
+
+                                match self.settings.activity.source {
+                                    activity::ActivitySource::RescueTime => {
+                                        ui.label("RescueTime API key:");
+                                        integrations_changed |= ui
+                                            .add(egui::TextEdit::singleline(&mut self.settings.activity.rescuetime_key).password(true))
+                                            .changed();
+                                        ui.end_row();
+                                    }
+                                    activity::ActivitySource::ActivityWatch => {
+                                        ui.label("ActivityWatch URL:");
+                                        integrations_changed |= ui
+                                            .add(egui::TextEdit::singleline(&mut self.settings.activity.activitywatch_url)
+                                                .hint_text("http://localhost:5600"))
+                                            .changed();
+                                        ui.end_row();
+                                    }
+                                    activity::ActivitySource::None => {}
+                                }
+
+                                ui.label("SMTP server:");
+                                ui.horizontal(|ui| {
+                                    integrations_changed |= ui
+                                        .add(egui::TextEdit::singleline(&mut self.settings.smtp.host).desired_width(140.0))
+                                        .changed();
+                                    ui.label("Port:");
+                                    integrations_changed |= ui
+                                        .add(egui::DragValue::new(&mut self.settings.smtp.port))
+                                        .on_hover_text("0 uses 587 (STARTTLS)")
+                                        .changed();
+                                });
+                                ui.end_row();
+
+                                ui.label("SMTP username:");
+                                integrations_changed |= ui.text_edit_singleline(&mut self.settings.smtp.username).changed();
+                                ui.end_row();
+
+                                ui.label("SMTP password:");
+                                integrations_changed |= ui
+                                    .add(egui::TextEdit::singleline(&mut self.settings.smtp.password).password(true))
+                                    .changed();
+                                ui.end_row();
+
+                                ui.label("Send mail as:");
+                                integrations_changed |= ui
+                                    .add(egui::TextEdit::singleline(&mut self.settings.smtp.from).hint_text("Name <me@example.com>"))
+                                    .changed();
+                                ui.end_row();
+
+                                ui.label("Obsidian daily note:");
+                                integrations_changed |= ui
+                                    .text_edit_singleline(&mut self.settings.obsidian.path_pattern)
+                                    .on_hover_text("e.g. vault/Daily/{date}.md, {date} becomes YYYY-MM-DD")
+                                    .changed();
+                                ui.end_row();
+
+                                ui.label("Append automatically:");
+                                ui.horizontal(|ui| {
+                                    let mut auto_append = self.settings.obsidian.auto_append_at.is_some();
+                                    if ui.checkbox(&mut auto_append, "at").changed() {
+                                        self.settings.obsidian.auto_append_at =
+                                            auto_append.then(|| NaiveTime::from_hms_opt(18, 0, 0).unwrap());
+                                        integrations_changed = true;
+                                    }
+                                    if let Some(append_at) = self.settings.obsidian.auto_append_at {
+                                        let mut hour = append_at.hour();
+                                        let mut minute = append_at.minute();
+                                        let hour_changed = ui.add(egui::DragValue::new(&mut hour).range(0..=23)).changed();
+                                        ui.label(":");
+                                        let minute_changed = ui.add(egui::DragValue::new(&mut minute).range(0..=59)).changed();
+                                        if hour_changed || minute_changed {
+                                            self.settings.obsidian.auto_append_at = NaiveTime::from_hms_opt(hour, minute, 0);
+                                            integrations_changed = true;
+                                        }
+                                    }
+                                });
+                                ui.end_row();
+                            });
+                        if integrations_changed {
+                            self.save_settings();
+                        }
+                        ui.horizontal(|ui| {
+                            if ui.button("Harvest…").clicked() {
+                                self.show_harvest = true;
+                            }
+                            if ui.button("Redmine…").clicked() {
+                                self.show_redmine = true;
+                            }
+                            if ui.button("Plugins…").clicked() {
+                                self.show_plugins = true;
+                            }
+                            if ui.button("Import Tasks…").clicked() {
+                                self.show_task_import = true;
+                                if self.import_folder.is_none() {
+                                    self.import_folder = self.selected_folder.clone();
+                                }
+                            }
+                            let can_sync = self.settings.notion.is_configured() && self.integration_job.is_none();
+                            if ui
+                                .add_enabled(can_sync, egui::Button::new("Sync to Notion"))
+                                .on_hover_text(format!(
+                                    "Database needs properties: {} (title), {}, {}, {} (text), {}, {} (number)",
+                                    notion::TITLE_PROPERTY,
+                                    notion::FOLDER_PROPERTY,
+                                    notion::STATUS_PROPERTY,
+                                    notion::DAILY_PROPERTY,
+                                    notion::TOTAL_HOURS_PROPERTY,
+                                    notion::TODAY_HOURS_PROPERTY,
+                                ))
+                                .clicked()
+                            {
+                                self.start_notion_sync();
+                                self.export_message = Some(("Syncing tasks to Notion…".to_string(), 3.0));
+                            }
+                            if ui.button("Append Today to Daily Note").clicked() {
+                                match self.append_daily_note(self.calendar().today()) {
+                                    Ok(path) => self.show_exported_file(format!("Daily summary appended to {}", path), &path),
+                                    Err(e) => self.export_message = Some((format!("Error appending daily note: {}", e), 3.0)),
+                                }
+                            }
+                            if ui.button("Export Tempo Worklogs").clicked() {
+                                match self.export_tempo_worklogs() {
+                                    Ok((filename, 0)) => self.show_exported_file(format!("Worklogs exported to {}", filename), &filename),
+                                    Ok((filename, skipped)) => self.show_exported_file(
+                                        format!("Worklogs exported to {} ({} tasks without issue key skipped)", filename, skipped),
+                                        &filename,
+                                    ),
+                                    Err(e) => self.export_message = Some((format!("Error exporting worklogs: {}", e), 3.0)),
+                                }
+                            }
+                        });
+
+                        ui.add_space(8.0);
+                        ui.separator();
+                        ui.heading("HTTP API");
+                        ui.add_space(4.0);
+                        ui.label(egui::RichText::new(
+                            "JSON for dashboards, scripts and tools embedding the tracker, only reachable from this computer: \
+                             /tasks, /report?from=YYYY-MM-DD&to=YYYY-MM-DD&group_by=folder|day|tag, and tracked sessions as a \
+                             calendar feed to subscribe to at /calendar.ics?days=90"
+                        ).weak());
+                        let mut api_changed = false;
+                        ui.horizontal(|ui| {
+                            api_changed |= ui.checkbox(&mut self.settings.api.enabled, "Enable on port").changed();
+                            api_changed |= ui
+                                .add_enabled(self.api_port.is_none(), egui::DragValue::new(&mut self.settings.api.port).range(1024..=65535))
+                                .changed();
+                        });
+                        match self.api_port {
+                            Some(port) if self.settings.api.enabled => {
+                                let url = format!("http://127.0.0.1:{}/report", port);
+                                ui.hyperlink_to(&url, &url);
+                                ui.horizontal(|ui| {
+                                    let feed = format!("http://127.0.0.1:{}/calendar.ics", port);
+                                    ui.label(egui::RichText::new(&feed).monospace());
+                                    if ui.small_button(fill::COPY).on_hover_text("Copy calendar feed address").clicked() {
+                                        ctx.copy_text(feed);
+                                    }
+                                });
+                            }
+                            Some(_) => {
+                                ui.label(egui::RichText::new("Requests are refused while it is off").weak());
+                            }
+                            None => {}
+                        }
+                        api_changed |= ui
+                            .add_enabled(
+                                self.settings.api.enabled,
+                                egui::Checkbox::new(&mut self.settings.api.allow_control, "Let clients start and stop timers"),
+                            )
+                            .on_hover_text("POST /tasks/<id>/start and /tasks/<id>/stop, for tools and scripts on this computer. Web pages are always refused.")
+                            .changed();
+                        if api_changed {
+                            if self.settings.api.enabled && self.api_port.is_none() {
+                                if let Err(e) = self.start_api(ctx) {
+                                    self.settings.api.enabled = false;
+                                    self.export_message = Some((format!("Error starting the API: {}", e), 5.0));
+                                }
+                            }
+                            self.save_settings();
+                        }
+
+                        ui.add_space(8.0);
+                        ui.separator();
+                        ui.heading("Diagnostics");
+                        ui.add_space(4.0);
+
+                        let data_dir = std::env::current_dir().unwrap_or_default();
+                        ui.horizontal(|ui| {
+                            ui.label("Data folder:");
+                            ui.label(egui::RichText::new(data_dir.display().to_string()).monospace());
+                            if ui.small_button(fill::COPY).on_hover_text("Copy path").clicked() {
+                                ctx.copy_text(data_dir.display().to_string());
+                            }
+                        });
+                        egui::CollapsingHeader::new("Data files").show(ui, |ui| {
+                            egui::Grid::new("diagnostics_files_grid")
+                                .num_columns(2)
+                                .spacing([20.0, 2.0])
+                                .show(ui, |ui| {
+                                    for file in DATA_FILES {
+                                        ui.label(*file);
+                                        match fs::metadata(file) {
+                                            Ok(metadata) => ui.label(format_file_size(metadata.len())),
+                                            Err(_) => ui.weak("not created yet"),
+                                        };
+                                        ui.end_row();
+                                    }
+                                });
+                        });
+                        match logging::last_save() {
+                            Some(save) => match save.error {
+                                None => ui.label(format!("Last save: {} at {}", save.path, save.time.format("%H:%M:%S"))),
+                                Some(error) => ui.colored_label(
+                                    egui::Color32::from_rgb(220, 80, 80),
+                                    format!("Last save failed: {} at {}: {}", save.path, save.time.format("%H:%M:%S"), error),
+                                ),
+                            },
+                            None => ui.label("Last save: nothing saved since the app started"),
+                        };
+
+                        let problems = logging::recent_problems();
+                        if problems.is_empty() {
+                            ui.label("No errors since the app started");
+                        } else {
+                            egui::CollapsingHeader::new(format!("Recent errors ({})", problems.len()))
+                                .default_open(true)
+                                .show(ui, |ui| {
+                                    egui::ScrollArea::vertical().max_height(120.0).show(ui, |ui| {
+                                        for problem in problems.iter().rev() {
+                                            let color = if problem.level == tracing::Level::ERROR {
+                                                egui::Color32::from_rgb(220, 80, 80)
+                                            } else {
+                                                egui::Color32::from_rgb(230, 160, 40)
+                                            };
+                                            ui.horizontal_wrapped(|ui| {
+                                                ui.label(problem.time.format("%H:%M:%S").to_string());
+                                                ui.colored_label(color, &problem.message);
+                                            });
+                                        }
+                                    });
+                                });
+                        }
+                        ui.label(egui::RichText::new(format!("Full log: in the {} folder, a file per day", logging::LOG_DIR)).weak());
+                        let mut log_save_checks = LOG_SAVE_CHECKS.load(Ordering::Relaxed);
+                        if ui.checkbox(&mut log_save_checks, "Round-trip testing")
+                            .on_hover_text("Check every save against the data file schemas and read it back. Problems are logged as errors, the file is saved anyway.")
+                            .changed()
+                        {
+                            LOG_SAVE_CHECKS.store(log_save_checks, Ordering::Relaxed);
+                        }
+                        let mut show_frame_times = self.frame_times.is_some();
+                        if ui.checkbox(&mut show_frame_times, "Show frame times")
+                            .on_hover_text("How long each frame takes to build, for spotting slow lists and statistics")
+                            .changed()
+                        {
+                            self.frame_times = show_frame_times.then(std::collections::VecDeque::new);
+                        }
+
+                        ui.add_space(8.0);
+                        ui.horizontal(|ui| {
+                            if ui.button("Revert to Default").clicked() {
+                                self.temporary_ui_scale = 2.0;
+                            }
+
+                            ui.with_layout(
+                                egui::Layout::right_to_left(egui::Align::Center),
+                                |ui| {
+                                    if ui.button("Close").clicked() {
+                                        self.temporary_ui_scale = self.ui_scale; // Reset temporary scale
+                                        self.show_settings = false;
+                                    }
+                                    if ui.button("Apply").clicked() {
+                                        self.ui_scale = self.temporary_ui_scale;
+                                        ctx.set_pixels_per_point(self.ui_scale);
+                                    }
+                                },
+                            );
+                        });
+                    });
+            }
+
+            // Add the statistics window after the shortcuts window
+            if self.show_statistics {
+                self.refresh_stats();
+                dialog_window(ctx, &self.settings.window_positions, "statistics", "Statistics")
+                    .collapsible(false)
+                    .resizable(true)
+                    .default_size([400.0, 500.0])
+                    .show(ctx, |ui| {
+                        let content_height = ui.available_height() - 40.0; // Reserve space for close button
+
+                        ui.horizontal(|ui| {
+                            ui.selectable_value(&mut self.selected_stats_tab, StatsTab::Overview, "Overview");
+                            ui.selectable_value(&mut self.selected_stats_tab, StatsTab::Dashboard, "Dashboard");
+                            ui.selectable_value(&mut self.selected_stats_tab, StatsTab::Projects, "Projects");
+                            ui.selectable_value(&mut self.selected_stats_tab, StatsTab::Timeline, "Timeline");
+                            ui.selectable_value(&mut self.selected_stats_tab, StatsTab::Details, "Details");
+                            ui.selectable_value(&mut self.selected_stats_tab, StatsTab::PayPeriods, "Pay Periods");
+                            ui.selectable_value(&mut self.selected_stats_tab, StatsTab::Plan, "Plan");
+                            ui.selectable_value(&mut self.selected_stats_tab, StatsTab::Focus, "Focus");
+                            ui.selectable_value(&mut self.selected_stats_tab, StatsTab::Pomodoro, "Pomodoro");
+                        });
+                        
+                        ui.separator();
+
+                        egui::ScrollArea::vertical()
+                            .max_height(content_height)
+                            .show(ui, |ui| {
+                                match self.selected_stats_tab {
+                                    StatsTab::Overview => {
+                                        ui.heading("Overview");
+                                        ui.add_space(8.0);
+                                        
+                                        // Total tracked time
+                                        let total_time = self.stats.total_time;
+                                        ui.label(format!("Total Time Tracked: {}", self.format_duration(total_time)));
+                                        
+                                        // Active tasks
+                                        ui.label(format!("Currently Active Tasks: {}", self.stats.active_tasks));
+                                        
+                                        // Average task duration
+                                        let avg_duration = if self.stats.task_count > 0 {
+                                            total_time / self.stats.task_count as i64
+                                        } else {
+                                            0
+                                        };
+                                        ui.label(format!("Average Task Duration: {}", self.format_duration(avg_duration)));
+                                        
+                                        ui.add_space(16.0);
+                                        
+                                        // Quick stats grid
+                                        egui::Grid::new("stats_grid")
+                                            .num_columns(2)
+                                            .spacing([40.0, 8.0])
+                                            .show(ui, |ui| {
+                                                ui.label("Total Projects:");
+                                                ui.label(format!("{}", self.folders.len()));
+                                                ui.end_row();
+                                                
+                                                ui.label("Total Tasks:");
+                                                ui.label(format!("{}", self.stats.task_count));
+                                                ui.end_row();
+                                                
+                                                ui.label("Completed Tasks:");
+                                                ui.label(format!("{}", self.stats.completed_tasks));
+                                                ui.end_row();
+                                            });
+
+                                        ui.add_space(16.0);
+
+                                        // Billable vs non-billable breakdown per folder
+                                        ui.horizontal(|ui| {
+                                            ui.label("Billable Time:");
+                                            egui::ComboBox::from_id_salt("billable_range")
+                                                .selected_text(self.billable_range.label())
+                                                .show_ui(ui, |ui| {
+                                                    for range in reports::DateRange::ALL {
+                                                        if ui.selectable_value(&mut self.billable_range, range, range.label()).changed() {
+                                                            self.stats.key = None;
+                                                        }
+                                                    }
+                                                });
+                                        });
+                                        ui.add_space(4.0);
+
+                                        let billable_split = &self.stats.billable_split;
+                                        let total_billable: i64 = billable_split.iter().map(|(_, b, _)| b).sum();
+                                        let total_non_billable: i64 = billable_split.iter().map(|(_, _, n)| n).sum();
+
+                                        egui::Grid::new("billable_grid")
+                                            .num_columns(4)
+                                            .spacing([20.0, 4.0])
+                                            .striped(true)
+                                            .show(ui, |ui| {
+                                                ui.strong("Project");
+                                                ui.strong("Billable");
+                                                ui.strong("Non-billable");
+                                                ui.strong("Utilization");
+                                                ui.end_row();
+
+                                                for (folder, billable, non_billable) in billable_split {
+                                                    ui.label(self.shown_folder_name(folder));
+                                                    ui.label(self.format_duration(*billable));
+                                                    ui.label(self.format_duration(*non_billable));
+                                                    ui.label(Self::format_utilization(*billable, *non_billable));
+                                                    ui.end_row();
+                                                }
+
+                                                ui.strong("Total");
+                                                ui.strong(self.format_duration(total_billable));
+                                                ui.strong(self.format_duration(total_non_billable));
+                                                ui.strong(Self::format_utilization(total_billable, total_non_billable));
+                                                ui.end_row();
+                                            });
+                                    },
+                                    StatsTab::Projects => {
+                                        ui.heading("Project Statistics");
+                                        ui.add_space(8.0);
+                                        
+                                        // Project time distribution
+                                        let folder_durations = &self.stats.folder_durations;
+                                        let folder_expenses = &self.stats.folder_expenses;
+                                        
+                                        // Skip rendering if no data
+                                        if folder_durations.is_empty() {
+                                            ui.label("No project data available");
+                                            return;
+                                        }
+                                        
+                                        let max_duration = folder_durations[0].1;
+                                        if max_duration == 0 {
+                                            ui.label("No time tracked in any projects");
+                                            return;
+                                        }
+                                        
+                                        // Use a fixed width for consistent layout
+                                        let available_width = ui.available_width();
+                                        let label_width = available_width * 0.3;
+                                        let bar_width = available_width * 0.7;
+                                        
+                                        for (folder, duration) in folder_durations {
+                                            let duration = *duration;
+                                            ui.horizontal(|ui| {
+                                                // Fixed width for the folder name
+                                                ui.with_layout(egui::Layout::left_to_right(egui::Align::Center), |ui| {
+                                                    ui.set_min_width(label_width);
+                                                    ui.label(self.shown_folder_name(folder));
+                                                });
+                                                
+                                                // Fixed width for the progress bar
+                                                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                                    ui.set_min_width(bar_width);
+                                                    let progress = duration as f32 / max_duration as f32;
+                                                    let bar = egui::ProgressBar::new(progress)
+                                                        .text(self.format_duration(duration))
+                                                        .animate(false);  // Disable animation
+                                                    ui.add(bar);
+                                                });
+                                            });
+                                        }
+
+                                        // Expenses logged per project
+                                        if !folder_expenses.is_empty() {
+                                            ui.add_space(16.0);
+                                            ui.label("Expenses:");
+                                            ui.add_space(4.0);
+
+                                            egui::Grid::new("project_expenses_grid")
+                                                .num_columns(2)
+                                                .spacing([40.0, 4.0])
+                                                .show(ui, |ui| {
+                                                    for (folder, amount) in folder_expenses {
+                                                        ui.label(self.shown_folder_name(folder));
+                                                        ui.label(format!("{:.2}", amount));
+                                                        ui.end_row();
+                                                    }
+                                                    ui.strong("Total");
+                                                    ui.strong(format!("{:.2}", folder_expenses.iter().map(|(_, a)| a).sum::<f64>()));
+                                                    ui.end_row();
+                                                });
+                                        }
+                                    },
+                                    StatsTab::Timeline => {
+                                        ui.heading("Activity Timeline");
+                                        ui.add_space(8.0);
+                                        
+                                        let today = self.calendar().today();
+                                        let first_day = today - chrono::Duration::days(6);
+
+                                        let source = self.settings.activity.source;
+                                        if self.settings.activity.is_configured() {
+                                            ui.horizontal(|ui| {
+                                                let can_load = self.integration_job.is_none();
+                                                if ui
+                                                    .add_enabled(can_load, egui::Button::new(format!("Load {} data", source.label())))
+                                                    .clicked()
+                                                {
+                                                    self.start_activity_fetch(first_day, today);
+                                                }
+                                                if !can_load {
+                                                    ui.spinner();
+                                                }
+                                            });
+                                        } else {
+                                            ui.label(egui::RichText::new("Connect RescueTime or ActivityWatch in Settings to compare with computer activity")
+                                                .italics()
+                                                .color(egui::Color32::from_rgb(128, 128, 128)));
+                                        }
+                                        ui.add_space(4.0);
+
+                                        let tracked_color = egui::Color32::from_rgb(0, 180, 180);
+                                        let activity_color = egui::Color32::from_rgb(150, 120, 200);
+                                        let days: Vec<_> = self.stats.tracked_days
+                                            .iter()
+                                            .map(|(date, tracked, pomodoros)| {
+                                                let activity = self.activity_overlay.iter().find(|day| day.date == *date);
+                                                (*date, *tracked, *pomodoros, activity)
+                                            })
+                                            .collect();
+                                        let longest_day = days
+                                            .iter()
+                                            .map(|(_, tracked, _, activity)| (*tracked).max(activity.map_or(0, |day| day.total())))
+                                            .max()
+                                            .unwrap_or(0)
+                                            .max(1);
+
+                                        for (date, tracked, pomodoros, activity) in days.iter().rev() {
+                                            ui.horizontal(|ui| {
+                                                ui.strong(date.format("%a, %b %d").to_string());
+                                                if let Some(day) = self.day_off(*date) {
+                                                    ui.label(egui::RichText::new(day.kind.label()).small().weak())
+                                                        .on_hover_text(&day.note);
+                                                }
+                                                if *pomodoros > 0 {
+                                                    ui.label(format!("{} 🍅", pomodoros))
+                                                        .on_hover_text(format!("Full {}-minute pomodoros in the day's sessions", self.pomodoro_minutes()));
+                                                }
+                                                if self.settings.day_review.reviewed_days.contains(date) {
+                                                    ui.label(egui::RichText::new("✔ Reviewed").small().color(tracked_color));
+                                                }
+                                            });
+                                            ui.add(egui::ProgressBar::new(*tracked as f32 / longest_day as f32)
+                                                .fill(tracked_color)
+                                                .text(format!("Tracked {}", Self::format_hours_minutes(*tracked))));
+                                            if let Some(activity) = activity {
+                                                ui.add(egui::ProgressBar::new(activity.total() as f32 / longest_day as f32)
+                                                    .fill(activity_color)
+                                                    .text(format!("{} {}", source.label(), Self::format_hours_minutes(activity.total()))));
+                                                let top_categories: Vec<String> = activity.categories
+                                                    .iter()
+                                                    .take(3)
+                                                    .map(|(name, seconds)| format!("{} {}", name, Self::format_hours_minutes(*seconds)))
+                                                    .collect();
+                                                if !top_categories.is_empty() {
+                                                    ui.label(egui::RichText::new(top_categories.join(" · ")).weak());
+                                                }
+                                            }
+                                            ui.add_space(6.0);
+                                        }
+                                    },
+                                    StatsTab::Dashboard => {
+                                        if self.stats.folder_cards.is_empty() {
+                                            ui.label(egui::RichText::new("No folders yet")
+                                                .italics()
+                                                .color(egui::Color32::from_rgb(128, 128, 128)));
+                                            return;
+                                        }
+                                        let tracked_color = egui::Color32::from_rgb(0, 180, 180);
+                                        let over_color = egui::Color32::from_rgb(220, 80, 80);
+                                        ui.horizontal_wrapped(|ui| {
+                                            for card in &self.stats.folder_cards {
+                                                egui::Frame::group(ui.style()).show(ui, |ui| {
+                                                    ui.set_width(200.0);
+                                                    ui.strong(self.shown_folder_name(&card.folder));
+                                                    ui.label(format!("Spent {}", Self::format_hours_minutes(card.tracked)));
+                                                    if let Some((tracked, budgeted, period)) = card.budget {
+                                                        let used = tracked as f32 / budgeted.max(1) as f32;
+                                                        ui.add(egui::ProgressBar::new(used.min(1.0))
+                                                            .fill(if tracked > budgeted { over_color } else { tracked_color })
+                                                            .text(format!(
+                                                                "{} of {} {}",
+                                                                Self::format_hours_minutes(tracked),
+                                                                Self::format_hours_minutes(budgeted),
+                                                                period.to_lowercase()
+                                                            )));
+                                                    } else {
+                                                        ui.label(egui::RichText::new("No budget").weak());
+                                                    }
+                                                    let billing = &self.settings.billing;
+                                                    if card.earnings.tax > 0.0 {
+                                                        ui.label(format!(
+                                                            "Earned {} + {} tax",
+                                                            billing.format(card.earnings.subtotal),
+                                                            billing.format(card.earnings.tax)
+                                                        )).on_hover_text(format!("{} in total", billing.format(card.earnings.total)));
+                                                    } else if card.earnings.total > 0.0 {
+                                                        ui.label(format!("Earned {}", billing.format(card.earnings.subtotal)));
+                                                    }
+                                                    ui.label(egui::RichText::new(match card.last_active {
+                                                        Some(last_active) => format!("Last active {}", format_relative(last_active)),
+                                                        None => "Never worked on".to_string(),
+                                                    }).weak());
+
+                                                    // Sparkline of the last two weeks, one bar per day
+                                                    let (rect, response) = ui.allocate_exact_size(egui::vec2(200.0, 28.0), egui::Sense::hover());
+                                                    let longest = card.days.iter().copied().max().unwrap_or(0).max(1);
+                                                    let bar_width = rect.width() / card.days.len().max(1) as f32;
+                                                    let today = self.calendar().today();
+                                                    for (index, seconds) in card.days.iter().enumerate() {
+                                                        let height = (*seconds as f32 / longest as f32 * rect.height()).max(1.0);
+                                                        let left = rect.left() + index as f32 * bar_width;
+                                                        let date = today - chrono::Duration::days(card.days.len() as i64 - 1 - index as i64);
+                                                        if self.day_off(date).is_some() {
+                                                            ui.painter().rect_filled(
+                                                                egui::Rect::from_min_max(egui::pos2(left, rect.top()), egui::pos2(left + bar_width, rect.bottom())),
+                                                                0.0,
+                                                                ui.visuals().extreme_bg_color,
+                                                            );
+                                                        }
+                                                        ui.painter().rect_filled(
+                                                            egui::Rect::from_min_max(
+                                                                egui::pos2(left + 1.0, rect.bottom() - height),
+                                                                egui::pos2(left + bar_width - 1.0, rect.bottom()),
+                                                            ),
+                                                            1.0,
+                                                            if *seconds > 0 { tracked_color } else { ui.visuals().faint_bg_color },
+                                                        );
+                                                    }
+                                                    response.on_hover_text(format!(
+                                                        "{} in the last {} days",
+                                                        Self::format_hours_minutes(card.days.iter().sum()),
+                                                        DASHBOARD_DAYS
+                                                    ));
+                                                });
+                                            }
+                                        });
+                                    },
+                                    StatsTab::Plan => {
+                                        ui.heading("Plan Adherence");
+                                        ui.add_space(8.0);
+
+                                        let days = &self.stats.plan_days;
+                                        if days.is_empty() {
+                                            ui.label(egui::RichText::new("No planned blocks in the last two weeks")
+                                                .italics()
+                                                .color(egui::Color32::from_rgb(128, 128, 128)));
+                                            return;
+                                        }
+
+                                        let planned: i64 = days.iter().map(|(_, planned, _)| planned).sum();
+                                        let on_plan: i64 = days.iter().map(|(_, _, on_plan)| on_plan).sum();
+                                        ui.label(format!(
+                                            "{:.0}% of planned time spent as planned over the last two weeks",
+                                            on_plan as f64 / planned.max(1) as f64 * 100.0
+                                        ));
+                                        ui.add_space(4.0);
+
+                                        egui::Grid::new("plan_adherence_grid")
+                                            .num_columns(4)
+                                            .spacing([20.0, 4.0])
+                                            .striped(true)
+                                            .show(ui, |ui| {
+                                                ui.strong("Day");
+                                                ui.strong("Planned");
+                                                ui.strong("As planned");
+                                                ui.strong("Adherence");
+                                                ui.end_row();
+
+                                                for (date, planned, on_plan) in days {
+                                                    ui.label(date.format("%a, %b %d").to_string());
+                                                    ui.label(Self::format_hours_minutes(*planned));
+                                                    ui.label(Self::format_hours_minutes(*on_plan));
+                                                    ui.label(format!("{:.0}%", *on_plan as f64 / (*planned).max(1) as f64 * 100.0));
+                                                    ui.end_row();
+                                                }
+                                            });
+                                    },
+                                    StatsTab::Focus => {
+                                        ui.heading("Deep vs Shallow Work");
+                                        ui.add_space(8.0);
+                                        ui.horizontal(|ui| {
+                                            ui.label("Target deep work share:");
+                                            if ui
+                                                .add(egui::DragValue::new(&mut self.settings.deep_work_target_percent).range(0..=100).suffix("%"))
+                                                .on_hover_text("0 for no target")
+                                                .changed()
+                                            {
+                                                self.save_settings();
+                                            }
+                                        });
+                                        ui.add_space(4.0);
+
+                                        let days = &self.stats.focus_days;
+                                        if days.iter().all(|(_, deep, shallow)| deep + shallow == 0) {
+                                            ui.label(egui::RichText::new("No deep or shallow work in the last four weeks. Folders and tasks are marked in their settings.")
+                                                .italics()
+                                                .color(egui::Color32::from_rgb(128, 128, 128)));
+                                            return;
+                                        }
+                                        let deep_color = egui::Color32::from_rgb(0, 180, 180);
+                                        let trend_color = egui::Color32::from_rgb(230, 150, 40);
+                                        let target_color = egui::Color32::from_rgb(220, 80, 80);
+                                        let target = self.settings.deep_work_target_percent;
+
+                                        // Daily deep share as bars, the rest of each bar is shallow work, with the
+                                        // share over the trailing seven days as the trend line
+                                        let (rect, response) = ui.allocate_exact_size(egui::vec2(ui.available_width().min(560.0), 120.0), egui::Sense::hover());
+                                        let painter = ui.painter();
+                                        let bar_width = rect.width() / days.len() as f32;
+                                        let mut trend = Vec::new();
+                                        for (index, (_, deep, shallow)) in days.iter().enumerate() {
+                                            let left = rect.left() + index as f32 * bar_width;
+                                            if let Some(share) = deep_share(*deep, *shallow) {
+                                                let bar = egui::Rect::from_min_max(
+                                                    egui::pos2(left + 1.0, rect.top()),
+                                                    egui::pos2(left + bar_width - 1.0, rect.bottom()),
+                                                );
+                                                painter.rect_filled(bar, 1.0, ui.visuals().faint_bg_color);
+                                                painter.rect_filled(
+                                                    egui::Rect::from_min_max(egui::pos2(bar.left(), rect.bottom() - share * rect.height()), bar.max),
+                                                    1.0,
+                                                    deep_color,
+                                                );
+                                            }
+                                            let window = &days[index.saturating_sub(6)..=index];
+                                            let (deep, shallow) = window.iter().fold((0, 0), |(deep, shallow), day| (deep + day.1, shallow + day.2));
+                                            if let Some(share) = deep_share(deep, shallow) {
+                                                trend.push(egui::pos2(left + bar_width / 2.0, rect.bottom() - share * rect.height()));
+                                            }
+                                        }
+                                        painter.add(egui::Shape::line(trend, egui::Stroke::new(2.0, trend_color)));
+                                        if target > 0 {
+                                            let y = rect.bottom() - target as f32 / 100.0 * rect.height();
+                                            painter.hline(rect.x_range(), y, egui::Stroke::new(1.0, target_color));
+                                        }
+                                        response.on_hover_text(format!(
+                                            "Deep work share per day over the last {} days. The orange line is the share over the trailing week, the red line the target.",
+                                            FOCUS_DAYS
+                                        ));
+                                        ui.add_space(8.0);
+
+                                        let share_text = |deep: i64, shallow: i64| -> egui::RichText {
+                                            let Some(share) = deep_share(deep, shallow) else {
+                                                return egui::RichText::new("-");
+                                            };
+                                            let text = egui::RichText::new(format!("{:.0}%", share * 100.0));
+                                            if target > 0 && share * 100.0 < target as f32 {
+                                                text.color(target_color)
+                                            } else {
+                                                text
+                                            }
+                                        };
+                                        let calendar = self.calendar();
+                                        let mut weeks: Vec<(NaiveDate, i64, i64)> = Vec::new();
+                                        for (date, deep, shallow) in days {
+                                            let week = calendar.week_start(*date);
+                                            match weeks.last_mut() {
+                                                Some(last) if last.0 == week => {
+                                                    last.1 += deep;
+                                                    last.2 += shallow;
+                                                }
+                                                _ => weeks.push((week, *deep, *shallow)),
+                                            }
+                                        }
+                                        egui::Grid::new("focus_grid")
+                                            .num_columns(4)
+                                            .spacing([20.0, 4.0])
+                                            .striped(true)
+                                            .show(ui, |ui| {
+                                                ui.strong("Week of");
+                                                ui.strong("Deep");
+                                                ui.strong("Shallow");
+                                                ui.strong("Deep share");
+                                                ui.end_row();
+                                                for (week, deep, shallow) in weeks.iter().rev() {
+                                                    ui.label(week.format("%b %d").to_string());
+                                                    ui.label(Self::format_hours_minutes(*deep));
+                                                    ui.label(Self::format_hours_minutes(*shallow));
+                                                    ui.label(share_text(*deep, *shallow));
+                                                    ui.end_row();
+                                                }
+
+                                                ui.strong("Day");
+                                                ui.end_row();
+                                                for (date, deep, shallow) in days.iter().rev().take(7) {
+                                                    ui.label(date.format("%a, %b %d").to_string());
+                                                    ui.label(Self::format_hours_minutes(*deep));
+                                                    ui.label(Self::format_hours_minutes(*shallow));
+                                                    ui.label(share_text(*deep, *shallow));
+                                                    ui.end_row();
+                                                }
+                                            });
+                                    },
+                                    StatsTab::Pomodoro => {
+                                        ui.heading("Pomodoros");
+                                        ui.add_space(8.0);
+                                        let config = &self.settings.pomodoro;
+                                        if config.enabled {
+                                            ui.label(format!(
+                                                "{} minutes of work, {} minute breaks and a {} minute break after every {} (change in Settings)",
+                                                config.work_minutes, config.short_break_minutes, config.long_break_minutes, config.long_break_every
+                                            ));
+                                        } else {
+                                            ui.label("Pomodoro mode is off, turn it on in Settings");
+                                        }
+                                        ui.add_space(4.0);
+
+                                        let days = &self.stats.pomodoro_days;
+                                        if days.iter().all(|day| day.finished + day.cut_short + day.breaks == 0) {
+                                            ui.label(egui::RichText::new("No pomodoros in the last four weeks")
+                                                .italics()
+                                                .color(egui::Color32::from_rgb(128, 128, 128)));
+                                            return;
+                                        }
+                                        let finished_color = egui::Color32::from_rgb(220, 80, 80);
+                                        let cut_short_color = egui::Color32::from_rgb(230, 160, 40);
+
+                                        let finished: usize = days.iter().map(|day| day.finished).sum();
+                                        let started = finished + days.iter().map(|day| day.cut_short).sum::<usize>();
+                                        let breaks: usize = days.iter().map(|day| day.breaks).sum();
+                                        let break_seconds: i64 = days.iter().map(|day| day.break_seconds).sum();
+                                        let skipped: usize = days.iter().map(|day| day.skipped_breaks).sum();
+                                        egui::Grid::new("pomodoro_summary").num_columns(2).spacing([20.0, 4.0]).show(ui, |ui| {
+                                            ui.label("Today:");
+                                            ui.strong(format!("{} 🍅", days.last().map_or(0, |day| day.finished)));
+                                            ui.end_row();
+                                            ui.label("Finished:");
+                                            ui.label(format!("{} of {} started ({:.0}%)", finished, started, finished as f64 / started.max(1) as f64 * 100.0));
+                                            ui.end_row();
+                                            ui.label("Breaks:");
+                                            ui.label(format!(
+                                                "{}, {} on average, {} cut short",
+                                                breaks,
+                                                Self::format_hours_minutes(break_seconds / breaks.max(1) as i64),
+                                                skipped
+                                            ));
+                                            ui.end_row();
+                                        });
+                                        ui.add_space(8.0);
+
+                                        // Finished pomodoros per day, with the ones cut short stacked on top
+                                        let most = days.iter().map(|day| day.finished + day.cut_short).max().unwrap_or(0).max(1);
+                                        let (rect, response) = ui.allocate_exact_size(egui::vec2(ui.available_width().min(560.0), 100.0), egui::Sense::hover());
+                                        let painter = ui.painter();
+                                        let bar_width = rect.width() / days.len() as f32;
+                                        let unit = rect.height() / most as f32;
+                                        for (index, day) in days.iter().enumerate() {
+                                            let left = rect.left() + index as f32 * bar_width + 1.0;
+                                            let right = left + bar_width - 2.0;
+                                            let finished_top = rect.bottom() - day.finished as f32 * unit;
+                                            let cut_short_top = finished_top - day.cut_short as f32 * unit;
+                                            painter.rect_filled(egui::Rect::from_min_max(egui::pos2(left, finished_top), egui::pos2(right, rect.bottom())), 1.0, finished_color);
+                                            painter.rect_filled(egui::Rect::from_min_max(egui::pos2(left, cut_short_top), egui::pos2(right, finished_top)), 1.0, cut_short_color);
+                                        }
+                                        response.on_hover_text(format!(
+                                            "Pomodoros per day over the last {} days. Red ones ran their full length, orange ones were cut short.",
+                                            FOCUS_DAYS
+                                        ));
+                                        ui.add_space(8.0);
+
+                                        egui::Grid::new("pomodoro_days_grid")
+                                            .num_columns(5)
+                                            .spacing([20.0, 4.0])
+                                            .striped(true)
+                                            .show(ui, |ui| {
+                                                ui.strong("Day");
+                                                ui.strong("Finished");
+                                                ui.strong("Cut short");
+                                                ui.strong("Breaks");
+                                                ui.strong("Break time");
+                                                ui.end_row();
+                                                for day in days.iter().rev().take(7) {
+                                                    ui.label(day.date.format("%a, %b %d").to_string());
+                                                    ui.label(day.finished.to_string());
+                                                    ui.label(day.cut_short.to_string());
+                                                    ui.label(day.breaks.to_string());
+                                                    ui.label(Self::format_hours_minutes(day.break_seconds));
+                                                    ui.end_row();
+                                                }
+                                            });
+
+                                        if !self.stats.pomodoro_tasks.is_empty() {
+                                            ui.add_space(8.0);
+                                            ui.strong("Most pomodoros");
+                                            for (task_id, count) in &self.stats.pomodoro_tasks {
+                                                let name = self.tasks.get(task_id).map_or("Deleted task".to_string(), |task| self.shown_task_name(task));
+                                                ui.label(format!("{} 🍅  {}", count, name));
+                                            }
+                                        }
+                                    },
+                                    StatsTab::PayPeriods => {
+                                        ui.heading("Pay Periods");
+                                        ui.add_space(8.0);
+
+                                        let config = &self.settings.pay_period;
+                                        ui.label(format!(
+                                            "{} periods, {:.1} contracted hours each (change in Settings)",
+                                            config.kind.label(),
+                                            config.contracted_hours
+                                        ));
+                                        ui.add_space(4.0);
+
+                                        let periods = self.stats.pay_periods.clone();
+                                        if periods.is_empty() {
+                                            ui.label(egui::RichText::new("No tracked sessions yet")
+                                                .italics()
+                                                .color(egui::Color32::from_rgb(128, 128, 128)));
+                                            return;
+                                        }
+
+                                        if ui.button("📊 Export CSV").clicked() {
+                                            match self.export_pay_periods_to_csv() {
+                                                Ok(filename) => {
+                                                    self.show_exported_file(format!("Pay periods exported to {}", filename), &filename);
+                                                }
+                                                Err(e) => {
+                                                    self.export_message = Some((format!("Error exporting pay periods: {}", e), 3.0));
+                                                }
+                                            }
+                                        }
+                                        ui.add_space(4.0);
+
+                                        egui::Grid::new("pay_periods_grid")
+                                            .num_columns(4)
+                                            .spacing([20.0, 4.0])
+                                            .striped(true)
+                                            .show(ui, |ui| {
+                                                ui.strong("Period");
+                                                ui.strong("Tracked");
+                                                ui.strong("Overtime");
+                                                ui.strong("Carry-over");
+                                                ui.end_row();
+
+                                                for period in periods.iter().rev() {
+                                                    let overtime = period.tracked - period.contracted;
+                                                    let balance_color = |seconds: i64| if seconds < 0 {
+                                                        egui::Color32::from_rgb(220, 80, 80)
+                                                    } else {
+                                                        egui::Color32::from_rgb(0, 180, 180)
+                                                    };
+                                                    ui.label(format!("{} – {}", period.start.format("%b %d"), period.end.format("%b %d, %Y")));
+                                                    ui.label(self.format_duration(period.tracked));
+                                                    ui.label(egui::RichText::new(self.format_signed_duration(overtime)).color(balance_color(overtime)));
+                                                    ui.label(egui::RichText::new(self.format_signed_duration(period.carry_over)).color(balance_color(period.carry_over)));
+                                                    ui.end_row();
+                                                }
+                                            });
+                                    },
+                                    StatsTab::Details => {
+                                        ui.heading("Detailed Statistics");
+                                        ui.add_space(8.0);
+                                        
+                                        // Most time-consuming tasks
+                                        ui.label("Top Tasks by Duration:");
+                                        ui.add_space(4.0);
+                                        
+                                        if self.stats.top_tasks.is_empty() {
+                                            ui.label(egui::RichText::new("No tasks available")
+                                                .italics()
+                                                .color(egui::Color32::from_rgb(128, 128, 128)));
+                                            return;
+                                        }
+                                        
+                                        for (task_id, duration) in &self.stats.top_tasks {
+                                            let Some(task) = self.tasks.get(task_id) else {
+                                                continue;
+                                            };
+                                            ui.horizontal(|ui| {
+                                                // Show folder name along with task description
+                                                let folder_name = self.shown_folder_name(task.folder.as_deref().unwrap_or("Uncategorized"));
+                                                ui.label(format!("{} ({})", self.shown_task_name(task), folder_name));
+                                                
+                                                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                                    ui.label(self.format_duration(*duration));
+                                                });
+                                            });
+                                        }
+                                    }
+                                }
+                            });
+
+                        // Always show close button at the bottom
+                        ui.add_space(8.0);
+                        ui.with_layout(egui::Layout::bottom_up(egui::Align::Center), |ui| {
+                            if ui.button("Close").clicked() {
+                                self.show_statistics = false;
+                            }
+                        });
+                    });
+            }
+
+            // Expenses window
+            if self.show_expenses {
+                let mut expense_to_delete = None;
+
+                dialog_window(ctx, &self.settings.window_positions, "expenses", "Expenses")
+                    .collapsible(false)
+                    .resizable(true)
+                    .default_size([400.0, 400.0])
+                    .show(ctx, |ui| {
+                        egui::Grid::new("new_expense_grid")
+                            .num_columns(2)
+                            .spacing([10.0, 4.0])
+                            .show(ui, |ui| {
+                                ui.label("Description:");
+                                ui.text_edit_singleline(&mut self.new_expense_description);
+                                ui.end_row();
+
+                                ui.label("Amount:");
+                                ui.text_edit_singleline(&mut self.new_expense_amount);
+                                ui.end_row();
+
+                                ui.label("Date (YYYY-MM-DD):");
+                                ui.text_edit_singleline(&mut self.new_expense_date);
+                                ui.end_row();
+
+                                ui.label("Project:");
+                                egui::ComboBox::from_id_salt("new_expense_folder")
+                                    .selected_text(self.new_expense_folder.as_deref().unwrap_or("Uncategorized"))
+                                    .show_ui(ui, |ui| {
+                                        ui.selectable_value(&mut self.new_expense_folder, None, "Uncategorized");
+                                        for folder in &self.folders {
+                                            ui.selectable_value(&mut self.new_expense_folder, Some(folder.clone()), folder);
+                                        }
+                                    });
+                                ui.end_row();
+                            });
+
+                        ui.add_space(4.0);
+                        if ui.button("Add Expense").clicked() {
+                            let amount = self.new_expense_amount.trim().replace(',', ".").parse::<f64>();
+                            let date = NaiveDate::parse_from_str(self.new_expense_date.trim(), "%Y-%m-%d");
+                            match (amount, date) {
+                                _ if self.new_expense_description.trim().is_empty() => {
+                                    self.export_message = Some(("Error adding expense: description is empty".to_string(), 3.0));
+                                }
+                                (Ok(amount), Ok(date)) => {
+                                    self.add_expense(
+                                        self.new_expense_description.trim().to_string(),
+                                        amount,
+                                        date,
+                                        self.new_expense_folder.clone(),
+                                    );
+                                    self.new_expense_description.clear();
+                                    self.new_expense_amount.clear();
+                                }
+                                (Err(_), _) => {
+                                    self.export_message = Some(("Error adding expense: invalid amount".to_string(), 3.0));
+                                }
+                                (_, Err(_)) => {
+                                    self.export_message = Some(("Error adding expense: invalid date".to_string(), 3.0));
+                                }
+                            }
+                        }
+
+                        ui.separator();
+
+                        egui::ScrollArea::vertical()
+                            .max_height(ui.available_height() - 40.0)
+                            .show(ui, |ui| {
+                                if self.expenses.is_empty() {
+                                    ui.label(egui::RichText::new("No expenses logged")
+                                        .italics()
+                                        .color(egui::Color32::from_rgb(128, 128, 128)));
+                                }
+
+                                for expense in &self.expenses {
+                                    ui.horizontal(|ui| {
+                                        ui.label(expense.date.to_string());
+                                        ui.label(&expense.description);
+                                        ui.label(egui::RichText::new(expense.folder.as_deref().unwrap_or("Uncategorized")).weak());
+
+                                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                            if ui.button(fill::TRASH).clicked() {
+                                                expense_to_delete = Some(expense.id.clone());
+                                            }
+                                            ui.label(format!("{:.2}", expense.amount));
+                                        });
+                                    });
+                                }
+                            });
+
+                        ui.add_space(8.0);
+                        ui.with_layout(egui::Layout::bottom_up(egui::Align::Center), |ui| {
+                            if ui.button("Close").clicked() {
+                                self.show_expenses = false;
+                            }
+                        });
+                    });
+
+                if let Some(expense_id) = expense_to_delete {
+                    self.delete_expense(&expense_id);
+                }
+            }
+
+            // Planner window
+            if self.show_planner {
+                let mut block_to_delete = None;
+                let mut block_to_add = None;
+                let mut allocation_change = None;
+                let date = self.planner_date;
+                let now = Local::now();
+
+                dialog_window(ctx, &self.settings.window_positions, "planner", "Planner")
+                    .collapsible(false)
+                    .resizable(true)
+                    .default_size([440.0, 420.0])
+                    .show(ctx, |ui| {
+                        ui.horizontal(|ui| {
+                            if ui.button(fill::CARET_LEFT).clicked() {
+                                self.planner_date = date - chrono::Duration::days(1);
+                            }
+                            ui.strong(date.format("%A, %b %d").to_string());
+                            if ui.button(fill::CARET_RIGHT).clicked() {
+                                self.planner_date = date + chrono::Duration::days(1);
+                            }
+                            if date != now.date_naive() && ui.button("Today").clicked() {
+                                self.planner_date = now.date_naive();
+                            }
+                        });
+                        ui.add_space(4.0);
+
+                        let mut tasks: Vec<_> = self
+                            .tasks
+                            .values()
+                            .filter(|task| task.folder.as_ref().is_none_or(|folder| self.folders.contains(folder)))
+                            .collect();
+                        tasks.sort_by(|a, b| a.description.cmp(&b.description));
+
+                        ui.horizontal(|ui| {
+                            let selected = self
+                                .new_block_task
+                                .as_ref()
+                                .and_then(|task_id| self.tasks.get(task_id))
+                                .map_or("Choose a task", |task| task.description.as_str());
+                            egui::ComboBox::from_id_salt("new_block_task")
+                                .selected_text(selected)
+                                .show_ui(ui, |ui| {
+                                    for task in &tasks {
+                                        ui.selectable_value(&mut self.new_block_task, Some(task.id.clone()), &task.description);
+                                    }
+                                });
+
+                            let mut hour = self.new_block_start.hour();
+                            let mut minute = self.new_block_start.minute();
+                            ui.label("at");
+                            let hour_changed = ui.add(egui::DragValue::new(&mut hour).range(0..=23)).changed();
+                            ui.label(":");
+                            let minute_changed = ui.add(egui::DragValue::new(&mut minute).range(0..=59)).changed();
+                            if hour_changed || minute_changed {
+                                self.new_block_start = NaiveTime::from_hms_opt(hour, minute, 0).unwrap_or(self.new_block_start);
+                            }
+                            ui.label("for");
+                            ui.add(egui::DragValue::new(&mut self.new_block_minutes).range(5..=24 * 60).speed(5).suffix(" min"));
+
+                            if ui.add_enabled(self.new_block_task.is_some(), egui::Button::new("Add")).clicked() {
+                                block_to_add = self.new_block_task.clone();
+                            }
+                        });
+
+                        ui.separator();
+
+                        let blocks: Vec<_> = self.plan.iter().filter(|block| block.date == date).collect();
+                        if blocks.is_empty() {
+                            ui.label(egui::RichText::new("Nothing planned for this day")
+                                .italics()
+                                .color(egui::Color32::from_rgb(128, 128, 128)));
+                        }
+
+                        egui::ScrollArea::vertical()
+                            .max_height(ui.available_height() - 60.0)
+                            .show(ui, |ui| {
+                                egui::Grid::new("planner_grid")
+                                    .num_columns(5)
+                                    .spacing([12.0, 4.0])
+                                    .striped(true)
+                                    .show(ui, |ui| {
+                                        if !blocks.is_empty() {
+                                            ui.strong("Time");
+                                            ui.strong("Task");
+                                            ui.strong("Planned");
+                                            ui.strong("Actual");
+                                            ui.label("");
+                                            ui.end_row();
+                                        }
+
+                                        for block in &blocks {
+                                            let (from, to) = block.window();
+                                            let actual = self.planned_block_actual(block);
+                                            let task_name = self
+                                                .tasks
+                                                .get(&block.task_id)
+                                                .map_or("Deleted task".to_string(), |task| self.shown_task_name(task));
+
+                                            ui.label(format!("{}–{}", from.format("%H:%M"), to.format("%H:%M")));
+                                            ui.label(task_name);
+                                            ui.label(Self::format_hours_minutes(block.minutes as i64 * 60));
+                                            let actual_text = egui::RichText::new(Self::format_hours_minutes(actual));
+                                            // Only judge blocks that have started
+                                            ui.label(if from > now {
+                                                actual_text.weak()
+                                            } else if actual * 2 < block.minutes as i64 * 60 && to <= now {
+                                                actual_text.color(egui::Color32::from_rgb(220, 80, 80))
+                                            } else {
+                                                actual_text.color(egui::Color32::from_rgb(0, 180, 180))
+                                            });
+                                            if ui.button(fill::TRASH).clicked() {
+                                                block_to_delete = Some(block.id.clone());
+                                            }
+                                            ui.end_row();
+                                        }
+                                    });
+                            });
+
+                        if let Some((planned, on_plan)) = self.plan_adherence(date) {
+                            ui.add_space(4.0);
+                            ui.label(format!(
+                                "{} of {} planned spent as planned ({:.0}%)",
+                                Self::format_hours_minutes(on_plan),
+                                Self::format_hours_minutes(planned),
+                                on_plan as f64 / planned.max(1) as f64 * 100.0
+                            ));
+                        }
+
+                        ui.add_space(8.0);
+                        egui::CollapsingHeader::new("Weekly capacity").default_open(true).show(ui, |ui| {
+                            let week = self.capacity_week;
+                            let this_week = self.calendar().week_start(self.calendar().today());
+                            ui.horizontal(|ui| {
+                                if ui.button(fill::CARET_LEFT).clicked() {
+                                    self.capacity_week = week - chrono::Duration::days(7);
+                                }
+                                ui.strong(format!("Week of {}", week.format("%b %d")));
+                                if ui.button(fill::CARET_RIGHT).clicked() {
+                                    self.capacity_week = week + chrono::Duration::days(7);
+                                }
+                                if week != this_week && ui.button("This Week").clicked() {
+                                    self.capacity_week = this_week;
+                                }
+                            });
+
+                            egui::Grid::new("capacity_grid")
+                                .num_columns(4)
+                                .spacing([12.0, 4.0])
+                                .striped(true)
+                                .show(ui, |ui| {
+                                    ui.strong("Folder");
+                                    ui.strong("Allocated");
+                                    ui.strong("Tracked");
+                                    ui.strong("Pace");
+                                    ui.end_row();
+
+                                    for folder in &self.folders {
+                                        let (allocated, tracked, projected) = self.capacity_usage(week, folder);
+                                        ui.label(self.shown_folder_name(folder));
+                                        let mut hours = allocated as f64 / 3600.0;
+                                        if ui.add(egui::DragValue::new(&mut hours).range(0.0..=168.0).speed(0.5).suffix(" h")).changed() {
+                                            allocation_change = Some((week, folder.clone(), hours));
+                                        }
+                                        let over = allocated > 0 && tracked > allocated;
+                                        let tracked_text = egui::RichText::new(Self::format_hours_minutes(tracked));
+                                        ui.label(if over { tracked_text.color(egui::Color32::from_rgb(220, 80, 80)) } else { tracked_text });
+                                        match projected {
+                                            Some(projected) if allocated > 0 && projected > allocated => {
+                                                ui.colored_label(
+                                                    egui::Color32::from_rgb(220, 80, 80),
+                                                    format!("⚠ heading for {}", Self::format_hours_minutes(projected)),
+                                                )
+                                                .on_hover_text("At the pace so far this week the folder goes over its allocation");
+                                            }
+                                            Some(projected) => {
+                                                ui.label(egui::RichText::new(format!("heading for {}", Self::format_hours_minutes(projected))).weak());
+                                            }
+                                            None => {
+                                                ui.label("");
+                                            }
+                                        }
+                                        ui.end_row();
+                                    }
+                                });
+                        });
+
+                        ui.add_space(8.0);
+                        ui.with_layout(egui::Layout::bottom_up(egui::Align::Center), |ui| {
+                            if ui.button("Close").clicked() {
+                                self.show_planner = false;
+                            }
+                        });
+                    });
+
+                if let Some((week, folder, hours)) = allocation_change {
+                    self.set_allocation(week, &folder, hours);
+                }
+                if let Some(task_id) = block_to_add {
+                    self.add_planned_block(task_id, date, self.new_block_start, self.new_block_minutes);
+                    // Suggest the next block right after this one
+                    self.new_block_start += chrono::Duration::minutes(self.new_block_minutes as i64);
+                }
+                if let Some(block_id) = block_to_delete {
+                    self.delete_planned_block(&block_id);
+                }
+            }
+
+            // Stale task cleanup window
+            if self.show_cleanup {
+                let stale = self.stale_tasks(self.stale_weeks);
+                let mut apply = false;
+                let mut restore = None;
+                let mut close = false;
+
+                let mut merge_targets: Vec<_> = self
+                    .tasks
+                    .values()
+                    .filter(|task| !task.archived)
+                    .map(|task| (task.id.clone(), task.description.clone()))
+                    .collect();
+                merge_targets.sort_by(|a, b| a.1.cmp(&b.1));
+
+                dialog_window(ctx, &self.settings.window_positions, "clean_up_tasks", "Clean Up Tasks")
+                    .collapsible(false)
+                    .resizable(true)
+                    .default_size([560.0, 440.0])
+                    .show(ctx, |ui| {
+                        ui.horizontal(|ui| {
+                            ui.label("Tasks with no activity for");
+                            ui.add(egui::DragValue::new(&mut self.stale_weeks).range(1..=104).suffix(" weeks"));
+                        });
+                        ui.horizontal(|ui| {
+                            ui.label(format!("{} stale tasks. Set all to:", stale.len()));
+                            for (label, action) in [
+                                ("Keep", CleanupAction::Keep),
+                                ("Archive", CleanupAction::Archive),
+                                ("Delete", CleanupAction::Delete),
+                            ] {
+                                if ui.button(label).clicked() {
+                                    for task_id in &stale {
+                                        self.cleanup_actions.insert(task_id.clone(), action.clone());
+                                    }
+                                }
+                            }
+                        });
+                        ui.separator();
+
+                        if stale.is_empty() {
+                            ui.label(egui::RichText::new("No stale tasks")
+                                .italics()
+                                .color(egui::Color32::from_rgb(128, 128, 128)));
+                        }
+
+                        egui::ScrollArea::vertical()
+                            .max_height(ui.available_height() - 90.0)
+                            .show(ui, |ui| {
+                                egui::Grid::new("cleanup_grid")
+                                    .num_columns(4)
+                                    .spacing([12.0, 4.0])
+                                    .striped(true)
+                                    .show(ui, |ui| {
+                                        for task_id in &stale {
+                                            let task = &self.tasks[task_id];
+                                            ui.label(&task.description);
+                                            ui.label(egui::RichText::new(task.folder.as_deref().unwrap_or("Uncategorized")).weak());
+                                            ui.label(match task.last_active_at() {
+                                                Some(last) => format!("Active {}", format_relative(last)),
+                                                None => "Never started".to_string(),
+                                            });
+
+                                            let action = self.cleanup_actions.entry(task_id.clone()).or_default();
+                                            let selected_text = match action {
+                                                CleanupAction::Keep => "Keep".to_string(),
+                                                CleanupAction::Archive => "Archive".to_string(),
+                                                CleanupAction::Delete => "Delete".to_string(),
+                                                CleanupAction::MergeInto(into_id) => format!(
+                                                    "Merge into {}",
+                                                    merge_targets
+                                                        .iter()
+                                                        .find(|(id, _)| id == into_id)
+                                                        .map_or("?", |(_, name)| name.as_str())
+                                                ),
+                                            };
+                                            egui::ComboBox::from_id_salt(("cleanup_action", task_id))
+                                                .selected_text(selected_text)
+                                                .width(200.0)
+                                                .show_ui(ui, |ui| {
+                                                    ui.selectable_value(action, CleanupAction::Keep, "Keep");
+                                                    ui.selectable_value(action, CleanupAction::Archive, "Archive");
+                                                    ui.selectable_value(action, CleanupAction::Delete, "Delete");
+                                                    ui.separator();
+                                                    for (id, name) in merge_targets.iter().filter(|(id, _)| id != task_id) {
+                                                        ui.selectable_value(
+                                                            action,
+                                                            CleanupAction::MergeInto(id.clone()),
+                                                            format!("Merge into {}", name),
+                                                        );
+                                                    }
+                                                });
+                                            ui.end_row();
+                                        }
+                                    });
+
+                                let mut archived: Vec<_> = self.tasks.values().filter(|task| task.archived).collect();
+                                if !archived.is_empty() {
+                                    archived.sort_by(|a, b| a.description.cmp(&b.description));
+                                    ui.add_space(8.0);
+                                    ui.collapsing(format!("Archived tasks ({})", archived.len()), |ui| {
+                                        for task in archived {
+                                            ui.horizontal(|ui| {
+                                                if ui.small_button("Restore").clicked() {
+                                                    restore = Some(task.id.clone());
+                                                }
+                                                ui.label(&task.description);
+                                                ui.label(egui::RichText::new(task.folder.as_deref().unwrap_or("Uncategorized")).weak());
+                                            });
+                                        }
+                                    });
+                                }
+                            });
+
+                        ui.add_space(8.0);
+                        ui.horizontal(|ui| {
+                            let pending = stale
+                                .iter()
+                                .filter(|task_id| self.cleanup_actions.get(*task_id).is_some_and(|action| *action != CleanupAction::Keep))
+                                .count();
+                            if ui.add_enabled(pending > 0, egui::Button::new(format!("Apply to {} Tasks", pending))).clicked() {
+                                apply = true;
+                            }
+                            if ui.button("Close").clicked() {
+                                close = true;
+                            }
+                        });
+                    });
+
+                if let Some(task_id) = restore {
+                    if let Some(task) = self.tasks.get_mut(&task_id) {
+                        task.archived = false;
+                    }
+                    self.save_tasks();
+                }
+                if apply {
+                    // Only act on tasks that are still listed as stale
+                    self.cleanup_actions.retain(|task_id, _| stale.contains(task_id));
+                    let (archived, deleted, merged, skipped) = self.apply_cleanup();
+                    let mut message = format!("Archived {}, deleted {}, merged {} tasks", archived, deleted, merged);
+                    if skipped > 0 {
+                        message.push_str(&format!(" ({} merges skipped: target was removed)", skipped));
+                    }
+                    self.export_message = Some((message, 3.0));
+                }
+                if close {
+                    self.show_cleanup = false;
+                    self.cleanup_actions.clear();
+                }
+            }
+
+            // Harvest integration window
+            if self.show_harvest {
+                let mut mappings_changed = false;
+                let is_busy = self.integration_job.is_some();
+
+                dialog_window(ctx, &self.settings.window_positions, "harvest", "Harvest")
+                    .collapsible(false)
+                    .resizable(true)
+                    .default_size([460.0, 480.0])
+                    .show(ctx, |ui| {
+                        if !self.settings.harvest.is_configured() {
+                            ui.label("Enter your Harvest access token and account ID in Settings first.");
+                        }
+
+                        ui.horizontal(|ui| {
+                            ui.add_enabled_ui(self.settings.harvest.is_configured() && !is_busy, |ui| {
+                                if ui.button("Load Projects").clicked() {
+                                    self.start_harvest_fetch();
+                                }
+                                let unpushed = self.harvest_unpushed_entries().len();
+                                if ui.button(format!("Push {} Unpushed", unpushed)).clicked() {
+                                    self.start_harvest_push();
+                                }
+                            });
+                            if is_busy {
+                                ui.spinner();
+                            }
+                        });
+
+                        ui.separator();
+                        ui.label("Folder mappings:");
+                        ui.add_space(4.0);
+
+                        egui::Grid::new("harvest_mappings_grid")
+                            .num_columns(2)
+                            .spacing([10.0, 4.0])
+                            .show(ui, |ui| {
+                                for folder in &self.folders {
+                                    ui.label(folder);
+                                    let current = self.settings.harvest.mappings.get(folder).cloned();
+                                    let selected_text = current
+                                        .as_ref()
+                                        .map(|m| format!("{} / {}", m.project_name, m.task_name))
+                                        .unwrap_or_else(|| "Not mapped".to_string());
+
+                                    egui::ComboBox::from_id_salt(format!("harvest_mapping_{}", folder))
+                                        .selected_text(selected_text)
+                                        .show_ui(ui, |ui| {
+                                            if ui.selectable_label(current.is_none(), "Not mapped").clicked() {
+                                                self.settings.harvest.mappings.remove(folder);
+                                                mappings_changed = true;
+                                            }
+                                            if self.harvest_projects.is_empty() {
+                                                ui.label(egui::RichText::new("Load projects to choose").italics());
+                                            }
+                                            for project in &self.harvest_projects {
+                                                for (task_id, task_name) in &project.tasks {
+                                                    let mapping = harvest::HarvestMapping {
+                                                        project_id: project.id,
+                                                        project_name: project.name.clone(),
+                                                        task_id: *task_id,
+                                                        task_name: task_name.clone(),
+                                                    };
+                                                    let is_selected = current.as_ref() == Some(&mapping);
+                                                    if ui
+                                                        .selectable_label(is_selected, format!("{} / {}", project.name, task_name))
+                                                        .clicked()
+                                                    {
+                                                        self.settings.harvest.mappings.insert(folder.clone(), mapping);
+                                                        mappings_changed = true;
+                                                    }
+                                                }
+                                            }
+                                        });
+                                    ui.end_row();
+                                }
+                            });
+
+                        ui.separator();
+                        ui.label("Last 30 days:");
+                        ui.add_space(4.0);
+
+                        let daily_status = self.harvest_daily_status();
+                        egui::ScrollArea::vertical()
+                            .max_height(ui.available_height() - 40.0)
+                            .show(ui, |ui| {
+                                if daily_status.is_empty() {
+                                    ui.label(egui::RichText::new("No sessions in mapped folders")
+                                        .italics()
+                                        .color(egui::Color32::from_rgb(128, 128, 128)));
+                                }
+                                egui::Grid::new("harvest_status_grid")
+                                    .num_columns(4)
+                                    .spacing([20.0, 4.0])
+                                    .striped(true)
+                                    .show(ui, |ui| {
+                                        for (date, description, hours, pushed, total) in &daily_status {
+                                            ui.label(date.to_string());
+                                            ui.label(description);
+                                            ui.label(format!("{:.2}h", hours));
+                                            if pushed == total {
+                                                ui.label(egui::RichText::new("Pushed").color(egui::Color32::from_rgb(0, 180, 180)));
+                                            } else {
+                                                ui.label(egui::RichText::new(format!("Unpushed ({}/{})", total - pushed, total))
+                                                    .color(egui::Color32::YELLOW));
+                                            }
+                                            ui.end_row();
+                                        }
+                                    });
+                            });
+
+                        ui.add_space(8.0);
+                        ui.with_layout(egui::Layout::bottom_up(egui::Align::Center), |ui| {
+                            if ui.button("Close").clicked() {
+                                self.show_harvest = false;
+                            }
+                        });
+                    });
+
+                if mappings_changed {
+                    self.save_settings();
+                }
+            }
+
+            // Redmine integration window
+            if self.show_redmine {
+                let mut settings_changed = false;
+                let mut tasks_changed = false;
+                let mut retry = None;
+                let is_busy = self.integration_job.is_some();
+
+                dialog_window(ctx, &self.settings.window_positions, "redmine", "Redmine")
+                    .collapsible(false)
+                    .resizable(true)
+                    .default_size([460.0, 520.0])
+                    .show(ctx, |ui| {
+                        if !self.settings.redmine.is_configured() {
+                            ui.label("Enter your Redmine URL and API key in Settings first.");
+                        }
+
+                        ui.horizontal(|ui| {
+                            ui.add_enabled_ui(self.settings.redmine.is_configured() && !is_busy, |ui| {
+                                if ui.button("Load Activities").clicked() {
+                                    self.start_redmine_fetch();
+                                }
+                                let unpushed = self.redmine_unpushed_entries();
+                                if ui.button(format!("Push {} Unpushed", unpushed.len())).clicked() {
+                                    self.start_redmine_push(unpushed);
+                                }
+                            });
+                            if is_busy {
+                                ui.spinner();
+                            }
+                        });
+
+                        ui.separator();
+                        ui.label("Activity types:");
+                        ui.add_space(4.0);
+
+                        let activities = self.redmine_activities.clone();
+                        let activity_combo = |ui: &mut egui::Ui, id: String, value: &mut Option<redmine::RedmineActivity>, none_label: &str| {
+                            let mut changed = false;
+                            egui::ComboBox::from_id_salt(id)
+                                .selected_text(value.as_ref().map(|a| a.name.as_str()).unwrap_or(none_label))
+                                .show_ui(ui, |ui| {
+                                    changed |= ui.selectable_value(value, None, none_label).changed();
+                                    if activities.is_empty() {
+                                        ui.label(egui::RichText::new("Load activities to choose").italics());
+                                    }
+                                    for activity in &activities {
+                                        changed |= ui.selectable_value(value, Some(activity.clone()), &activity.name).changed();
+                                    }
+                                });
+                            changed
+                        };
+
+                        egui::Grid::new("redmine_activities_grid")
+                            .num_columns(2)
+                            .spacing([10.0, 4.0])
+                            .show(ui, |ui| {
+                                ui.label("Default:");
+                                settings_changed |= activity_combo(
+                                    ui,
+                                    "redmine_default_activity".to_string(),
+                                    &mut self.settings.redmine.default_activity,
+                                    "Redmine default",
+                                );
+                                ui.end_row();
+
+                                for folder in &self.folders {
+                                    ui.label(folder);
+                                    let mut mapped = self.settings.redmine.activity_mappings.get(folder).cloned();
+                                    if activity_combo(ui, format!("redmine_activity_{}", folder), &mut mapped, "Use default") {
+                                        match mapped {
+                                            Some(activity) => self.settings.redmine.activity_mappings.insert(folder.clone(), activity),
+                                            None => self.settings.redmine.activity_mappings.remove(folder),
+                                        };
+                                        settings_changed = true;
+                                    }
+                                    ui.end_row();
+                                }
+                            });
+
+                        ui.separator();
+                        ui.label("Issue per task:");
+                        ui.add_space(4.0);
+
+                        let mut task_ids: Vec<_> = self.tasks.keys().cloned().collect();
+                        task_ids.sort_by(|a, b| self.tasks[a].description.cmp(&self.tasks[b].description));
+
+                        egui::ScrollArea::vertical()
+                            .id_salt("redmine_tasks")
+                            .max_height(160.0)
+                            .show(ui, |ui| {
+                                egui::Grid::new("redmine_issues_grid")
+                                    .num_columns(3)
+                                    .spacing([10.0, 4.0])
+                                    .show(ui, |ui| {
+                                        for task_id in &task_ids {
+                                            let Some(task) = self.tasks.get_mut(task_id) else {
+                                                continue;
+                                            };
+                                            ui.label(&task.description);
+                                            ui.label("#");
+                                            let mut issue = task.redmine_issue_id.map(|id| id.to_string()).unwrap_or_default();
+                                            if ui.add(egui::TextEdit::singleline(&mut issue).desired_width(80.0)).changed() {
+                                                let issue = issue.trim();
+                                                if issue.is_empty() {
+                                                    task.redmine_issue_id = None;
+                                                    tasks_changed = true;
+                                                } else if let Ok(id) = issue.parse::<u64>() {
+                                                    task.redmine_issue_id = Some(id);
+                                                    tasks_changed = true;
+                                                }
+                                            }
+                                            ui.end_row();
+                                        }
+                                    });
+                            });
+
+                        ui.separator();
+                        ui.horizontal(|ui| {
+                            ui.label(format!("Failed pushes: {}", self.redmine_queue.len()));
+                            if !self.redmine_queue.is_empty() && !is_busy && ui.button("Retry All").clicked() {
+                                retry = Some(self.redmine_queue.iter().map(|failed| failed.entry.clone()).collect::<Vec<_>>());
+                            }
+                        });
+
+                        egui::ScrollArea::vertical()
+                            .id_salt("redmine_queue")
+                            .max_height(ui.available_height() - 40.0)
+                            .show(ui, |ui| {
+                                for failed in &self.redmine_queue {
+                                    ui.horizontal(|ui| {
+                                        ui.label(format!("{} #{} {:.2}h", failed.entry.date, failed.entry.issue_id, failed.entry.hours));
+                                        ui.label(egui::RichText::new(&failed.error).color(egui::Color32::RED))
+                                            .on_hover_text(format!(
+                                                "{} attempts, last at {}",
+                                                failed.attempts,
+                                                failed.last_attempt.format("%Y-%m-%d %H:%M")
+                                            ));
+                                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                            if !is_busy && ui.button("Retry").clicked() {
+                                                retry = Some(vec![failed.entry.clone()]);
+                                            }
+                                        });
+                                    });
                                 }
+                            });
+
+                        ui.add_space(8.0);
+                        ui.with_layout(egui::Layout::bottom_up(egui::Align::Center), |ui| {
+                            if ui.button("Close").clicked() {
+                                self.show_redmine = false;
+                            }
+                        });
+                    });
+
+                if let Some(mut entries) = retry {
+                    // Pick up issue and activity fixes made since the push failed
+                    for entry in &mut entries {
+                        if let Some(task) = self.tasks.get(&entry.task_id) {
+                            entry.issue_id = task.redmine_issue_id.unwrap_or(entry.issue_id);
+                            entry.activity_id = self.settings.redmine.activity_for(task.folder.as_deref()).map(|a| a.id);
+                        }
+                    }
+                    self.start_redmine_push(entries);
+                }
+                if settings_changed {
+                    self.save_settings();
+                }
+                if tasks_changed {
+                    self.save_tasks();
+                }
+            }
+
+            // Reminder shown when no timer has run for a while during work hours
+            if self.show_idle_reminder {
+                let recent: Vec<(String, String)> = self
+                    .recent_tasks(5)
+                    .into_iter()
+                    .map(|task| (task.id.clone(), task.description.clone()))
+                    .collect();
+                let mut start_task = None;
+                let mut snooze = false;
+
+                dialog_window(ctx, &self.settings.window_positions, "what_are_you_working_on", "What are you working on?")
+                    .collapsible(false)
+                    .resizable(false)
+                    .show(ctx, |ui| {
+                        ui.label(format!(
+                            "No timer has been running for {} minutes.",
+                            self.settings.idle_reminder_minutes
+                        ));
+                        ui.add_space(4.0);
+                        for (task_id, description) in &recent {
+                            if ui.button(format!("{} {}", fill::PLAY, description)).clicked() {
+                                start_task = Some(task_id.clone());
+                            }
+                        }
+                        ui.add_space(8.0);
+                        if ui.button("Not now").clicked() {
+                            snooze = true;
+                        }
+                    });
+
+                if let Some(task_id) = start_task {
+                    let action = if self.tasks.get(&task_id).is_some_and(|task| task.is_paused) {
+                        TaskAction::Resume
+                    } else {
+                        TaskAction::Start
+                    };
+                    self.handle_task_action(&task_id, action);
+                    self.save_tasks();
+                    self.show_idle_reminder = false;
+                }
+                if snooze {
+                    self.show_idle_reminder = false;
+                    self.last_timer_running = Some(Local::now());
+                }
+            }
+
+            // Free time between two meetings
+            if let Some(next_meeting) = self.free_time_prompt {
+                let recent: Vec<(String, String)> = self
+                    .recent_tasks(5)
+                    .into_iter()
+                    .map(|task| (task.id.clone(), task.description.clone()))
+                    .collect();
+                let minutes = (next_meeting - Local::now()).num_minutes();
+                let mut start_task = None;
+                let mut close = false;
+
+                dialog_window(ctx, &self.settings.window_positions, "free_time", "Free Time")
+                    .collapsible(false)
+                    .resizable(false)
+                    .show(ctx, |ui| {
+                        ui.label(format!("You have {} free minutes — start a focus task?", minutes));
+                        ui.label(egui::RichText::new(format!("Next meeting at {}", next_meeting.format("%H:%M"))).weak());
+                        ui.add_space(4.0);
+                        for (task_id, description) in &recent {
+                            if ui.button(format!("{} {}", fill::PLAY, description)).clicked() {
+                                start_task = Some(task_id.clone());
+                            }
+                        }
+                        ui.add_space(8.0);
+                        if ui.button("Not now").clicked() {
+                            close = true;
+                        }
+                    });
+
+                if let Some(task_id) = start_task {
+                    self.switch_to_task(&task_id);
+                    close = true;
+                }
+                // Nothing to suggest once the meeting is about to start
+                if close || minutes <= 0 {
+                    self.free_time_prompt = None;
+                }
+            }
+
+            // Data files that had to be recovered or could not be read
+            if !self.data_file_problems.is_empty() {
+                let mut close = false;
+                dialog_window(ctx, &self.settings.window_positions, "data_file_problems", "Problems Loading Data")
+                    .collapsible(false)
+                    .resizable(false)
+                    .show(ctx, |ui| {
+                        ui.set_max_width(420.0);
+                        for problem in &self.data_file_problems {
+                            ui.label(problem);
+                            ui.add_space(4.0);
+                        }
+                        ui.add_space(4.0);
+                        if ui.button("OK").clicked() {
+                            close = true;
+                        }
+                    });
+                if close {
+                    self.data_file_problems.clear();
+                }
+            }
+
+            // Global search window
+            if self.show_search {
+                let mut open = true;
+                let mut jump = None;
+                let mut start = None;
+                let mut bulk = None;
+                let hits = self.search_everything(&self.search_query);
+                let containing = self.tasks_containing(&self.search_query);
+                let folders = self.folders.clone();
+                dialog_window(ctx, &self.settings.window_positions, "search", "Search")
+                    .collapsible(false)
+                    .resizable(true)
+                    .default_size([420.0, 400.0])
+                    .open(&mut open)
+                    .show(ctx, |ui| {
+                        let response = ui.add(
+                            egui::TextEdit::singleline(&mut self.search_query)
+                                .hint_text("Tasks, folders, tags and session notes")
+                                .desired_width(f32::INFINITY),
+                        );
+                        if self.search_query.is_empty() && ui.memory(|memory| memory.focused().is_none()) {
+                            response.request_focus();
+                        }
+                        // Enter jumps to the best match
+                        if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                            if let Some(hit) = hits.first() {
+                                jump = Some((hit.folder.clone(), hit.task_id.clone()));
                             }
                         }
+                        ui.add_space(4.0);
+                        if hits.is_empty() && !self.search_query.trim().is_empty() {
+                            ui.label(egui::RichText::new("No matches").italics());
+                        }
+
+                        // Bulk edits of the tasks ticked below
+                        if !containing.is_empty() || !self.search_selected.is_empty() {
+                            ui.horizontal(|ui| {
+                                if !containing.is_empty()
+                                    && ui
+                                        .button(format!("Select all containing \"{}\" ({})", self.search_query.trim(), containing.len()))
+                                        .on_hover_text("Tasks with the text in their name, a tag or a session note")
+                                        .clicked()
+                                {
+                                    self.search_selected.extend(containing.iter().cloned());
+                                }
+                                if !self.search_selected.is_empty() && ui.button("Clear selection").clicked() {
+                                    self.search_selected.clear();
+                                }
+                            });
+                        }
+                        if !self.search_selected.is_empty() {
+                            ui.horizontal(|ui| {
+                                ui.label(format!("{} selected:", self.search_selected.len()));
+                                ui.add(egui::TextEdit::singleline(&mut self.search_tag).hint_text("tag").desired_width(100.0));
+                                let tag = self.search_tag.trim().trim_start_matches('#').to_string();
+                                if ui.add_enabled(!tag.is_empty(), egui::Button::new("Add Tag")).clicked() {
+                                    bulk = Some(BulkEdit::AddTag(tag.clone()));
+                                }
+                                if ui.add_enabled(!tag.is_empty(), egui::Button::new("Remove Tag")).clicked() {
+                                    bulk = Some(BulkEdit::RemoveTag(tag));
+                                }
+                                ui.menu_button("Move to…", |ui| {
+                                    for folder in &folders {
+                                        if ui.button(folder).clicked() {
+                                            bulk = Some(BulkEdit::MoveTo(Some(folder.clone())));
+                                            ui.close_menu();
+                                        }
+                                    }
+                                    if ui.button("Uncategorized").clicked() {
+                                        bulk = Some(BulkEdit::MoveTo(None));
+                                        ui.close_menu();
+                                    }
+                                });
+                            });
+                        }
+                        ui.add_space(4.0);
+
+                        egui::ScrollArea::vertical().show(ui, |ui| {
+                            for group in SearchGroup::ALL {
+                                let group_hits: Vec<&SearchHit> = hits.iter().filter(|hit| hit.group == group).collect();
+                                if group_hits.is_empty() {
+                                    continue;
+                                }
+                                ui.strong(format!("{} ({})", group.label(), group_hits.len()));
+                                for hit in group_hits {
+                                    ui.horizontal(|ui| {
+                                        if let Some(task_id) = &hit.task_id {
+                                            let mut selected = self.search_selected.contains(task_id);
+                                            if ui.checkbox(&mut selected, "").on_hover_text("Select for a bulk edit").changed() {
+                                                if selected {
+                                                    self.search_selected.insert(task_id.clone());
+                                                } else {
+                                                    self.search_selected.remove(task_id);
+                                                }
+                                            }
+                                        }
+                                        if ui.button(fill::ARROW_RIGHT).on_hover_text("Jump to").clicked() {
+                                            jump = Some((hit.folder.clone(), hit.task_id.clone()));
+                                        }
+                                        if let Some(task_id) = &hit.task_id {
+                                            if ui.button(fill::PLAY).on_hover_text("Start timer").clicked() {
+                                                start = Some(task_id.clone());
+                                            }
+                                        }
+                                        ui.label(&hit.title);
+                                        if !hit.detail.is_empty() {
+                                            ui.label(egui::RichText::new(&hit.detail).weak());
+                                        }
+                                    });
+                                }
+                                ui.add_space(4.0);
+                            }
+                        });
+                    });
+
+                if let Some((folder, task_id)) = jump {
+                    self.jump_to(ctx, &folder, task_id.as_deref());
+                    open = false;
+                }
+                if let Some(task_id) = start {
+                    let action = match self.tasks.get(&task_id).map(|task| task.status()) {
+                        Some(TaskStatus::Paused) => Some(TaskAction::Resume),
+                        Some(TaskStatus::NotStarted | TaskStatus::Completed) => Some(TaskAction::Start),
+                        Some(TaskStatus::Running) | None => None,
+                    };
+                    if let Some(action) = action {
+                        self.handle_task_action(&task_id, action);
+                        self.save_tasks();
                     }
+                    open = false;
+                }
+                if let Some(edit) = bulk {
+                    let selected: Vec<String> = self.search_selected.iter().cloned().collect();
+                    let changed = self.bulk_edit(&selected, &edit);
+                    let message = match &edit {
+                        BulkEdit::AddTag(tag) => format!("Tagged {} tasks #{}", changed, tag),
+                        BulkEdit::RemoveTag(tag) => format!("Removed #{} from {} tasks", tag, changed),
+                        BulkEdit::MoveTo(folder) => {
+                            format!("Moved {} tasks to {}", changed, folder.as_deref().unwrap_or("Uncategorized"))
+                        }
+                    };
+                    self.export_message = Some((message, 3.0));
+                }
+                if !open {
+                    self.show_search = false;
+                    self.search_selected.clear();
                 }
             }
 
-            // Handle Cmd+Delete for focused item
-            if ctx.input(|i| i.modifiers.command && (i.key_pressed(egui::Key::Backspace) || i.key_pressed(egui::Key::Delete))) {
-                let folders = self.get_folders();
-                if let Some(current_folder_idx) = self.focused_folder_index {
-                    let folder_name = &folders[current_folder_idx];
-                    let folder_id = egui::Id::new(format!("folder_{}", folder_name));
-                    let is_open = ctx.memory(|mem| mem.data.get_temp::<bool>(folder_id).unwrap_or(true));
-                    
-                    // If we have a focused task in an open folder, delete the task
-                    if is_open && self.focused_task_index.is_some() {
-                        let tasks = self.get_tasks_by_folder();
-                        if let Some(task_ids) = tasks.get(folder_name.as_str()) {
-                            if let Some(task_idx) = self.focused_task_index {
-                                self.show_delete_task_confirm = Some(task_ids[task_idx].clone());
-                            }
+            // Asks what to do with timers paused while the user was away
+            if self.show_idle_return {
+                let mut resume = false;
+                let mut keep_time = false;
+                let mut keep_paused = false;
+                let names: Vec<String> = self
+                    .idle_paused
+                    .iter()
+                    .filter_map(|task_id| self.tasks.get(task_id))
+                    .map(|task| self.shown_task_name(task))
+                    .collect();
+                let paused_at = self
+                    .idle_paused
+                    .iter()
+                    .filter_map(|task_id| self.tasks.get(task_id)?.entries.last())
+                    .map(|entry| entry.end)
+                    .max();
+                dialog_window(ctx, &self.settings.window_positions, "welcome_back", "Welcome Back")
+                    .collapsible(false)
+                    .resizable(false)
+                    .show(ctx, |ui| {
+                        if let Some(paused_at) = paused_at {
+                            let away = (Local::now() - paused_at).num_seconds();
+                            ui.label(format!(
+                                "You were away since {} ({}). Paused:",
+                                paused_at.format("%H:%M"),
+                                Self::format_hours_minutes(away)
+                            ));
                         }
-                    } else {
-                        // If we're on a folder header, delete the folder
-                        self.show_clear_folder_confirm = Some(folder_name.clone());
-                    }
+                        for name in &names {
+                            ui.label(format!("• {}", name));
+                        }
+                        ui.add_space(8.0);
+                        ui.horizontal(|ui| {
+                            if ui.button(format!("{} Resume", fill::PLAY))
+                                .on_hover_text("Leave the time away out and carry on from now")
+                                .clicked()
+                            {
+                                resume = true;
+                            }
+                            if ui.button("Keep Time Away")
+                                .on_hover_text("Count the time away, e.g. for a meeting or a call away from the computer")
+                                .clicked()
+                            {
+                                keep_time = true;
+                            }
+                            if ui.button("Keep Paused").on_hover_text("Leave the time away out and stay paused").clicked() {
+                                keep_paused = true;
+                            }
+                        });
+                    });
+                if resume {
+                    self.show_idle_return = false;
+                    self.resume_idle_paused(EntrySource::Gui);
+                }
+                if keep_time {
+                    self.show_idle_return = false;
+                    self.keep_idle_time();
+                }
+                if keep_paused {
+                    self.show_idle_return = false;
+                    self.idle_paused.clear();
                 }
             }
 
-            if ctx.input(|i| i.key_pressed(egui::Key::ArrowUp)) {
-                let folders = self.get_folders();
-                if let Some(current_folder_idx) = self.focused_folder_index {
-                    let folder_name = &folders[current_folder_idx];
-                    let folder_id = egui::Id::new(format!("folder_{}", folder_name));
-                    let is_open = ctx.memory(|mem| mem.data.get_temp::<bool>(folder_id).unwrap_or(true));
-                    
-                    if is_open && self.focused_task_index.is_some() {
-                        // If we're focused on a task, move up through tasks
-                        if let Some(current_task_idx) = self.focused_task_index {
-                            if current_task_idx > 0 {
-                                self.focused_task_index = Some(current_task_idx - 1);
-                            } else {
-                                // If at first task, move to folder header
-                                self.focused_task_index = None;
-                            }
-                        }
+            // Overlapping sessions window
+            if let Some(conflicts) = self.conflicts.clone() {
+                let mut open = true;
+                let mut resolve = None;
+                let time = |entry: &ConflictEntry| {
+                    let end = if entry.end.date_naive() == entry.start.date_naive() {
+                        entry.end.format("%H:%M").to_string()
                     } else {
-                        // If we're on a folder header, move to previous folder
-                        if current_folder_idx > 0 {
-                            self.focused_folder_index = Some(current_folder_idx - 1);
-                            self.focused_task_index = None;
+                        entry.end.format("%a %b %d %H:%M").to_string()
+                    };
+                    format!("{} – {}", entry.start.format("%a %b %d %H:%M"), end)
+                };
+                dialog_window(ctx, &self.settings.window_positions, "conflicts", "Overlapping Sessions")
+                    .collapsible(false)
+                    .resizable(true)
+                    .open(&mut open)
+                    .show(ctx, |ui| {
+                        if conflicts.is_empty() {
+                            ui.label(egui::RichText::new("No overlapping sessions")
+                                .italics()
+                                .color(egui::Color32::from_rgb(128, 128, 128)));
+                            return;
                         }
-                    }
+                        ui.label(format!("{} pairs of sessions cover the same time", conflicts.len()));
+                        ui.add_space(4.0);
+                        egui::ScrollArea::vertical().max_height(420.0).show(ui, |ui| {
+                            for (index, conflict) in conflicts.iter().enumerate() {
+                                let name = |task_id: &str| {
+                                    self.tasks.get(task_id).map(|task| self.shown_task_name(task)).unwrap_or_default()
+                                };
+                                egui::Frame::group(ui.style()).show(ui, |ui| {
+                                    ui.label(format!("{}: {}", name(&conflict.first.task_id), time(&conflict.first)));
+                                    ui.label(format!("{}: {}", name(&conflict.second.task_id), time(&conflict.second)));
+                                    ui.label(egui::RichText::new(format!("{} counted twice", self.format_duration(conflict.overlap))).weak());
+                                    ui.horizontal(|ui| {
+                                        // A session inside the other can't be trimmed to after it
+                                        if conflict.second.end > conflict.first.end
+                                            && ui.button("Trim").on_hover_text("Start the later session when the earlier one ends").clicked()
+                                        {
+                                            resolve = Some((index, ConflictResolution::Trim));
+                                        }
+                                        if ui
+                                            .button("Merge")
+                                            .on_hover_text(format!("One session on {}", name(&conflict.first.task_id)))
+                                            .clicked()
+                                        {
+                                            resolve = Some((index, ConflictResolution::Merge));
+                                        }
+                                        if ui.button("Delete first").clicked() {
+                                            resolve = Some((index, ConflictResolution::DeleteFirst));
+                                        }
+                                        if ui.button("Delete second").clicked() {
+                                            resolve = Some((index, ConflictResolution::DeleteSecond));
+                                        }
+                                    });
+                                });
+                            }
+                        });
+                    });
+                if let Some((index, resolution)) = resolve {
+                    self.resolve_conflict(&conflicts[index], resolution);
+                    self.conflicts = Some(self.find_conflicts());
+                }
+                if !open {
+                    self.conflicts = None;
                 }
             }
 
-            if ctx.input(|i| i.key_pressed(egui::Key::ArrowDown)) {
-                let folders = self.get_folders();
-                if let Some(current_folder_idx) = self.focused_folder_index {
-                    let folder_name = &folders[current_folder_idx];
-                    let folder_id = egui::Id::new(format!("folder_{}", folder_name));
-                    let is_open = ctx.memory(|mem| mem.data.get_temp::<bool>(folder_id).unwrap_or(true));
-                    let tasks = self.get_tasks_by_folder();
-                    let task_ids = tasks.get(folder_name.as_str()).cloned().unwrap_or_default();
-                    
-                    if is_open && !task_ids.is_empty() {
-                        // If folder is open and has tasks
-                        if self.focused_task_index.is_none() {
-                            // If on folder header, move to first task
-                            self.focused_task_index = Some(0);
-                        } else if let Some(current_task_idx) = self.focused_task_index {
-                            // If on a task, try to move to next task
-                            if current_task_idx < task_ids.len() - 1 {
-                                self.focused_task_index = Some(current_task_idx + 1);
-                            } else {
-                                // If at last task, move to next folder
-                                if current_folder_idx < folders.len() - 1 {
-                                    self.focused_folder_index = Some(current_folder_idx + 1);
-                                    self.focused_task_index = None;
-                                }
+            // Year in review window
+            if let Some(review) = self.year_review.take() {
+                let mut open = true;
+                let mut show_year = None;
+                dialog_window(ctx, &self.settings.window_positions, "year_review", "Year in Review")
+                    .collapsible(false)
+                    .resizable(false)
+                    .open(&mut open)
+                    .show(ctx, |ui| {
+                        ui.horizontal(|ui| {
+                            if ui.small_button("◀").clicked() {
+                                show_year = Some(review.year - 1);
                             }
+                            ui.heading(review.year.to_string());
+                            if ui
+                                .add_enabled(review.year < self.calendar().today().year(), egui::Button::new("▶").small())
+                                .clicked()
+                            {
+                                show_year = Some(review.year + 1);
+                            }
+                        });
+                        ui.add_space(4.0);
+                        if review.days_tracked == 0 {
+                            ui.label(egui::RichText::new("Nothing tracked this year")
+                                .italics()
+                                .color(egui::Color32::from_rgb(128, 128, 128)));
+                            return;
                         }
-                    } else {
-                        // If folder is closed or empty, move to next folder
-                        if current_folder_idx < folders.len() - 1 {
-                            self.focused_folder_index = Some(current_folder_idx + 1);
-                            self.focused_task_index = None;
+
+                        egui::Grid::new("year_review_figures")
+                            .num_columns(2)
+                            .spacing([20.0, 4.0])
+                            .show(ui, |ui| {
+                                ui.label("Total:");
+                                ui.strong(Self::format_hours_minutes(review.total));
+                                ui.end_row();
+                                ui.label("Days tracked:");
+                                ui.strong(review.days_tracked.to_string());
+                                ui.end_row();
+                                ui.label("Average per tracked day:");
+                                ui.strong(Self::format_hours_minutes(review.average_per_day()));
+                                ui.end_row();
+                                if let Some((first, last)) = review.longest_streak {
+                                    ui.label("Longest streak:");
+                                    ui.strong(format!("{} days", review.streak_days()))
+                                        .on_hover_text(format!("{} to {}", first.format("%b %d"), last.format("%b %d")));
+                                    ui.end_row();
+                                }
+                            });
+
+                        ui.add_space(8.0);
+                        ui.strong("By month");
+                        let busiest_month = review.months.iter().copied().max().unwrap_or(0).max(1);
+                        for (month, seconds) in review.months.iter().enumerate() {
+                            let name = NaiveDate::from_ymd_opt(review.year, month as u32 + 1, 1)
+                                .map(|date| date.format("%b").to_string())
+                                .unwrap_or_default();
+                            ui.add(egui::ProgressBar::new(*seconds as f32 / busiest_month as f32)
+                                .fill(egui::Color32::from_rgb(0, 180, 180))
+                                .text(format!("{} {}", name, Self::format_hours_minutes(*seconds))));
                         }
-                    }
-                }
-            }
-        }
 
-        // Handle keyboard shortcuts only when no dialog is open
-        if !self.is_any_dialog_open() {
-            if ctx.input(|i| i.modifiers.command && i.key_pressed(egui::Key::N)) {
-                self.show_new_folder_dialog = true;
-                self.focus_new_folder = true;
-            }
-            if ctx.input(|i| i.modifiers.command && i.key_pressed(egui::Key::E)) {
-                if let Err(e) = self.export_to_csv() {
-                    self.export_message = Some((format!("Error exporting CSV: {}", e), 3.0));
+                        ui.add_space(8.0);
+                        egui::Grid::new("year_review_tops")
+                            .num_columns(2)
+                            .spacing([20.0, 4.0])
+                            .striped(true)
+                            .show(ui, |ui| {
+                                ui.strong("Top projects");
+                                ui.end_row();
+                                for (folder, seconds) in &review.top_folders {
+                                    ui.label(self.shown_folder_name(folder));
+                                    ui.label(Self::format_hours_minutes(*seconds));
+                                    ui.end_row();
+                                }
+                                ui.strong("Top tasks");
+                                ui.end_row();
+                                for (task_id, seconds) in &review.top_tasks {
+                                    match self.tasks.get(task_id) {
+                                        Some(task) => ui.label(self.shown_task_name(task)),
+                                        None => ui.weak("(deleted task)"),
+                                    };
+                                    ui.label(Self::format_hours_minutes(*seconds));
+                                    ui.end_row();
+                                }
+                                ui.strong("Busiest weeks");
+                                ui.end_row();
+                                for (week_start, seconds) in &review.busiest_weeks {
+                                    ui.label(format!("Week of {}", week_start.format("%b %d")));
+                                    ui.label(Self::format_hours_minutes(*seconds));
+                                    ui.end_row();
+                                }
+                            });
+
+                        ui.add_space(8.0);
+                        if ui.button("Export HTML").clicked() {
+                            match self.export_year_review(&review) {
+                                Ok(filename) => self.show_exported_file(format!("Year in review saved to {}", filename), &filename),
+                                Err(e) => self.export_message = Some((format!("Error saving year in review: {}", e), 3.0)),
+                            }
+                        }
+                    });
+                if open {
+                    self.year_review = Some(match show_year {
+                        Some(year) => self.year_review(year),
+                        None => review,
+                    });
                 }
             }
-            if ctx.input(|i| i.modifiers.command && i.key_pressed(egui::Key::T)) {
-                if let Some(focused_idx) = self.focused_folder_index {
-                    // If a folder is focused, open the add task dialog for that folder
-                    if let Some(folder_name) = self.folders.get(focused_idx) {
-                        self.show_add_task_dialog = true;
-                        self.add_task_to_folder = Some(folder_name.clone());
-                        self.new_task_in_folder.clear();
-                    }
-                } else {
-                    // If no folder is focused, focus the quick add task input
-                    self.focus_new_task = true;
+
+            // Weekly summary window
+            if let Some((week_start, summary)) = self.weekly_summary.clone() {
+                let mut open = true;
+                dialog_window(ctx, &self.settings.window_positions, "weekly_summary", "Weekly Summary")
+                    .collapsible(false)
+                    .resizable(true)
+                    .open(&mut open)
+                    .show(ctx, |ui| {
+                        ui.label(egui::RichText::new(&summary).monospace());
+
+                        ui.add_space(8.0);
+                        ui.horizontal(|ui| {
+                            let can_save = !self.settings.weekly_summary.save_dir.trim().is_empty();
+                            if ui
+                                .add_enabled(can_save, egui::Button::new("Save to File"))
+                                .on_disabled_hover_text("Set a directory in Settings")
+                                .clicked()
+                            {
+                                match self.save_weekly_summary(week_start, &summary) {
+                                    Ok(path) => self.show_exported_file(format!("Weekly summary saved to {}", path), &path),
+                                    Err(e) => self.export_message = Some((format!("Error saving weekly summary: {}", e), 3.0)),
+                                }
+                            }
+                            let can_email = !self.settings.weekly_summary.email_to.trim().is_empty()
+                                && self.settings.smtp.is_configured()
+                                && self.integration_job.is_none();
+                            if ui
+                                .add_enabled(can_email, egui::Button::new("Send Email"))
+                                .on_disabled_hover_text("Set a recipient and SMTP account in Settings")
+                                .clicked()
+                            {
+                                self.start_summary_email(week_start, summary.clone());
+                            }
+                            if ui.button("Copy").clicked() {
+                                ctx.copy_text(summary.clone());
+                            }
+                        });
+                    });
+                if !open {
+                    self.weekly_summary = None;
                 }
             }
-            if ctx.input(|i| i.modifiers.command && i.key_pressed(egui::Key::S)) {
-                self.show_statistics = true;
-            }
-            if ctx.input(|i| i.modifiers.command && i.key_pressed(egui::Key::Comma)) {
-                self.show_settings = true;
-            }
-        }
 
-        egui::CentralPanel::default().show(ctx, |ui| {
-            ui.heading("Work Timer");
+            // End-of-day review window
+            if let Some(date) = self.show_day_review {
+                let (from, to) = self.calendar().day_range(date);
+                let mut sessions: Vec<(String, DateTime<Local>)> = self
+                    .tasks
+                    .iter()
+                    .flat_map(|(task_id, task)| {
+                        task.entries
+                            .iter()
+                            .filter(|entry| entry.start >= from && entry.start < to)
+                            .map(move |entry| (task_id.clone(), entry.start))
+                    })
+                    .collect();
+                sessions.sort_by_key(|(_, start)| *start);
+                let running: Vec<String> = self
+                    .tasks
+                    .values()
+                    .filter(|task| task.start_time.is_some_and(|start| start < to))
+                    .map(|task| task.description.clone())
+                    .collect();
+                let done: Vec<String> = self.done_on(date).into_iter().map(|task| task.description.clone()).collect();
+
+                let adjustments: Vec<Adjustment> =
+                    self.adjustments.iter().filter(|adjustment| adjustment.date == date).cloned().collect();
+
+                let mut tasks_changed = false;
+                let mut move_task: Option<(String, String)> = None;
+                let mut add_adjustment = false;
+                let mut remove_adjustment: Option<String> = None;
+                let mut confirm = false;
+                let mut close = false;
+
+                dialog_window(ctx, &self.settings.window_positions, "day_review", format!("Review {}", date.format("%A, %b %d")))
+                    .collapsible(false)
+                    .resizable(true)
+                    .default_size([520.0, 400.0])
+                    .show(ctx, |ui| {
+                        if self.settings.day_review.reviewed_days.contains(&date) {
+                            ui.label(egui::RichText::new("✔ This day is confirmed").color(egui::Color32::from_rgb(0, 180, 180)));
+                        }
+                        if sessions.is_empty() {
+                            ui.label(egui::RichText::new("No sessions recorded on this day")
+                                .italics()
+                                .color(egui::Color32::from_rgb(128, 128, 128)));
+                        }
 
-            // Top bar with theme toggle, export and clear buttons
-            ui.horizontal(|ui| {
-                if ui.button(if self.dark_mode { "☀" } else { "🌙" }).clicked() {
-                    self.dark_mode = !self.dark_mode;
-                }
+                        egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                            egui::Grid::new("day_review_grid")
+                                .num_columns(4)
+                                .spacing([10.0, 4.0])
+                                .striped(true)
+                                .show(ui, |ui| {
+                                    ui.strong("Time");
+                                    ui.strong("Task");
+                                    ui.strong("Minutes");
+                                    ui.strong("Notes");
+                                    ui.end_row();
+
+                                    for (task_id, start) in &sessions {
+                                        let Some(task) = self.tasks.get_mut(task_id) else {
+                                            continue;
+                                        };
+                                        let Some(entry) = task.entries.iter_mut().find(|entry| entry.start == *start) else {
+                                            continue;
+                                        };
 
-                if ui.button("⚙").clicked() {
-                    self.show_settings = true;
-                }
+                                        ui.label(format!("{}–{}", entry.start.format("%H:%M"), entry.end.format("%H:%M")));
+
+                                        ui.horizontal(|ui| {
+                                            ui.label(&task.description);
+                                            if task.folder.is_none() {
+                                                egui::ComboBox::from_id_salt(("day_review_folder", task_id, *start))
+                                                    .selected_text("Assign folder")
+                                                    .show_ui(ui, |ui| {
+                                                        for folder in &self.folders {
+                                                            if ui.selectable_label(false, folder).clicked() {
+                                                                move_task = Some((task_id.clone(), folder.clone()));
+                                                            }
+                                                        }
+                                                    });
+                                            }
+                                        });
+
+                                        let seconds = (entry.end - entry.start).num_seconds();
+                                        let mut minutes = seconds / 60;
+                                        let mut drag = ui.add(egui::DragValue::new(&mut minutes).range(0..=24 * 60));
+                                        if seconds >= LONG_SESSION_SECONDS {
+                                            drag = drag.on_hover_text("Unusually long session, was the timer left running?");
+                                            ui.painter().rect_stroke(
+                                                drag.rect.expand(2.0),
+                                                2.0,
+                                                egui::Stroke::new(1.0, egui::Color32::from_rgb(220, 80, 80)),
+                                                egui::StrokeKind::Outside,
+                                            );
+                                        }
+                                        if drag.changed() {
+                                            entry.end = entry.start + chrono::Duration::minutes(minutes);
+                                            tasks_changed = true;
+                                        }
 
-                if ui.button("⌨").clicked() {
-                    self.show_shortcuts = true;
-                }
+                                        let mut notes = entry.annotation.clone().unwrap_or_default();
+                                        if ui.text_edit_singleline(&mut notes).changed() {
+                                            let notes = notes.trim().to_string();
+                                            entry.annotation = (!notes.is_empty()).then_some(notes);
+                                            tasks_changed = true;
+                                        }
+                                        ui.end_row();
+                                    }
+                                });
+                        });
 
-                if ui.button("📊").clicked() {
-                    self.show_statistics = true;
-                }
+                        for description in &running {
+                            ui.label(egui::RichText::new(format!("{} is still running", description)).weak());
+                        }
 
-                ui.separator();
+                        if !done.is_empty() {
+                            ui.add_space(8.0);
+                            ui.strong(format!("{} Done ({})", fill::CHECK_CIRCLE, done.len()));
+                            for description in &done {
+                                ui.label(description);
+                            }
+                        }
 
-                if !self.tasks.is_empty() {
-                    if ui.button("📊 Export All Tasks").clicked() {
-                        match self.export_to_csv() {
-                            Ok(filename) => {
-                                self.export_message =
-                                    Some((format!("Tasks exported to {}", filename), 3.0));
+                        ui.add_space(8.0);
+                        ui.strong("Adjustments");
+                        for adjustment in &adjustments {
+                            ui.horizontal(|ui| {
+                                ui.label(self.format_signed_duration(adjustment.seconds));
+                                ui.label(&adjustment.reason);
+                                if ui.small_button(fill::TRASH).on_hover_text("Remove adjustment").clicked() {
+                                    remove_adjustment = Some(adjustment.id.clone());
+                                }
+                            });
+                        }
+                        ui.horizontal(|ui| {
+                            ui.add(
+                                egui::DragValue::new(&mut self.new_adjustment_minutes)
+                                    .range(-24 * 60..=24 * 60)
+                                    .suffix(" min"),
+                            )
+                            .on_hover_text("Negative to take time off the day");
+                            ui.add(egui::TextEdit::singleline(&mut self.new_adjustment_reason).hint_text("Reason"));
+                            let can_add = self.new_adjustment_minutes != 0 && !self.new_adjustment_reason.trim().is_empty();
+                            if ui.add_enabled(can_add, egui::Button::new("Add")).clicked() {
+                                add_adjustment = true;
                             }
-                            Err(e) => {
-                                eprintln!("Failed to export CSV: {}", e);
-                                self.export_message =
-                                    Some((format!("Error exporting CSV: {}", e), 3.0));
+                        });
+
+                        ui.add_space(8.0);
+                        ui.horizontal(|ui| {
+                            let (confirm_label, close_label) = if self.quit_after_review {
+                                ("Confirm Day and Quit", "Quit")
+                            } else {
+                                ("Confirm Day", "Later")
+                            };
+                            if ui.button(confirm_label).clicked() {
+                                confirm = true;
                             }
-                        }
-                    }
+                            if ui.button(close_label).clicked() {
+                                close = true;
+                            }
+                        });
+                    });
 
-                    if ui.button("🗑 Clear All Tasks").clicked() {
-                        self.show_clear_confirm = true;
-                    }
+                if tasks_changed {
+                    self.save_tasks();
                 }
-            });
-
-            // Show export message if exists
-            if let Some((msg, time_left)) = &mut self.export_message {
-                let color = if msg.starts_with("Error") {
-                    egui::Color32::RED
-                } else {
-                    egui::Color32::GREEN
-                };
-                ui.label(egui::RichText::new(msg.clone()).color(color));
-                *time_left -= ui.input(|i| i.unstable_dt);
-                if *time_left <= 0.0 {
-                    self.export_message = None;
+                if let Some((task_id, folder)) = move_task {
+                    self.move_task_to_folder(&task_id, Some(folder));
+                }
+                if add_adjustment {
+                    self.adjustments.push(Adjustment {
+                        id: Uuid::new_v4().to_string(),
+                        date,
+                        seconds: self.new_adjustment_minutes * 60,
+                        reason: self.new_adjustment_reason.trim().to_string(),
+                    });
+                    self.new_adjustment_reason.clear();
+                    self.save_adjustments();
+                }
+                if let Some(id) = remove_adjustment {
+                    self.adjustments.retain(|adjustment| adjustment.id != id);
+                    self.save_adjustments();
+                }
+                if confirm {
+                    self.settings.day_review.reviewed_days.insert(date);
+                    self.save_settings();
+                }
+                if confirm || close {
+                    self.show_day_review = None;
+                    if self.quit_after_review {
+                        ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+                    }
                 }
-                ctx.request_repaint();
             }
 
-            // Confirmation dialog for clearing all tasks
-            if self.show_clear_confirm {
-                egui::Window::new("Confirm Clear All")
+            // Pause annotation prompt
+            if let Some((task_id, entry_start)) = self.pending_annotation.clone() {
+                let task_name = self.tasks.get(&task_id).map(|task| task.description.clone()).unwrap_or_default();
+                let mut save = false;
+                let mut close = false;
+
+                dialog_window(ctx, &self.settings.window_positions, "what_did_you_just_do", "What did you just do?")
                     .collapsible(false)
                     .resizable(false)
                     .show(ctx, |ui| {
-                        ui.label(
-                            "Are you sure you want to clear all tasks? This cannot be undone.",
-                        );
+                        ui.label(egui::RichText::new(&task_name).weak());
+                        let response = ui.text_edit_singleline(&mut self.annotation_input);
+                        response.request_focus();
+                        if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                            save = true;
+                        }
+
+                        ui.add_space(8.0);
                         ui.horizontal(|ui| {
-                            ui.spacing_mut().item_spacing.x = 10.0;
-                            let yes_button = ui.add(egui::Button::new("Yes"));
-                            let no_button = ui.add(egui::Button::new("No"));
-                            
-                            let dialog_id = ui.id().with("clear_all_dialog");
-                            let focus_id = dialog_id.with("focus");
-                            
-                            // Initialize focus to "yes" if not set
-                            if !ui.memory(|mem| mem.data.get_temp::<bool>(focus_id).is_some()) {
-                                ui.memory_mut(|mem| mem.data.insert_temp(focus_id, true));  // true = yes focused
+                            if ui.button("Save").clicked() {
+                                save = true;
                             }
+                            if ui.button("Skip").clicked() {
+                                close = true;
+                            }
+                        });
+                    });
 
-                            let mut yes_focused = ui.memory(|mem| mem.data.get_temp::<bool>(focus_id).unwrap_or(true));
+                if save {
+                    let annotation = self.annotation_input.trim().to_string();
+                    if let Some(entry) = self
+                        .tasks
+                        .get_mut(&task_id)
+                        .and_then(|task| task.entries.iter_mut().find(|entry| entry.start == entry_start))
+                    {
+                        entry.annotation = (!annotation.is_empty()).then_some(annotation);
+                    }
+                    self.save_tasks();
+                }
+                if save || close {
+                    self.pending_annotation = None;
+                    self.annotation_input.clear();
+                }
+            }
 
-                            // Handle tab navigation
-                            if ui.input(|i| i.key_pressed(egui::Key::Tab)) {
-                                yes_focused = !yes_focused;
-                                ui.memory_mut(|mem| mem.data.insert_temp(focus_id, yes_focused));
-                            }
+            // Plugin manager window
+            if self.show_plugins {
+                dialog_window(ctx, &self.settings.window_positions, "plugins", "Plugins")
+                    .collapsible(false)
+                    .resizable(true)
+                    .default_size([360.0, 300.0])
+                    .show(ctx, |ui| {
+                        ui.label(format!("Rhai scripts in the '{}' directory", plugins::PLUGIN_DIR));
+                        ui.add_space(4.0);
 
-                            // Apply focus based on memory state
-                            if yes_focused {
-                                yes_button.request_focus();
-                            } else {
-                                no_button.request_focus();
+                        if self.plugin_host.plugins.is_empty() {
+                            ui.label(egui::RichText::new("No plugins found")
+                                .italics()
+                                .color(egui::Color32::from_rgb(128, 128, 128)));
+                        }
+
+                        let mut settings_changed = false;
+                        let mut export_plugin = None;
+                        egui::ScrollArea::vertical().max_height(240.0).show(ui, |ui| {
+                            for plugin in &self.plugin_host.plugins {
+                                ui.horizontal(|ui| {
+                                    let mut enabled = !self.settings.disabled_plugins.contains(&plugin.name);
+                                    if ui.checkbox(&mut enabled, &plugin.name).changed() {
+                                        if enabled {
+                                            self.settings.disabled_plugins.retain(|name| name != &plugin.name);
+                                        } else {
+                                            self.settings.disabled_plugins.push(plugin.name.clone());
+                                        }
+                                        settings_changed = true;
+                                    }
+                                    if let Some(label) = &plugin.export_label {
+                                        if ui.add_enabled(enabled, egui::Button::new(format!("Export {}", label))).clicked() {
+                                            export_plugin = Some(plugin.name.clone());
+                                        }
+                                    }
+                                });
+                                if let Some(error) = &plugin.error {
+                                    ui.label(egui::RichText::new(error).color(egui::Color32::from_rgb(220, 80, 80)));
+                                }
                             }
+                        });
+                        if settings_changed {
+                            self.save_settings();
+                        }
+                        if let Some(plugin_name) = export_plugin {
+                            let tasks = plugins::task_array(self.tasks.values());
+                            match self.plugin_host.export(&plugin_name, tasks) {
+                                Ok(filename) => self.show_exported_file(format!("Exported to {}", filename), &filename),
+                                Err(e) => self.export_message = Some((format!("Error exporting with {}: {}", plugin_name, e), 3.0)),
+                            }
+                        }
 
-                            if yes_button.clicked() || (yes_button.has_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter))) {
-                                self.clear_all_tasks();
-                                self.show_clear_confirm = false;
-                                self.export_message = Some(("All tasks cleared".to_string(), 3.0));
+                        ui.add_space(8.0);
+                        ui.horizontal(|ui| {
+                            if ui.button("Reload").clicked() {
+                                self.plugin_host.reload();
                             }
-                            if no_button.clicked() || (no_button.has_focus() && (ui.input(|i| i.key_pressed(egui::Key::Enter)) || ui.input(|i| i.key_pressed(egui::Key::Escape)))) {
-                                self.show_clear_confirm = false;
+                            if ui.button("Close").clicked() {
+                                self.show_plugins = false;
                             }
                         });
                     });
             }
 
-            // Confirmation dialog for clearing a folder
-            if let Some(folder_name) = &self.show_clear_folder_confirm.clone() {
-                let folder_name = folder_name.clone();
-                egui::Window::new(format!("Clear Folder '{}'", folder_name))
+            // Asana/Trello task import window
+            if self.show_task_import {
+                let is_busy = self.integration_job.is_some();
+
+                dialog_window(ctx, &self.settings.window_positions, "import_tasks", "Import Tasks")
                     .collapsible(false)
                     .resizable(false)
                     .show(ctx, |ui| {
-                        ui.label(format!(
-                            "Are you sure you want to delete the folder '{}'? This will remove the folder and all its tasks. This cannot be undone.",
-                            folder_name
-                        ));
+                        egui::Grid::new("task_import_grid")
+                            .num_columns(2)
+                            .spacing([10.0, 4.0])
+                            .show(ui, |ui| {
+                                ui.label("Source:");
+                                ui.horizontal(|ui| {
+                                    for source in [task_import::ImportSource::Asana, task_import::ImportSource::Trello] {
+                                        ui.selectable_value(&mut self.import_source, source, source.label());
+                                    }
+                                });
+                                ui.end_row();
+
+                                ui.label(format!("{} ID:", self.import_source.label()));
+                                ui.text_edit_singleline(&mut self.import_container_id);
+                                ui.end_row();
+
+                                ui.label("Into folder:");
+                                egui::ComboBox::from_id_salt("task_import_folder")
+                                    .selected_text(self.import_folder.as_deref().unwrap_or("Choose a folder"))
+                                    .show_ui(ui, |ui| {
+                                        for folder in &self.folders {
+                                            ui.selectable_value(&mut self.import_folder, Some(folder.clone()), folder);
+                                        }
+                                    });
+                                ui.end_row();
+                            });
+
+                        if !self.import_source.is_configured(&self.settings.task_import) {
+                            ui.label(format!("Add your {} credentials in Settings first.", self.import_source.label()));
+                        }
+                        ui.label(egui::RichText::new("Tasks imported before are updated, not duplicated.").weak());
+
+                        ui.add_space(8.0);
                         ui.horizontal(|ui| {
-                            ui.spacing_mut().item_spacing.x = 10.0;
-                            let yes_button = ui.add(egui::Button::new("Yes"));
-                            let no_button = ui.add(egui::Button::new("No"));
-                            
-                            let dialog_id = ui.id().with("clear_folder_dialog");
-                            let focus_id = dialog_id.with("focus");
-                            
-                            // Initialize focus to "yes" only if focus state doesn't exist yet
-                            if !ui.memory(|mem| mem.data.get_temp::<bool>(focus_id).is_some()) {
-                                ui.memory_mut(|mem| mem.data.insert_temp(focus_id, true));
+                            let folder = self.import_folder.clone().filter(|folder| self.folders.contains(folder));
+                            let can_import = folder.is_some()
+                                && !is_busy
+                                && !self.import_container_id.trim().is_empty()
+                                && self.import_source.is_configured(&self.settings.task_import);
+                            if ui.add_enabled(can_import, egui::Button::new("Import")).clicked() {
+                                if let Some(folder) = folder {
+                                    self.start_task_import(folder);
+                                }
+                            }
+                            if is_busy {
+                                ui.spinner();
+                            }
+                            if ui.button("Close").clicked() {
+                                self.show_task_import = false;
                             }
+                        });
+                    });
+            }
 
-                            let mut yes_focused = ui.memory(|mem| mem.data.get_temp::<bool>(focus_id).unwrap_or(true));
+            // Whole-workspace backup as a single zip file
+            if self.show_backup {
+                dialog_window(ctx, &self.settings.window_positions, "backup", "Backup & Restore")
+                    .collapsible(false)
+                    .resizable(false)
+                    .show(ctx, |ui| {
+                        ui.horizontal(|ui| {
+                            ui.label("Archive:");
+                            ui.add(egui::TextEdit::singleline(&mut self.backup_path).desired_width(280.0));
+                        });
+                        ui.label(
+                            egui::RichText::new(
+                                "Includes tasks, folders, settings, expenses, the plan, the activity log and attachments. \
+                                 Tokens and passwords are left out.",
+                            )
+                            .weak(),
+                        );
 
-                            // Handle tab navigation
-                            if ui.input(|i| i.key_pressed(egui::Key::Tab)) {
-                                yes_focused = !yes_focused;
-                                ui.memory_mut(|mem| mem.data.insert_temp(focus_id, yes_focused));
+                        let path = self.backup_path.trim().to_string();
+                        ui.add_space(8.0);
+                        if ui.add_enabled(!path.is_empty(), egui::Button::new(format!("{} Backup to zip", fill::ARCHIVE))).clicked() {
+                            match self.backup_to_zip(&path) {
+                                Ok(count) => self.show_exported_file(format!("Backed up {} files to {}", count, path), &path),
+                                Err(e) => self.export_message = Some((format!("Error creating backup: {}", e), 5.0)),
                             }
+                        }
 
-                            // Apply focus based on memory state
-                            if yes_focused {
-                                yes_button.request_focus();
-                            } else {
-                                no_button.request_focus();
+                        ui.add_space(8.0);
+                        ui.label("Restore from this archive:");
+                        ui.horizontal(|ui| {
+                            let exists = Path::new(&path).is_file();
+                            if ui
+                                .add_enabled(exists, egui::Button::new("Replace workspace"))
+                                .on_hover_text("Your current data is saved to a separate zip first")
+                                .clicked()
+                            {
+                                self.export_message = Some(match self.restore_from_zip(&path, false) {
+                                    Ok(message) => (message, 6.0),
+                                    Err(e) => (format!("Error restoring backup: {}", e), 5.0),
+                                });
+                            }
+                            if ui
+                                .add_enabled(exists, egui::Button::new("Merge into workspace"))
+                                .on_hover_text(
+                                    "Adds tasks, folders, expenses, plan blocks, adjustments, days off and allocations that aren't here \
+                                     yet. Your settings and history are kept.",
+                                )
+                                .clicked()
+                            {
+                                self.export_message = Some(match self.restore_from_zip(&path, true) {
+                                    Ok(message) => (message, 4.0),
+                                    Err(e) => (format!("Error restoring backup: {}", e), 5.0),
+                                });
                             }
+                        });
 
-                            if yes_button.clicked() || (yes_button.has_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter))) {
-                                self.clear_folder(&folder_name);
-                                self.show_clear_folder_confirm = None;
-                                // Clear the focus state from memory when closing
-                                ui.memory_mut(|mem| mem.data.remove::<bool>(focus_id));
-                                self.export_message = Some((format!("Folder '{}' deleted", folder_name), 3.0));
+                        ui.add_space(8.0);
+                        if ui.button("Close").clicked() {
+                            self.show_backup = false;
+                        }
+                    });
+            }
+
+            // Restore tasks from an "Export All Tasks" CSV
+            if self.show_csv_import {
+                dialog_window(ctx, &self.settings.window_positions, "csv_import", "Import from CSV Export")
+                    .collapsible(false)
+                    .resizable(false)
+                    .show(ctx, |ui| {
+                        ui.horizontal(|ui| {
+                            ui.label("File:");
+                            ui.add(egui::TextEdit::singleline(&mut self.csv_import_path).desired_width(260.0));
+                        });
+                        ui.label(
+                            egui::RichText::new("Tasks that already exist are skipped. Totals are restored without individual sessions.")
+                                .weak(),
+                        );
+
+                        ui.add_space(8.0);
+                        ui.horizontal(|ui| {
+                            let path = self.csv_import_path.trim().to_string();
+                            if ui.add_enabled(!path.is_empty(), egui::Button::new("Import")).clicked() {
+                                self.export_message = Some(match self.import_from_csv(&path) {
+                                    Ok((created, skipped)) => {
+                                        self.show_csv_import = false;
+                                        (format!("Imported {} tasks from {} ({} already present)", created, path, skipped), 3.0)
+                                    }
+                                    Err(e) => (format!("Error importing {}: {}", path, e), 5.0),
+                                });
                             }
-                            if no_button.clicked() || (no_button.has_focus() && (ui.input(|i| i.key_pressed(egui::Key::Enter)) || ui.input(|i| i.key_pressed(egui::Key::Escape)))) {
-                                self.show_clear_folder_confirm = None;
-                                // Clear the focus state from memory when closing
-                                ui.memory_mut(|mem| mem.data.remove::<bool>(focus_id));
+                            if ui.button("Cancel").clicked() {
+                                self.show_csv_import = false;
                             }
                         });
                     });
             }
 
-            // Confirmation dialog for deleting a task
-            if let Some(task_id) = &self.show_delete_task_confirm.clone() {
-                let task_id = task_id.clone();
-                let task_info = self.tasks.get(&task_id).map(|task| (task.description.clone()));
-                if let Some(task_description) = task_info {
-                    egui::Window::new("Delete Task")
+            // Task details window
+            if let Some(task_id) = self.show_task_details.clone() {
+                let mut task_changed = false;
+                let mut open = true;
+                let mut move_entry_to = None;
+                let mut open_attachment = None;
+                let mut remove_attachment = None;
+                // Files dropped anywhere on the window while the details are open
+                let mut attach: Vec<std::path::PathBuf> =
+                    ctx.input(|i| i.raw.dropped_files.iter().filter_map(|file| file.path.clone()).collect());
+
+                // Best matches for the move picker, worked out before the task is borrowed
+                let move_candidates: Vec<(String, String)> = if self.moving_entry.as_ref().is_some_and(|(id, _)| *id == task_id) {
+                    let mut scored: Vec<(i32, String, String)> = self
+                        .tasks
+                        .values()
+                        .filter(|task| task.id != task_id && !task.archived)
+                        .filter_map(|task| {
+                            let name = self.shown_task_name(task);
+                            let folder = self.shown_folder_name(task.folder.as_deref().unwrap_or("Uncategorized"));
+                            let score = fuzzy_score(&self.move_entry_query, &name)
+                                .max(fuzzy_score(&self.move_entry_query, &format!("{} {}", folder, name)).map(|score| score - 2))?;
+                            Some((score, task.id.clone(), format!("{}  ·  {}", name, folder)))
+                        })
+                        .collect();
+                    scored.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.2.cmp(&b.2)));
+                    scored.into_iter().take(8).map(|(_, id, label)| (id, label)).collect()
+                } else {
+                    Vec::new()
+                };
+
+                let duration_format = self.settings.duration_format;
+                let was_pinned = self.settings.pinned_tasks.contains(&task_id);
+                let mut pinned = was_pinned;
+                let pins_full = self.settings.pinned_tasks.iter().filter(|id| self.tasks.contains_key(*id)).count() >= MAX_PINNED_TASKS;
+                if let Some(task) = self.tasks.get_mut(&task_id) {
+                    dialog_window(ctx, &self.settings.window_positions, "task_details", "Task Details")
                         .collapsible(false)
                         .resizable(false)
+                        .open(&mut open)
                         .show(ctx, |ui| {
-                            ui.label(format!(
-                                "Are you sure you want to delete task '{}'? This cannot be undone.",
-                                task_description
-                            ));
-                            ui.horizontal(|ui| {
-                                ui.spacing_mut().item_spacing.x = 10.0;
-                                let yes_button = ui.add(egui::Button::new("Yes"));
-                                let no_button = ui.add(egui::Button::new("No"));
-                                
-                                let dialog_id = ui.id().with("delete_task_dialog");
-                                let focus_id = dialog_id.with("focus");
-                                
-                                // Initialize focus to "yes" if not set
-                                if !ui.memory(|mem| mem.data.get_temp::<bool>(focus_id).is_some()) {
-                                    ui.memory_mut(|mem| mem.data.insert_temp(focus_id, true));  // true = yes focused
-                                }
+                            ui.heading(if self.privacy_mode { "Task".to_string() } else { task.description.clone() });
+                            ui.add_space(4.0);
+
+                            egui::Grid::new("task_details_grid")
+                                .num_columns(2)
+                                .spacing([10.0, 4.0])
+                                .show(ui, |ui| {
+                                    ui.label("Folder:");
+                                    ui.label(task.folder.as_deref().unwrap_or("Uncategorized"));
+                                    ui.end_row();
+
+                                    ui.label("Tracked:");
+                                    ui.label(duration_format.format(task.get_current_duration()));
+                                    ui.end_row();
+
+                                    ui.label("Sessions:");
+                                    ui.label(task.entries.len().to_string());
+                                    ui.end_row();
+
+                                    let hours = task.hourly_distribution();
+                                    let busiest = hours.iter().copied().max().unwrap_or(0);
+                                    if busiest > 0 {
+                                        ui.label("Time of day:");
+                                        ui.vertical(|ui| {
+                                            let bar_width = 8.0;
+                                            let height = 40.0;
+                                            let (rect, _) = ui.allocate_exact_size(egui::vec2(bar_width * 24.0, height), egui::Sense::hover());
+                                            let color = ui.visuals().selection.bg_fill;
+                                            for (hour, seconds) in hours.iter().enumerate() {
+                                                let bar_height = height * *seconds as f32 / busiest as f32;
+                                                let left = rect.left() + hour as f32 * bar_width;
+                                                let bar = egui::Rect::from_min_max(
+                                                    egui::pos2(left + 1.0, rect.bottom() - bar_height),
+                                                    egui::pos2(left + bar_width - 1.0, rect.bottom()),
+                                                );
+                                                ui.painter().rect_filled(bar, 1.0, color);
+                                                // Per-hour totals on hover
+                                                let column = egui::Rect::from_min_max(
+                                                    egui::pos2(left, rect.top()),
+                                                    egui::pos2(left + bar_width, rect.bottom()),
+                                                );
+                                                if *seconds > 0 && ui.rect_contains_pointer(column) {
+                                                    egui::show_tooltip_at_pointer(ui.ctx(), ui.layer_id(), ui.id().with(("hour_tooltip", hour)), |ui| {
+                                                        ui.label(format!("{:02}:00–{:02}:00: {}", hour, (hour + 1) % 24, Self::format_hours_minutes(*seconds)));
+                                                    });
+                                                }
+                                            }
+                                            let (axis, _) = ui.allocate_exact_size(egui::vec2(bar_width * 24.0, 12.0), egui::Sense::hover());
+                                            for hour in [0, 6, 12, 18] {
+                                                ui.painter().text(
+                                                    egui::pos2(axis.left() + hour as f32 * bar_width, axis.top()),
+                                                    egui::Align2::LEFT_TOP,
+                                                    hour.to_string(),
+                                                    egui::FontId::proportional(10.0),
+                                                    ui.visuals().weak_text_color(),
+                                                );
+                                            }
+                                        });
+                                        ui.end_row();
+                                    }
 
-                                let mut yes_focused = ui.memory(|mem| mem.data.get_temp::<bool>(focus_id).unwrap_or(true));
+                                    ui.label("Billable:");
+                                    task_changed |= ui.checkbox(&mut task.billable, "").changed();
+                                    ui.end_row();
+
+                                    ui.label("Quick start:");
+                                    ui.add_enabled(was_pinned || !pins_full, egui::Checkbox::new(&mut pinned, "Pin to the top bar"))
+                                        .on_disabled_hover_text(format!("Up to {} tasks can be pinned", MAX_PINNED_TASKS));
+                                    ui.end_row();
+
+                                    ui.label("Tags:");
+                                    task_changed |= edit_tags(ui, ui.id().with(("task_tags", &task_id)), &mut task.tags);
+                                    ui.end_row();
+
+                                    ui.label("Work type:");
+                                    let folder_kind = task
+                                        .folder
+                                        .as_ref()
+                                        .and_then(|folder| self.folder_styles.get(folder)?.work_kind);
+                                    let unset_label = match folder_kind {
+                                        Some(kind) => format!("As the folder ({})", kind.label()),
+                                        None => "Not set".to_string(),
+                                    };
+                                    task_changed |= edit_work_kind(ui, ("task_work_kind", &task_id), &mut task.work_kind, &unset_label);
+                                    ui.end_row();
+
+                                    ui.label("Color label:");
+                                    egui::ComboBox::from_id_salt(("task_color_label", &task_id))
+                                        .selected_text(task.color_label.map_or("None", |label| label.label()))
+                                        .show_ui(ui, |ui| {
+                                            task_changed |= ui.selectable_value(&mut task.color_label, None, "None").changed();
+                                            for option in ColorLabel::ALL {
+                                                task_changed |= ui.selectable_value(&mut task.color_label, Some(option), option.text()).changed();
+                                            }
+                                        });
+                                    ui.end_row();
+
+                                    ui.label("Hourly rate:");
+                                    task_changed |= edit_optional_rate(ui, &mut task.hourly_rate);
+                                    ui.end_row();
+
+                                    if task.is_meeting() {
+                                        ui.label("Attendees:");
+                                        let mut attendees = task.attendees.unwrap_or(0);
+                                        if ui.add(egui::DragValue::new(&mut attendees).range(0..=1000)).on_hover_text("0 for none").changed() {
+                                            task.attendees = (attendees > 0).then_some(attendees);
+                                            task_changed = true;
+                                        }
+                                        ui.end_row();
+
+                                        ui.label("Average rate:");
+                                        ui.horizontal(|ui| {
+                                            task_changed |= edit_optional_rate(ui, &mut task.attendee_rate);
+                                            ui.label("per attendee and hour");
+                                        });
+                                        ui.end_row();
+
+                                        if let Some(cost) = task.meeting_cost() {
+                                            ui.label("Meeting cost:");
+                                            ui.strong(format!("{:.2}", cost));
+                                            ui.end_row();
+                                        }
+                                    }
 
-                                // Handle tab navigation
-                                if ui.input(|i| i.key_pressed(egui::Key::Tab)) {
-                                    yes_focused = !yes_focused;
-                                    ui.memory_mut(|mem| mem.data.insert_temp(focus_id, yes_focused));
-                                }
+                                    ui.label("Estimate:");
+                                    ui.horizontal(|ui| {
+                                        task_changed |= if task.estimate_in_pomodoros {
+                                            edit_optional_pomodoros(ui, &mut task.estimate_minutes, self.settings.pomodoro.work_minutes.max(1))
+                                        } else {
+                                            edit_optional_minutes(ui, &mut task.estimate_minutes)
+                                        };
+                                        task_changed |= ui.checkbox(&mut task.estimate_in_pomodoros, "In pomodoros").changed();
+                                        if let Some(estimate) = task.estimate_minutes {
+                                            let used = task.get_current_duration() as f64 / (estimate as f64 * 60.0);
+                                            ui.label(egui::RichText::new(format!("{:.0}% used", used * 100.0)).weak());
+                                        }
+                                    });
+                                    ui.end_row();
+
+                                    ui.label("Jira issue key:");
+                                    let mut issue_key = task.issue_key.clone().unwrap_or_default();
+                                    if ui.text_edit_singleline(&mut issue_key).changed() {
+                                        let issue_key = issue_key.trim().to_uppercase();
+                                        task.issue_key = if issue_key.is_empty() { None } else { Some(issue_key) };
+                                        task_changed = true;
+                                    }
+                                    ui.end_row();
+
+                                    ui.label("Redmine issue:");
+                                    let mut issue = task.redmine_issue_id.map(|id| id.to_string()).unwrap_or_default();
+                                    if ui.text_edit_singleline(&mut issue).changed() {
+                                        let issue = issue.trim();
+                                        if issue.is_empty() {
+                                            task.redmine_issue_id = None;
+                                            task_changed = true;
+                                        } else if let Ok(id) = issue.parse::<u64>() {
+                                            task.redmine_issue_id = Some(id);
+                                            task_changed = true;
+                                        }
+                                    }
+                                    ui.end_row();
+                                });
 
-                                // Apply focus based on memory state
-                                if yes_focused {
-                                    yes_button.request_focus();
-                                } else {
-                                    no_button.request_focus();
-                                }
+                            ui.separator();
+                            egui::CollapsingHeader::new(format!("Attachments ({})", task.attachments.len()))
+                                .default_open(!task.attachments.is_empty())
+                                .show(ui, |ui| {
+                                    ui.horizontal_wrapped(|ui| {
+                                        for path in &task.attachments {
+                                            ui.vertical(|ui| {
+                                                ui.set_max_width(110.0);
+                                                let thumbnail = self.attachment_thumbnails.entry(path.clone()).or_insert_with(|| {
+                                                    attachments::thumbnail(path)
+                                                        .map(|image| ui.ctx().load_texture(path, image, egui::TextureOptions::default()))
+                                                });
+                                                let response = match thumbnail {
+                                                    Some(texture) => ui.add(egui::ImageButton::new(egui::load::SizedTexture::from_handle(texture))),
+                                                    None => ui.add_sized([96.0, 96.0], egui::Button::new(egui::RichText::new(fill::FILE).size(32.0))),
+                                                };
+                                                if response.on_hover_text("Open").clicked() {
+                                                    open_attachment = Some(path.clone());
+                                                }
+                                                ui.horizontal(|ui| {
+                                                    ui.add(egui::Label::new(attachments::file_name(path)).truncate());
+                                                    if ui.small_button(fill::TRASH).on_hover_text("Remove attachment").clicked() {
+                                                        remove_attachment = Some(path.clone());
+                                                    }
+                                                });
+                                            });
+                                        }
+                                    });
+                                    ui.horizontal(|ui| {
+                                        ui.add(
+                                            egui::TextEdit::singleline(&mut self.attachment_path)
+                                                .hint_text("File path, or drop files on the window"),
+                                        );
+                                        if ui.add_enabled(!self.attachment_path.trim().is_empty(), egui::Button::new("Attach")).clicked() {
+                                            attach.push(self.attachment_path.trim().into());
+                                            self.attachment_path.clear();
+                                        }
+                                    });
+                                });
 
-                                if yes_button.clicked() || (yes_button.has_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter))) {
-                                    self.tasks.remove(&task_id);
-                                    self.save_tasks();
-                                    self.show_delete_task_confirm = None;
-                                    self.export_message = Some((format!("Task '{}' deleted", task_description), 3.0));
-                                }
-                                if no_button.clicked() || (no_button.has_focus() && (ui.input(|i| i.key_pressed(egui::Key::Enter)) || ui.input(|i| i.key_pressed(egui::Key::Escape)))) {
-                                    self.show_delete_task_confirm = None;
-                                }
-                            });
+                            ui.separator();
+                            egui::CollapsingHeader::new(format!("Entries ({})", task.entries.len()))
+                                .default_open(true)
+                                .show(ui, |ui| {
+                                    if self.entry_undo.as_ref().is_some_and(|(id, _)| *id == task_id)
+                                        && ui.button(format!("{} Undo last change", fill::ARROW_COUNTER_CLOCKWISE)).clicked()
+                                    {
+                                        if let Some((_, entries)) = self.entry_undo.take() {
+                                            let before = task.total_duration();
+                                            task.entries = entries;
+                                            audit::log("entry change undone", task, task.total_duration() - before);
+                                            task_changed = true;
+                                        }
+                                    }
+                                    if task.entries.is_empty() {
+                                        ui.label(egui::RichText::new("No finished sessions yet").italics());
+                                        return;
+                                    }
+
+                                    let mut delete_index = None;
+                                    let mut save_edit = None;
+                                    let mut cancel_edit = false;
+                                    egui::ScrollArea::vertical().max_height(220.0).show(ui, |ui| {
+                                        egui::Grid::new(("task_entries_grid", &task_id))
+                                            .num_columns(4)
+                                            .spacing([10.0, 4.0])
+                                            .striped(true)
+                                            .show(ui, |ui| {
+                                                // Newest first
+                                                for index in (0..task.entries.len()).rev() {
+                                                    let entry = &task.entries[index];
+                                                    let editing = self
+                                                        .editing_entry
+                                                        .as_mut()
+                                                        .filter(|edit| edit.task_id == task_id && edit.index == index);
+                                                    if let Some(edit) = editing {
+                                                        ui.vertical(|ui| {
+                                                            ui.add(egui::TextEdit::singleline(&mut edit.start).desired_width(130.0));
+                                                            ui.add(egui::TextEdit::singleline(&mut edit.end).desired_width(130.0));
+                                                        });
+                                                        let times = (parse_entry_time(&edit.start), parse_entry_time(&edit.end));
+                                                        match times {
+                                                            (Some(start), Some(end)) if end > start => {
+                                                                ui.label(duration_format.format((end - start).num_seconds()));
+                                                            }
+                                                            _ => {
+                                                                ui.label(egui::RichText::new("Invalid").color(egui::Color32::from_rgb(220, 80, 80)))
+                                                                    .on_hover_text("Use YYYY-MM-DD HH:MM, with the end after the start");
+                                                            }
+                                                        }
+                                                        ui.add(egui::TextEdit::singleline(&mut edit.annotation).hint_text("Notes").desired_width(140.0));
+                                                        ui.horizontal(|ui| {
+                                                            if let (Some(start), Some(end)) = times {
+                                                                if end > start && ui.button(fill::CHECK).on_hover_text("Save").clicked() {
+                                                                    save_edit = Some((index, start, end, edit.annotation.trim().to_string()));
+                                                                }
+                                                            }
+                                                            if ui.button(fill::X).on_hover_text("Cancel").clicked() {
+                                                                cancel_edit = true;
+                                                            }
+                                                        });
+                                                    } else {
+                                                        ui.label(format!(
+                                                            "{} – {}",
+                                                            entry.start.format("%a %b %d %H:%M"),
+                                                            if entry.end.date_naive() == entry.start.date_naive() {
+                                                                entry.end.format("%H:%M").to_string()
+                                                            } else {
+                                                                entry.end.format("%a %b %d %H:%M").to_string()
+                                                            }
+                                                        ));
+                                                        ui.label(duration_format.format((entry.end - entry.start).num_seconds()));
+                                                        ui.horizontal(|ui| {
+                                                            if !entry.source.is_gui() {
+                                                                ui.label(egui::RichText::new(entry.source.label()).small().color(egui::Color32::from_rgb(0, 180, 180)))
+                                                                    .on_hover_text("How this session was created");
+                                                            }
+                                                            ui.label(egui::RichText::new(entry.annotation.as_deref().unwrap_or("")).weak());
+                                                        });
+                                                        ui.horizontal(|ui| {
+                                                            if ui.button(fill::PENCIL_SIMPLE).on_hover_text("Edit").clicked() {
+                                                                self.editing_entry = Some(EntryEdit {
+                                                                    task_id: task_id.clone(),
+                                                                    index,
+                                                                    start: entry.start.format(ENTRY_TIME_FORMAT).to_string(),
+                                                                    end: entry.end.format(ENTRY_TIME_FORMAT).to_string(),
+                                                                    annotation: entry.annotation.clone().unwrap_or_default(),
+                                                                });
+                                                            }
+                                                            if ui.button(fill::ARROW_BEND_UP_RIGHT).on_hover_text("Move entry to…").clicked() {
+                                                                self.moving_entry = Some((task_id.clone(), index));
+                                                                self.move_entry_query.clear();
+                                                            }
+                                                            if ui.button(fill::TRASH).on_hover_text("Delete").clicked() {
+                                                                delete_index = Some(index);
+                                                            }
+                                                        });
+                                                    }
+                                                    ui.end_row();
+                                                }
+                                            });
+                                    });
+
+                                    if let Some(index) = self.moving_entry.as_ref().filter(|(id, _)| *id == task_id).map(|(_, index)| *index) {
+                                        if let Some(entry) = task.entries.get(index) {
+                                            ui.separator();
+                                            ui.label(format!(
+                                                "Move the session from {} ({}) to:",
+                                                entry.start.format("%a %b %d %H:%M"),
+                                                duration_format.format((entry.end - entry.start).num_seconds())
+                                            ));
+                                            let response = ui.add(
+                                                egui::TextEdit::singleline(&mut self.move_entry_query)
+                                                    .hint_text("Search tasks")
+                                                    .desired_width(260.0),
+                                            );
+                                            if ui.memory(|memory| memory.focused().is_none()) && self.move_entry_query.is_empty() {
+                                                response.request_focus();
+                                            }
+                                            if move_candidates.is_empty() {
+                                                ui.label(egui::RichText::new("No matching tasks").italics());
+                                            }
+                                            for (candidate_id, label) in &move_candidates {
+                                                if ui.selectable_label(false, label).clicked() {
+                                                    move_entry_to = Some((index, candidate_id.clone()));
+                                                }
+                                            }
+                                            // Enter picks the best match
+                                            if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                                                if let Some((candidate_id, _)) = move_candidates.first() {
+                                                    move_entry_to = Some((index, candidate_id.clone()));
+                                                }
+                                            }
+                                            if ui.button("Cancel").clicked() {
+                                                self.moving_entry = None;
+                                            }
+                                        } else {
+                                            self.moving_entry = None;
+                                        }
+                                    }
+
+                                    if cancel_edit {
+                                        self.editing_entry = None;
+                                    }
+                                    if let Some(index) = delete_index {
+                                        self.entry_undo = Some((task_id.clone(), task.entries.clone()));
+                                        let entry = task.entries.remove(index);
+                                        audit::log("entry deleted", task, -(entry.end - entry.start).num_seconds());
+                                        self.editing_entry = None;
+                                        task_changed = true;
+                                    }
+                                    if let Some((index, start, end, annotation)) = save_edit {
+                                        self.entry_undo = Some((task_id.clone(), task.entries.clone()));
+                                        let entry = &mut task.entries[index];
+                                        // Keep the seconds when a time wasn't changed
+                                        let same_minute = |a: DateTime<Local>, b: DateTime<Local>| {
+                                            a.format(ENTRY_TIME_FORMAT).to_string() == b.format(ENTRY_TIME_FORMAT).to_string()
+                                        };
+                                        let start = if same_minute(start, entry.start) { entry.start } else { start };
+                                        let end = if same_minute(end, entry.end) { entry.end } else { end };
+                                        let delta = (end - start).num_seconds() - (entry.end - entry.start).num_seconds();
+                                        entry.start = start;
+                                        entry.end = end;
+                                        entry.annotation = (!annotation.is_empty()).then_some(annotation);
+                                        task.entries.sort_by_key(|entry| entry.start);
+                                        audit::log("entry edited", task, delta);
+                                        self.editing_entry = None;
+                                        task_changed = true;
+                                    }
+                                });
                         });
+                } else {
+                    open = false;
+                }
+
+                if let Some((index, target_id)) = move_entry_to {
+                    // Only taken out of this task once the target is known to exist
+                    let entry = if self.tasks.contains_key(&target_id) {
+                        self.tasks.get_mut(&task_id).filter(|task| index < task.entries.len()).map(|task| {
+                            let entry = task.entries.remove(index);
+                            audit::log("entry moved away", task, -(entry.end - entry.start).num_seconds());
+                            entry
+                        })
+                    } else {
+                        None
+                    };
+                    if let Some(entry) = entry {
+                        let seconds = (entry.end - entry.start).num_seconds();
+                        if let Some(target) = self.tasks.get_mut(&target_id) {
+                            target.entries.push(entry);
+                            target.entries.sort_by_key(|entry| entry.start);
+                            audit::log("entry moved in", target, seconds);
+                        }
+                        let target_name = self.tasks.get(&target_id).map(|target| self.shown_task_name(target)).unwrap_or_default();
+                        self.export_message = Some((format!("Moved {} to {}", self.format_duration(seconds), target_name), 3.0));
+                        task_changed = true;
+                    }
+                    // The undo snapshot only covers one task, so it no longer applies
+                    self.entry_undo = None;
+                    self.editing_entry = None;
+                    self.moving_entry = None;
+                }
+                if let Some(task) = self.tasks.get_mut(&task_id) {
+                    for path in attach {
+                        match attachments::add(&task_id, &path) {
+                            Ok(stored) => {
+                                task.attachments.push(stored);
+                                task_changed = true;
+                            }
+                            Err(e) => self.export_message = Some((format!("Error attaching file: {}", e), 3.0)),
+                        }
+                    }
+                    if let Some(path) = remove_attachment {
+                        task.attachments.retain(|attachment| *attachment != path);
+                        attachments::remove(&path);
+                        self.attachment_thumbnails.remove(&path);
+                        task_changed = true;
+                    }
+                }
+                if let Some(path) = open_attachment {
+                    if let Err(e) = desktop::open(&path) {
+                        self.export_message = Some((format!("Error opening {}: {}", path, e), 3.0));
+                    }
+                }
+                if task_changed {
+                    self.save_tasks();
+                }
+                if pinned != was_pinned {
+                    // Forget pins of tasks deleted since
+                    self.settings.pinned_tasks.retain(|id| self.tasks.contains_key(id));
+                    if pinned {
+                        self.settings.pinned_tasks.push(task_id.clone());
+                    } else {
+                        self.settings.pinned_tasks.retain(|id| *id != task_id);
+                    }
+                    self.save_settings();
+                }
+                if !open {
+                    self.show_task_details = None;
+                    self.moving_entry = None;
                 }
             }
 
-            // Add the shortcuts popup window
-            if self.show_shortcuts {
-                egui::Window::new("Keyboard Shortcuts")
+            // Export templates window
+            if self.show_export_templates {
+                let mut templates_changed = false;
+                let mut template_to_delete = None;
+
+                dialog_window(ctx, &self.settings.window_positions, "export_templates", "Export Templates")
                     .collapsible(false)
-                    .resizable(false)
+                    .resizable(true)
+                    .default_size([420.0, 420.0])
                     .show(ctx, |ui| {
-                        ui.label("Global Shortcuts:");
-                        ui.add_space(4.0);
-
-                        egui::Grid::new("shortcuts_grid")
-                            .num_columns(2)
-                            .spacing([40.0, 4.0])
-                            .show(ui, |ui| {
-                                ui.label("⌘T");
-                                ui.label("New Task");
-                                ui.end_row();
-
-                                ui.label("⌘D");
-                                ui.label("Toggle Dark/Light Mode");
-                                ui.end_row();
-
-                                ui.label("⌘E");
-                                ui.label("Export All Tasks");
-                                ui.end_row();
-
-                                ui.label("⌘N");
-                                ui.label("New Folder");
-                                ui.end_row();
-
-                                ui.label("⌘S");
-                                ui.label("Show Statistics");
-                                ui.end_row();
-
-                                ui.label("⌘,");
-                                ui.label("Show Settings");
-                                ui.end_row();
-
-                                ui.label("Enter");
-                                ui.label("Create Task/Folder");
-                                ui.end_row();
-                            });
-
-                        ui.add_space(8.0);
                         ui.horizontal(|ui| {
-                            if ui.button("Close").clicked() {
-                                self.show_shortcuts = false;
+                            if ui.button("➕ New Template").clicked() {
+                                let template = ExportTemplate::new(
+                                    format!("Template {}", self.settings.export_templates.len() + 1),
+                                    None,
+                                );
+                                self.selected_export_template = Some(template.id.clone());
+                                self.settings.export_templates.push(template);
+                                templates_changed = true;
                             }
                         });
-                    });
-            }
 
-            // Add the settings popup window
-            if self.show_settings {
-                egui::Window::new("Settings")
-                    .collapsible(false)
-                    .resizable(false)
-                    .show(ctx, |ui| {
-                        ui.heading("UI Scale");
-                        ui.add_space(4.0);
+                        ui.separator();
 
-                        ui.horizontal(|ui| {
-                            if ui.button("➖").clicked() && self.temporary_ui_scale > 1.0 {
-                                self.temporary_ui_scale = (self.temporary_ui_scale - 0.1).max(1.0);
+                        for template in &self.settings.export_templates {
+                            let is_selected = self.selected_export_template.as_deref() == Some(template.id.as_str());
+                            let client = template.folder.as_deref().unwrap_or("All folders");
+                            if ui.selectable_label(is_selected, format!("{} ({})", template.name, client)).clicked() {
+                                self.selected_export_template = Some(template.id.clone());
                             }
+                        }
 
-                            ui.add(
-                                egui::Slider::new(&mut self.temporary_ui_scale, 1.0..=2.5)
-                                    .step_by(0.1)
-                                    .text("Scale"),
-                            );
+                        let folders = self.folders.clone();
+                        let default_format = self.settings.duration_format;
+                        let selected = self.selected_export_template.clone();
+                        if let Some(template) = self
+                            .settings
+                            .export_templates
+                            .iter_mut()
+                            .find(|template| Some(&template.id) == selected.as_ref())
+                        {
+                            ui.separator();
+                            egui::Grid::new("export_template_grid")
+                                .num_columns(2)
+                                .spacing([10.0, 4.0])
+                                .show(ui, |ui| {
+                                    ui.label("Name:");
+                                    templates_changed |= ui.text_edit_singleline(&mut template.name).changed();
+                                    ui.end_row();
+
+                                    ui.label("Client folder:");
+                                    egui::ComboBox::from_id_salt("export_template_folder")
+                                        .selected_text(template.folder.as_deref().unwrap_or("All folders"))
+                                        .show_ui(ui, |ui| {
+                                            templates_changed |= ui.selectable_value(&mut template.folder, None, "All folders").changed();
+                                            for folder in &folders {
+                                                templates_changed |= ui
+                                                    .selectable_value(&mut template.folder, Some(folder.clone()), folder)
+                                                    .changed();
+                                            }
+                                        });
+                                    ui.end_row();
+
+                                    ui.label("Columns:");
+                                    ui.vertical(|ui| {
+                                        for column in ExportColumn::ALL {
+                                            let mut enabled = template.columns.contains(&column);
+                                            let mut checkbox = ui.checkbox(&mut enabled, column.label());
+                                            if column.is_amount() {
+                                                checkbox = checkbox.on_hover_text("The folder's expenses are listed after the tasks");
+                                            }
+                                            if checkbox.changed() {
+                                                // Keep columns in their canonical order
+                                                template.columns = ExportColumn::ALL
+                                                    .into_iter()
+                                                    .filter(|c| if *c == column { enabled } else { template.columns.contains(c) })
+                                                    .collect();
+                                                templates_changed = true;
+                                            }
+                                        }
+                                    });
+                                    ui.end_row();
+
+                                    ui.label("Durations:");
+                                    let app_setting = format!("App setting ({})", default_format.label());
+                                    egui::ComboBox::from_id_salt("export_template_duration_format")
+                                        .selected_text(template.duration_format.map_or(app_setting.as_str(), |format| format.label()))
+                                        .show_ui(ui, |ui| {
+                                            templates_changed |= ui.selectable_value(&mut template.duration_format, None, &app_setting).changed();
+                                            for format in DurationFormat::ALL {
+                                                templates_changed |= ui
+                                                    .selectable_value(&mut template.duration_format, Some(format), format.label())
+                                                    .changed();
+                                            }
+                                        });
+                                    ui.end_row();
+
+                                    ui.label("Round up to (minutes):");
+                                    templates_changed |= ui
+                                        .add(egui::DragValue::new(&mut template.rounding_minutes).range(0..=60))
+                                        .changed();
+                                    ui.end_row();
+
+                                    ui.label("Grouping:");
+                                    egui::ComboBox::from_id_salt("export_template_grouping")
+                                        .selected_text(template.grouping.label())
+                                        .show_ui(ui, |ui| {
+                                            for grouping in [ExportGrouping::Task, ExportGrouping::TaskAndDay, ExportGrouping::Entry] {
+                                                templates_changed |= ui
+                                                    .selectable_value(&mut template.grouping, grouping, grouping.label())
+                                                    .changed();
+                                            }
+                                        });
+                                    ui.end_row();
+
+                                    if template.grouping == ExportGrouping::Entry {
+                                        ui.label("Leave out:");
+                                        ui.horizontal_wrapped(|ui| {
+                                            for source in EntrySource::ALL {
+                                                let mut excluded = template.excluded_sources.contains(&source);
+                                                if ui.checkbox(&mut excluded, source.label()).changed() {
+                                                    template.excluded_sources.retain(|s| *s != source);
+                                                    if excluded {
+                                                        template.excluded_sources.push(source);
+                                                    }
+                                                    templates_changed = true;
+                                                }
+                                            }
+                                        });
+                                        ui.end_row();
+                                    }
 
-                            if ui.button("➕").clicked() && self.temporary_ui_scale < 2.5 {
-                                self.temporary_ui_scale = (self.temporary_ui_scale + 0.1).min(2.5);
+                                    ui.label("Format:");
+                                    egui::ComboBox::from_id_salt("export_template_format")
+                                        .selected_text(template.format.label())
+                                        .show_ui(ui, |ui| {
+                                            for format in [ExportFormat::Csv, ExportFormat::Tsv] {
+                                                templates_changed |= ui
+                                                    .selectable_value(&mut template.format, format, format.label())
+                                                    .changed();
+                                            }
+                                        });
+                                    ui.end_row();
+                                });
+
+                            ui.add_space(4.0);
+                            if ui.button("🗑 Delete Template").clicked() {
+                                template_to_delete = Some(template.id.clone());
                             }
-                        });
+                        }
 
                         ui.add_space(8.0);
-                        ui.horizontal(|ui| {
-                            if ui.button("Revert to Default").clicked() {
-                                self.temporary_ui_scale = 2.0;
+                        ui.with_layout(egui::Layout::bottom_up(egui::Align::Center), |ui| {
+                            if ui.button("Close").clicked() {
+                                self.show_export_templates = false;
                             }
-
-                            ui.with_layout(
-                                egui::Layout::right_to_left(egui::Align::Center),
-                                |ui| {
-                                    if ui.button("Close").clicked() {
-                                        self.temporary_ui_scale = self.ui_scale; // Reset temporary scale
-                                        self.show_settings = false;
-                                    }
-                                    if ui.button("Apply").clicked() {
-                                        self.ui_scale = self.temporary_ui_scale;
-                                        ctx.set_pixels_per_point(self.ui_scale);
-                                    }
-                                },
-                            );
                         });
                     });
+
+                if let Some(template_id) = template_to_delete {
+                    self.settings.export_templates.retain(|template| template.id != template_id);
+                    self.selected_export_template = None;
+                    templates_changed = true;
+                }
+                if templates_changed {
+                    self.save_settings();
+                }
             }
 
-            // Add the statistics window after the shortcuts window
-            if self.show_statistics {
-                egui::Window::new("Statistics")
+            // Report builder window
+            if self.show_reports {
+                self.report_history();
+                let mut reports_changed = false;
+                let mut report_to_delete = None;
+                let mut report_to_run = None;
+                let mut report_to_send = None;
+                let can_send = self.settings.smtp.is_configured() && self.integration_job.is_none();
+                let weekdays = self.calendar().weekdays();
+
+                let mut all_tags: Vec<String> = self.tasks.values().flat_map(|task| task.tags.iter().cloned()).collect();
+                all_tags.sort();
+                all_tags.dedup();
+
+                dialog_window(ctx, &self.settings.window_positions, "reports", "Reports")
                     .collapsible(false)
                     .resizable(true)
-                    .default_size([400.0, 500.0])
+                    .default_size([480.0, 520.0])
                     .show(ctx, |ui| {
-                        let content_height = ui.available_height() - 40.0; // Reserve space for close button
+                        if ui.button("➕ New Report").clicked() {
+                            let report = reports::ReportDefinition::new(format!("Report {}", self.settings.reports.len() + 1));
+                            self.selected_report = Some(report.id.clone());
+                            self.settings.reports.push(report);
+                            reports_changed = true;
+                        }
 
-                        ui.horizontal(|ui| {
-                            ui.selectable_value(&mut self.selected_stats_tab, StatsTab::Overview, "Overview");
-                            ui.selectable_value(&mut self.selected_stats_tab, StatsTab::Projects, "Projects");
-                            ui.selectable_value(&mut self.selected_stats_tab, StatsTab::Timeline, "Timeline");
-                            ui.selectable_value(&mut self.selected_stats_tab, StatsTab::Details, "Details");
-                        });
-                        
                         ui.separator();
 
-                        egui::ScrollArea::vertical()
-                            .max_height(content_height)
-                            .show(ui, |ui| {
-                                match self.selected_stats_tab {
-                                    StatsTab::Overview => {
-                                        ui.heading("Overview");
-                                        ui.add_space(8.0);
-                                        
-                                        // Filter tasks to only include those in existing folders or uncategorized
-                                        let current_tasks: Vec<_> = self.tasks.values()
-                                            .filter(|task| {
-                                                match &task.folder {
-                                                    None => true, // Include uncategorized tasks
-                                                    Some(folder) => self.folders.contains(folder) // Only include tasks from existing folders
-                                                }
-                                            })
-                                            .collect();
-                                        
-                                        // Total tracked time
-                                        let total_time: i64 = current_tasks.iter()
-                                            .map(|t| t.get_current_duration())
-                                            .sum();
-                                        ui.label(format!("Total Time Tracked: {}", Self::format_duration(total_time)));
-                                        
-                                        // Active tasks
-                                        let active_tasks = current_tasks.iter()
-                                            .filter(|t| t.start_time.is_some())
-                                            .count();
-                                        ui.label(format!("Currently Active Tasks: {}", active_tasks));
-                                        
-                                        // Average task duration
-                                        let avg_duration = if !current_tasks.is_empty() {
-                                            total_time / current_tasks.len() as i64
-                                        } else {
-                                            0
-                                        };
-                                        ui.label(format!("Average Task Duration: {}", Self::format_duration(avg_duration)));
-                                        
-                                        ui.add_space(16.0);
-                                        
-                                        // Quick stats grid
-                                        egui::Grid::new("stats_grid")
-                                            .num_columns(2)
-                                            .spacing([40.0, 8.0])
-                                            .show(ui, |ui| {
-                                                ui.label("Total Projects:");
-                                                ui.label(format!("{}", self.folders.len()));
-                                                ui.end_row();
-                                                
-                                                ui.label("Total Tasks:");
-                                                ui.label(format!("{}", current_tasks.len()));
-                                                ui.end_row();
-                                                
-                                                ui.label("Completed Tasks:");
-                                                ui.label(format!("{}", current_tasks.iter()
-                                                    .filter(|t| t.total_duration > 0 && !t.is_paused && t.start_time.is_none())
-                                                    .count()));
-                                                ui.end_row();
-                                            });
-                                    },
-                                    StatsTab::Projects => {
-                                        ui.heading("Project Statistics");
-                                        ui.add_space(8.0);
-                                        
-                                        // Project time distribution
-                                        let folder_durations = self.calculate_folder_durations();
-                                        
-                                        // Skip rendering if no data
-                                        if folder_durations.is_empty() {
-                                            ui.label("No project data available");
-                                            return;
-                                        }
-                                        
-                                        let max_duration = folder_durations[0].1;
-                                        if max_duration == 0 {
-                                            ui.label("No time tracked in any projects");
-                                            return;
-                                        }
-                                        
-                                        // Use a fixed width for consistent layout
-                                        let available_width = ui.available_width();
-                                        let label_width = available_width * 0.3;
-                                        let bar_width = available_width * 0.7;
-                                        
-                                        for (folder, duration) in folder_durations {
-                                            ui.horizontal(|ui| {
-                                                // Fixed width for the folder name
-                                                ui.with_layout(egui::Layout::left_to_right(egui::Align::Center), |ui| {
-                                                    ui.set_min_width(label_width);
-                                                    ui.label(&folder);
-                                                });
-                                                
-                                                // Fixed width for the progress bar
-                                                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                                                    ui.set_min_width(bar_width);
-                                                    let progress = duration as f32 / max_duration as f32;
-                                                    let bar = egui::ProgressBar::new(progress)
-                                                        .text(Self::format_duration(duration))
-                                                        .animate(false);  // Disable animation
-                                                    ui.add(bar);
-                                                });
-                                            });
-                                        }
-                                    },
-                                    StatsTab::Timeline => {
-                                        ui.heading("Activity Timeline");
-                                        ui.add_space(8.0);
-                                        
-                                        ui.label("Coming soon: Activity visualization");
-                                        ui.add_space(8.0);
-                                        ui.label("This tab will show your activity patterns over time,");
-                                        ui.label("including daily and weekly summaries.");
-                                    },
-                                    StatsTab::Details => {
-                                        ui.heading("Detailed Statistics");
-                                        ui.add_space(8.0);
-                                        
-                                        // Most time-consuming tasks
-                                        ui.label("Top Tasks by Duration:");
-                                        ui.add_space(4.0);
-                                        
-                                        // Filter tasks to only include those in existing folders or uncategorized
-                                        let mut tasks: Vec<_> = self.tasks.values()
-                                            .filter(|task| {
-                                                match &task.folder {
-                                                    None => true, // Include uncategorized tasks
-                                                    Some(folder) => self.folders.contains(folder) // Only include tasks from existing folders
-                                                }
-                                            })
-                                            .collect();
-                                        
-                                        if tasks.is_empty() {
-                                            ui.label(egui::RichText::new("No tasks available")
-                                                .italics()
-                                                .color(egui::Color32::from_rgb(128, 128, 128)));
-                                            return;
+                        if self.settings.reports.is_empty() {
+                            ui.label(egui::RichText::new("No saved reports")
+                                .italics()
+                                .color(egui::Color32::from_rgb(128, 128, 128)));
+                        }
+                        for report in &self.settings.reports {
+                            ui.horizontal(|ui| {
+                                if ui.button(fill::PLAY).on_hover_text("Run and save").clicked() {
+                                    report_to_run = Some(report.id.clone());
+                                }
+                                let is_selected = self.selected_report.as_deref() == Some(report.id.as_str());
+                                if ui.selectable_label(is_selected, &report.name).clicked() {
+                                    self.selected_report = Some(report.id.clone());
+                                }
+                                if let Some(last_run) = report.last_run {
+                                    ui.label(egui::RichText::new(format!("last run {}", format_relative(last_run))).weak());
+                                }
+                            });
+                        }
+
+                        let folders = self.folders.clone();
+                        let selected = self.selected_report.clone();
+                        if let Some(report) = self
+                            .settings
+                            .reports
+                            .iter_mut()
+                            .find(|report| Some(&report.id) == selected.as_ref())
+                        {
+                            ui.separator();
+                            egui::Grid::new("report_definition_grid")
+                                .num_columns(2)
+                                .spacing([10.0, 4.0])
+                                .show(ui, |ui| {
+                                    ui.label("Name:");
+                                    reports_changed |= ui.text_edit_singleline(&mut report.name).changed();
+                                    ui.end_row();
+
+                                    ui.label("Group by:");
+                                    ui.horizontal_wrapped(|ui| {
+                                        for dimension in reports::Dimension::ALL {
+                                            let mut enabled = report.dimensions.contains(&dimension);
+                                            if ui.checkbox(&mut enabled, dimension.label()).changed() {
+                                                // Keep dimensions in their canonical order
+                                                report.dimensions = reports::Dimension::ALL
+                                                    .into_iter()
+                                                    .filter(|d| if *d == dimension { enabled } else { report.dimensions.contains(d) })
+                                                    .collect();
+                                                reports_changed = true;
+                                            }
                                         }
-                                        
-                                        tasks.sort_by_key(|t| std::cmp::Reverse(t.get_current_duration()));
-                                        
-                                        for task in tasks.iter().take(5) {
-                                            ui.horizontal(|ui| {
-                                                // Show folder name along with task description
-                                                let folder_name = task.folder.as_deref().unwrap_or("Uncategorized");
-                                                ui.label(format!("{} ({})", task.description, folder_name));
-                                                
-                                                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                                                    ui.label(Self::format_duration(task.get_current_duration()));
-                                                });
-                                            });
+                                    });
+                                    ui.end_row();
+
+                                    ui.label("Measures:");
+                                    ui.horizontal_wrapped(|ui| {
+                                        for measure in reports::Measure::ALL {
+                                            let mut enabled = report.measures.contains(&measure);
+                                            if ui.checkbox(&mut enabled, measure.label()).changed() {
+                                                report.measures = reports::Measure::ALL
+                                                    .into_iter()
+                                                    .filter(|m| if *m == measure { enabled } else { report.measures.contains(m) })
+                                                    .collect();
+                                                reports_changed = true;
+                                            }
                                         }
-                                    }
+                                    });
+                                    ui.end_row();
+
+                                    ui.label("Period:");
+                                    egui::ComboBox::from_id_salt("report_range")
+                                        .selected_text(report.range.label())
+                                        .show_ui(ui, |ui| {
+                                            for range in reports::DateRange::ALL {
+                                                reports_changed |= ui.selectable_value(&mut report.range, range, range.label()).changed();
+                                            }
+                                        });
+                                    ui.end_row();
+
+                                    ui.label("Folder:");
+                                    egui::ComboBox::from_id_salt("report_folder")
+                                        .selected_text(report.folder.as_deref().unwrap_or("All folders"))
+                                        .show_ui(ui, |ui| {
+                                            reports_changed |= ui.selectable_value(&mut report.folder, None, "All folders").changed();
+                                            for folder in &folders {
+                                                reports_changed |= ui
+                                                    .selectable_value(&mut report.folder, Some(folder.clone()), folder)
+                                                    .changed();
+                                            }
+                                        });
+                                    ui.end_row();
+
+                                    ui.label("Tag:");
+                                    egui::ComboBox::from_id_salt("report_tag")
+                                        .selected_text(report.tag.as_deref().unwrap_or("Any tag"))
+                                        .show_ui(ui, |ui| {
+                                            reports_changed |= ui.selectable_value(&mut report.tag, None, "Any tag").changed();
+                                            for tag in &all_tags {
+                                                reports_changed |= ui.selectable_value(&mut report.tag, Some(tag.clone()), tag).changed();
+                                            }
+                                        });
+                                    ui.end_row();
+
+                                    ui.label("");
+                                    reports_changed |= ui.checkbox(&mut report.billable_only, "Billable tasks only").changed();
+                                    ui.end_row();
+
+                                    ui.label("Format:");
+                                    egui::ComboBox::from_id_salt("report_format")
+                                        .selected_text(report.format.label())
+                                        .show_ui(ui, |ui| {
+                                            for format in [reports::ReportFormat::Csv, reports::ReportFormat::Markdown] {
+                                                reports_changed |= ui.selectable_value(&mut report.format, format, format.label()).changed();
+                                            }
+                                        });
+                                    ui.end_row();
+
+                                    ui.label("Time zone:");
+                                    ui.horizontal(|ui| {
+                                        let timezone = report.timezone.get_or_insert_with(String::new);
+                                        reports_changed |= ui
+                                            .add(egui::TextEdit::singleline(timezone).hint_text(timezones::workspace()))
+                                            .on_hover_text("Days are counted in this zone, e.g. the client's America/New_York")
+                                            .changed();
+                                        if let Err(e) = report.zone() {
+                                            ui.colored_label(egui::Color32::from_rgb(220, 80, 80), fill::WARNING).on_hover_text(e);
+                                        }
+                                    });
+                                    ui.end_row();
+
+                                    ui.label("Email to:");
+                                    reports_changed |= ui
+                                        .add(egui::TextEdit::singleline(&mut report.email_to).hint_text("manager@example.com"))
+                                        .changed();
+                                    ui.end_row();
+
+                                    ui.label("Send weekly:");
+                                    egui::ComboBox::from_id_salt("report_email_weekday")
+                                        .selected_text(report.email_weekday.map_or("Never".to_string(), |weekday| weekday.to_string()))
+                                        .show_ui(ui, |ui| {
+                                            reports_changed |= ui.selectable_value(&mut report.email_weekday, None, "Never").changed();
+                                            for weekday in &weekdays {
+                                                reports_changed |= ui
+                                                    .selectable_value(&mut report.email_weekday, Some(*weekday), weekday.to_string())
+                                                    .changed();
+                                            }
+                                        });
+                                    ui.end_row();
+                                });
+
+                            ui.add_space(4.0);
+                            ui.horizontal(|ui| {
+                                if ui.button("🗑 Delete Report").clicked() {
+                                    report_to_delete = Some(report.id.clone());
+                                }
+                                if ui
+                                    .add_enabled(can_send && !report.email_to.trim().is_empty(), egui::Button::new("✉ Send Report"))
+                                    .on_disabled_hover_text("Set a recipient and an SMTP account in Settings")
+                                    .clicked()
+                                {
+                                    report_to_send = Some(report.id.clone());
                                 }
                             });
 
-                        // Always show close button at the bottom
-                        ui.add_space(8.0);
-                        ui.with_layout(egui::Layout::bottom_up(egui::Align::Center), |ui| {
-                            if ui.button("Close").clicked() {
-                                self.show_statistics = false;
+                            let sent: Vec<&reports::SentReport> =
+                                self.report_history.iter().flatten().rev().filter(|sent| sent.report_id == report.id).take(5).collect();
+                            if !sent.is_empty() {
+                                ui.add_space(4.0);
+                                ui.strong("Sent");
+                                for sent in sent {
+                                    let text = format!("{} to {}", sent.sent_at.format("%Y-%m-%d %H:%M"), sent.to);
+                                    match &sent.error {
+                                        None => ui.label(text),
+                                        Some(e) => ui.colored_label(egui::Color32::from_rgb(220, 80, 80), format!("{} failed", text))
+                                            .on_hover_text(e),
+                                    };
+                                }
                             }
-                        });
+                        }
+
+                        // Table from the last run of the selected report
+                        if let Some((report_id, table)) = &self.report_preview {
+                            if self.selected_report.as_ref() == Some(report_id) {
+                                ui.separator();
+                                if table.rows.is_empty() {
+                                    ui.label(egui::RichText::new("No time tracked for this report").italics());
+                                }
+                                egui::ScrollArea::both().max_height(200.0).show(ui, |ui| {
+                                    egui::Grid::new("report_preview_grid")
+                                        .num_columns(table.headers.len())
+                                        .spacing([16.0, 4.0])
+                                        .striped(true)
+                                        .show(ui, |ui| {
+                                            for header in &table.headers {
+                                                ui.strong(header);
+                                            }
+                                            ui.end_row();
+                                            for row in &table.rows {
+                                                for cell in row {
+                                                    ui.label(cell);
+                                                }
+                                                ui.end_row();
+                                            }
+                                        });
+                                });
+                            }
+                        }
+
+                        ui.add_space(8.0);
+                        if ui.button("Close").clicked() {
+                            self.show_reports = false;
+                        }
                     });
+
+                if let Some(report_id) = report_to_delete {
+                    self.settings.reports.retain(|report| report.id != report_id);
+                    self.selected_report = None;
+                    reports_changed = true;
+                }
+                if reports_changed {
+                    self.save_settings();
+                }
+                if let Some(report_id) = report_to_send {
+                    if let Err(e) = self.start_report_email(&report_id) {
+                        self.export_message = Some((format!("Error sending report: {}", e), 3.0));
+                    }
+                }
+                if let Some(report_id) = report_to_run {
+                    self.selected_report = Some(report_id.clone());
+                    match self.run_report(&report_id) {
+                        Ok(filename) => self.show_exported_file(format!("Report saved to {}", filename), &filename),
+                        Err(e) => self.export_message = Some((format!("Error running report: {}", e), 3.0)),
+                    }
+                }
             }
 
             ui.add_space(16.0);
@@ -1423,7 +12597,7 @@ impl eframe::App for WorkTimer {
 
             // Confirmation dialog for clearing all folders
             if self.show_clear_folders_confirm {
-                egui::Window::new("Clear All Folders")
+                dialog_window(ctx, &self.settings.window_positions, "clear_all_folders", "Clear All Folders")
                     .collapsible(false)
                     .resizable(false)
                     .show(ctx, |ui| {
@@ -1470,7 +12644,7 @@ impl eframe::App for WorkTimer {
 
             // New folder dialog
             if self.show_new_folder_dialog {
-                egui::Window::new("New Folder")
+                dialog_window(ctx, &self.settings.window_positions, "new_folder", "New Folder")
                     .collapsible(false)
                     .resizable(false)
                     .show(ctx, |ui| {
@@ -1545,6 +12719,28 @@ impl eframe::App for WorkTimer {
 
             ui.add_space(16.0);
 
+            // Tasks finished today, until the day rolls over
+            let done_today: Vec<(String, Option<DateTime<Local>>)> = self
+                .done_on(self.calendar().today())
+                .into_iter()
+                .map(|task| (self.shown_task_name(task), task.completed_at))
+                .collect();
+            if !done_today.is_empty() {
+                egui::CollapsingHeader::new(format!("{} Done today ({})", fill::CHECK_CIRCLE, done_today.len()))
+                    .id_salt("done_today")
+                    .show(ui, |ui| {
+                        for (name, completed_at) in &done_today {
+                            ui.horizontal(|ui| {
+                                if let Some(at) = completed_at {
+                                    ui.weak(at.format("%H:%M").to_string());
+                                }
+                                ui.label(name);
+                            });
+                        }
+                    });
+                ui.add_space(8.0);
+            }
+
             // Display tasks by folder with custom colors
             egui::ScrollArea::vertical().show(ui, |ui| {
                 let folders = self.get_folders();
@@ -1582,12 +12778,13 @@ impl eframe::App for WorkTimer {
                     }
                 }
 
+                let compact = self.settings.density == Density::Compact;
                 for (folder_idx, folder) in folders.iter().enumerate() {
                     let folder_name = folder.clone();
                     let task_ids = tasks_by_folder.get(folder_name.as_str()).cloned().unwrap_or_default();
 
                     egui::Frame::new()
-                        .outer_margin(egui::Vec2::splat(2.0))
+                        .outer_margin(egui::Vec2::splat(if compact { 0.0 } else { 2.0 }))
                         .show(ui, |ui| {
                             let folder_id = egui::Id::new(format!("folder_{}", folder_name));
                             let mut is_open = ui.memory_mut(|mem| {
@@ -1596,13 +12793,13 @@ impl eframe::App for WorkTimer {
 
                             // Handle left/right arrow keys for the focused folder
                             if Some(folder_idx) == self.focused_folder_index {
-                                if ctx.input(|i| i.key_pressed(egui::Key::ArrowRight)) && !is_open {
+                                if Shortcut::ExpandFolder.pressed(ctx) && !is_open {
                                     is_open = true;
                                     ui.memory_mut(|mem| {
                                         mem.data.insert_temp(folder_id, true);
                                     });
                                 }
-                                if ctx.input(|i| i.key_pressed(egui::Key::ArrowLeft)) && is_open {
+                                if Shortcut::CollapseFolder.pressed(ctx) && is_open {
                                     is_open = false;
                                     ui.memory_mut(|mem| {
                                         mem.data.insert_temp(folder_id, false);
@@ -1614,18 +12811,84 @@ impl eframe::App for WorkTimer {
                             ui.horizontal(|ui| {
                                 ui.spacing_mut().item_spacing.x = 10.0;
 
+                                // Access key hint while the modifier is held
+                                if folder_idx < FOLDER_KEYS.len() && ui.input(|i| i.modifiers.command) {
+                                    ui.label(egui::RichText::new((folder_idx + 1).to_string()).monospace().strong());
+                                }
+
                                 // Create a draggable button that contains the folder name and arrow
                                 let arrow = if is_open { fill::CARET_DOWN } else { fill::CARET_RIGHT };
                                 
                                 // Add visual feedback for focused folder
-                                let mut button = egui::Button::new(format!("{} {} ({})", arrow, folder_name, task_ids.len()))
-                                    .sense(egui::Sense::click_and_drag());
+                                let mut title = egui::RichText::new(format!("{} {} ({})", arrow, self.shown_folder_name(&folder_name), task_ids.len()));
+                                if let Some([r, g, b]) = self.folder_styles.get(&folder_name).and_then(|style| style.color) {
+                                    title = title.color(egui::Color32::from_rgb(r, g, b));
+                                }
+                                let mut button = egui::Button::new(title).sense(egui::Sense::click_and_drag());
                                 
                                 if Some(folder_idx) == self.focused_folder_index {
                                     button = button.fill(ui.visuals().selection.bg_fill);
                                 }
                                 
                                 let folder_button = ui.add(button);
+                                if Some(folder_idx) == self.focused_folder_index && self.focused_task_index.is_none() && self.scroll_to_focused {
+                                    folder_button.scroll_to_me(Some(egui::Align::Center));
+                                    self.scroll_to_focused = false;
+                                }
+                                folder_button.context_menu(|ui| self.folder_menu(ui, &folder_name));
+
+                                let mut rename_to = None;
+                                if let Some((_, name)) = self.renaming_folder.as_mut().filter(|(old, _)| *old == folder_name) {
+                                    let response = ui.add(egui::TextEdit::singleline(name).desired_width(160.0));
+                                    if !response.has_focus() && !response.lost_focus() {
+                                        response.request_focus();
+                                    }
+                                    if response.lost_focus() {
+                                        if ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                                            rename_to = Some(name.clone());
+                                        }
+                                        self.renaming_folder = None;
+                                    }
+                                }
+                                if let Some(new_name) = rename_to {
+                                    if let Err(e) = self.rename_folder(&folder_name, &new_name) {
+                                        self.export_message = Some((format!("Error renaming folder: {}", e), 3.0));
+                                    }
+                                }
+
+                                // Status counts; clicking one shows only tasks with that status
+                                let mut status_counts = [0; TaskStatus::ALL.len()];
+                                for task in self.tasks.values().filter(|task| !task.archived) {
+                                    if task.folder.as_deref().unwrap_or("Uncategorized") == folder_name {
+                                        status_counts[TaskStatus::ALL.iter().position(|s| *s == task.status()).unwrap()] += 1;
+                                    }
+                                }
+                                let active_filter = self.status_filters.get(&folder_name).copied();
+                                ui.spacing_mut().item_spacing.x = 4.0;
+                                for (status, count) in TaskStatus::ALL.iter().zip(status_counts) {
+                                    if count == 0 && active_filter != Some(*status) {
+                                        continue;
+                                    }
+                                    let text = egui::RichText::new(format!("{} {}", count, status.label()))
+                                        .small()
+                                        .color(status.color());
+                                    let response = ui
+                                        .selectable_label(active_filter == Some(*status), text)
+                                        .on_hover_text(if active_filter == Some(*status) {
+                                            "Show all tasks"
+                                        } else {
+                                            "Show only these tasks"
+                                        });
+                                    if response.clicked() {
+                                        if active_filter == Some(*status) {
+                                            self.status_filters.remove(&folder_name);
+                                        } else {
+                                            self.status_filters.insert(folder_name.clone(), *status);
+                                        }
+                                        self.focused_task_index = None;
+                                    }
+                                }
+                                ui.spacing_mut().item_spacing.x = 10.0;
 
                                 // Handle drag and drop
                                 if folder_button.drag_started() {
@@ -1715,43 +12978,22 @@ impl eframe::App for WorkTimer {
                                     });
                                 }
 
-                                // Right side: Export and Clear buttons
+                                // Right side: Add Task inline, everything else in the overflow menu
                                 ui.with_layout(
                                     egui::Layout::right_to_left(egui::Align::Center),
                                     |ui| {
-                                        if ui.button("🗑").clicked() {
-                                            self.show_clear_folder_confirm = Some(folder_name.clone());
-                                        }
-                                        ui.small("Clear");
-
-                                        ui.separator();
-
-                                        if ui.button("📊").clicked() {
-                                            match self.export_folder_to_csv(&folder_name) {
-                                                Ok(filename) => {
-                                                    self.export_message = Some((
-                                                        format!("Folder exported to {}", filename),
-                                                        3.0,
-                                                    ));
-                                                }
-                                                Err(e) => {
-                                                    self.export_message = Some((
-                                                        format!("Error exporting folder: {}", e),
-                                                        3.0,
-                                                    ));
-                                                }
-                                            }
-                                        }
-                                        ui.small("Export");
-
-                                        ui.separator();
+                                        ui.menu_button(fill::DOTS_THREE, |ui| self.folder_menu(ui, &folder_name))
+                                            .response
+                                            .on_hover_text("Folder actions");
 
-                                        if ui.button("➕").clicked() {
+                                        if ui.button("➕").on_hover_text("Add Task").clicked() {
                                             self.show_add_task_dialog = true;
                                             self.add_task_to_folder = Some(folder_name.clone());
                                             self.new_task_in_folder.clear();
                                         }
-                                        ui.small("Add Task");
+                                        if !compact && ui.available_width() > 200.0 {
+                                            ui.small("Add Task");
+                                        }
                                     },
                                 );
                             });
@@ -1769,6 +13011,11 @@ impl eframe::App for WorkTimer {
                                         let mut task_action = None;
                                         let mut task_action_id = None;
                                         let mut task_export_error = None;
+                                        let mut rename_to = None;
+                                        let folders = self.folders.clone();
+                                        // Below this the row buttons would run into the task name
+                                        let narrow = ui.available_width() < 420.0;
+                                        let sessions_loading = self.sessions_loading.is_some();
 
                                         for (task_idx, task_id) in task_ids.iter().enumerate() {
                                             if let Some(task) = self.tasks.get(task_id) {
@@ -1777,13 +13024,49 @@ impl eframe::App for WorkTimer {
                                                 
                                                 // Collect all the data we need before the closure
                                                 let task_id = task_id.to_string();
-                                                let description = task.description.clone();
+                                                let description = self.shown_task_name(task);
                                                 let duration = task.get_current_duration();
                                                 let start_time = task.start_time;
                                                 let is_paused = task.is_paused;
+                                                let billable = task.billable;
+                                                let color_label = task.color_label;
+                                                let last_active = task.last_active_at();
+                                                let created_at = task.created_at;
+                                                let meeting_cost = task.meeting_cost().map(|cost| {
+                                                    let people = task.attendees.unwrap_or(0);
+                                                    let rate = task.attendee_rate.unwrap_or(0.0);
+                                                    (cost, format!("Meeting cost: {} attendees at {:.2} an hour", people, rate))
+                                                });
+                                                let pomodoro_progress = task
+                                                    .estimate_minutes
+                                                    .filter(|_| task.estimate_in_pomodoros)
+                                                    .map(|estimate| (task.pomodoros(self.pomodoro_minutes()), estimate.div_ceil(self.pomodoro_minutes())));
+                                                let pomodoro_left = self
+                                                    .pomodoro
+                                                    .as_ref()
+                                                    .filter(|current| current.phase == pomodoro::Phase::Work && current.task_id == task_id.as_str())
+                                                    .map(|current| current.remaining(Local::now()));
                                                 let is_editing = Some(&task_id) == self.editing_duration_task_id.as_ref();
                                                 let editing_value = self.editing_duration_value.clone();
-                                                
+
+                                                // Rows scrolled out of view just reserve their last height,
+                                                // so long lists stay cheap to lay out while a timer ticks.
+                                                // The height is only reused at the same width and while no
+                                                // data file was written since, e.g. after a rename or new tags.
+                                                let row_id = egui::Id::new(("task_row", &task_id));
+                                                let row_revision = (DATA_VERSION.load(Ordering::Relaxed), ui.available_width());
+                                                let is_renaming = self.renaming_task.as_ref().is_some_and(|(id, _)| *id == task_id);
+                                                if !is_focused && !is_editing && !is_renaming {
+                                                    let cached = ui.memory(|mem| mem.data.get_temp::<((u64, f32), f32)>(row_id));
+                                                    if let Some((_, height)) = cached.filter(|(revision, _)| *revision == row_revision) {
+                                                        let row = egui::Rect::from_min_size(ui.cursor().min, egui::vec2(ui.available_width(), height));
+                                                        if !ui.is_rect_visible(row) {
+                                                            ui.allocate_space(row.size());
+                                                            continue;
+                                                        }
+                                                    }
+                                                }
+
                                                 let task_frame = egui::Frame::new()
                                                     .fill(if is_focused { 
                                                         ui.visuals().selection.bg_fill 
@@ -1791,7 +13074,7 @@ impl eframe::App for WorkTimer {
                                                         egui::Color32::TRANSPARENT 
                                                     });
 
-                                                task_frame.show(ui, |ui| {
+                                                let frame = task_frame.show(ui, |ui| {
                                                     ui.horizontal(|ui| {
                                                         // Complete button (checkbox style) on the left
                                                         let is_completed = duration > 0 && start_time.is_none() && !is_paused;
@@ -1805,18 +13088,130 @@ impl eframe::App for WorkTimer {
                                                             task_action_id = Some(task_id.clone());
                                                         }
                                                         
-                                                        ui.label(&description);
-                                                        
-                                                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                                                            // Delete button
-                                                            if ui.button(fill::TRASH).clicked() {
-                                                                task_action = Some(TaskAction::Delete);
+                                                        let renaming = self.renaming_task.as_mut().filter(|(id, _)| *id == task_id);
+                                                        if let Some((_, name)) = renaming {
+                                                            let response = ui.add(egui::TextEdit::singleline(name).desired_width(200.0));
+                                                            if !response.has_focus() && !response.lost_focus() {
+                                                                response.request_focus();
+                                                            }
+                                                            if response.lost_focus() {
+                                                                if ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                                                                    rename_to = Some((task_id.clone(), name.clone()));
+                                                                }
+                                                                self.renaming_task = None;
+                                                            }
+                                                        } else {
+                                                            // The name takes its own clicks, so it gets the menu too
+                                                            let mut menu_action = None;
+                                                            let mut label = egui::Label::new(&description).sense(egui::Sense::click());
+                                                            let name = if narrow {
+                                                                // Leave room for the controls and cut the name short instead
+                                                                label = label.truncate();
+                                                                ui.scope(|ui| {
+                                                                    ui.set_max_width((ui.available_width() - 190.0).max(60.0));
+                                                                    ui.add(label)
+                                                                })
+                                                                .inner
+                                                            } else {
+                                                                ui.add(label)
+                                                            };
+                                                            name.context_menu(|ui| menu_action = self.task_context_menu(ui, &task_id));
+                                                            if name.double_clicked() {
+                                                                menu_action = Some(TaskAction::Rename);
+                                                            }
+                                                            if let Some(action) = menu_action {
+                                                                task_action = Some(action);
                                                                 task_action_id = Some(task_id.clone());
                                                             }
+                                                        }
+
+                                                        if let Some((cost, explanation)) = &meeting_cost {
+                                                            ui.label(format!("{} {:.2}", fill::USERS, cost)).on_hover_text(explanation);
+                                                        }
+                                                        if let Some((done, estimate)) = pomodoro_progress {
+                                                            ui.label(format!("{}/{} 🍅", done, estimate))
+                                                                .on_hover_text(format!("Pomodoros done of the estimate ({} minutes each)", self.pomodoro_minutes()));
+                                                        }
+                                                        if let Some(seconds) = pomodoro_left {
+                                                            ui.label(egui::RichText::new(format!("🍅 {}", pomodoro::format_remaining(seconds))).strong())
+                                                                .on_hover_text("Left of this pomodoro, the task pauses when it is up");
+                                                        }
+
+                                                        // Dot for tasks worked on today
+                                                        if last_active.is_some_and(|last| self.calendar().day_of(last) == self.calendar().today()) {
+                                                            ui.label(egui::RichText::new("●").small().color(egui::Color32::GREEN))
+                                                                .on_hover_text("Worked on today");
+                                                        }
+                                                        
+                                                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                                            if narrow {
+                                                                // No room for the icon buttons; they are all in the row menu
+                                                                let mut menu_action = None;
+                                                                ui.menu_button(fill::DOTS_THREE, |ui| menu_action = self.task_context_menu(ui, &task_id));
+                                                                if let Some(action) = menu_action {
+                                                                    task_action = Some(action);
+                                                                    task_action_id = Some(task_id.clone());
+                                                                }
+                                                            } else {
+                                                                // Delete button
+                                                                if ui.button(fill::TRASH).clicked() {
+                                                                    task_action = Some(TaskAction::Delete);
+                                                                    task_action_id = Some(task_id.clone());
+                                                                }
+
+                                                                // Export single task button
+                                                                if ui.button(fill::EXPORT).on_hover_text("Export to CSV").clicked() {
+                                                                    task_action = Some(TaskAction::Export);
+                                                                    task_action_id = Some(task_id.clone());
+                                                                }
+
+                                                                // Copy summary button
+                                                                if ui.button(fill::CLIPBOARD_TEXT).on_hover_text("Copy summary").clicked() {
+                                                                    if let Some(summary) = self.task_summary_line(&task_id) {
+                                                                        ui.ctx().copy_text(summary);
+                                                                        task_export_error = Some("Copied task summary".to_string());
+                                                                    }
+                                                                }
+
+                                                                // Details button
+                                                                if ui.button(fill::INFO).on_hover_text("Details").clicked() {
+                                                                    task_action = Some(TaskAction::ShowDetails);
+                                                                    task_action_id = Some(task_id.clone());
+                                                                }
 
-                                                            // Export single task button
-                                                            if ui.button(fill::EXPORT).clicked() {
-                                                                task_export_error = Some(format!("Error exporting task: Task export not implemented in closure"));
+                                                                // Duplicate menu
+                                                                ui.menu_button(fill::COPY, |ui| {
+                                                                    if ui.button("Duplicate").clicked() {
+                                                                        task_action = Some(TaskAction::Duplicate);
+                                                                        task_action_id = Some(task_id.clone());
+                                                                        ui.close_menu();
+                                                                    }
+                                                                    ui.menu_button("Duplicate into", |ui| {
+                                                                        for folder in folders.iter().filter(|folder| **folder != folder_name) {
+                                                                            if ui.button(folder).clicked() {
+                                                                                task_action = Some(TaskAction::DuplicateInto(Some(folder.clone())));
+                                                                                task_action_id = Some(task_id.clone());
+                                                                                ui.close_menu();
+                                                                            }
+                                                                        }
+                                                                        if ui.button("Uncategorized").clicked() {
+                                                                            task_action = Some(TaskAction::DuplicateInto(None));
+                                                                            task_action_id = Some(task_id.clone());
+                                                                            ui.close_menu();
+                                                                        }
+                                                                    });
+                                                                })
+                                                                .response
+                                                                .on_hover_text("Duplicate");
+
+                                                                // Billable toggle
+                                                                if ui.selectable_label(billable, fill::CURRENCY_DOLLAR)
+                                                                    .on_hover_text(if billable { "Billable" } else { "Non-billable" })
+                                                                    .clicked()
+                                                                {
+                                                                    task_action = Some(TaskAction::ToggleBillable);
+                                                                    task_action_id = Some(task_id.clone());
+                                                                }
                                                             }
 
                                                             // Only show play/pause button if task is not completed
@@ -1858,28 +13253,62 @@ impl eframe::App for WorkTimer {
                                                                 } else {
                                                                     self.editing_duration_value = edit_value;
                                                                 }
+                                                            } else if sessions_loading {
+                                                                ui.label("…").on_hover_text("Loading sessions");
                                                             } else {
-                                                                let formatted_duration = Self::format_duration(duration);
-                                                                let duration_label = ui.label(&formatted_duration);
+                                                                let duration_label = ui.label(self.format_duration(duration));
                                                                 if duration_label.double_clicked() {
+                                                                    // Edited as HH:MM:SS whatever the display format
                                                                     self.editing_duration_task_id = Some(task_id.clone());
-                                                                    self.editing_duration_value = formatted_duration;
+                                                                    self.editing_duration_value = DurationFormat::Clock.format(duration);
                                                                 }
                                                             }
 
-                                                            let status_text = if start_time.is_some() {
-                                                                egui::RichText::new("Running").color(egui::Color32::GREEN)
+                                                            let (status, status_name) = if start_time.is_some() {
+                                                                (TaskStatus::Running, "Running")
                                                             } else if is_paused {
-                                                                egui::RichText::new("Paused").color(egui::Color32::YELLOW)
-                                                            } else if duration == 0 && !is_paused {
-                                                                egui::RichText::new("Not Started").color(egui::Color32::GRAY)
+                                                                (TaskStatus::Paused, "Paused")
+                                                            } else if duration == 0 {
+                                                                (TaskStatus::NotStarted, "Not Started")
                                                             } else {
-                                                                egui::RichText::new("Completed").color(egui::Color32::from_rgb(0, 180, 180))
+                                                                (TaskStatus::Completed, "Completed")
                                                             };
-                                                            ui.label(status_text);
+                                                            let mut activity = match last_active {
+                                                                Some(last) => format!("Last active {}", format_relative(last)),
+                                                                None => "Never started".to_string(),
+                                                            };
+                                                            if let Some(created) = created_at {
+                                                                activity.push_str(&format!("\nCreated {}", format_relative(created)));
+                                                            }
+                                                            if compact {
+                                                                // Just a dot in the status color, with the status in the tooltip
+                                                                ui.label(egui::RichText::new("●").color(status.color()))
+                                                                    .on_hover_text(format!("{}\n{}", status_name, activity));
+                                                            } else {
+                                                                ui.label(egui::RichText::new(status_name).color(status.color())).on_hover_text(activity);
+                                                            }
                                                         });
                                                     });
+
+                                                    // Right-click anywhere on the row for every task action
+                                                    let mut menu_action = None;
+                                                    ui.response().context_menu(|ui| menu_action = self.task_context_menu(ui, &task_id));
+                                                    if let Some(action) = menu_action {
+                                                        task_action = Some(action);
+                                                        task_action_id = Some(task_id.clone());
+                                                    }
                                                 });
+                                                if let Some(color_label) = color_label {
+                                                    // In the indent to the left of the row, so labelled rows line up with the rest
+                                                    let row = frame.response.rect;
+                                                    let strip = egui::Rect::from_min_max(row.left_top() - egui::vec2(6.0, 0.0), row.left_bottom() - egui::vec2(2.0, 0.0));
+                                                    ui.painter().rect_filled(strip, 2.0, color_label.color());
+                                                }
+                                                ui.memory_mut(|mem| mem.data.insert_temp(row_id, (row_revision, frame.response.rect.height())));
+                                                if is_focused && self.scroll_to_focused {
+                                                    frame.response.scroll_to_me(Some(egui::Align::Center));
+                                                    self.scroll_to_focused = false;
+                                                }
                                             }
                                         }
 
@@ -1889,6 +13318,9 @@ impl eframe::App for WorkTimer {
                                                 self.handle_task_action(&id, action);
                                             }
                                         }
+                                        if let Some((id, name)) = rename_to {
+                                            self.rename_task(&id, &name);
+                                        }
                                         if let Some(error) = task_export_error {
                                             self.export_message = Some((error, 3.0));
                                         }
@@ -1906,7 +13338,7 @@ impl eframe::App for WorkTimer {
                     let mut should_add_task = false;
                     let folder_name = folder_name.clone();
 
-                    egui::Window::new(format!("Add Task to '{}'", folder_name))
+                    dialog_window(ctx, &self.settings.window_positions, "add_task", format!("Add Task to '{}'", folder_name))
                         .collapsible(false)
                         .resizable(false)
                         .show(ctx, |ui| {
@@ -1971,10 +13403,7 @@ impl eframe::App for WorkTimer {
                         });
 
                     if should_add_task {
-                        let mut task = Task::new(self.new_task_in_folder.trim().to_string());
-                        task.folder = Some(folder_name);
-                        self.tasks.insert(task.id.clone(), task);
-                        self.save_tasks();
+                        self.add_task(self.new_task_in_folder.trim().to_string(), Some(folder_name));
                     }
 
                     if should_close {
@@ -1986,14 +13415,71 @@ impl eframe::App for WorkTimer {
             }
         });
 
-        // Request repaint for timer updates
-        if self.tasks.values().any(|task| task.start_time.is_some()) {
-            ctx.request_repaint();
+        self.remember_dialog_positions(ctx);
+        self.remember_appearance();
+        self.show_overlay(ctx);
+        self.show_frame_times(ctx, frame_start);
+
+        // Repaint when a running timer reaches its next second
+        if let Some(tick) = self.next_timer_tick() {
+            ctx.request_repaint_after(tick);
+        }
+
+        // Keep polling background integration jobs
+        if self.integration_job.is_some() {
+            ctx.request_repaint_after(std::time::Duration::from_millis(200));
+        }
+
+        // Wake up now and then so scheduled checks and reminders run while the app is idle
+        ctx.request_repaint_after(std::time::Duration::from_secs(30));
+    }
+}
+
+// Moves into the data folder before anything is read or written, for the command line too
+fn enter_data_dir() {
+    if let Err(e) = paths::enter_data_dir() {
+        eprintln!("Keeping the data in the current folder, the data folder isn't available: {}", e);
+    }
+}
+
+// Brings along the data of an older version that kept it where the app was started. Only
+// the app does this, not the command line, so e.g. validating a file somewhere else never
+// moves the data next to it.
+fn move_old_data() {
+    let Some(from) = paths::old_data_dir() else {
+        return;
+    };
+    let to = std::env::current_dir().unwrap_or_default();
+    tracing::info!("Moving the data of an older version from {} to {}", from.display(), to.display());
+    let dirs = [backup::ATTACHMENTS_DIR, plugins::PLUGIN_DIR, screenshots::SCREENSHOTS_DIR];
+    let moved = paths::move_old_data(DATA_FILES, &dirs);
+    for result in &moved {
+        match result {
+            Ok(name) => tracing::info!("Moved {}", name),
+            Err(e) => tracing::warn!("{}", e),
+        }
+    }
+    if moved.iter().any(|name| name.as_deref() == Ok(EXPORTS_MANIFEST)) {
+        // Exports stay where they were written, so they can still be cleared along with the tasks
+        let exports: Vec<String> = read_data_file(EXPORTS_MANIFEST);
+        let exports: Vec<String> = exports.iter().map(|path| paths::from_launch_dir(path).to_string_lossy().to_string()).collect();
+        if let Ok(data) = serde_json::to_string(&exports) {
+            write_data_file(EXPORTS_MANIFEST, data);
         }
     }
 }
 
 fn main() -> Result<(), eframe::Error> {
+    enter_data_dir();
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(exit_code) = cli::run(&args) {
+        std::process::exit(exit_code);
+    }
+
+    let _log_guard = logging::init();
+    tracing::info!("Starting Work Timer {} in {}", env!("CARGO_PKG_VERSION"), std::env::current_dir().unwrap_or_default().display());
+    move_old_data();
+
     let options = eframe::NativeOptions {
         window_builder: Some(Box::new(|builder| {
             builder.with_inner_size(egui::Vec2::new(480.0, 640.0))
@@ -2015,3 +13501,504 @@ fn main() -> Result<(), eframe::Error> {
         }),
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fuzzy_score_needs_every_character_in_order() {
+        assert_eq!(fuzzy_score("xyz", "Write report"), None);
+        assert_eq!(fuzzy_score("tw", "Write"), None);
+        assert_eq!(fuzzy_score("reportt", "report"), None);
+        assert!(fuzzy_score("wrp", "Write report").is_some());
+        assert_eq!(fuzzy_score("", "anything"), Some(0));
+    }
+
+    #[test]
+    fn fuzzy_score_ignores_case_and_spaces() {
+        assert_eq!(fuzzy_score("WRITE REPORT", "write report"), fuzzy_score("writereport", "Write Report"));
+        assert!(fuzzy_score("ü", "Überblick").is_some());
+    }
+
+    #[test]
+    fn fuzzy_score_prefers_runs_and_word_starts() {
+        // Consecutive characters beat the same characters spread out
+        assert!(fuzzy_score("rep", "report") > fuzzy_score("rep", "roundup edit plan"));
+        // The start of a word beats the middle of one
+        assert!(fuzzy_score("t", "timer") > fuzzy_score("t", "at"));
+        assert!(fuzzy_score("mr", "Monthly report") > fuzzy_score("mr", "summary"));
+        // A match far into the text scores less than one near the start
+        assert!(fuzzy_score("x", "x marks") > fuzzy_score("x", "long text with an x"));
+    }
+
+    // Local time in these tests is Central European: clocks go forward at 02:00 on the last
+    // Sunday of March and back at 03:00 on the last Sunday of October. Every test that
+    // depends on the zone sets the same one, so running them in parallel is fine.
+    pub(crate) fn central_european(text: &str) -> DateTime<Local> {
+        std::env::set_var("TZ", "CET-1CEST,M3.5.0,M10.5.0/3");
+        let naive = chrono::NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M").unwrap();
+        naive.and_local_timezone(Local).earliest().unwrap()
+    }
+
+    // Tests that save run in a scratch folder, the way the app runs in its data folder
+    pub(crate) fn in_scratch_dir() {
+        static SCRATCH: std::sync::Once = std::sync::Once::new();
+        SCRATCH.call_once(|| {
+            let dir = std::env::temp_dir().join(format!("work_timer_test_{}", std::process::id()));
+            fs::create_dir_all(&dir).unwrap();
+            std::env::set_current_dir(&dir).unwrap();
+        });
+    }
+
+    fn date(text: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(text, "%Y-%m-%d").unwrap()
+    }
+
+    fn calendar(day_start_hour: u32) -> Calendar {
+        Calendar { day_start_hour, week_start: chrono::Weekday::Mon }
+    }
+
+    const HOUR: i64 = 3600;
+
+    #[test]
+    fn split_by_day_keeps_a_session_within_one_day_whole() {
+        let (start, end) = (central_european("2024-05-06 09:00"), central_european("2024-05-06 12:30"));
+        assert_eq!(calendar(0).split_by_day(start, end), vec![(date("2024-05-06"), 3 * HOUR + 1800)]);
+        assert_eq!(calendar(0).split_by_day(end, end), vec![]);
+    }
+
+    #[test]
+    fn split_by_day_splits_at_midnight() {
+        let (start, end) = (central_european("2024-05-06 23:00"), central_european("2024-05-08 01:30"));
+        assert_eq!(
+            calendar(0).split_by_day(start, end),
+            vec![(date("2024-05-06"), HOUR), (date("2024-05-07"), 24 * HOUR), (date("2024-05-08"), HOUR + 1800)]
+        );
+    }
+
+    #[test]
+    fn split_by_day_splits_at_the_day_start_hour() {
+        let (start, end) = (central_european("2024-05-06 23:00"), central_european("2024-05-07 03:00"));
+        assert_eq!(calendar(4).split_by_day(start, end), vec![(date("2024-05-06"), 4 * HOUR)]);
+        let (start, end) = (central_european("2024-05-07 03:00"), central_european("2024-05-07 05:00"));
+        assert_eq!(calendar(4).split_by_day(start, end), vec![(date("2024-05-06"), HOUR), (date("2024-05-07"), HOUR)]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn split_by_day_counts_real_time_when_clocks_go_forward() {
+        // 22:00 to 04:00 on the clock, but only five hours pass
+        let (start, end) = (central_european("2024-03-30 22:00"), central_european("2024-03-31 04:00"));
+        assert_eq!(calendar(0).split_by_day(start, end), vec![(date("2024-03-30"), 2 * HOUR), (date("2024-03-31"), 3 * HOUR)]);
+        // The day itself is 23 hours long
+        let (start, end) = (central_european("2024-03-30 12:00"), central_european("2024-04-01 12:00"));
+        assert_eq!(
+            calendar(0).split_by_day(start, end),
+            vec![(date("2024-03-30"), 12 * HOUR), (date("2024-03-31"), 23 * HOUR), (date("2024-04-01"), 12 * HOUR)]
+        );
+        // A new day still starts at 04:00 on the clock
+        let (start, end) = (central_european("2024-03-31 03:00"), central_european("2024-03-31 06:00"));
+        assert_eq!(calendar(4).split_by_day(start, end), vec![(date("2024-03-30"), HOUR), (date("2024-03-31"), 2 * HOUR)]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn split_by_day_counts_real_time_when_clocks_go_back() {
+        // The hour from 02:00 to 03:00 happens twice
+        let (start, end) = (central_european("2024-10-26 12:00"), central_european("2024-10-28 00:00"));
+        assert_eq!(calendar(0).split_by_day(start, end), vec![(date("2024-10-26"), 12 * HOUR), (date("2024-10-27"), 25 * HOUR)]);
+        let (start, end) = (central_european("2024-10-27 01:00"), central_european("2024-10-27 05:00"));
+        assert_eq!(calendar(4).split_by_day(start, end), vec![(date("2024-10-26"), 4 * HOUR), (date("2024-10-27"), HOUR)]);
+    }
+
+    #[test]
+    fn pay_periods_start_where_they_should() {
+        let weekly = PayPeriodConfig::default();
+        assert_eq!(weekly.period_start(date("2024-05-08"), calendar(0)), date("2024-05-06"));
+        assert_eq!(weekly.period_start(date("2024-05-06"), calendar(0)), date("2024-05-06"));
+        let sunday_weeks = Calendar { day_start_hour: 0, week_start: chrono::Weekday::Sun };
+        assert_eq!(weekly.period_start(date("2024-05-08"), sunday_weeks), date("2024-05-05"));
+        assert_eq!(weekly.next_period_start(date("2024-05-06")), date("2024-05-13"));
+
+        let bi_weekly = PayPeriodConfig { kind: PayPeriodKind::BiWeekly, ..Default::default() };
+        assert_eq!(bi_weekly.period_start(date("2024-01-14"), calendar(0)), date("2024-01-01"));
+        assert_eq!(bi_weekly.period_start(date("2024-01-15"), calendar(0)), date("2024-01-15"));
+        // Before the anchor date too
+        assert_eq!(bi_weekly.period_start(date("2023-12-31"), calendar(0)), date("2023-12-18"));
+        assert_eq!(bi_weekly.next_period_start(date("2023-12-18")), date("2024-01-01"));
+
+        let monthly = PayPeriodConfig { kind: PayPeriodKind::Monthly, ..Default::default() };
+        assert_eq!(monthly.period_start(date("2024-02-29"), calendar(0)), date("2024-02-01"));
+        assert_eq!(monthly.next_period_start(date("2024-01-01")), date("2024-02-01"));
+        assert_eq!(monthly.next_period_start(date("2024-12-01")), date("2025-01-01"));
+    }
+
+    // Eight hours on each of the given days, plus `extra` on the first
+    fn worked_days(days: &[&str], extra: i64) -> WorkTimer {
+        let mut task = Task::new("Work".to_string());
+        for (index, day) in days.iter().enumerate() {
+            let start = central_european(&format!("{} 09:00", day));
+            let end = start + chrono::Duration::hours(8) + chrono::Duration::seconds(if index == 0 { extra } else { 0 });
+            task.entries.push(TimeEntry::new(start, end));
+        }
+        WorkTimer { tasks: HashMap::from([(task.id.clone(), task)]), ..Default::default() }
+    }
+
+    #[test]
+    fn pay_periods_carry_over_overtime() {
+        let week = ["2024-05-06", "2024-05-07", "2024-05-08", "2024-05-09", "2024-05-10"];
+        let timer = worked_days(&week, 2 * HOUR);
+        let periods = timer.calculate_pay_periods(date("2024-05-20"));
+        let summary: Vec<_> = periods.iter().map(|period| (period.start, period.end, period.tracked, period.contracted, period.carry_over)).collect();
+        assert_eq!(
+            summary,
+            vec![
+                (date("2024-05-06"), date("2024-05-12"), 42 * HOUR, 40 * HOUR, 2 * HOUR),
+                (date("2024-05-13"), date("2024-05-19"), 0, 40 * HOUR, -38 * HOUR),
+                // Only Monday of this week has passed
+                (date("2024-05-20"), date("2024-05-26"), 0, 8 * HOUR, -46 * HOUR),
+            ]
+        );
+    }
+
+    #[test]
+    fn current_pay_period_expects_only_the_days_so_far() {
+        let days = ["2024-05-06", "2024-05-07", "2024-05-08", "2024-05-09", "2024-05-10", "2024-05-13", "2024-05-14", "2024-05-15"];
+        let timer = worked_days(&days, 2 * HOUR);
+        let periods = timer.calculate_pay_periods(date("2024-05-15"));
+        let current = periods.last().unwrap();
+        assert_eq!((current.tracked, current.contracted), (24 * HOUR, 24 * HOUR));
+        assert_eq!(current.carry_over, 2 * HOUR);
+        // The weekend doesn't change what was expected
+        let periods = timer.calculate_pay_periods(date("2024-05-19"));
+        assert_eq!(periods.last().unwrap().contracted, 40 * HOUR);
+    }
+
+    #[test]
+    fn days_off_lower_the_contracted_hours() {
+        let mut timer = worked_days(&["2024-05-06", "2024-05-07", "2024-05-08", "2024-05-09"], 0);
+        timer.time_off.push(TimeOff { date: date("2024-05-10"), kind: TimeOffKind::Vacation, note: String::new() });
+        let periods = timer.calculate_pay_periods(date("2024-05-12"));
+        assert_eq!(periods.len(), 1);
+        assert_eq!((periods[0].tracked, periods[0].contracted, periods[0].carry_over), (32 * HOUR, 32 * HOUR, 0));
+    }
+
+    fn task_with_session(hours: i64) -> Task {
+        let mut task = Task::new("Legacy".to_string());
+        let start = central_european("2024-05-06 09:00");
+        task.entries.push(TimeEntry::new(start, start + chrono::Duration::hours(hours)));
+        task
+    }
+
+    fn migrated(task: &Task, legacy_total: Option<i64>) -> Task {
+        let mut value = serde_json::to_value(task).unwrap();
+        if let Some(total) = legacy_total {
+            value["total_duration"] = total.into();
+        }
+        let tasks: HashMap<String, Task> = serde_json::from_value(serde_json::json!({ "id": value })).unwrap();
+        migrate_tasks(tasks).remove("id").unwrap()
+    }
+
+    #[test]
+    fn legacy_totals_beyond_the_sessions_become_time_without_sessions() {
+        let task = migrated(&task_with_session(2), Some(3 * HOUR));
+        assert_eq!(task.untracked_duration, HOUR);
+        assert_eq!(task.total_duration(), 3 * HOUR);
+        assert_eq!(task.daily_durations(calendar(0)), vec![(None, HOUR), (Some(date("2024-05-06")), 2 * HOUR)]);
+        // Written back without the old field, and read again unchanged
+        let saved = serde_json::to_value(&task).unwrap();
+        assert!(saved.get("total_duration").is_none());
+        assert_eq!(migrated(&task, None).untracked_duration, HOUR);
+    }
+
+    #[test]
+    fn totals_never_go_below_the_sessions() {
+        // An old total lower than the sessions
+        let task = migrated(&task_with_session(2), Some(HOUR));
+        assert_eq!((task.untracked_duration, task.total_duration()), (0, 2 * HOUR));
+        // Time taken off by hand in an older version
+        let mut task = task_with_session(2);
+        task.untracked_duration = -HOUR;
+        let task = migrated(&task, None);
+        assert_eq!((task.untracked_duration, task.total_duration()), (0, 2 * HOUR));
+        assert_eq!(task.daily_durations(calendar(0)), vec![(Some(date("2024-05-06")), 2 * HOUR)]);
+    }
+
+    #[test]
+    fn setting_a_total_keeps_the_sessions() {
+        let mut task = task_with_session(2);
+        assert_eq!(task.set_total_duration(5 * HOUR), 5 * HOUR);
+        assert_eq!(task.untracked_duration, 3 * HOUR);
+        assert_eq!(task.set_total_duration(HOUR), 2 * HOUR);
+        assert_eq!((task.untracked_duration, task.total_duration(), task.entries.len()), (0, 2 * HOUR, 1));
+    }
+
+    #[test]
+    fn clear_folder_dialog_opens_on_cancel() {
+        let ctx = egui::Context::default();
+        let mut timer = WorkTimer::default();
+        // Left on "Delete folder and tasks" the last time it was open
+        ctx.memory_mut(|mem| mem.data.insert_temp(egui::Id::new(CLEAR_FOLDER_FOCUS), 1u8));
+        timer.open_clear_folder_confirm(&ctx, "Client".to_string());
+        assert_eq!(timer.show_clear_folder_confirm.as_deref(), Some("Client"));
+        assert_eq!(ctx.memory(|mem| mem.data.get_temp::<u8>(egui::Id::new(CLEAR_FOLDER_FOCUS))), None);
+    }
+
+    #[test]
+    fn billable_split_counts_only_the_chosen_days() {
+        let session = |task: &mut Task, day: &str| {
+            let start = central_european(&format!("{} 10:00", day));
+            task.entries.push(TimeEntry::new(start, start + chrono::Duration::hours(2)));
+        };
+        let mut billable = Task::new("Design".to_string());
+        billable.billable = true;
+        session(&mut billable, "2024-05-06");
+        session(&mut billable, "2024-05-13");
+        billable.untracked_duration = HOUR;
+        let mut internal = Task::new("Admin".to_string());
+        session(&mut internal, "2024-05-13");
+        let timer = WorkTimer {
+            tasks: [billable, internal].into_iter().map(|task| (task.id.clone(), task)).collect(),
+            ..Default::default()
+        };
+
+        let week = Some((date("2024-05-13"), date("2024-05-19")));
+        assert_eq!(timer.calculate_billable_split(week), vec![("Uncategorized".to_string(), 2 * HOUR, 2 * HOUR)]);
+        assert_eq!(timer.calculate_billable_split(None), vec![("Uncategorized".to_string(), 5 * HOUR, 2 * HOUR)]);
+    }
+
+    #[test]
+    fn expenses_are_exported_with_their_tax() {
+        let billing = billing::BillingConfig { tax_percent: 20.0, ..Default::default() };
+        let expense = Expense {
+            id: "1".to_string(),
+            description: "Train ticket".to_string(),
+            amount: 50.0,
+            date: date("2024-05-06"),
+            folder: Some("Client".to_string()),
+        };
+        let columns = [ExportColumn::Task, ExportColumn::Date, ExportColumn::DecimalHours, ExportColumn::Amount, ExportColumn::Tax, ExportColumn::Total];
+        assert_eq!(WorkTimer::expense_record(&columns, &expense, &billing), ["Train ticket", "2024-05-06", "", "50.00", "10.00", "60.00"]);
+        assert!(ExportColumn::Total.is_amount());
+        assert!(!ExportColumn::DecimalHours.is_amount());
+    }
+
+    #[test]
+    fn api_and_imported_sessions_record_their_source() {
+        in_scratch_dir();
+        let task = Task::new("Review".to_string());
+        let id = task.id.clone();
+        let (sender, requests) = mpsc::channel();
+        let mut timer = WorkTimer {
+            tasks: [(id.clone(), task)].into(),
+            data_file: "source_test_tasks.json".to_string(),
+            api_requests: Some(requests),
+            api_checked: true,
+            ..Default::default()
+        };
+        let ctx = egui::Context::default();
+        let (reply, replies) = mpsc::channel();
+        for command in [api::Command::Start(id.clone()), api::Command::Stop(id.clone())] {
+            sender.send(api::Request { command, reply: reply.clone() }).unwrap();
+            timer.check_api(&ctx);
+        }
+        assert_eq!(replies.try_iter().collect::<Vec<_>>(), [Ok(()), Ok(())]);
+        assert_eq!(timer.tasks[&id].entries.iter().map(|entry| entry.source).collect::<Vec<_>>(), [EntrySource::Api]);
+
+        fs::write("source_test.csv", "Task,Project,Status,Task ID,Seconds\nPlanning,Client,Stopped,planning,5400\n").unwrap();
+        assert_eq!(timer.import_from_csv("source_test.csv").unwrap(), (1, 0));
+        let imported = &timer.tasks["planning"];
+        assert_eq!(imported.entries.iter().map(|entry| entry.source).collect::<Vec<_>>(), [EntrySource::Import]);
+        assert_eq!((imported.untracked_duration, imported.total_duration()), (0, 5400));
+    }
+
+    #[test]
+    fn saves_while_the_sessions_load_wait_for_them() {
+        in_scratch_dir();
+        let path = "loading_test_tasks.json";
+        let task = task_with_session(2);
+        let id = task.id.clone();
+        fs::write(path, serde_json::to_string(&HashMap::from([(id.clone(), task)])).unwrap()).unwrap();
+        let (sender, sessions_loading) = mpsc::channel();
+        let mut timer = WorkTimer {
+            tasks: read_task_list(path).unwrap(),
+            data_file: path.to_string(),
+            sessions_loading: Some(sessions_loading),
+            listed_without_sessions: true,
+            ..Default::default()
+        };
+        assert!(timer.tasks[&id].entries.is_empty());
+
+        timer.tasks.get_mut(&id).unwrap().description = "Renamed".to_string();
+        timer.save_tasks();
+        let saved = || serde_json::from_str::<HashMap<String, Task>>(&fs::read_to_string(path).unwrap()).unwrap();
+        assert_eq!(saved()[&id].description, "Legacy");
+
+        sender.send(migrate_tasks(read_data_file(path))).unwrap();
+        assert!(timer.poll_sessions_loading());
+        assert_eq!((saved()[&id].description.as_str(), saved()[&id].entries.len()), ("Renamed", 1));
+    }
+
+    #[test]
+    fn invalid_tasks_are_not_saved() {
+        in_scratch_dir();
+        let path = "invalid_test_tasks.json";
+        let _ = fs::remove_file(path);
+        let save = |task: Task| {
+            let timer = WorkTimer { tasks: [(task.id.clone(), task)].into(), data_file: path.to_string(), ..Default::default() };
+            timer.save_tasks();
+            Path::new(path).exists()
+        };
+        let start = central_european("2024-05-06 09:00");
+        let mut backwards = Task::new("Backwards".to_string());
+        backwards.entries.push(TimeEntry::new(start, start - chrono::Duration::hours(1)));
+        assert!(!save(backwards));
+        let mut negative = task_with_session(1);
+        negative.untracked_duration = -HOUR;
+        assert!(!save(negative));
+        let mut overlapping = task_with_session(2);
+        overlapping.entries.push(TimeEntry::new(start + chrono::Duration::hours(1), start + chrono::Duration::hours(3)));
+        assert!(!save(overlapping));
+        assert!(save(task_with_session(2)));
+    }
+
+    // Two tasks with one session each, times as "HH:MM" on 2024-05-06
+    fn two_sessions(first: (&str, &str), second: (&str, &str)) -> (WorkTimer, String, String) {
+        let session = |(start, end): (&str, &str)| {
+            let mut task = Task::new(format!("{}-{}", start, end));
+            let mut entry = TimeEntry::new(central_european(&format!("2024-05-06 {}", start)), central_european(&format!("2024-05-06 {}", end)));
+            entry.annotation = Some(task.description.clone());
+            task.entries.push(entry);
+            task
+        };
+        let (first, second) = (session(first), session(second));
+        let ids = (first.id.clone(), second.id.clone());
+        let timer = WorkTimer {
+            tasks: [first, second].into_iter().map(|task| (task.id.clone(), task)).collect(),
+            data_file: "conflict_test_tasks.json".to_string(),
+            ..Default::default()
+        };
+        (timer, ids.0, ids.1)
+    }
+
+    fn sessions(timer: &WorkTimer, task_id: &str) -> Vec<(String, String)> {
+        let time = |time: DateTime<Local>| time.format("%H:%M").to_string();
+        timer.tasks[task_id].entries.iter().map(|entry| (time(entry.start), time(entry.end))).collect()
+    }
+
+    fn hours(start: &str, end: &str) -> (String, String) {
+        (start.to_string(), end.to_string())
+    }
+
+    #[test]
+    fn adjacent_sessions_dont_conflict() {
+        let (timer, _, _) = two_sessions(("09:00", "10:00"), ("10:00", "11:00"));
+        assert!(timer.find_conflicts().is_empty());
+    }
+
+    #[test]
+    fn conflicts_are_found_across_tasks() {
+        let (timer, first, second) = two_sessions(("10:00", "12:00"), ("09:00", "11:00"));
+        let conflicts = timer.find_conflicts();
+        assert_eq!(conflicts.len(), 1);
+        // The one that started first comes first, whichever task it is on
+        assert_eq!((conflicts[0].first.task_id.as_str(), conflicts[0].second.task_id.as_str()), (second.as_str(), first.as_str()));
+        assert_eq!(conflicts[0].overlap, HOUR);
+    }
+
+    #[test]
+    fn nested_sessions_overlap_by_the_inner_one() {
+        in_scratch_dir();
+        let (mut timer, outer, inner) = two_sessions(("09:00", "13:00"), ("10:00", "11:00"));
+        let conflicts = timer.find_conflicts();
+        assert_eq!(conflicts[0].overlap, HOUR);
+
+        timer.resolve_conflict(&conflicts[0], ConflictResolution::Merge);
+        assert_eq!(sessions(&timer, &outer), [hours("09:00", "13:00")]);
+        assert!(sessions(&timer, &inner).is_empty());
+    }
+
+    #[test]
+    fn trim_starts_the_later_session_when_the_earlier_one_ends() {
+        in_scratch_dir();
+        let (mut timer, first, second) = two_sessions(("09:00", "11:00"), ("10:00", "12:00"));
+        let conflict = timer.find_conflicts().remove(0);
+        timer.resolve_conflict(&conflict, ConflictResolution::Trim);
+        assert_eq!(sessions(&timer, &first), [hours("09:00", "11:00")]);
+        assert_eq!(sessions(&timer, &second), [hours("11:00", "12:00")]);
+        assert!(timer.find_conflicts().is_empty());
+    }
+
+    #[test]
+    fn merge_keeps_one_session_with_both_notes() {
+        in_scratch_dir();
+        let (mut timer, first, second) = two_sessions(("09:00", "11:00"), ("10:00", "12:00"));
+        let conflict = timer.find_conflicts().remove(0);
+        timer.resolve_conflict(&conflict, ConflictResolution::Merge);
+        assert_eq!(sessions(&timer, &first), [hours("09:00", "12:00")]);
+        assert_eq!(timer.tasks[&first].entries[0].annotation.as_deref(), Some("09:00-11:00; 10:00-12:00"));
+        assert!(sessions(&timer, &second).is_empty());
+    }
+
+    #[test]
+    fn delete_keeps_only_the_other_session() {
+        in_scratch_dir();
+        for (resolution, left) in [(ConflictResolution::DeleteFirst, [0, 1]), (ConflictResolution::DeleteSecond, [1, 0])] {
+            let (mut timer, first, second) = two_sessions(("09:00", "11:00"), ("10:00", "12:00"));
+            let conflict = timer.find_conflicts().remove(0);
+            timer.resolve_conflict(&conflict, resolution);
+            assert_eq!([&first, &second].map(|id| timer.tasks[id].entries.len()), left);
+        }
+    }
+
+    #[test]
+    fn csv_export_imports_back_as_the_same_tasks() {
+        in_scratch_dir();
+        let mut design = task_with_session(2);
+        design.description = "Design, \"phase 2\"".to_string();
+        design.folder = Some("Client".to_string());
+        design.billable = true;
+        design.tags = vec!["ui".to_string(), "urgent".to_string()];
+        design.is_paused = true;
+        let mut admin = Task::new("Admin".to_string());
+        admin.untracked_duration = 90;
+        let exported = WorkTimer {
+            tasks: [design, admin].into_iter().map(|task| (task.id.clone(), task)).collect(),
+            folders: vec!["Client".to_string()],
+            data_file: "csv_test_tasks.json".to_string(),
+            ..Default::default()
+        };
+        let file = exported.export_to_csv().unwrap();
+
+        let mut imported = WorkTimer { folders: vec!["Zebra".to_string()], data_file: "csv_test_tasks.json".to_string(), ..Default::default() };
+        assert_eq!(imported.import_from_csv(&file).unwrap(), (2, 0));
+        assert_eq!(imported.folders, ["Client", "Zebra"]);
+        assert!(imported.folder_styles.contains_key("Client"));
+        let fields = |task: &Task| (task.description.clone(), task.folder.clone(), task.billable, task.tags.clone(), task.is_paused, task.total_duration());
+        for task in exported.tasks.values() {
+            assert_eq!(fields(&imported.tasks[&task.id]), fields(task));
+        }
+        // Importing again finds every task already there
+        assert_eq!(imported.import_from_csv(&file).unwrap(), (0, 2));
+    }
+
+    #[test]
+    fn cleanup_moves_pins_and_the_launch_task_off_removed_tasks() {
+        in_scratch_dir();
+        let tasks: Vec<Task> = ["Old", "Duplicate", "Kept"].into_iter().map(|name| Task::new(name.to_string())).collect();
+        let [old, duplicate, kept] = [0, 1, 2].map(|index| tasks[index].id.clone());
+        let mut timer = WorkTimer {
+            tasks: tasks.into_iter().map(|task| (task.id.clone(), task)).collect(),
+            data_file: "cleanup_test_tasks.json".to_string(),
+            ..Default::default()
+        };
+        timer.settings.pinned_tasks = vec![old.clone(), duplicate.clone()];
+        timer.settings.launch_task = Some(duplicate.clone());
+        timer.cleanup_actions = [(old.clone(), CleanupAction::Delete), (duplicate.clone(), CleanupAction::MergeInto(kept.clone()))].into();
+
+        assert_eq!(timer.apply_cleanup(), (0, 1, 1, 0));
+        assert_eq!(timer.settings.launch_task.as_ref(), Some(&kept));
+        assert_eq!(timer.settings.pinned_tasks, [kept]);
+    }
+}
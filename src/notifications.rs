@@ -0,0 +1,90 @@
+use serde::{Deserialize, Serialize};
+
+// Native desktop notifications through notify-rust: the notification service over D-Bus on
+// Linux, Notification Center on macOS and toasts on Windows. They are sent from a thread of
+// their own, so a slow notification service doesn't hold up the UI.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct NotificationConfig {
+    pub enabled: bool,
+    pub timer_started: bool,
+    pub long_running: bool,
+    pub long_running_minutes: u32, // A session running this long gets a notification
+    pub pomodoro: bool,            // Pomodoros and breaks that are up
+    pub export_finished: bool,
+    pub budget_alert: bool,  // A folder's budget passing one of its alert thresholds
+    pub idle_reminder: bool, // No timer running for a while during work hours
+}
+
+impl Default for NotificationConfig {
+    fn default() -> Self {
+        NotificationConfig {
+            enabled: false,
+            timer_started: true,
+            long_running: true,
+            long_running_minutes: 120,
+            pomodoro: true,
+            export_finished: true,
+            budget_alert: true,
+            idle_reminder: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Event {
+    TimerStarted,
+    LongRunning,
+    Pomodoro,
+    ExportFinished,
+    BudgetAlert,
+    IdleReminder,
+}
+
+impl NotificationConfig {
+    pub fn wants(&self, event: Event) -> bool {
+        self.enabled
+            && match event {
+                Event::TimerStarted => self.timer_started,
+                Event::LongRunning => self.long_running,
+                Event::Pomodoro => self.pomodoro,
+                Event::ExportFinished => self.export_finished,
+                Event::BudgetAlert => self.budget_alert,
+                Event::IdleReminder => self.idle_reminder,
+            }
+    }
+}
+
+pub fn send(title: &str, body: &str) {
+    let mut notification = notify_rust::Notification::new();
+    notification.appname("Work Timer").summary(title).body(body);
+    std::thread::spawn(move || {
+        if let Err(e) = notification.show() {
+            tracing::warn!("Failed to show a notification: {}", e);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn settings_from_before_new_events_turn_them_on() {
+        let config: NotificationConfig = serde_json::from_str(
+            r#"{"enabled": true, "timer_started": false, "long_running": true, "long_running_minutes": 90, "pomodoro": true, "export_finished": false}"#,
+        )
+        .unwrap();
+        assert_eq!(config.long_running_minutes, 90);
+        assert!(!config.wants(Event::TimerStarted));
+        assert!(config.wants(Event::BudgetAlert));
+        assert!(config.wants(Event::IdleReminder));
+    }
+
+    #[test]
+    fn nothing_is_wanted_while_turned_off() {
+        let config = NotificationConfig::default();
+        assert!(!config.wants(Event::BudgetAlert));
+        assert!(!config.wants(Event::ExportFinished));
+    }
+}
@@ -0,0 +1,107 @@
+use serde::{Deserialize, Serialize};
+
+const BASE_URL: &str = "https://api.notion.com/v1";
+const NOTION_VERSION: &str = "2022-06-28";
+
+// Key used in `Task::external_ids` for the Notion page mirroring the task
+pub const EXTERNAL_ID_KEY: &str = "notion";
+
+// Property names the target database is expected to have
+pub const TITLE_PROPERTY: &str = "Name";
+pub const FOLDER_PROPERTY: &str = "Folder";
+pub const STATUS_PROPERTY: &str = "Status";
+pub const TOTAL_HOURS_PROPERTY: &str = "Total Hours";
+pub const TODAY_HOURS_PROPERTY: &str = "Today Hours";
+pub const DAILY_PROPERTY: &str = "Daily";
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct NotionConfig {
+    pub token: String, // Internal integration secret
+    pub database_id: String,
+    #[serde(default)]
+    pub sync_interval_minutes: u32, // 0 syncs only on demand
+}
+
+impl NotionConfig {
+    pub fn is_configured(&self) -> bool {
+        !self.token.trim().is_empty() && !self.database_id.trim().is_empty()
+    }
+}
+
+// One task as it should appear in the database
+#[derive(Debug, Clone)]
+pub struct NotionRow {
+    pub task_id: String,
+    pub page_id: Option<String>,
+    pub title: String,
+    pub folder: String,
+    pub status: String,
+    pub total_hours: f64,
+    pub today_hours: f64,
+    pub daily: String, // Per-day breakdown, e.g. "2024-05-12: 1.50h"
+}
+
+#[derive(Deserialize)]
+struct Page {
+    id: String,
+}
+
+fn request(config: &NotionConfig, method: &str, path: &str) -> ureq::Request {
+    ureq::request(method, &format!("{}{}", BASE_URL, path))
+        .set("Authorization", &format!("Bearer {}", config.token.trim()))
+        .set("Notion-Version", NOTION_VERSION)
+}
+
+fn describe_error(error: ureq::Error) -> String {
+    match error {
+        ureq::Error::Status(code, response) => {
+            format!("HTTP {}: {}", code, response.into_string().unwrap_or_default())
+        }
+        ureq::Error::Transport(transport) => transport.to_string(),
+    }
+}
+
+fn rich_text(content: &str) -> serde_json::Value {
+    // Notion limits a single text object to 2000 characters
+    let content: String = content.chars().take(2000).collect();
+    serde_json::json!({ "rich_text": [{ "text": { "content": content } }] })
+}
+
+fn properties(row: &NotionRow) -> serde_json::Value {
+    let round = |hours: f64| (hours * 100.0).round() / 100.0;
+    serde_json::json!({
+        TITLE_PROPERTY: { "title": [{ "text": { "content": row.title } }] },
+        FOLDER_PROPERTY: rich_text(&row.folder),
+        STATUS_PROPERTY: rich_text(&row.status),
+        TOTAL_HOURS_PROPERTY: { "number": round(row.total_hours) },
+        TODAY_HOURS_PROPERTY: { "number": round(row.today_hours) },
+        DAILY_PROPERTY: rich_text(&row.daily),
+    })
+}
+
+fn create_page(config: &NotionConfig, row: &NotionRow) -> Result<String, String> {
+    let page: Page = request(config, "POST", "/pages")
+        .send_json(serde_json::json!({
+            "parent": { "database_id": config.database_id.trim() },
+            "properties": properties(row),
+        }))
+        .map_err(describe_error)?
+        .into_json()
+        .map_err(|e| e.to_string())?;
+    Ok(page.id)
+}
+
+// Updates the row's page, creating it when it doesn't exist yet or was deleted in Notion.
+// Returns the page id.
+pub fn sync_row(config: &NotionConfig, row: &NotionRow) -> Result<String, String> {
+    if let Some(page_id) = &row.page_id {
+        let result = request(config, "PATCH", &format!("/pages/{}", page_id))
+            .send_json(serde_json::json!({ "properties": properties(row), "archived": false }));
+        match result {
+            Ok(_) => return Ok(page_id.clone()),
+            Err(ureq::Error::Status(404, _)) => {}
+            Err(e) => return Err(describe_error(e)),
+        }
+    }
+    create_page(config, row)
+}
@@ -0,0 +1,87 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+// The app works in its data folder, so data files, attachments, plugins and exports all live
+// there instead of wherever it was started from. That is the platform's folder for app data,
+// the one eframe keeps the window geometry in: ~/.local/share/worktimer on Linux,
+// ~/Library/Application Support/Work-Timer on macOS and %APPDATA%\Work Timer\data on Windows.
+// DATA_DIR_VAR picks another one, e.g. a synced folder or an empty one for test data.
+pub const DATA_DIR_VAR: &str = "WORK_TIMER_DATA_DIR";
+
+const APP_ID: &str = "Work Timer";
+
+// Where the app was started, for paths given on the command line
+static LAUNCH_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+pub fn data_dir() -> Result<PathBuf, String> {
+    if let Some(dir) = std::env::var_os(DATA_DIR_VAR).filter(|dir| !dir.is_empty()) {
+        return Ok(PathBuf::from(dir));
+    }
+    eframe::storage_dir(APP_ID).ok_or_else(|| "there is no app data folder on this system".to_string())
+}
+
+// Changes into the data folder, remembering where the app was started
+pub fn enter_data_dir() -> Result<(), String> {
+    let launch_dir = std::env::current_dir().map_err(|e| e.to_string())?;
+    let _ = LAUNCH_DIR.set(launch_dir);
+    let data_dir = data_dir()?;
+    fs::create_dir_all(&data_dir).map_err(|e| format!("{}: {}", data_dir.display(), e))?;
+    std::env::set_current_dir(&data_dir).map_err(|e| format!("{}: {}", data_dir.display(), e))
+}
+
+// The folder the app was started from when an older version kept its data there and the
+// data folder has none yet
+pub fn old_data_dir() -> Option<PathBuf> {
+    let launch_dir = LAUNCH_DIR.get()?;
+    let data_dir = std::env::current_dir().ok()?;
+    let same = data_dir.canonicalize().ok() == launch_dir.canonicalize().ok();
+    (!same && !data_dir.join("tasks.json").exists() && launch_dir.join("tasks.json").is_file()).then(|| launch_dir.clone())
+}
+
+// Moves the files and folders of an older version into the data folder, once entered.
+// Returns the name of each one moved, or why it couldn't be.
+pub fn move_old_data(files: &[&str], dirs: &[&str]) -> Vec<Result<String, String>> {
+    match (old_data_dir(), std::env::current_dir()) {
+        (Some(from), Ok(to)) => migrate(&from, &to, files, dirs),
+        _ => Vec::new(),
+    }
+}
+
+fn migrate(from: &Path, to: &Path, files: &[&str], dirs: &[&str]) -> Vec<Result<String, String>> {
+    let mut moved = Vec::new();
+    // Along with the previous saves kept for recovery
+    let files = files.iter().flat_map(|file| [file.to_string(), format!("{}.bak", file)]);
+    for name in files.chain(dirs.iter().map(|dir| dir.to_string())) {
+        let source = from.join(&name);
+        if !source.exists() {
+            continue;
+        }
+        moved.push(
+            move_path(&source, &to.join(&name))
+                .map(|()| name)
+                .map_err(|e| format!("{} wasn't moved to {}: {}", source.display(), to.display(), e)),
+        );
+    }
+    moved
+}
+
+// Renaming doesn't work across drives, files are copied there instead. Folders are left
+// where they are in that case.
+fn move_path(source: &Path, target: &Path) -> Result<(), String> {
+    let Err(e) = fs::rename(source, target) else {
+        return Ok(());
+    };
+    if source.is_dir() {
+        return Err(e.to_string());
+    }
+    fs::copy(source, target).and_then(|_| fs::remove_file(source)).map_err(|e| e.to_string())
+}
+
+// A path as the user meant it, relative to where the app was started
+pub fn from_launch_dir(path: &str) -> PathBuf {
+    match LAUNCH_DIR.get() {
+        Some(dir) => dir.join(path),
+        None => PathBuf::from(path),
+    }
+}
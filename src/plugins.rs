@@ -0,0 +1,167 @@
+use rhai::{Array, Dynamic, Engine, Map, Scope, AST};
+use std::{fs, path::Path};
+
+// Rhai scripts in this directory are loaded as plugins
+pub const PLUGIN_DIR: &str = "plugins";
+
+// Functions a plugin may define. Event hooks may return a string, which is shown as a message.
+pub const ON_TASK_STARTED: &str = "on_task_started"; // (task)
+pub const ON_TASK_STOPPED: &str = "on_task_stopped"; // (task)
+pub const ON_TASK_COMPLETED: &str = "on_task_completed"; // (task)
+pub const ON_DAY_ENDED: &str = "on_day_ended"; // (date, tasks)
+const EXPORT_LABEL: &str = "export_label"; // () -> menu label of the export format
+const EXPORT_EXTENSION: &str = "export_extension"; // () -> file extension, "txt" when missing
+const EXPORT: &str = "export"; // (tasks) -> file contents
+
+pub struct Plugin {
+    pub name: String, // File name without the .rhai extension
+    ast: Option<AST>,
+    pub error: Option<String>, // Compile error or last runtime error
+    pub export_label: Option<String>,
+}
+
+impl Plugin {
+    fn defines(&self, function: &str) -> bool {
+        self.ast
+            .as_ref()
+            .is_some_and(|ast| ast.iter_functions().any(|f| f.name == function))
+    }
+}
+
+#[derive(Default)]
+pub struct PluginHost {
+    engine: Engine,
+    pub plugins: Vec<Plugin>,
+}
+
+// Task fields exposed to scripts
+pub fn task_map(task: &crate::Task) -> Dynamic {
+    let mut map = Map::new();
+    map.insert("id".into(), task.id.clone().into());
+    map.insert("description".into(), task.description.clone().into());
+    map.insert("folder".into(), task.folder.clone().map_or(Dynamic::UNIT, Dynamic::from));
+    map.insert("duration_seconds".into(), task.get_current_duration().into());
+    map.insert("running".into(), task.start_time.is_some().into());
+    map.insert("billable".into(), task.billable.into());
+    map.into()
+}
+
+pub fn task_array<'a>(tasks: impl Iterator<Item = &'a crate::Task>) -> Dynamic {
+    tasks.map(task_map).collect::<Array>().into()
+}
+
+impl PluginHost {
+    pub fn load() -> Self {
+        let mut engine = Engine::new();
+        // Keep a runaway script from freezing the UI
+        engine.set_max_operations(1_000_000);
+        engine.set_max_call_levels(32);
+        engine.set_max_string_size(10_000_000);
+
+        let mut host = PluginHost {
+            engine,
+            plugins: Vec::new(),
+        };
+        host.reload();
+        host
+    }
+
+    pub fn reload(&mut self) {
+        self.plugins.clear();
+        let Ok(dir) = fs::read_dir(PLUGIN_DIR) else {
+            return;
+        };
+
+        let mut paths: Vec<_> = dir
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "rhai"))
+            .collect();
+        paths.sort();
+
+        for path in paths {
+            let name = path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let mut plugin = match self.engine.compile_file(path.clone()) {
+                Ok(ast) => Plugin {
+                    name,
+                    ast: Some(ast),
+                    error: None,
+                    export_label: None,
+                },
+                Err(e) => Plugin {
+                    name,
+                    ast: None,
+                    error: Some(e.to_string()),
+                    export_label: None,
+                },
+            };
+            if plugin.defines(EXPORT) {
+                plugin.export_label = Some(
+                    self.call(&plugin, EXPORT_LABEL, ())
+                        .ok()
+                        .and_then(|label| label.into_string().ok())
+                        .unwrap_or_else(|| plugin.name.clone()),
+                );
+            }
+            self.plugins.push(plugin);
+        }
+    }
+
+    fn call(&self, plugin: &Plugin, function: &str, args: impl rhai::FuncArgs) -> Result<Dynamic, String> {
+        let Some(ast) = &plugin.ast else {
+            return Err("plugin failed to load".to_string());
+        };
+        self.engine
+            .call_fn::<Dynamic>(&mut Scope::new(), ast, function, args)
+            .map_err(|e| e.to_string())
+    }
+
+    // Runs the hook in every enabled plugin that defines it. Returns the messages the
+    // hooks returned and the errors they raised, prefixed with the plugin name.
+    pub fn dispatch(&mut self, hook: &str, args: Vec<Dynamic>, disabled: &[String]) -> Vec<String> {
+        let mut messages = Vec::new();
+        for index in 0..self.plugins.len() {
+            let plugin = &self.plugins[index];
+            if disabled.contains(&plugin.name) || !plugin.defines(hook) {
+                continue;
+            }
+            let name = plugin.name.clone();
+            match self.call(plugin, hook, args.clone()) {
+                Ok(result) => {
+                    if let Ok(message) = result.into_string() {
+                        if !message.is_empty() {
+                            messages.push(format!("{}: {}", name, message));
+                        }
+                    }
+                }
+                Err(e) => {
+                    messages.push(format!("Error in plugin {}: {}", name, e));
+                    self.plugins[index].error = Some(e);
+                }
+            }
+        }
+        messages
+    }
+
+    // Runs the plugin's export over the tasks and writes the result. Returns the file name.
+    pub fn export(&self, plugin_name: &str, tasks: Dynamic) -> Result<String, String> {
+        let plugin = self
+            .plugins
+            .iter()
+            .find(|plugin| plugin.name == plugin_name)
+            .ok_or_else(|| format!("plugin {} not found", plugin_name))?;
+
+        let contents = self.call(plugin, EXPORT, (tasks,))?.to_string();
+        let extension = if plugin.defines(EXPORT_EXTENSION) {
+            self.call(plugin, EXPORT_EXTENSION, ())?.to_string()
+        } else {
+            "txt".to_string()
+        };
+
+        let filename = format!("{}_export.{}", plugin.name, extension.trim_start_matches('.'));
+        fs::write(Path::new(&filename), contents).map_err(|e| e.to_string())?;
+        Ok(filename)
+    }
+}
@@ -0,0 +1,126 @@
+use chrono::{DateTime, Duration, Local};
+use serde::{Deserialize, Serialize};
+
+// Pomodoro mode: work intervals that pause the task when they are up, with a short break
+// after each and a long one after every few. Finished intervals and breaks are kept in
+// LOG_FILE for the Pomodoro statistics.
+pub const LOG_FILE: &str = "pomodoros.json";
+
+// A break left running this long past its end means the user stopped for the day, so the
+// cycle starts over with the next timer
+pub const ABANDONED_BREAK_MINUTES: i64 = 60;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PomodoroConfig {
+    pub enabled: bool,
+    pub work_minutes: u32,
+    pub short_break_minutes: u32,
+    pub long_break_minutes: u32,
+    pub long_break_every: u32, // Work intervals before a long break
+    #[serde(default)]
+    pub auto_resume: bool, // Resume the task when the break is over
+}
+
+impl Default for PomodoroConfig {
+    fn default() -> Self {
+        PomodoroConfig {
+            enabled: false,
+            work_minutes: crate::POMODORO_MINUTES,
+            short_break_minutes: 5,
+            long_break_minutes: 15,
+            long_break_every: 4,
+            auto_resume: false,
+        }
+    }
+}
+
+impl PomodoroConfig {
+    pub fn work(&self, task_id: String, now: DateTime<Local>, streak: u32) -> Current {
+        Current::new(Phase::Work, task_id, now, self.work_minutes, streak)
+    }
+
+    // The break after a finished work interval, `streak` counting that one
+    pub fn break_after(&self, task_id: String, now: DateTime<Local>, streak: u32) -> Current {
+        if streak >= self.long_break_every.max(1) {
+            Current::new(Phase::LongBreak, task_id, now, self.long_break_minutes, 0)
+        } else {
+            Current::new(Phase::ShortBreak, task_id, now, self.short_break_minutes, streak)
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum Phase {
+    Work,
+    ShortBreak,
+    LongBreak,
+}
+
+impl Phase {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Phase::Work => "Pomodoro",
+            Phase::ShortBreak => "Short break",
+            Phase::LongBreak => "Long break",
+        }
+    }
+
+    pub fn is_break(&self) -> bool {
+        *self != Phase::Work
+    }
+}
+
+// A finished work interval or break
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Interval {
+    pub phase: Phase,
+    pub task_id: String, // For breaks the task that was paused for it
+    pub start: DateTime<Local>,
+    pub end: DateTime<Local>,
+    pub completed: bool, // Work that ran its full length, or a break taken in full
+}
+
+// The interval in progress. Breaks last until work starts again, even past their length.
+#[derive(Debug, Clone)]
+pub struct Current {
+    pub phase: Phase,
+    pub task_id: String,
+    pub start: DateTime<Local>,
+    pub due: DateTime<Local>,
+    pub streak: u32, // Finished work intervals since the last long break
+    pub announced: bool, // The end was shown, so it isn't shown on every frame
+}
+
+impl Current {
+    fn new(phase: Phase, task_id: String, now: DateTime<Local>, minutes: u32, streak: u32) -> Self {
+        Current {
+            phase,
+            task_id,
+            start: now,
+            due: now + Duration::minutes(minutes.max(1) as i64),
+            streak,
+            announced: false,
+        }
+    }
+
+    // Seconds until it is due, negative once a break runs over
+    pub fn remaining(&self, now: DateTime<Local>) -> i64 {
+        (self.due - now).num_seconds()
+    }
+
+    pub fn finish(&self, now: DateTime<Local>) -> Interval {
+        Interval {
+            phase: self.phase,
+            task_id: self.task_id.clone(),
+            start: self.start,
+            end: now,
+            completed: now >= self.due,
+        }
+    }
+}
+
+// 12:05 for a countdown
+pub fn format_remaining(seconds: i64) -> String {
+    let seconds = seconds.max(0);
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
@@ -0,0 +1,131 @@
+use chrono::{DateTime, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+// Key used in `TimeEntry::synced` for entries pushed to Redmine
+pub const SYNC_KEY: &str = "redmine";
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct RedmineConfig {
+    pub base_url: String, // e.g. https://redmine.example.com
+    pub api_key: String,
+    #[serde(default)]
+    pub default_activity: Option<RedmineActivity>,
+    #[serde(default)]
+    pub activity_mappings: HashMap<String, RedmineActivity>, // Folder name -> activity type
+}
+
+impl RedmineConfig {
+    pub fn is_configured(&self) -> bool {
+        !self.base_url.trim().is_empty() && !self.api_key.trim().is_empty()
+    }
+
+    pub fn activity_for(&self, folder: Option<&str>) -> Option<&RedmineActivity> {
+        folder
+            .and_then(|folder| self.activity_mappings.get(folder))
+            .or(self.default_activity.as_ref())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RedmineActivity {
+    pub id: u64,
+    pub name: String,
+}
+
+// One task's tracked time for one day, pushed as a single Redmine time entry
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RedmineEntry {
+    pub task_id: String,
+    pub issue_id: u64,
+    pub date: NaiveDate,
+    pub hours: f64,
+    pub comments: String,
+    pub activity_id: Option<u64>,
+    pub entry_starts: Vec<DateTime<Local>>, // Starts of the local sessions folded into this entry
+}
+
+// A push that failed and waits to be retried
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FailedPush {
+    pub entry: RedmineEntry,
+    pub error: String,
+    pub attempts: u32,
+    pub last_attempt: DateTime<Local>,
+}
+
+#[derive(Deserialize)]
+struct ActivitiesResponse {
+    time_entry_activities: Vec<ActivityRecord>,
+}
+
+#[derive(Deserialize)]
+struct ActivityRecord {
+    id: u64,
+    name: String,
+    #[serde(default)]
+    active: Option<bool>,
+}
+
+#[derive(Deserialize)]
+struct CreatedResponse {
+    time_entry: CreatedTimeEntry,
+}
+
+#[derive(Deserialize)]
+struct CreatedTimeEntry {
+    id: u64,
+}
+
+fn request(config: &RedmineConfig, method: &str, path: &str) -> ureq::Request {
+    let base_url = config.base_url.trim().trim_end_matches('/');
+    ureq::request(method, &format!("{}{}", base_url, path))
+        .set("X-Redmine-API-Key", config.api_key.trim())
+}
+
+fn describe_error(error: ureq::Error) -> String {
+    match error {
+        ureq::Error::Status(code, response) => {
+            format!("HTTP {}: {}", code, response.into_string().unwrap_or_default())
+        }
+        ureq::Error::Transport(transport) => transport.to_string(),
+    }
+}
+
+pub fn fetch_activities(config: &RedmineConfig) -> Result<Vec<RedmineActivity>, String> {
+    let response: ActivitiesResponse = request(config, "GET", "/enumerations/time_entry_activities.json")
+        .call()
+        .map_err(describe_error)?
+        .into_json()
+        .map_err(|e| e.to_string())?;
+
+    Ok(response
+        .time_entry_activities
+        .into_iter()
+        .filter(|activity| activity.active.unwrap_or(true))
+        .map(|activity| RedmineActivity {
+            id: activity.id,
+            name: activity.name,
+        })
+        .collect())
+}
+
+// Creates the time entry on the issue and returns its id
+pub fn push_entry(config: &RedmineConfig, entry: &RedmineEntry) -> Result<u64, String> {
+    let mut time_entry = serde_json::json!({
+        "issue_id": entry.issue_id,
+        "spent_on": entry.date.to_string(),
+        "hours": (entry.hours * 100.0).round() / 100.0,
+        "comments": entry.comments,
+    });
+    if let Some(activity_id) = entry.activity_id {
+        time_entry["activity_id"] = activity_id.into();
+    }
+
+    let created: CreatedResponse = request(config, "POST", "/time_entries.json")
+        .send_json(serde_json::json!({ "time_entry": time_entry }))
+        .map_err(describe_error)?
+        .into_json()
+        .map_err(|e| e.to_string())?;
+    Ok(created.time_entry.id)
+}
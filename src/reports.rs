@@ -0,0 +1,657 @@
+use crate::billing::{BillingConfig, Taxed};
+use chrono::{DateTime, Datelike, Local, Months, NaiveDate};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use uuid::Uuid;
+
+const UNTAGGED: &str = "(untagged)";
+const ADJUSTMENTS: &str = "(adjustments)";
+const WITHOUT_SESSIONS: &str = "(without sessions)";
+const EXPENSES: &str = "(expenses)";
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum Dimension {
+    Day,
+    Week,
+    Month,
+    Folder,
+    Tag,
+    Task,
+}
+
+impl Dimension {
+    pub const ALL: [Dimension; 6] = [
+        Dimension::Day,
+        Dimension::Week,
+        Dimension::Month,
+        Dimension::Folder,
+        Dimension::Tag,
+        Dimension::Task,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Dimension::Day => "Day",
+            Dimension::Week => "Week",
+            Dimension::Month => "Month",
+            Dimension::Folder => "Folder / client",
+            Dimension::Tag => "Tag",
+            Dimension::Task => "Task",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum Measure {
+    Hours,
+    BillableHours,
+    BillableAmount,
+    Tax,
+    TotalAmount, // Billable amount with tax
+    Expenses,
+}
+
+impl Measure {
+    pub const ALL: [Measure; 6] = [
+        Measure::Hours,
+        Measure::BillableHours,
+        Measure::BillableAmount,
+        Measure::Tax,
+        Measure::TotalAmount,
+        Measure::Expenses,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Measure::Hours => "Hours",
+            Measure::BillableHours => "Billable hours",
+            Measure::BillableAmount => "Billable amount",
+            Measure::Tax => "Tax",
+            Measure::TotalAmount => "Total with tax",
+            Measure::Expenses => "Expenses",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+pub enum DateRange {
+    ThisWeek,
+    LastWeek,
+    ThisMonth,
+    LastMonth,
+    Last30Days,
+    #[default]
+    AllTime,
+    Custom { from: NaiveDate, to: NaiveDate }, // Set from the command line
+}
+
+impl DateRange {
+    pub const ALL: [DateRange; 6] = [
+        DateRange::ThisWeek,
+        DateRange::LastWeek,
+        DateRange::ThisMonth,
+        DateRange::LastMonth,
+        DateRange::Last30Days,
+        DateRange::AllTime,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            DateRange::ThisWeek => "This week",
+            DateRange::LastWeek => "Last week",
+            DateRange::ThisMonth => "This month",
+            DateRange::LastMonth => "Last month",
+            DateRange::Last30Days => "Last 30 days",
+            DateRange::AllTime => "All time",
+            DateRange::Custom { .. } => "Custom",
+        }
+    }
+
+    // First and last day included, relative to today. None for all time.
+    pub fn days(&self, today: NaiveDate, calendar: crate::Calendar) -> Option<(NaiveDate, NaiveDate)> {
+        let week_start = calendar.week_start(today);
+        let month_start = today.with_day(1)?;
+        match self {
+            DateRange::ThisWeek => Some((week_start, today)),
+            DateRange::LastWeek => Some((week_start - chrono::Duration::days(7), week_start - chrono::Duration::days(1))),
+            DateRange::ThisMonth => Some((month_start, today)),
+            DateRange::LastMonth => Some((month_start.checked_sub_months(Months::new(1))?, month_start - chrono::Duration::days(1))),
+            DateRange::Last30Days => Some((today - chrono::Duration::days(29), today)),
+            DateRange::AllTime => None,
+            DateRange::Custom { from, to } => Some((*from, *to)),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum ReportFormat {
+    Csv,
+    Markdown,
+}
+
+impl ReportFormat {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ReportFormat::Csv => "CSV",
+            ReportFormat::Markdown => "Markdown table",
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ReportFormat::Csv => "text/csv",
+            ReportFormat::Markdown => "text/markdown",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReportDefinition {
+    pub id: String,
+    pub name: String,
+    pub dimensions: Vec<Dimension>, // Rows are grouped by these, in this order
+    pub measures: Vec<Measure>,
+    pub range: DateRange,
+    #[serde(default)]
+    pub folder: Option<String>, // Only this folder, None for every folder
+    #[serde(default)]
+    pub tag: Option<String>, // Only tasks with this tag
+    #[serde(default)]
+    pub billable_only: bool,
+    pub format: ReportFormat,
+    #[serde(default)]
+    pub last_run: Option<DateTime<Local>>,
+    #[serde(default)]
+    pub email_to: String, // Recipient for sending the report, empty to not mail it
+    #[serde(default)]
+    pub email_weekday: Option<chrono::Weekday>, // Also mail it every week on this day
+    #[serde(default)]
+    pub last_scheduled_send: Option<NaiveDate>, // Day the weekly mail was last due and sent
+    #[serde(default)]
+    pub timezone: Option<String>, // Days in this zone, e.g. the client's, instead of the workspace's
+}
+
+impl ReportDefinition {
+    pub fn new(name: String) -> Self {
+        ReportDefinition {
+            id: Uuid::new_v4().to_string(),
+            name,
+            dimensions: vec![Dimension::Folder],
+            measures: vec![Measure::Hours],
+            range: DateRange::LastMonth,
+            folder: None,
+            tag: None,
+            billable_only: false,
+            format: ReportFormat::Csv,
+            last_run: None,
+            email_to: String::new(),
+            email_weekday: None,
+            last_scheduled_send: None,
+            timezone: None,
+        }
+    }
+
+    // The zone the report's days are in, None for the workspace zone
+    pub fn zone(&self) -> Result<Option<Tz>, String> {
+        self.timezone
+            .as_deref()
+            .filter(|name| !name.trim().is_empty())
+            .map(crate::timezones::parse)
+            .transpose()
+    }
+
+    // The day it is in the report's zone, which periods like "This week" count from
+    pub fn today(&self, calendar: crate::Calendar) -> NaiveDate {
+        match self.zone() {
+            Ok(Some(zone)) => calendar.day_of(crate::timezones::to_wall_clock(Local::now(), zone)),
+            _ => calendar.today(),
+        }
+    }
+
+    fn zone_label(&self) -> String {
+        match self.zone() {
+            Ok(Some(zone)) => zone.name().to_string(),
+            _ => crate::timezones::workspace(),
+        }
+    }
+}
+
+// One attempt at mailing a report, kept as send history
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SentReport {
+    pub report_id: String,
+    pub to: String,
+    pub filename: String,
+    pub sent_at: DateTime<Local>,
+    pub error: Option<String>,
+}
+
+pub struct ReportTable {
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+#[derive(Default, Clone, Copy)]
+struct Totals {
+    seconds: i64,
+    billable_seconds: i64,
+    amount: Taxed,
+    expenses: f64,
+}
+
+impl Totals {
+    fn value(&self, measure: Measure) -> f64 {
+        match measure {
+            Measure::Hours => self.seconds as f64 / 3600.0,
+            Measure::BillableHours => self.billable_seconds as f64 / 3600.0,
+            Measure::BillableAmount => self.amount.subtotal,
+            Measure::Tax => self.amount.tax,
+            Measure::TotalAmount => self.amount.total,
+            Measure::Expenses => self.expenses,
+        }
+    }
+}
+
+// Value of a date dimension, or None for the others
+fn date_key(dimension: Dimension, date: NaiveDate, calendar: crate::Calendar) -> Option<String> {
+    match dimension {
+        Dimension::Day => Some(date.to_string()),
+        Dimension::Week => Some(format!("Week of {}", calendar.week_start(date))),
+        Dimension::Month => Some(date.format("%Y-%m").to_string()),
+        _ => None,
+    }
+}
+
+// Values of one dimension for a session, or for time without one when there's no date. A task
+// with several tags counts under each of them.
+fn keys(dimension: Dimension, task: &crate::Task, date: Option<NaiveDate>, calendar: crate::Calendar) -> Vec<String> {
+    match dimension {
+        Dimension::Day | Dimension::Week | Dimension::Month => match date {
+            Some(date) => date_key(dimension, date, calendar).into_iter().collect(),
+            None => vec![WITHOUT_SESSIONS.to_string()],
+        },
+        Dimension::Folder => vec![task.folder.clone().unwrap_or_else(|| "Uncategorized".to_string())],
+        Dimension::Tag if task.tags.is_empty() => vec![UNTAGGED.to_string()],
+        Dimension::Tag => task.tags.clone(),
+        Dimension::Task => vec![task.description.clone()],
+    }
+}
+
+// Sessions, including the one still running, are split at the day boundary so each part
+// counts towards the day it ran on, in the report's zone when it has one. Time without a
+// session has no day, so only reports over all time count it. Adjustments belong to no
+// task, so they only count when the report isn't limited to a folder, tag or billable time.
+// Billable amounts are taxed at the rate of the task's folder. Expenses belong to a folder and
+// a day but no task or tag, so a report limited to a tag leaves them out.
+fn group<'a>(
+    definition: &ReportDefinition,
+    tasks: impl Iterator<Item = &'a crate::Task>,
+    adjustments: &[crate::Adjustment],
+    expenses: &[crate::Expense],
+    today: NaiveDate,
+    calendar: crate::Calendar,
+    billing: &BillingConfig,
+) -> BTreeMap<Vec<String>, Totals> {
+    // An unknown zone is reported where the report is edited or run, here it falls back
+    // to the workspace zone
+    let zone = definition.zone().ok().flatten();
+    let wall_clock = |time: DateTime<Local>| zone.map_or(time, |zone| crate::timezones::to_wall_clock(time, zone));
+    let days = definition.range.days(today, calendar);
+    let mut groups: BTreeMap<Vec<String>, Totals> = BTreeMap::new();
+
+    let tasks = tasks
+        .filter(|task| definition.folder.is_none() || task.folder == definition.folder)
+        .filter(|task| definition.tag.as_ref().is_none_or(|tag| task.tags.contains(tag)))
+        .filter(|task| !definition.billable_only || task.billable);
+    for task in tasks {
+        let running = task.start_time.map(|start| (start, Local::now()));
+        let sessions = task.entries.iter().map(|entry| (entry.start, entry.end)).chain(running);
+        let parts = sessions
+            .flat_map(|(start, end)| calendar.split_by_day(wall_clock(start), wall_clock(end)))
+            .map(|(date, seconds)| (Some(date), seconds));
+        let untracked = (days.is_none() && task.untracked_duration > 0).then_some((None, task.untracked_duration));
+        for (date, seconds) in parts.chain(untracked) {
+            if date.is_some_and(|date| days.is_some_and(|(from, to)| date < from || date > to)) {
+                continue;
+            }
+
+            // Every combination of the dimension values
+            let mut group_keys: Vec<Vec<String>> = vec![Vec::new()];
+            for dimension in &definition.dimensions {
+                let values = keys(*dimension, task, date, calendar);
+                group_keys = group_keys
+                    .into_iter()
+                    .flat_map(|key| {
+                        values.iter().map(move |value| {
+                            let mut key = key.clone();
+                            key.push(value.clone());
+                            key
+                        })
+                    })
+                    .collect();
+            }
+
+            for key in group_keys {
+                let totals = groups.entry(key).or_default();
+                totals.seconds += seconds;
+                if task.billable {
+                    totals.billable_seconds += seconds;
+                    let amount = seconds as f64 / 3600.0 * task.hourly_rate.unwrap_or(0.0);
+                    totals.amount += billing.taxed(amount, task.folder.as_deref());
+                }
+            }
+        }
+    }
+
+    if definition.folder.is_none() && definition.tag.is_none() && !definition.billable_only {
+        for adjustment in adjustments {
+            if days.is_some_and(|(from, to)| adjustment.date < from || adjustment.date > to) {
+                continue;
+            }
+            let key = definition
+                .dimensions
+                .iter()
+                .map(|dimension| date_key(*dimension, adjustment.date, calendar).unwrap_or_else(|| ADJUSTMENTS.to_string()))
+                .collect();
+            groups.entry(key).or_default().seconds += adjustment.seconds;
+        }
+    }
+
+    if definition.tag.is_none() {
+        let expenses = expenses
+            .iter()
+            .filter(|expense| definition.folder.is_none() || expense.folder == definition.folder)
+            .filter(|expense| days.is_none_or(|(from, to)| expense.date >= from && expense.date <= to));
+        for expense in expenses {
+            let key = definition
+                .dimensions
+                .iter()
+                .map(|dimension| match dimension {
+                    Dimension::Folder => expense.folder.clone().unwrap_or_else(|| "Uncategorized".to_string()),
+                    dimension => date_key(*dimension, expense.date, calendar).unwrap_or_else(|| EXPENSES.to_string()),
+                })
+                .collect();
+            groups.entry(key).or_default().expenses += expense.amount;
+        }
+    }
+    groups
+}
+
+// Expenses only add rows when the report shows them
+pub fn build<'a>(
+    definition: &ReportDefinition,
+    tasks: impl Iterator<Item = &'a crate::Task>,
+    adjustments: &[crate::Adjustment],
+    expenses: &[crate::Expense],
+    today: NaiveDate,
+    calendar: crate::Calendar,
+    billing: &BillingConfig,
+) -> ReportTable {
+    let expenses = if definition.measures.contains(&Measure::Expenses) { expenses } else { &[] };
+    let groups = group(definition, tasks, adjustments, expenses, today, calendar, billing);
+    let headers = definition
+        .dimensions
+        .iter()
+        .map(|dimension| match dimension {
+            Dimension::Day | Dimension::Week | Dimension::Month => format!("{} ({})", dimension.label(), definition.zone_label()),
+            _ => dimension.label().to_string(),
+        })
+        .chain(definition.measures.iter().map(|measure| measure.label().to_string()))
+        .collect();
+    let rows = groups
+        .into_iter()
+        .map(|(key, totals)| {
+            key.into_iter()
+                .chain(definition.measures.iter().map(|measure| billing.format(totals.value(*measure))))
+                .collect()
+        })
+        .collect();
+    ReportTable { headers, rows }
+}
+
+// The report as JSON for the HTTP API: one object per group, keyed by the lowercase
+// dimension names, with every measure as a number
+pub fn build_json<'a>(
+    definition: &ReportDefinition,
+    tasks: impl Iterator<Item = &'a crate::Task>,
+    adjustments: &[crate::Adjustment],
+    expenses: &[crate::Expense],
+    today: NaiveDate,
+    calendar: crate::Calendar,
+    billing: &BillingConfig,
+) -> serde_json::Value {
+    let rows: Vec<serde_json::Value> = group(definition, tasks, adjustments, expenses, today, calendar, billing)
+        .into_iter()
+        .map(|(key, totals)| {
+            let mut row = serde_json::Map::new();
+            for (dimension, value) in definition.dimensions.iter().zip(key) {
+                row.insert(format!("{:?}", dimension).to_lowercase(), value.into());
+            }
+            row.insert("hours".to_string(), (totals.seconds as f64 / 3600.0).into());
+            row.insert("billable_hours".to_string(), (totals.billable_seconds as f64 / 3600.0).into());
+            row.insert("billable_amount".to_string(), totals.amount.subtotal.into());
+            row.insert("tax".to_string(), totals.amount.tax.into());
+            row.insert("total_amount".to_string(), totals.amount.total.into());
+            row.insert("expenses".to_string(), totals.expenses.into());
+            row.into()
+        })
+        .collect();
+    let (from, to) = definition.range.days(today, calendar).unzip();
+    serde_json::json!({
+        "from": from,
+        "to": to,
+        "timezone": definition.zone_label(),
+        "group_by": definition.dimensions.iter().map(|dimension| format!("{:?}", dimension).to_lowercase()).collect::<Vec<_>>(),
+        "rows": rows,
+    })
+}
+
+// The report in its output format
+pub fn render(definition: &ReportDefinition, table: &ReportTable) -> Result<String, Box<dyn std::error::Error>> {
+    match definition.format {
+        ReportFormat::Csv => {
+            let mut writer = csv::Writer::from_writer(Vec::new());
+            writer.write_record(&table.headers)?;
+            for row in &table.rows {
+                writer.write_record(row)?;
+            }
+            Ok(String::from_utf8(writer.into_inner()?)?)
+        }
+        ReportFormat::Markdown => {
+            let escape = |cell: &String| cell.replace('|', "\\|");
+            let mut markdown = format!("# {}\n\n", definition.name);
+            markdown.push_str(&format!("| {} |\n", table.headers.iter().map(escape).collect::<Vec<_>>().join(" | ")));
+            markdown.push_str(&format!("|{}\n", "---|".repeat(table.headers.len())));
+            for row in &table.rows {
+                markdown.push_str(&format!("| {} |\n", row.iter().map(escape).collect::<Vec<_>>().join(" | ")));
+            }
+            Ok(markdown)
+        }
+    }
+}
+
+// Writes the report next to the data files. Returns the file name.
+pub fn write(definition: &ReportDefinition, table: &ReportTable, today: NaiveDate) -> Result<String, Box<dyn std::error::Error>> {
+    let extension = match definition.format {
+        ReportFormat::Csv => "csv",
+        ReportFormat::Markdown => "md",
+    };
+    let filename = format!("report_{}_{}.{}", crate::sanitize_filename(&definition.name), today, extension);
+    fs::write(&filename, render(definition, table)?)?;
+    if extension == "csv" {
+        crate::record_export(&filename);
+    }
+    Ok(filename)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::central_european;
+    use crate::{Adjustment, Calendar, Expense, Task, TimeEntry};
+
+    const CALENDAR: Calendar = Calendar { day_start_hour: 0, week_start: chrono::Weekday::Mon };
+
+    fn date(text: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(text, "%Y-%m-%d").unwrap()
+    }
+
+    fn task(description: &str, folder: Option<&str>, sessions: &[(&str, &str)]) -> Task {
+        let mut task = Task::new(description.to_string());
+        task.folder = folder.map(str::to_string);
+        for (start, end) in sessions {
+            task.entries.push(TimeEntry::new(central_european(start), central_european(end)));
+        }
+        task
+    }
+
+    fn definition(dimensions: Vec<Dimension>, range: DateRange) -> ReportDefinition {
+        ReportDefinition { dimensions, range, ..ReportDefinition::new("Test".to_string()) }
+    }
+
+    fn may() -> DateRange {
+        DateRange::Custom { from: date("2024-05-01"), to: date("2024-05-31") }
+    }
+
+    fn hours(groups: &BTreeMap<Vec<String>, Totals>, key: &[&str]) -> f64 {
+        let key: Vec<String> = key.iter().map(|value| value.to_string()).collect();
+        groups.get(&key).map_or(0.0, |totals| totals.value(Measure::Hours))
+    }
+
+    #[test]
+    fn groups_sessions_within_the_range() {
+        let tasks = [
+            task("Design", Some("Client"), &[("2024-05-06 09:00", "2024-05-06 12:00"), ("2024-04-30 09:00", "2024-04-30 17:00")]),
+            task("Email", None, &[("2024-05-06 13:00", "2024-05-06 14:00"), ("2024-05-07 09:00", "2024-05-07 09:30")]),
+        ];
+        let definition = definition(vec![Dimension::Folder, Dimension::Day], may());
+        let groups = group(&definition, tasks.iter(), &[], &[], date("2024-06-01"), CALENDAR, &BillingConfig::default());
+
+        assert_eq!(groups.len(), 3);
+        assert_eq!(hours(&groups, &["Client", "2024-05-06"]), 3.0);
+        assert_eq!(hours(&groups, &["Uncategorized", "2024-05-06"]), 1.0);
+        assert_eq!(hours(&groups, &["Uncategorized", "2024-05-07"]), 0.5);
+    }
+
+    #[test]
+    fn a_task_counts_under_each_of_its_tags() {
+        let mut tagged = task("Review", None, &[("2024-05-06 09:00", "2024-05-06 11:00")]);
+        tagged.tags = vec!["code".to_string(), "meeting".to_string()];
+        let untagged = task("Lunch", None, &[("2024-05-06 12:00", "2024-05-06 13:00")]);
+        let tasks = [tagged, untagged];
+        let groups = group(&definition(vec![Dimension::Tag], may()), tasks.iter(), &[], &[], date("2024-06-01"), CALENDAR, &BillingConfig::default());
+
+        assert_eq!(hours(&groups, &["code"]), 2.0);
+        assert_eq!(hours(&groups, &["meeting"]), 2.0);
+        assert_eq!(hours(&groups, &[UNTAGGED]), 1.0);
+    }
+
+    #[test]
+    fn billable_amounts_are_taxed_at_the_folder_rate() {
+        let mut local = task("Local", Some("Local"), &[("2024-05-06 09:00", "2024-05-06 11:00")]);
+        let mut abroad = task("Abroad", Some("Abroad"), &[("2024-05-06 13:00", "2024-05-06 14:00")]);
+        let unbilled = task("Admin", Some("Local"), &[("2024-05-07 09:00", "2024-05-07 10:00")]);
+        for task in [&mut local, &mut abroad] {
+            task.billable = true;
+            task.hourly_rate = Some(50.0);
+        }
+        let mut billing = BillingConfig { tax_percent: 20.0, ..Default::default() };
+        billing.folder_tax_percents.insert("Abroad".to_string(), 0.0);
+        let tasks = [local, abroad, unbilled];
+        let groups = group(&definition(vec![Dimension::Folder], may()), tasks.iter(), &[], &[], date("2024-06-01"), CALENDAR, &billing);
+
+        let local = groups[&vec!["Local".to_string()]];
+        assert_eq!(local.value(Measure::Hours), 3.0);
+        assert_eq!(local.value(Measure::BillableHours), 2.0);
+        assert_eq!(local.value(Measure::BillableAmount), 100.0);
+        assert!((local.value(Measure::Tax) - 20.0).abs() < 1e-9);
+        assert!((local.value(Measure::TotalAmount) - 120.0).abs() < 1e-9);
+        let abroad = groups[&vec!["Abroad".to_string()]];
+        assert_eq!(abroad.value(Measure::Tax), 0.0);
+        assert_eq!(abroad.value(Measure::TotalAmount), 50.0);
+    }
+
+    #[test]
+    fn time_without_sessions_only_counts_over_all_time() {
+        let mut imported = task("Imported", None, &[("2024-05-06 09:00", "2024-05-06 10:00")]);
+        imported.untracked_duration = 2 * 3600;
+        let tasks = [imported];
+        let today = date("2024-06-01");
+
+        let all_time = group(&definition(vec![Dimension::Day], DateRange::AllTime), tasks.iter(), &[], &[], today, CALENDAR, &BillingConfig::default());
+        assert_eq!(hours(&all_time, &["2024-05-06"]), 1.0);
+        assert_eq!(hours(&all_time, &[WITHOUT_SESSIONS]), 2.0);
+        let by_task = group(&definition(vec![Dimension::Task], DateRange::AllTime), tasks.iter(), &[], &[], today, CALENDAR, &BillingConfig::default());
+        assert_eq!(hours(&by_task, &["Imported"]), 3.0);
+
+        let in_may = group(&definition(vec![Dimension::Task], may()), tasks.iter(), &[], &[], today, CALENDAR, &BillingConfig::default());
+        assert_eq!(hours(&in_may, &["Imported"]), 1.0);
+    }
+
+    #[test]
+    fn adjustments_count_unless_the_report_is_filtered() {
+        let adjustment = |day: &str, seconds: i64| Adjustment {
+            id: day.to_string(),
+            date: date(day),
+            seconds,
+            reason: String::new(),
+        };
+        let adjustments = [adjustment("2024-05-06", 1800), adjustment("2024-05-08", -3600), adjustment("2024-04-30", 3600)];
+        let tasks = [task("Design", Some("Client"), &[("2024-05-06 09:00", "2024-05-06 12:00")])];
+        let today = date("2024-06-01");
+
+        let by_day = group(&definition(vec![Dimension::Day, Dimension::Folder], may()), tasks.iter(), &adjustments, &[], today, CALENDAR, &BillingConfig::default());
+        assert_eq!(hours(&by_day, &["2024-05-06", "Client"]), 3.0);
+        assert_eq!(hours(&by_day, &["2024-05-06", ADJUSTMENTS]), 0.5);
+        assert_eq!(hours(&by_day, &["2024-05-08", ADJUSTMENTS]), -1.0);
+        assert_eq!(by_day.len(), 3);
+
+        let mut one_folder = definition(vec![Dimension::Folder], may());
+        one_folder.folder = Some("Client".to_string());
+        let filtered = group(&one_folder, tasks.iter(), &adjustments, &[], today, CALENDAR, &BillingConfig::default());
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(hours(&filtered, &["Client"]), 3.0);
+    }
+
+    #[test]
+    fn expenses_count_by_folder_and_day() {
+        let expense = |day: &str, folder: Option<&str>, amount: f64| Expense {
+            id: day.to_string(),
+            description: "Train".to_string(),
+            amount,
+            date: date(day),
+            folder: folder.map(str::to_string),
+        };
+        let expenses = [
+            expense("2024-05-06", Some("Client"), 40.0),
+            expense("2024-05-20", Some("Client"), 10.0),
+            expense("2024-05-07", None, 5.0),
+            expense("2024-04-30", Some("Client"), 99.0),
+        ];
+        let tasks = [task("Design", Some("Client"), &[("2024-05-06 09:00", "2024-05-06 12:00")])];
+        let today = date("2024-06-01");
+        let value = |groups: &BTreeMap<Vec<String>, Totals>, key: &[&str]| {
+            let key: Vec<String> = key.iter().map(|value| value.to_string()).collect();
+            groups[&key].value(Measure::Expenses)
+        };
+
+        let by_folder = group(&definition(vec![Dimension::Folder], may()), tasks.iter(), &[], &expenses, today, CALENDAR, &BillingConfig::default());
+        assert_eq!(value(&by_folder, &["Client"]), 50.0);
+        assert_eq!(hours(&by_folder, &["Client"]), 3.0);
+        assert_eq!(value(&by_folder, &["Uncategorized"]), 5.0);
+
+        let by_task = group(&definition(vec![Dimension::Week, Dimension::Task], may()), tasks.iter(), &[], &expenses, today, CALENDAR, &BillingConfig::default());
+        assert_eq!(value(&by_task, &["Week of 2024-05-06", EXPENSES]), 45.0);
+        assert_eq!(value(&by_task, &["Week of 2024-05-20", EXPENSES]), 10.0);
+
+        let mut tagged = definition(vec![Dimension::Folder], may());
+        tagged.tag = Some("design".to_string());
+        assert!(group(&tagged, tasks.iter(), &[], &expenses, today, CALENDAR, &BillingConfig::default()).is_empty());
+
+        // Only reports showing expenses get rows for them
+        let hours_only = build(&definition(vec![Dimension::Task], may()), tasks.iter(), &[], &expenses, today, CALENDAR, &BillingConfig::default());
+        assert_eq!(hours_only.rows, vec![vec!["Design".to_string(), "3.00".to_string()]]);
+    }
+}
@@ -0,0 +1,425 @@
+use crate::pomodoro;
+use crate::{Adjustment, Expense, FolderStyle, PlannedBlock, Settings, Task, TimeOff, WeekAllocation};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+// JSON Schemas for the data files other tools are most likely to write, printed by
+// `work_timer validate --print-schema <file>`. Only the keywords the checker below
+// understands are used: type, properties, required, additionalProperties, items, enum,
+// format (date-time, date, time) and minimum.
+pub const SCHEMA_FILES: [&str; 7] = [
+    "tasks.json",
+    "folders.json",
+    "adjustments.json",
+    "expenses.json",
+    "time_off.json",
+    "plan.json",
+    "allocations.json",
+];
+
+// Files the app can read and write back, with or without a schema
+pub const CHECKED_FILES: [&str; 10] = [
+    "tasks.json",
+    "folders.json",
+    "folder_styles.json",
+    "settings.json",
+    "adjustments.json",
+    "expenses.json",
+    "time_off.json",
+    "plan.json",
+    "allocations.json",
+    pomodoro::LOG_FILE,
+];
+
+fn nullable(kind: &str) -> Value {
+    json!({ "type": [kind, "null"] })
+}
+
+fn formatted(format: &str) -> Value {
+    json!({ "type": "string", "format": format })
+}
+
+fn object(properties: Value, required: &[&str]) -> Value {
+    json!({
+        "type": "object",
+        "properties": properties,
+        "required": required,
+        "additionalProperties": false,
+    })
+}
+
+fn optional_time() -> Value {
+    json!({ "type": ["string", "null"], "format": "date-time" })
+}
+
+fn array_of(items: Value) -> Value {
+    json!({ "type": "array", "items": items })
+}
+
+fn time_entry() -> Value {
+    object(
+        json!({
+            "start": formatted("date-time"),
+            "end": formatted("date-time"),
+            "synced": { "type": "object", "additionalProperties": { "type": "string" } },
+            "annotation": nullable("string"),
+            "source": { "enum": ["Gui", "Cli", "Api", "Import", "Auto"] },
+            "timezone": nullable("string"),
+        }),
+        &["start", "end"],
+    )
+}
+
+fn task() -> Value {
+    object(
+        json!({
+            "id": { "type": "string" },
+            "description": { "type": "string" },
+            "folder": nullable("string"),
+            "total_duration": {
+                "type": "integer",
+                "deprecated": true,
+                "description": "Written by older versions, see untracked_duration",
+            },
+            "untracked_duration": {
+                "type": "integer",
+                "minimum": 0,
+                "description": "Seconds without a recorded session, added to the sum of the entries",
+            },
+            "start_time": optional_time(),
+            "is_paused": { "type": "boolean" },
+            "billable": { "type": "boolean" },
+            "entries": array_of(time_entry()),
+            "redmine_issue_id": { "type": ["integer", "null"], "minimum": 0 },
+            "issue_key": nullable("string"),
+            "external_ids": { "type": "object", "additionalProperties": { "type": "string" } },
+            "tags": array_of(json!({ "type": "string" })),
+            "hourly_rate": nullable("number"),
+            "estimate_minutes": { "type": ["integer", "null"], "minimum": 0 },
+            "estimate_in_pomodoros": { "type": "boolean" },
+            "attendees": { "type": ["integer", "null"], "minimum": 0 },
+            "attendee_rate": nullable("number"),
+            "created_at": optional_time(),
+            "last_active": optional_time(),
+            "archived": { "type": "boolean" },
+            "completed_at": optional_time(),
+            "attachments": array_of(json!({ "type": "string" })),
+            "work_kind": { "enum": ["Deep", "Shallow", null] },
+            "running_source": { "enum": ["Gui", "Cli", "Api", "Import", "Auto"] },
+            "color_label": { "enum": ["Red", "Orange", "Yellow", "Green", "Blue", "Purple", "Gray", null] },
+        }),
+        &["id", "description", "is_paused"],
+    )
+}
+
+// The schema of one of SCHEMA_FILES, looked up by file name
+pub fn schema(file: &str) -> Option<Value> {
+    let (title, body) = match file_name(file) {
+        "tasks.json" => ("Tasks by id", json!({ "type": "object", "additionalProperties": task() })),
+        "folders.json" => ("Folder names", array_of(json!({ "type": "string" }))),
+        "adjustments.json" => (
+            "Time added to or taken off a day",
+            array_of(object(
+                json!({
+                    "id": { "type": "string" },
+                    "date": formatted("date"),
+                    "seconds": { "type": "integer" },
+                    "reason": { "type": "string" },
+                }),
+                &["id", "date", "seconds", "reason"],
+            )),
+        ),
+        "expenses.json" => (
+            "Expenses",
+            array_of(object(
+                json!({
+                    "id": { "type": "string" },
+                    "description": { "type": "string" },
+                    "amount": { "type": "number" },
+                    "date": formatted("date"),
+                    "folder": nullable("string"),
+                }),
+                &["id", "description", "amount", "date"],
+            )),
+        ),
+        "time_off.json" => (
+            "Days off",
+            array_of(object(
+                json!({
+                    "date": formatted("date"),
+                    "kind": { "enum": ["Vacation", "Sick", "Holiday"] },
+                    "note": { "type": "string" },
+                }),
+                &["date", "kind"],
+            )),
+        ),
+        "plan.json" => (
+            "Planned blocks",
+            array_of(object(
+                json!({
+                    "id": { "type": "string" },
+                    "task_id": { "type": "string" },
+                    "date": formatted("date"),
+                    "start": formatted("time"),
+                    "minutes": { "type": "integer", "minimum": 0 },
+                }),
+                &["id", "task_id", "date", "start", "minutes"],
+            )),
+        ),
+        "allocations.json" => (
+            "Weekly hours per folder",
+            array_of(object(
+                json!({
+                    "week_start": formatted("date"),
+                    "folder": { "type": "string" },
+                    "hours": { "type": "number" },
+                }),
+                &["week_start", "folder", "hours"],
+            )),
+        ),
+        _ => return None,
+    };
+    let mut schema = json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": title,
+    });
+    if let (Some(schema), Value::Object(body)) = (schema.as_object_mut(), body) {
+        schema.extend(body);
+    }
+    Some(schema)
+}
+
+fn file_name(path: &str) -> &str {
+    path.rsplit(['/', '\\']).next().unwrap_or(path)
+}
+
+pub fn is_checked(file: &str) -> bool {
+    CHECKED_FILES.contains(&file_name(file))
+}
+
+// Where the value breaks the schema, one line per problem with its JSON pointer
+pub fn validate(value: &Value, schema: &Value) -> Vec<String> {
+    let mut problems = Vec::new();
+    check(value, schema, "", &mut problems);
+    problems
+}
+
+fn type_matches(value: &Value, kind: &str) -> bool {
+    match kind {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "number" => value.is_number(),
+        "boolean" => value.is_boolean(),
+        "null" => value.is_null(),
+        _ => false,
+    }
+}
+
+fn check(value: &Value, schema: &Value, pointer: &str, problems: &mut Vec<String>) {
+    let at = if pointer.is_empty() { "/" } else { pointer };
+    if let Some(kinds) = schema.get("type") {
+        let kinds: Vec<&str> = match kinds {
+            Value::Array(kinds) => kinds.iter().filter_map(Value::as_str).collect(),
+            kind => kind.as_str().into_iter().collect(),
+        };
+        if !kinds.iter().any(|kind| type_matches(value, kind)) {
+            problems.push(format!("{}: expected {}, found {}", at, kinds.join(" or "), value));
+            return;
+        }
+    }
+    if let Some(allowed) = schema.get("enum").and_then(Value::as_array) {
+        if !allowed.contains(value) {
+            let allowed: Vec<String> = allowed.iter().map(Value::to_string).collect();
+            problems.push(format!("{}: {} is not one of {}", at, value, allowed.join(", ")));
+        }
+    }
+    if let (Some(minimum), Some(number)) = (schema.get("minimum").and_then(Value::as_f64), value.as_f64()) {
+        if number < minimum {
+            problems.push(format!("{}: {} is below {}", at, number, minimum));
+        }
+    }
+    if let (Some(format), Some(text)) = (schema.get("format").and_then(Value::as_str), value.as_str()) {
+        let valid = match format {
+            "date-time" => chrono::DateTime::parse_from_rfc3339(text).is_ok(),
+            "date" => chrono::NaiveDate::parse_from_str(text, "%Y-%m-%d").is_ok(),
+            "time" => text.parse::<chrono::NaiveTime>().is_ok(),
+            _ => true,
+        };
+        if !valid {
+            problems.push(format!("{}: \"{}\" is not a valid {}", at, text, format));
+        }
+    }
+
+    if let Some(object) = value.as_object() {
+        let properties = schema.get("properties").and_then(Value::as_object);
+        for required in schema.get("required").and_then(Value::as_array).into_iter().flatten().filter_map(Value::as_str) {
+            if !object.contains_key(required) {
+                problems.push(format!("{}: missing \"{}\"", at, required));
+            }
+        }
+        for (key, item) in object {
+            let item_pointer = format!("{}/{}", pointer, key.replace('~', "~0").replace('/', "~1"));
+            match (properties.and_then(|properties| properties.get(key)), schema.get("additionalProperties")) {
+                (Some(property), _) => check(item, property, &item_pointer, problems),
+                (None, Some(Value::Bool(false))) => problems.push(format!("{}: unknown field \"{}\"", at, key)),
+                (None, Some(additional)) if additional.is_object() => check(item, additional, &item_pointer, problems),
+                _ => {}
+            }
+        }
+    }
+    if let (Some(items), Some(array)) = (schema.get("items"), value.as_array()) {
+        for (index, item) in array.iter().enumerate() {
+            check(item, items, &format!("{}/{}", pointer, index), problems);
+        }
+    }
+}
+
+fn through<T: DeserializeOwned + Serialize>(value: &Value, migrate: impl FnOnce(T) -> T) -> Result<Value, String> {
+    let typed: T = serde_json::from_value(value.clone()).map_err(|e| e.to_string())?;
+    serde_json::to_value(migrate(typed)).map_err(|e| e.to_string())
+}
+
+// Reads the value the way the app does and returns what the app would save
+pub fn round_trip(file: &str, value: &Value) -> Result<Value, String> {
+    match file_name(file) {
+        "tasks.json" => through::<HashMap<String, Task>>(value, crate::migrate_tasks),
+        "folders.json" => through::<Vec<String>>(value, |folders| folders),
+        "folder_styles.json" => through::<HashMap<String, FolderStyle>>(value, |styles| styles),
+        "settings.json" => through::<Settings>(value, |settings| settings),
+        "adjustments.json" => through::<Vec<Adjustment>>(value, |adjustments| adjustments),
+        "expenses.json" => through::<Vec<Expense>>(value, |expenses| expenses),
+        "time_off.json" => through::<Vec<TimeOff>>(value, |days| days),
+        "plan.json" => through::<Vec<PlannedBlock>>(value, |plan| plan),
+        "allocations.json" => through::<Vec<WeekAllocation>>(value, |allocations| allocations),
+        pomodoro::LOG_FILE => through::<Vec<pomodoro::Interval>>(value, |log| log),
+        _ => Err(format!("{} is not a data file the app reads", file)),
+    }
+}
+
+// Everything wrong with the contents of a data file: JSON errors, schema problems, values
+// the app can't read, and saves that don't match the schema or change when read back.
+pub fn check_file(file: &str, text: &str) -> Vec<String> {
+    let value: Value = match serde_json::from_str(text) {
+        Ok(value) => value,
+        Err(e) => return vec![format!("not valid JSON: {}", e)],
+    };
+    let schema = schema(file);
+    let mut problems = schema.as_ref().map(|schema| validate(&value, schema)).unwrap_or_default();
+    let saved = match round_trip(file, &value) {
+        Ok(saved) => saved,
+        Err(e) => {
+            problems.push(format!("the app can't read it: {}", e));
+            return problems;
+        }
+    };
+    if let Some(schema) = &schema {
+        problems.extend(validate(&saved, schema).into_iter().map(|problem| format!("after saving, {}", problem)));
+    }
+    match round_trip(file, &saved) {
+        Ok(saved_again) if saved_again != saved => problems.push("changes when saved and read back a second time".to_string()),
+        Ok(_) => {}
+        Err(e) => problems.push(format!("the app can't read its own save: {}", e)),
+    }
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ColorLabel, TimeEntry, WorkKind};
+    use chrono::{Duration, Local};
+
+    fn tasks_file(task: Task) -> String {
+        serde_json::to_string(&HashMap::from([(task.id.clone(), task)])).unwrap()
+    }
+
+    #[test]
+    fn new_task_round_trips() {
+        assert_eq!(check_file("tasks.json", &tasks_file(Task::new("Write tests".to_string()))), Vec::<String>::new());
+    }
+
+    #[test]
+    fn task_with_everything_set_round_trips() {
+        let mut task = Task::new("Client work".to_string());
+        let now = Local::now();
+        let mut entry = TimeEntry::new(now - Duration::hours(2), now - Duration::hours(1));
+        entry.annotation = Some("Call".to_string());
+        entry.synced.insert("harvest".to_string(), "42".to_string());
+        task.entries.push(entry);
+        task.folder = Some("Client".to_string());
+        task.untracked_duration = 600;
+        task.tags = vec!["meeting".to_string()];
+        task.billable = true;
+        task.hourly_rate = Some(95.5);
+        task.estimate_minutes = Some(90);
+        task.redmine_issue_id = Some(7);
+        task.work_kind = Some(WorkKind::Deep);
+        task.color_label = Some(ColorLabel::Blue);
+        task.start();
+        assert_eq!(check_file("tasks.json", &tasks_file(task)), Vec::<String>::new());
+    }
+
+    #[test]
+    fn default_settings_round_trip() {
+        let settings = serde_json::to_string(&Settings::default()).unwrap();
+        assert_eq!(check_file("settings.json", &settings), Vec::<String>::new());
+    }
+
+    #[test]
+    fn empty_files_are_valid() {
+        for file in CHECKED_FILES {
+            let empty = match file {
+                "tasks.json" | "folder_styles.json" | "settings.json" => "{}",
+                _ => "[]",
+            };
+            assert_eq!(check_file(file, empty), Vec::<String>::new(), "{}", file);
+        }
+    }
+
+    #[test]
+    fn every_schema_file_is_checked() {
+        for file in SCHEMA_FILES {
+            assert!(schema(file).is_some(), "{}", file);
+            assert!(is_checked(file), "{}", file);
+        }
+        assert!(schema("settings.json").is_none());
+        assert!(is_checked("backup/tasks.json"));
+        assert!(!is_checked("notes.json"));
+    }
+
+    #[test]
+    fn validate_finds_every_kind_of_problem() {
+        let task = json!({
+            "id": "1",
+            "description": 3,
+            "start_time": "yesterday",
+            "estimate_minutes": -5,
+            "color_label": "Pink",
+            "colour": "Red",
+        });
+        let problems = validate(&json!({ "1": task }), &schema("tasks.json").unwrap());
+        let expected = [
+            "/1/description: expected string, found 3",
+            "/1/start_time: \"yesterday\" is not a valid date-time",
+            "/1/estimate_minutes: -5 is below 0",
+            "/1/color_label: \"Pink\" is not one of",
+            "/1: unknown field \"colour\"",
+            "/1: missing \"is_paused\"",
+        ];
+        for expected in expected {
+            assert!(problems.iter().any(|problem| problem.starts_with(expected)), "{} not in {:?}", expected, problems);
+        }
+        assert_eq!(problems.len(), expected.len(), "{:?}", problems);
+    }
+
+    #[test]
+    fn check_file_reports_unreadable_files() {
+        assert!(check_file("tasks.json", "{").iter().any(|problem| problem.starts_with("not valid JSON")));
+        let problems = check_file("folders.json", "[1]");
+        assert!(problems.iter().any(|problem| problem.starts_with("/0: expected string")), "{:?}", problems);
+        assert!(problems.iter().any(|problem| problem.starts_with("the app can't read it")), "{:?}", problems);
+    }
+}
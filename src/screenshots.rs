@@ -0,0 +1,108 @@
+use crate::backup::{self, ZipEntry};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+// Periodic screenshots as evidence of work, for contracts that ask for it. Only taken
+// while a task with this tag runs, and kept per session under screenshots/.
+pub const SCREENSHOTS_DIR: &str = "screenshots";
+pub const PROOF_OF_WORK_TAG: &str = "proof-of-work";
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ScreenshotConfig {
+    pub enabled: bool,
+    pub interval_minutes: u32,
+}
+
+impl Default for ScreenshotConfig {
+    fn default() -> Self {
+        ScreenshotConfig { enabled: false, interval_minutes: 10 }
+    }
+}
+
+// Folder for the screenshots of the session that started at `session_start`
+pub fn session_dir(task_id: &str, session_start: DateTime<Local>) -> PathBuf {
+    Path::new(SCREENSHOTS_DIR)
+        .join(task_id)
+        .join(session_start.format("%Y%m%d-%H%M%S").to_string())
+}
+
+// Takes one screenshot and stores a copy in each of the folders. Blocks until the
+// screenshot tool is done, so it runs off the UI thread.
+pub fn capture(dirs: &[PathBuf]) -> Result<(), String> {
+    let name = format!("{}.png", Local::now().format("%H%M%S"));
+    let Some((first, others)) = dirs.split_first() else {
+        return Ok(());
+    };
+    fs::create_dir_all(first).map_err(|e| e.to_string())?;
+    let path = first.join(&name);
+    take_screenshot(&path)?;
+    for dir in others {
+        fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        fs::copy(&path, dir.join(&name)).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+fn run(command: &mut Command) -> Result<(), String> {
+    let output = command.output().map_err(|e| e.to_string())?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn take_screenshot(path: &Path) -> Result<(), String> {
+    run(Command::new("screencapture").arg("-x").arg(path))
+}
+
+#[cfg(windows)]
+fn take_screenshot(path: &Path) -> Result<(), String> {
+    let script = format!(
+        "Add-Type -AssemblyName System.Windows.Forms,System.Drawing; \
+         $b = [System.Windows.Forms.SystemInformation]::VirtualScreen; \
+         $i = New-Object System.Drawing.Bitmap $b.Width, $b.Height; \
+         [System.Drawing.Graphics]::FromImage($i).CopyFromScreen($b.Left, $b.Top, 0, 0, $i.Size); \
+         $i.Save('{}')",
+        path.display().to_string().replace('\'', "''")
+    );
+    run(Command::new("powershell").args(["-NoProfile", "-Command", &script]))
+}
+
+// Whichever screenshot tool is installed: GNOME, wlroots compositors or ImageMagick on X11
+#[cfg(not(any(target_os = "macos", windows)))]
+fn take_screenshot(path: &Path) -> Result<(), String> {
+    run(Command::new("gnome-screenshot").arg("-f").arg(path))
+        .or_else(|_| run(Command::new("grim").arg(path)))
+        .or_else(|_| run(Command::new("import").args(["-window", "root"]).arg(path)))
+        .map_err(|e| format!("no screenshot tool worked, install gnome-screenshot, grim or ImageMagick ({})", e))
+}
+
+// Zips the screenshots of the tasks next to an export. Returns the number of screenshots,
+// nothing is written when there are none.
+pub fn bundle(task_ids: &[&str], zip_path: &str) -> Result<usize, String> {
+    let mut entries: Vec<ZipEntry> = Vec::new();
+    for task_id in task_ids {
+        let dir = Path::new(SCREENSHOTS_DIR).join(task_id);
+        if dir.is_dir() {
+            backup::collect_dir(&dir, &mut entries)?;
+        }
+    }
+    if !entries.is_empty() {
+        backup::write(zip_path, &entries)?;
+    }
+    Ok(entries.len())
+}
+
+pub fn remove_for(task_id: &str) {
+    let dir = Path::new(SCREENSHOTS_DIR).join(task_id);
+    if dir.exists() {
+        if let Err(e) = fs::remove_dir_all(&dir) {
+            tracing::warn!("Failed to delete screenshots in {}: {}", dir.display(), e);
+        }
+    }
+}
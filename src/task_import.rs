@@ -0,0 +1,180 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct TaskImportConfig {
+    pub asana_token: String, // Personal access token
+    pub trello_api_key: String,
+    pub trello_token: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ImportSource {
+    #[default]
+    Asana,
+    Trello,
+}
+
+impl ImportSource {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ImportSource::Asana => "Asana project",
+            ImportSource::Trello => "Trello board",
+        }
+    }
+
+    // Key used in `Task::external_ids` for tasks imported from this source
+    pub fn external_id_key(&self) -> &'static str {
+        match self {
+            ImportSource::Asana => "asana",
+            ImportSource::Trello => "trello",
+        }
+    }
+
+    pub fn is_configured(&self, config: &TaskImportConfig) -> bool {
+        match self {
+            ImportSource::Asana => !config.asana_token.trim().is_empty(),
+            ImportSource::Trello => {
+                !config.trello_api_key.trim().is_empty() && !config.trello_token.trim().is_empty()
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RemoteTask {
+    pub id: String,
+    pub name: String,
+}
+
+#[derive(Deserialize)]
+struct AsanaPage {
+    data: Vec<AsanaTask>,
+    next_page: Option<AsanaNextPage>,
+}
+
+#[derive(Deserialize)]
+struct AsanaTask {
+    gid: String,
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct AsanaNextPage {
+    offset: String,
+}
+
+#[derive(Deserialize)]
+struct TrelloCard {
+    id: String,
+    name: String,
+}
+
+fn describe_error(error: ureq::Error) -> String {
+    match error {
+        ureq::Error::Status(code, response) => {
+            format!("HTTP {}: {}", code, response.into_string().unwrap_or_default())
+        }
+        ureq::Error::Transport(transport) => transport.to_string(),
+    }
+}
+
+// Open (incomplete) tasks of an Asana project
+fn fetch_asana(config: &TaskImportConfig, project_id: &str) -> Result<Vec<RemoteTask>, String> {
+    let url = format!("https://app.asana.com/api/1.0/projects/{}/tasks", project_id);
+    let mut tasks = Vec::new();
+    let mut offset: Option<String> = None;
+
+    loop {
+        let mut request = ureq::get(&url)
+            .set("Authorization", &format!("Bearer {}", config.asana_token.trim()))
+            .query("opt_fields", "name")
+            .query("completed_since", "now")
+            .query("limit", "100");
+        if let Some(offset) = &offset {
+            request = request.query("offset", offset);
+        }
+
+        let page: AsanaPage = request
+            .call()
+            .map_err(describe_error)?
+            .into_json()
+            .map_err(|e| e.to_string())?;
+
+        tasks.extend(page.data.into_iter().map(|task| RemoteTask {
+            id: task.gid,
+            name: task.name,
+        }));
+
+        match page.next_page {
+            Some(next_page) => offset = Some(next_page.offset),
+            None => break,
+        }
+    }
+
+    Ok(tasks)
+}
+
+// Open cards of a Trello board
+fn fetch_trello(config: &TaskImportConfig, board_id: &str) -> Result<Vec<RemoteTask>, String> {
+    let cards: Vec<TrelloCard> = ureq::get(&format!("https://api.trello.com/1/boards/{}/cards/open", board_id))
+        .query("key", config.trello_api_key.trim())
+        .query("token", config.trello_token.trim())
+        .query("fields", "name")
+        .call()
+        .map_err(describe_error)?
+        .into_json()
+        .map_err(|e| e.to_string())?;
+
+    Ok(cards
+        .into_iter()
+        .map(|card| RemoteTask {
+            id: card.id,
+            name: card.name,
+        })
+        .collect())
+}
+
+pub fn fetch_open_tasks(
+    config: &TaskImportConfig,
+    source: ImportSource,
+    container_id: &str,
+) -> Result<Vec<RemoteTask>, String> {
+    let container_id = container_id.trim();
+    if container_id.is_empty() {
+        return Err(format!("no {} id given", source.label()));
+    }
+    match source {
+        ImportSource::Asana => fetch_asana(config, container_id),
+        ImportSource::Trello => fetch_trello(config, container_id),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::in_scratch_dir;
+    use crate::WorkTimer;
+
+    fn remote(id: &str, name: &str) -> RemoteTask {
+        RemoteTask { id: id.to_string(), name: name.to_string() }
+    }
+
+    #[test]
+    fn importing_again_updates_instead_of_duplicating() {
+        in_scratch_dir();
+        let mut timer = WorkTimer { data_file: "import_test_tasks.json".to_string(), ..Default::default() };
+        timer.apply_imported_tasks(ImportSource::Asana, "Client", vec![remote("1", "Write spec"), remote("2", "Review")]);
+        timer.apply_imported_tasks(ImportSource::Asana, "Client", vec![remote("1", "Write the spec"), remote("2", "Review")]);
+        // The same id from another source is another task
+        timer.apply_imported_tasks(ImportSource::Trello, "Client", vec![remote("1", "Write spec")]);
+
+        let mut names: Vec<(&str, &str)> = timer
+            .tasks
+            .values()
+            .flat_map(|task| task.external_ids.keys().map(|source| (source.as_str(), task.description.as_str())))
+            .collect();
+        names.sort();
+        assert_eq!(names, [("asana", "Review"), ("asana", "Write the spec"), ("trello", "Write spec")]);
+        assert_eq!(timer.tasks.len(), 3);
+    }
+}
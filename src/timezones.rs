@@ -0,0 +1,48 @@
+use chrono::{DateTime, Local, Offset, TimeZone};
+use chrono_tz::Tz;
+use std::sync::OnceLock;
+
+// Time zones for teams spread over several: exports say which zone their times are in,
+// entries remember the zone they were recorded in, and reports can be run in a client's
+// zone. Zones are IANA names like Europe/Berlin or America/New_York.
+
+// The zone this computer is set to, None if the system doesn't say. TZ comes first
+// because local times follow it.
+pub fn system() -> Option<&'static str> {
+    static SYSTEM: OnceLock<Option<String>> = OnceLock::new();
+    SYSTEM
+        .get_or_init(|| {
+            std::env::var("TZ")
+                .ok()
+                .map(|name| name.trim_start_matches(':').to_string())
+                .filter(|name| name.parse::<Tz>().is_ok())
+                .or_else(|| iana_time_zone::get_timezone().ok().filter(|name| name.parse::<Tz>().is_ok()))
+        })
+        .as_deref()
+}
+
+// How exports name the zone of their times: the system zone, or the current offset
+// from UTC when it is unknown, e.g. UTC+02:00
+pub fn workspace() -> String {
+    match system() {
+        Some(name) => name.to_string(),
+        None => format!("UTC{}", Local::now().offset()),
+    }
+}
+
+pub fn parse(name: &str) -> Result<Tz, String> {
+    name.trim()
+        .parse::<Tz>()
+        .map_err(|_| format!("\"{}\" is not a known time zone, use a name like Europe/Berlin", name.trim()))
+}
+
+// The moment with the date and time a clock in the zone shows for it, so it can be
+// grouped by day like local times. When that time doesn't exist locally (a clock change
+// here but not there) the difference of the two offsets is used instead.
+pub fn to_wall_clock(time: DateTime<Local>, zone: Tz) -> DateTime<Local> {
+    let there = time.with_timezone(&zone);
+    Local.from_local_datetime(&there.naive_local()).earliest().unwrap_or_else(|| {
+        let difference = there.offset().fix().local_minus_utc() - time.offset().local_minus_utc();
+        time + chrono::Duration::seconds(difference as i64)
+    })
+}
@@ -0,0 +1,222 @@
+use eframe::egui;
+use std::sync::mpsc;
+use tray_icon::menu::{Menu, MenuEvent, MenuItem, PredefinedMenuItem, Submenu};
+use tray_icon::{Icon, MouseButton, MouseButtonState, TrayIcon, TrayIconBuilder, TrayIconEvent};
+
+// Tray icon (menu bar icon on macOS) with the running task and quick timer controls.
+// GTK has to run the icon on Linux, which eframe doesn't use, so there it lives on a
+// thread of its own and gets the status over a channel. Elsewhere it lives on the UI
+// thread. The menu handlers can only be set once, so the icon is hidden rather than
+// removed when it is turned off.
+const ICON_SIZE: u32 = 32;
+
+pub enum Command {
+    Toggle, // Pause the running timers, or resume the last task
+    Start(String),
+    Show,
+    Quit,
+}
+
+#[derive(Clone, PartialEq, Default)]
+pub struct Status {
+    pub visible: bool,
+    pub running: Option<String>, // The running task with its elapsed time
+    pub resumable: bool,         // There is a task to resume
+    pub recent: Vec<(String, String)>, // Task ids and descriptions to switch to
+}
+
+impl Status {
+    fn title(&self) -> String {
+        self.running.clone().unwrap_or_else(|| "No timer running".to_string())
+    }
+}
+
+pub struct Tray {
+    commands: mpsc::Receiver<Command>,
+    shown: Option<Status>,
+    #[cfg(target_os = "linux")]
+    updates: mpsc::Sender<Status>,
+    #[cfg(not(target_os = "linux"))]
+    icon: Indicator,
+}
+
+impl Tray {
+    pub fn new(ctx: &egui::Context) -> Result<Self, String> {
+        let (sender, commands) = mpsc::channel();
+        forward_events(ctx.clone(), sender);
+
+        #[cfg(target_os = "linux")]
+        {
+            let (updates, received) = mpsc::channel::<Status>();
+            std::thread::spawn(move || {
+                if let Err(e) = gtk::init() {
+                    tracing::warn!("Failed to start GTK for the tray icon: {}", e);
+                    return;
+                }
+                let mut icon = match Indicator::new() {
+                    Ok(icon) => icon,
+                    Err(e) => {
+                        tracing::warn!("Failed to create the tray icon: {}", e);
+                        return;
+                    }
+                };
+                gtk::glib::timeout_add_local(std::time::Duration::from_millis(250), move || loop {
+                    match received.try_recv() {
+                        Ok(status) => icon.show(&status),
+                        Err(mpsc::TryRecvError::Empty) => return gtk::glib::ControlFlow::Continue,
+                        Err(mpsc::TryRecvError::Disconnected) => {
+                            gtk::main_quit();
+                            return gtk::glib::ControlFlow::Break;
+                        }
+                    }
+                });
+                gtk::main();
+            });
+            Ok(Tray { commands, shown: None, updates })
+        }
+        #[cfg(not(target_os = "linux"))]
+        {
+            Ok(Tray { commands, shown: None, icon: Indicator::new()? })
+        }
+    }
+
+    pub fn update(&mut self, status: Status) {
+        if self.shown.as_ref() == Some(&status) {
+            return;
+        }
+        #[cfg(target_os = "linux")]
+        let _ = self.updates.send(status.clone());
+        #[cfg(not(target_os = "linux"))]
+        self.icon.show(&status);
+        self.shown = Some(status);
+    }
+
+    pub fn commands(&self) -> Vec<Command> {
+        self.commands.try_iter().collect()
+    }
+}
+
+// Menu clicks, and clicks on the icon itself where there are any, wake up the UI so they
+// are handled even while the window is hidden
+fn forward_events(ctx: egui::Context, sender: mpsc::Sender<Command>) {
+    let menu_sender = sender.clone();
+    let menu_ctx = ctx.clone();
+    MenuEvent::set_event_handler(Some(move |event: MenuEvent| {
+        let command = match event.id.as_ref() {
+            "toggle" => Command::Toggle,
+            "show" => Command::Show,
+            "quit" => Command::Quit,
+            id => match id.strip_prefix("task:") {
+                Some(task_id) => Command::Start(task_id.to_string()),
+                None => return,
+            },
+        };
+        let _ = menu_sender.send(command);
+        menu_ctx.request_repaint();
+    }));
+    TrayIconEvent::set_event_handler(Some(move |event: TrayIconEvent| {
+        if let TrayIconEvent::Click { button: MouseButton::Left, button_state: MouseButtonState::Up, .. } = event {
+            let _ = sender.send(Command::Show);
+            ctx.request_repaint();
+        }
+    }));
+}
+
+// What the menu was built for: running, resumable and the recent tasks
+type MenuState = (bool, bool, Vec<(String, String)>);
+
+// The icon and the parts of its menu that change
+struct Indicator {
+    icon: TrayIcon,
+    heading: MenuItem,
+    menu_for: Option<MenuState>,
+}
+
+impl Indicator {
+    fn new() -> Result<Self, String> {
+        let status = Status::default();
+        let (menu, heading) = menu(&status)?;
+        let icon = TrayIconBuilder::new()
+            .with_icon(icon(false)?)
+            .with_tooltip("Work Timer")
+            .with_menu(Box::new(menu))
+            .build()
+            .map_err(|e| e.to_string())?;
+        let _ = icon.set_visible(false);
+        Ok(Indicator { icon, heading, menu_for: None })
+    }
+
+    fn show(&mut self, status: &Status) {
+        if let Err(e) = self.icon.set_visible(status.visible) {
+            tracing::warn!("Failed to show the tray icon: {}", e);
+        }
+        let title = status.title();
+        let _ = self.icon.set_tooltip(Some(format!("Work Timer: {}", title)));
+        // Shown next to the icon in the macOS menu bar and by some Linux panels
+        self.icon.set_title(status.running.as_ref());
+
+        let menu_for = (status.running.is_some(), status.resumable, status.recent.clone());
+        if self.menu_for.as_ref() == Some(&menu_for) {
+            self.heading.set_text(&title);
+            return;
+        }
+        match menu(status) {
+            Ok((menu, heading)) => {
+                self.icon.set_menu(Some(Box::new(menu)));
+                self.heading = heading;
+            }
+            Err(e) => tracing::warn!("Failed to build the tray menu: {}", e),
+        }
+        match icon(status.running.is_some()) {
+            Ok(icon) => {
+                let _ = self.icon.set_icon(Some(icon));
+            }
+            Err(e) => tracing::warn!("Failed to draw the tray icon: {}", e),
+        }
+        self.menu_for = Some(menu_for);
+    }
+}
+
+fn menu(status: &Status) -> Result<(Menu, MenuItem), String> {
+    let heading = MenuItem::new(status.title(), false, None);
+    let toggle = match &status.running {
+        Some(_) => MenuItem::with_id("toggle", "Pause", true, None),
+        None => MenuItem::with_id("toggle", "Resume", status.resumable, None),
+    };
+    let recent = Submenu::new("Switch to", !status.recent.is_empty());
+    for (task_id, description) in &status.recent {
+        recent
+            .append(&MenuItem::with_id(format!("task:{}", task_id), description, true, None))
+            .map_err(|e| e.to_string())?;
+    }
+    let menu = Menu::new();
+    menu.append_items(&[
+        &heading,
+        &PredefinedMenuItem::separator(),
+        &toggle,
+        &recent,
+        &PredefinedMenuItem::separator(),
+        &MenuItem::with_id("show", "Show Work Timer", true, None),
+        &MenuItem::with_id("quit", "Quit", true, None),
+    ])
+    .map_err(|e| e.to_string())?;
+    Ok((menu, heading))
+}
+
+// A filled circle, green while a timer runs and grey otherwise
+fn icon(running: bool) -> Result<Icon, String> {
+    let color: [u8; 3] = if running { [60, 180, 75] } else { [150, 150, 150] };
+    let center = ICON_SIZE as f32 / 2.0;
+    let radius = center - 2.0;
+    let mut rgba = Vec::with_capacity((ICON_SIZE * ICON_SIZE * 4) as usize);
+    for y in 0..ICON_SIZE {
+        for x in 0..ICON_SIZE {
+            let distance = ((x as f32 + 0.5 - center).powi(2) + (y as f32 + 0.5 - center).powi(2)).sqrt();
+            // Soft edge over one pixel
+            let alpha = (radius + 0.5 - distance).clamp(0.0, 1.0);
+            rgba.extend_from_slice(&color);
+            rgba.push((alpha * 255.0) as u8);
+        }
+    }
+    Icon::from_rgba(rgba, ICON_SIZE, ICON_SIZE).map_err(|e| e.to_string())
+}